@@ -0,0 +1,113 @@
+use adw::prelude::*;
+use adw::{AlertDialog, ComboRow, EntryRow, PreferencesGroup, ResponseAppearance};
+use gtk4::StringList;
+use serde::{Deserialize, Serialize};
+
+// How far the review of an annotated node has progressed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TriageState {
+    #[default]
+    Untriaged,
+    Investigating,
+    AcceptedRisk,
+    Resolved,
+}
+
+impl TriageState {
+    pub const ALL: [TriageState; 4] = [
+        TriageState::Untriaged,
+        TriageState::Investigating,
+        TriageState::AcceptedRisk,
+        TriageState::Resolved,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Untriaged => "Untriaged",
+            Self::Investigating => "Investigating",
+            Self::AcceptedRisk => "Accepted Risk",
+            Self::Resolved => "Resolved",
+        }
+    }
+}
+
+// A note attached to a node, identified like a bookmark by the names from the
+// root's children down to the node
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Annotation {
+    pub path: Vec<String>,
+    pub note: String,
+    pub state: TriageState,
+}
+
+impl Annotation {
+    pub fn summary(&self) -> String {
+        if self.note.is_empty() {
+            self.state.label().to_string()
+        } else {
+            format!("{}: {}", self.state.label(), self.note)
+        }
+    }
+}
+
+// Result of the annotation dialog
+pub enum Edit {
+    Save { note: String, state: TriageState },
+    Remove,
+}
+
+// Ask for the note and triage state of a node. Returns None when the dialog was cancelled.
+pub async fn edit_annotation(
+    parent: &impl IsA<gtk4::Widget>,
+    node_name: &str,
+    current: Option<&Annotation>,
+) -> Option<Edit> {
+    let state_names: Vec<&str> = TriageState::ALL.iter().map(|s| s.label()).collect();
+    let current_state = current.map(|a| a.state).unwrap_or_default();
+    let state_row = ComboRow::builder()
+        .title("Triage State")
+        .model(&StringList::new(&state_names))
+        .selected(
+            TriageState::ALL
+                .iter()
+                .position(|s| *s == current_state)
+                .unwrap_or(0) as u32,
+        )
+        .build();
+    let note_row = EntryRow::builder()
+        .title("Note")
+        .text(current.map(|a| a.note.as_str()).unwrap_or(""))
+        .build();
+
+    let group = PreferencesGroup::new();
+    group.add(&state_row);
+    group.add(&note_row);
+
+    let dialog = AlertDialog::builder()
+        .heading("Annotate Node")
+        .body(node_name)
+        .extra_child(&group)
+        .close_response("cancel")
+        .default_response("save")
+        .build();
+    dialog.add_response("cancel", "Cancel");
+    if current.is_some() {
+        dialog.add_response("remove", "Remove");
+        dialog.set_response_appearance("remove", ResponseAppearance::Destructive);
+    }
+    dialog.add_response("save", "Save");
+    dialog.set_response_appearance("save", ResponseAppearance::Suggested);
+
+    match dialog.clone().choose_future(parent).await.as_str() {
+        "save" => Some(Edit::Save {
+            note: note_row.text().trim().to_string(),
+            state: TriageState::ALL
+                .get(state_row.selected() as usize)
+                .copied()
+                .unwrap_or_default(),
+        }),
+        "remove" => Some(Edit::Remove),
+        _ => None,
+    }
+}
@@ -0,0 +1,137 @@
+use crate::data::{AuditEvent, CaptureMetadata};
+use gtk4::glib;
+use serde_json::Value;
+use std::collections::HashMap;
+
+// Event fields naming hosts, processes or captures, with the kind of their
+// pseudonyms. The bundle reader records each event's member in "source".
+const MASKED_FIELDS: [(&str, &str); 7] = [
+    ("tls::server_name", "host"),
+    ("tls::sni", "host"),
+    ("tls::peer", "host"),
+    ("tls::peer_address", "host"),
+    ("process::comm", "process"),
+    ("comm", "process"),
+    ("source", "capture"),
+];
+
+// Masks identifiers of the infrastructure with pseudonyms, so screenshots and
+// exports can be shared. Pseudonyms are keyed hashes of the identifiers: the
+// same identifier gets the same pseudonym every time, while the secret key
+// keeps guessable ones, like library names, from being looked up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Anonymizer {
+    key: String,
+}
+
+impl Anonymizer {
+    pub fn new(key: &str) -> Self {
+        Self {
+            key: key.to_string(),
+        }
+    }
+
+    // Random key for a new installation
+    pub fn generate_key() -> String {
+        glib::uuid_string_random().to_string()
+    }
+
+    // Pseudonym of an identifier of a kind, e.g. "context-1f3a9c2e"
+    pub fn pseudonym(&self, kind: &str, value: &str) -> String {
+        let hash = glib::compute_hmac_for_data(
+            glib::ChecksumType::Sha256,
+            self.key.as_bytes(),
+            format!("{}:{}", kind, value).as_bytes(),
+        );
+        format!("{}-{}", kind, &hash[..8])
+    }
+
+    // Copy of the events with their contexts, origins, boot IDs and the
+    // fields naming hosts, processes or captures masked
+    pub fn events(&self, events: &[AuditEvent]) -> Vec<AuditEvent> {
+        let mut contexts = HashMap::new();
+        let mut origins = HashMap::new();
+        self.mask_events(events, &mut contexts, &mut origins)
+    }
+
+    // Pseudonyms are remembered while masking, as many events share them
+    fn mask_events(
+        &self,
+        events: &[AuditEvent],
+        contexts: &mut HashMap<String, String>,
+        origins: &mut HashMap<String, String>,
+    ) -> Vec<AuditEvent> {
+        events
+            .iter()
+            .map(|event| {
+                let context = contexts
+                    .entry(event.context.clone())
+                    .or_insert_with(|| self.pseudonym("context", &event.context))
+                    .clone();
+                let origin = origins
+                    .entry(event.origin.clone())
+                    .or_insert_with(|| self.pseudonym("origin", &event.origin))
+                    .clone();
+                AuditEvent {
+                    context,
+                    origin,
+                    start: event.start,
+                    end: event.end,
+                    events: self.mask_fields(&event.events),
+                    spans: self.mask_events(&event.spans, contexts, origins),
+                    boot_id: event
+                        .boot_id
+                        .as_ref()
+                        .map(|boot_id| self.pseudonym("boot", boot_id)),
+                }
+            })
+            .collect()
+    }
+
+    fn mask_fields(&self, fields: &HashMap<String, Value>) -> HashMap<String, Value> {
+        let mut fields = fields.clone();
+        for (field, kind) in MASKED_FIELDS {
+            if let Some(value) = fields.get_mut(field) {
+                let text = match &*value {
+                    Value::String(text) => text.clone(),
+                    other => other.to_string(),
+                };
+                *value = Value::String(self.pseudonym(kind, &text));
+            }
+        }
+        fields
+    }
+
+    // Labels of masked contexts, themselves masked as they often name hosts
+    pub fn context_labels(&self, labels: &HashMap<String, String>) -> HashMap<String, String> {
+        labels
+            .iter()
+            .map(|(context, label)| {
+                (
+                    self.pseudonym("context", context),
+                    self.pseudonym("host", label),
+                )
+            })
+            .collect()
+    }
+
+    // The metadata header without the host's name and boot IDs
+    pub fn metadata(&self, metadata: &CaptureMetadata) -> CaptureMetadata {
+        CaptureMetadata {
+            hostname: metadata
+                .hostname
+                .as_ref()
+                .map(|host| self.pseudonym("host", host)),
+            boot_id: metadata
+                .boot_id
+                .as_ref()
+                .map(|boot_id| self.pseudonym("boot", boot_id)),
+            boots: metadata
+                .boots
+                .iter()
+                .map(|(boot_id, boot_time)| (self.pseudonym("boot", boot_id), *boot_time))
+                .collect(),
+            ..metadata.clone()
+        }
+    }
+}
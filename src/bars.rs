@@ -0,0 +1,100 @@
+use crate::data::NodeKind;
+use crate::models::StatsObject;
+use crate::palette::Palette;
+use crate::sunburst::SunburstChart;
+use adw::prelude::*;
+use cairo::Context;
+use gtk4::{gio, DrawingArea};
+use std::rc::Rc;
+
+// Number of entries drawn; the table below lists the rest
+const MAX_BARS: usize = 8;
+pub const ROW_HEIGHT: f64 = 22.0;
+const FONT_SIZE: f64 = 12.0;
+// Fractions of the width taken by the names and by the percentages
+const NAME_WIDTH: f64 = 0.35;
+const PERCENT_WIDTH: f64 = 0.15;
+
+// Nonzero counts of a stats store, in its order
+pub fn share_entries(store: &gio::ListStore) -> Vec<(String, usize)> {
+    store
+        .iter::<StatsObject>()
+        .filter_map(Result::ok)
+        .map(|obj| (obj.algorithm(), obj.count().parse().unwrap_or(0)))
+        // Entries only found in the baseline have no share
+        .filter(|(_, count)| *count > 0)
+        .collect()
+}
+
+// Draw one bar per entry from the top, up to max_bars, each ROW_HEIGHT high
+pub fn draw_share_bars(
+    cr: &Context,
+    entries: &[(String, usize)],
+    width: f64,
+    palette: Palette,
+    max_bars: usize,
+) {
+    let total: usize = entries.iter().map(|(_, count)| count).sum();
+    if total == 0 {
+        return;
+    }
+
+    let bar_start = width * NAME_WIDTH;
+    let bar_width = width * (1.0 - NAME_WIDTH - PERCENT_WIDTH);
+    cr.set_font_size(FONT_SIZE);
+
+    for (i, (name, count)) in entries.iter().take(max_bars).enumerate() {
+        let share = *count as f64 / total as f64;
+        let y = i as f64 * ROW_HEIGHT;
+        let text_y = y + ROW_HEIGHT / 2.0 + FONT_SIZE / 3.0;
+
+        // Names are cut to the space left of the bars
+        cr.save().unwrap();
+        cr.rectangle(0.0, y, bar_start - 6.0, ROW_HEIGHT);
+        cr.clip();
+        cr.set_source_rgb(0.3, 0.3, 0.3);
+        cr.move_to(0.0, text_y);
+        cr.show_text(name).unwrap();
+        cr.restore().unwrap();
+
+        let (r, g, b) = palette.node_color(name, NodeKind::Event, 1);
+        cr.set_source_rgb(r, g, b);
+        cr.rectangle(
+            bar_start,
+            y + 4.0,
+            (bar_width * share).max(1.0),
+            ROW_HEIGHT - 8.0,
+        );
+        cr.fill().unwrap();
+
+        cr.set_source_rgb(0.3, 0.3, 0.3);
+        cr.move_to(bar_start + bar_width + 6.0, text_y);
+        cr.show_text(&format!("{}%", (share * 100.0).round() as u32))
+            .unwrap();
+    }
+}
+
+// Horizontal bars of the largest shares of a stats store, colored like the
+// chart segments of the same algorithm
+pub fn create_share_bars(store: &gio::ListStore, chart: Rc<SunburstChart>) -> DrawingArea {
+    let drawing_area = DrawingArea::new();
+    drawing_area.set_hexpand(true);
+    drawing_area.set_visible(false);
+
+    let store_clone = store.clone();
+    drawing_area.set_draw_func(move |_, cr, width, _| {
+        let entries = share_entries(&store_clone);
+        draw_share_bars(cr, &entries, width as f64, chart.palette(), MAX_BARS);
+    });
+
+    // Resize to the number of bars whenever the stats change
+    let drawing_area_clone = drawing_area.clone();
+    store.connect_items_changed(move |store, _, _, _| {
+        let bars = share_entries(store).len().min(MAX_BARS);
+        drawing_area_clone.set_content_height((bars as f64 * ROW_HEIGHT) as i32);
+        drawing_area_clone.set_visible(bars > 0);
+        drawing_area_clone.queue_draw();
+    });
+
+    drawing_area
+}
@@ -0,0 +1,133 @@
+use crate::data::AuditEvent;
+use crate::sunburst::SunburstChart;
+use crate::timezone::format_time;
+use adw::prelude::*;
+use adw::ComboRow;
+use gtk4::StringList;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+// Events of one boot of a capture spanning several
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BootSession {
+    pub boot_id: String,
+    // Start and end of the session, in nanoseconds since the earliest boot
+    pub start: u64,
+    pub end: u64,
+    pub events: usize,
+}
+
+// Boot sessions of the events tagged with a boot ID, in order of time
+pub fn sessions(events: &[AuditEvent]) -> Vec<BootSession> {
+    let mut sessions: Vec<BootSession> = Vec::new();
+    for event in events {
+        let Some(boot_id) = &event.boot_id else {
+            continue;
+        };
+        match sessions
+            .iter_mut()
+            .find(|session| &session.boot_id == boot_id)
+        {
+            Some(session) => {
+                session.start = session.start.min(event.start);
+                session.end = session.end.max(event.end);
+                session.events += 1;
+            }
+            None => sessions.push(BootSession {
+                boot_id: boot_id.clone(),
+                start: event.start,
+                end: event.end,
+                events: 1,
+            }),
+        }
+    }
+    sessions.sort_by_key(|session| session.start);
+    sessions
+}
+
+// Top-level events of a boot, together with all of their spans
+pub fn filter_boot(events: &[AuditEvent], boot_id: &str) -> Vec<AuditEvent> {
+    events
+        .iter()
+        .filter(|event| event.boot_id.as_deref() == Some(boot_id))
+        .cloned()
+        .collect()
+}
+
+fn session_label(index: usize, session: &BootSession) -> String {
+    format!(
+        "Boot {}: {} – {}, {} events",
+        index + 1,
+        format_time(session.start, "%F %T"),
+        format_time(session.end, "%F %T"),
+        session.events
+    )
+}
+
+// Picker of the boot session the chart shows, for captures spanning several
+// boots, hidden for the others
+pub fn create_boot_picker(chart: Rc<SunburstChart>) -> gtk4::ListBox {
+    let list = gtk4::ListBox::new();
+    list.set_selection_mode(gtk4::SelectionMode::None);
+    list.add_css_class("boxed-list");
+    list.set_margin_top(6);
+    list.set_visible(false);
+
+    let model = StringList::new(&["All Boots"]);
+    let row = ComboRow::builder()
+        .title("Boot Session")
+        .model(&model)
+        .build();
+    list.append(&row);
+
+    let sessions: Rc<RefCell<Vec<BootSession>>> = Rc::new(RefCell::new(Vec::new()));
+    // Choices shown, which change with the display of times as well
+    let labels: RefCell<Vec<String>> = RefCell::new(Vec::new());
+    // Set while the choices are replaced, which changes the selection
+    let updating = Rc::new(Cell::new(false));
+
+    let chart_clone = chart.clone();
+    let sessions_clone = sessions.clone();
+    let updating_clone = updating.clone();
+    row.connect_selected_notify(move |row| {
+        if updating_clone.get() {
+            return;
+        }
+        let boot_id = match row.selected() {
+            0 => None,
+            index => sessions_clone
+                .borrow()
+                .get(index as usize - 1)
+                .map(|session| session.boot_id.clone()),
+        };
+        chart_clone.set_boot_session(boot_id);
+    });
+
+    let list_clone = list.clone();
+    let chart_clone = chart.clone();
+    chart.connect_view_changed(move |_| {
+        let current = self::sessions(&chart_clone.events());
+        let current_labels: Vec<String> = std::iter::once("All Boots".to_string())
+            .chain(current.iter().enumerate().map(|(i, s)| session_label(i, s)))
+            .collect();
+        if *labels.borrow() == current_labels {
+            return;
+        }
+        let selected = chart_clone
+            .boot_session()
+            .and_then(|boot_id| current.iter().position(|s| s.boot_id == boot_id))
+            .map_or(0, |index| index as u32 + 1);
+
+        updating.set(true);
+        let choices: Vec<&str> = current_labels.iter().map(String::as_str).collect();
+        model.splice(0, model.n_items(), &choices);
+        row.set_selected(selected);
+        updating.set(false);
+
+        list_clone.set_visible(current.len() > 1);
+        *sessions.borrow_mut() = current;
+        *labels.borrow_mut() = current_labels;
+    });
+
+    list
+}
@@ -0,0 +1,221 @@
+use crate::data::{AuditEvent, Capture};
+use anyhow::{anyhow, Result};
+use gtk4::{gio, glib};
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Archive formats the collection script produces
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    // Optionally compressed; tar detects the compression itself
+    Tar,
+    Zip,
+}
+
+fn format_of(path: &Path) -> Option<Format> {
+    let name = path.file_name()?.to_string_lossy().to_lowercase();
+    if [".tar", ".tar.gz", ".tgz", ".tar.xz", ".tar.zst"]
+        .iter()
+        .any(|ext| name.ends_with(ext))
+    {
+        Some(Format::Tar)
+    } else if name.ends_with(".zip") {
+        Some(Format::Zip)
+    } else {
+        None
+    }
+}
+
+// Whether the path names an archive of several captures rather than a capture
+pub fn is_bundle(path: &Path) -> bool {
+    format_of(path).is_some()
+}
+
+// Standard output of a command, which is expected to succeed
+async fn output(argv: &[&OsStr]) -> Result<String> {
+    let process = gio::Subprocess::newv(
+        argv,
+        gio::SubprocessFlags::STDOUT_PIPE | gio::SubprocessFlags::STDERR_PIPE,
+    )?;
+    let (stdout, stderr) = process.communicate_utf8_future(None).await?;
+
+    if !process.is_successful() {
+        let message = stderr
+            .as_ref()
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .unwrap_or_else(|| format!("exit status {}", process.exit_status()));
+        return Err(anyhow!("{} failed: {}", argv[0].to_string_lossy(), message));
+    }
+    Ok(stdout.map(|s| s.to_string()).unwrap_or_default())
+}
+
+// unzip takes member names as wildcard patterns, so escape the characters it
+// would match with to select the member itself
+fn zip_pattern(member: &str) -> String {
+    let mut pattern = String::with_capacity(member.len());
+    for c in member.chars() {
+        if matches!(c, '[' | ']' | '*' | '?' | '\\') {
+            pattern.push('\\');
+        }
+        pattern.push(c);
+    }
+    pattern
+}
+
+// Directory the members are extracted into, removed with what it holds
+struct TempDir(PathBuf);
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+// Record which archive member an event and its spans came from, unless the
+// capture already records a source of its own
+fn tag_source(event: &mut AuditEvent, member: &str) {
+    event
+        .events
+        .entry("source".to_string())
+        .or_insert_with(|| member.into());
+    for span in &mut event.spans {
+        tag_source(span, member);
+    }
+}
+
+// The events of every JSON capture in the archive, one per host, merged in
+// order of their start and tagged with the member they were read from. The
+// captures are extracted by tar or unzip at once into a temporary directory,
+// so a compressed archive is only decompressed once. Returns the merged events
+// along with the concatenated member contents, which identify the bundle.
+pub async fn read_bundle(path: &Path) -> Result<(Vec<AuditEvent>, String)> {
+    let format = format_of(path).ok_or_else(|| anyhow!("Not an archive"))?;
+    let archive = path.as_os_str();
+    let listing = match format {
+        Format::Tar => output(&[OsStr::new("tar"), OsStr::new("-tf"), archive]).await?,
+        Format::Zip => output(&[OsStr::new("unzip"), OsStr::new("-Z1"), archive]).await?,
+    };
+    let members: Vec<&str> = listing
+        .lines()
+        .filter(|member| member.to_lowercase().ends_with(".json"))
+        .collect();
+    if members.is_empty() {
+        return Err(anyhow!("The archive contains no audit files"));
+    }
+
+    let dir = glib::mkdtemp(glib::tmp_dir().join("crau-bundle-XXXXXX"))
+        .ok_or_else(|| anyhow!("Failed to create a temporary directory"))?;
+    let dir = TempDir(dir);
+    match format {
+        Format::Tar => {
+            // "--" keeps a member starting with a dash from being parsed as an option
+            let mut argv = vec![
+                OsStr::new("tar"),
+                OsStr::new("-xf"),
+                archive,
+                OsStr::new("-C"),
+                dir.0.as_os_str(),
+                OsStr::new("--"),
+            ];
+            argv.extend(members.iter().map(OsStr::new));
+            output(&argv).await?;
+        }
+        Format::Zip => {
+            let patterns: Vec<String> = members.iter().map(|member| zip_pattern(member)).collect();
+            let mut argv = vec![OsStr::new("unzip"), OsStr::new("-qq"), archive];
+            argv.extend(patterns.iter().map(OsStr::new));
+            argv.extend([OsStr::new("-d"), dir.0.as_os_str()]);
+            output(&argv).await?;
+        }
+    }
+
+    // Both tools drop the leading slash of absolute member names
+    let files: Vec<(String, PathBuf)> = members
+        .iter()
+        .map(|member| {
+            (
+                member.to_string(),
+                dir.0.join(member.trim_start_matches('/')),
+            )
+        })
+        .collect();
+    let contents = gio::spawn_blocking(move || {
+        files
+            .into_iter()
+            .map(|(member, file)| {
+                let content = fs::read_to_string(&file)
+                    .map_err(|e| anyhow!("Failed to read {}: {}", member, e))?;
+                Ok((member, content))
+            })
+            .collect::<Result<Vec<_>>>()
+    })
+    .await
+    .map_err(|_| anyhow!("Reading the archive was interrupted"))??;
+    drop(dir);
+
+    let mut events = Vec::new();
+    let mut all_contents = String::new();
+    for (member, content) in contents {
+        let capture =
+            Capture::parse(&content).map_err(|e| anyhow!("Failed to parse {}: {}", member, e))?;
+
+        // Name the host by the file, without the directories of the archive
+        let source = member.rsplit('/').next().unwrap_or(&member);
+        let mut member_events = capture.events;
+        for event in &mut member_events {
+            tag_source(event, source);
+        }
+        events.extend(member_events);
+        all_contents.push_str(&content);
+    }
+
+    if events.is_empty() {
+        return Err(anyhow!("The archive contains no audit files"));
+    }
+    events.sort_by_key(|event| event.start);
+    Ok((events, all_contents))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn archive_formats() {
+        assert_eq!(format_of(Path::new("hosts.tar.gz")), Some(Format::Tar));
+        assert_eq!(format_of(Path::new("hosts.tar.zst")), Some(Format::Tar));
+        assert_eq!(format_of(Path::new("hosts.ZIP")), Some(Format::Zip));
+        assert_eq!(format_of(Path::new("audit.json")), None);
+    }
+
+    #[test]
+    fn zip_patterns() {
+        assert_eq!(zip_pattern("host/audit.json"), "host/audit.json");
+        assert_eq!(zip_pattern("a[1]*?.json"), "a\\[1\\]\\*\\?.json");
+        assert_eq!(zip_pattern("C:\\audit.json"), "C:\\\\audit.json");
+    }
+
+    #[test]
+    fn source_tags() {
+        let mut event: AuditEvent = serde_json::from_value(serde_json::json!({
+            "context": "c",
+            "origin": "o",
+            "start": 0,
+            "end": 0,
+            "events": {"name": "tls::handshake_client"},
+            "spans": [{
+                "context": "c",
+                "origin": "o",
+                "start": 0,
+                "end": 0,
+                "events": {"name": "tls::handshake_server", "source": "proxy"}
+            }]
+        }))
+        .unwrap();
+        tag_source(&mut event, "web.json");
+        assert_eq!(event.events["source"], "web.json");
+        assert_eq!(event.spans[0].events["source"], "proxy");
+    }
+}
@@ -0,0 +1,271 @@
+use crate::compliance::{self, Target};
+use crate::data::AuditEvent;
+use crate::sunburst::SunburstChart;
+use adw::prelude::*;
+use adw::ActionRow;
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::rc::Rc;
+
+// An algorithm, protocol version or cipher suite a CBOM declares
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Declared {
+    pub name: String,
+    // "Algorithm", "Protocol" or "Cipher Suite"
+    pub kind: &'static str,
+}
+
+// Crypto assets of a CycloneDX component and the components nested in it
+fn collect_declared(component: &Value, declared: &mut Vec<Declared>) {
+    let properties = component.get("cryptoProperties");
+    if component.get("type").and_then(Value::as_str) == Some("cryptographic-asset") {
+        let name = component.get("name").and_then(Value::as_str);
+        match properties
+            .and_then(|p| p.get("assetType"))
+            .and_then(Value::as_str)
+        {
+            Some("algorithm") => {
+                if let Some(name) = name {
+                    declared.push(Declared {
+                        name: name.to_string(),
+                        kind: "Algorithm",
+                    });
+                }
+            }
+            Some("protocol") => {
+                let protocol = properties.and_then(|p| p.get("protocolProperties"));
+                let get = |key: &str| protocol.and_then(|p| p.get(key)).and_then(Value::as_str);
+                // e.g. "TLS 1.3" from the type "tls" and version "1.3"
+                if let (Some(kind), Some(version)) = (get("type"), get("version")) {
+                    declared.push(Declared {
+                        name: format!("{} {}", kind.to_uppercase(), version),
+                        kind: "Protocol",
+                    });
+                }
+                let suites = protocol
+                    .and_then(|p| p.get("cipherSuites"))
+                    .and_then(Value::as_array);
+                for suite in suites.into_iter().flatten() {
+                    if let Some(name) = suite.get("name").and_then(Value::as_str) {
+                        declared.push(Declared {
+                            name: name.to_string(),
+                            kind: "Cipher Suite",
+                        });
+                    }
+                }
+            }
+            // Certificates and key material aren't observed as algorithms
+            _ => {}
+        }
+    }
+    let children = component.get("components").and_then(Value::as_array);
+    for child in children.into_iter().flatten() {
+        collect_declared(child, declared);
+    }
+}
+
+// Read the algorithms, protocol versions and cipher suites a CycloneDX
+// CBOM declares
+pub fn load_cbom(path: &Path) -> Result<Vec<Declared>> {
+    let bom: Value = serde_json::from_str(&fs::read_to_string(path)?)?;
+    if bom.get("bomFormat").and_then(Value::as_str) != Some("CycloneDX") {
+        return Err(anyhow!("Not a CycloneDX BOM"));
+    }
+    let mut declared = Vec::new();
+    collect_declared(&bom, &mut declared);
+    if declared.is_empty() {
+        return Err(anyhow!("The BOM declares no cryptographic assets"));
+    }
+    let mut seen = HashSet::new();
+    declared.retain(|d| seen.insert((d.name.clone(), d.kind)));
+    Ok(declared)
+}
+
+// Whether a declared name stands for an observed one: the same letters and
+// digits, e.g. "SHA-256" and "SHA256", or a run of its tokens, e.g. "ECDSA"
+// for "ecdsa_secp256r1_sha256" or "AES" for "AES-256-GCM"
+fn covers(declared: &str, observed: &str) -> bool {
+    let declared = compliance::tokens(declared);
+    let observed = compliance::tokens(observed);
+    !declared.is_empty()
+        && (declared.concat() == observed.concat()
+            || observed
+                .windows(declared.len())
+                .any(|run| run == declared.as_slice()))
+}
+
+// Usages of the events by label, with their kind and number of events
+fn observed(events: &[AuditEvent], usages: &mut HashMap<String, (Target, usize)>) {
+    for event in events {
+        for usage in compliance::usages(&event.name(), &event.events) {
+            usages.entry(usage.label()).or_insert((usage.target, 0)).1 += 1;
+        }
+        observed(&event.spans, usages);
+    }
+}
+
+// Gap between a CBOM and the view
+pub struct Gap {
+    pub unobserved: Vec<Declared>,
+    // Observed usages no declaration covers, with their kind and count
+    pub undeclared: Vec<(String, Target, usize)>,
+}
+
+pub fn cross_check(declared: &[Declared], events: &[AuditEvent]) -> Gap {
+    let mut usages = HashMap::new();
+    observed(events, &mut usages);
+
+    let unobserved = declared
+        .iter()
+        .filter(|declared| !usages.keys().any(|name| covers(&declared.name, name)))
+        .cloned()
+        .collect();
+    let mut undeclared: Vec<(String, Target, usize)> = usages
+        .into_iter()
+        .filter(|(name, _)| !declared.iter().any(|d| covers(&d.name, name)))
+        .map(|(name, (target, count))| (name, target, count))
+        .collect();
+    undeclared.sort_by(|a, b| b.2.cmp(&a.2).then_with(|| a.0.cmp(&b.0)));
+    Gap {
+        unobserved,
+        undeclared,
+    }
+}
+
+// Sidebar section comparing an imported CBOM with the view, shown once one
+// is imported
+pub struct CbomSection {
+    pub widget: gtk4::Box,
+    declared: Rc<RefCell<Option<Vec<Declared>>>>,
+    update: Rc<dyn Fn()>,
+}
+
+impl CbomSection {
+    // Compare against the declarations from now on, or stop for None
+    pub fn set_declared(&self, declared: Option<Vec<Declared>>) {
+        *self.declared.borrow_mut() = declared;
+        (self.update)();
+    }
+}
+
+fn append_rows(list: &gtk4::ListBox, rows: impl IntoIterator<Item = (String, String, String)>) {
+    for (title, subtitle, suffix) in rows {
+        let row = ActionRow::builder()
+            .title(&title)
+            .subtitle(&subtitle)
+            .use_markup(false)
+            .build();
+        let suffix = gtk4::Label::new(Some(&suffix));
+        suffix.add_css_class("dim-label");
+        row.add_suffix(&suffix);
+        list.append(&row);
+    }
+}
+
+fn create_list(section: &gtk4::Box, heading: &str) -> (gtk4::Label, gtk4::ListBox) {
+    let label = gtk4::Label::new(Some(heading));
+    label.set_halign(gtk4::Align::Start);
+    label.add_css_class("heading");
+    section.append(&label);
+
+    let list = gtk4::ListBox::new();
+    list.set_selection_mode(gtk4::SelectionMode::None);
+    list.add_css_class("boxed-list");
+    section.append(&list);
+    (label, list)
+}
+
+// Declared-but-unobserved and observed-but-undeclared algorithms of the
+// view against an imported CBOM
+pub fn create_cbom_section(chart: Rc<SunburstChart>) -> CbomSection {
+    let section = gtk4::Box::new(gtk4::Orientation::Vertical, 6);
+    section.set_margin_start(12);
+    section.set_margin_end(12);
+    section.set_margin_top(12);
+    section.set_margin_bottom(12);
+    section.set_visible(false);
+
+    let header = gtk4::Box::new(gtk4::Orientation::Horizontal, 6);
+    let title = gtk4::Label::new(Some("CBOM Cross-Check"));
+    title.set_halign(gtk4::Align::Start);
+    title.set_hexpand(true);
+    title.add_css_class("title-4");
+    header.append(&title);
+    let clear_button = gtk4::Button::from_icon_name("window-close-symbolic");
+    clear_button.add_css_class("flat");
+    clear_button.set_tooltip_text(Some("Stop Comparing with the CBOM"));
+    header.append(&clear_button);
+    section.append(&header);
+
+    let summary = gtk4::Label::new(None);
+    summary.set_halign(gtk4::Align::Start);
+    summary.set_wrap(true);
+    section.append(&summary);
+
+    let (unobserved_label, unobserved_list) = create_list(&section, "Declared but Not Observed");
+    let (undeclared_label, undeclared_list) = create_list(&section, "Observed but Not Declared");
+
+    let declared: Rc<RefCell<Option<Vec<Declared>>>> = Rc::new(RefCell::new(None));
+    let update = {
+        let section = section.clone();
+        let declared = declared.clone();
+        let chart = chart.clone();
+        move || {
+            for list in [&unobserved_list, &undeclared_list] {
+                while let Some(row) = list.first_child() {
+                    list.remove(&row);
+                }
+            }
+            let declared = declared.borrow();
+            let Some(declared) = declared.as_ref() else {
+                section.set_visible(false);
+                return;
+            };
+            section.set_visible(true);
+
+            let gap = cross_check(declared, &chart.visible_events());
+            summary.set_text(&format!(
+                "{} of {} declared assets not observed, {} observed algorithms not declared",
+                gap.unobserved.len(),
+                declared.len(),
+                gap.undeclared.len()
+            ));
+            unobserved_label.set_visible(!gap.unobserved.is_empty());
+            unobserved_list.set_visible(!gap.unobserved.is_empty());
+            undeclared_label.set_visible(!gap.undeclared.is_empty());
+            undeclared_list.set_visible(!gap.undeclared.is_empty());
+            append_rows(
+                &unobserved_list,
+                gap.unobserved
+                    .into_iter()
+                    .map(|d| (d.name, d.kind.to_string(), String::new())),
+            );
+            append_rows(
+                &undeclared_list,
+                gap.undeclared.into_iter().map(|(name, target, count)| {
+                    (name, target.label().to_string(), count.to_string())
+                }),
+            );
+        }
+    };
+    let update: Rc<dyn Fn()> = Rc::new(update);
+
+    let update_clone = update.clone();
+    chart.connect_view_changed(move |_| update_clone());
+    let declared_clone = declared.clone();
+    let update_clone = update.clone();
+    clear_button.connect_clicked(move |_| {
+        *declared_clone.borrow_mut() = None;
+        update_clone();
+    });
+
+    CbomSection {
+        widget: section,
+        declared,
+        update,
+    }
+}
@@ -0,0 +1,118 @@
+use crate::data::{NodeKind, TreeNode};
+
+// A TLS handshake with the primitive operations it triggered, in time order
+pub struct Chain {
+    pub handshake: String,
+    pub steps: Vec<String>,
+}
+
+fn is_handshake(node: &TreeNode) -> bool {
+    node.kind == NodeKind::Event
+        && node
+            .event_name()
+            .is_some_and(|name| name.starts_with("tls::handshake_"))
+}
+
+// Public key and key exchange operations, which handshakes exercise
+fn is_operation(node: &TreeNode) -> bool {
+    node.kind == NodeKind::Event
+        && node.event_name().is_some_and(|name| {
+            name.starts_with("pk::")
+                || name.starts_with("kex::")
+                || matches!(name, "tls::key_exchange" | "tls::sign" | "tls::verify")
+        })
+}
+
+fn step_label(node: &TreeNode) -> String {
+    if node.count > 1 {
+        format!("{} ×{}", node.display_name(), node.count)
+    } else {
+        node.display_name()
+    }
+}
+
+fn start_of(node: &TreeNode) -> u64 {
+    node.time_range.map_or(0, |(start, _)| start)
+}
+
+// Collect the handshakes and operations of a scope. Operations nested in a
+// handshake remember the innermost one.
+fn collect<'a>(
+    node: &'a TreeNode,
+    enclosing: Option<usize>,
+    handshakes: &mut Vec<&'a TreeNode>,
+    operations: &mut Vec<(Option<usize>, &'a TreeNode)>,
+) {
+    let mut enclosing = enclosing;
+    if is_handshake(node) {
+        handshakes.push(node);
+        enclosing = Some(handshakes.len() - 1);
+    } else if is_operation(node) {
+        operations.push((enclosing, node));
+    }
+    for child in &node.children {
+        collect(child, enclosing, handshakes, operations);
+    }
+}
+
+// Link the operations of one context to its handshakes: those nested in a
+// handshake belong to it, others to the latest handshake running when they started
+fn scope_chains(scope: &TreeNode, chains: &mut Vec<Chain>) {
+    let mut handshakes = Vec::new();
+    let mut operations = Vec::new();
+    collect(scope, None, &mut handshakes, &mut operations);
+
+    let mut steps: Vec<Vec<&TreeNode>> = vec![Vec::new(); handshakes.len()];
+    for (enclosing, operation) in operations {
+        let start = start_of(operation);
+        let owner = enclosing.or_else(|| {
+            handshakes
+                .iter()
+                .enumerate()
+                .filter(|(_, handshake)| {
+                    handshake
+                        .time_range
+                        .is_some_and(|(first, last)| (first..=last).contains(&start))
+                })
+                .max_by_key(|(_, handshake)| start_of(handshake))
+                .map(|(i, _)| i)
+        });
+        if let Some(owner) = owner {
+            steps[owner].push(operation);
+        }
+    }
+
+    for (handshake, mut handshake_steps) in handshakes.into_iter().zip(steps) {
+        handshake_steps.sort_by_key(|node| start_of(node));
+        chains.push(Chain {
+            handshake: step_label(handshake),
+            steps: handshake_steps.into_iter().map(step_label).collect(),
+        });
+    }
+}
+
+fn find_contexts<'a>(node: &'a TreeNode, contexts: &mut Vec<&'a TreeNode>) {
+    if node.kind == NodeKind::Context {
+        contexts.push(node);
+        return;
+    }
+    for child in &node.children {
+        find_contexts(child, contexts);
+    }
+}
+
+// Chains of the handshakes below a node, linked within each context. A node
+// inside a context is a scope of its own.
+pub fn handshake_chains(node: &TreeNode) -> Vec<Chain> {
+    let mut contexts = Vec::new();
+    find_contexts(node, &mut contexts);
+    if contexts.is_empty() {
+        contexts.push(node);
+    }
+
+    let mut chains = Vec::new();
+    for context in contexts {
+        scope_chains(context, &mut chains);
+    }
+    chains
+}
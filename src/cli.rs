@@ -0,0 +1,149 @@
+use crate::compliance::{Profile, Severity};
+use crate::data::Capture;
+use crate::decoders;
+use crate::profiles;
+use crate::query::Query;
+use anyhow::{anyhow, bail, Result};
+use gtk4::glib;
+use std::fs;
+use std::path::Path;
+
+const USAGE: &str = "Usage: crypto-usage-analyzer --check [--profile NAME|FILE] [--fail-on warning|error] [--query EXPRESSION] FILE";
+
+// Exit codes of the check, so CI pipelines can gate on them
+const EXIT_OK: i32 = 0;
+const EXIT_VIOLATIONS: i32 = 1;
+const EXIT_FAILURE: i32 = 2;
+
+struct Options {
+    path: String,
+    profile: Option<String>,
+    fail_on: Severity,
+    query: Option<String>,
+}
+
+fn parse_options(args: &[String]) -> Result<Options> {
+    let mut options = Options {
+        path: String::new(),
+        profile: None,
+        fail_on: Severity::Error,
+        query: None,
+    };
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .cloned()
+                .ok_or_else(|| anyhow!("{} needs a value", arg))
+        };
+        match arg.as_str() {
+            "--check" => {}
+            "--profile" => options.profile = Some(value()?),
+            "--fail-on" => {
+                let name = value()?;
+                options.fail_on = Severity::from_name(&name)
+                    .filter(|severity| *severity != Severity::Ok)
+                    .ok_or_else(|| anyhow!("Unknown severity \"{}\"", name))?;
+            }
+            "--query" => options.query = Some(value()?),
+            _ if arg.starts_with("--") => bail!("Unknown option {}", arg),
+            _ if options.path.is_empty() => options.path = arg.clone(),
+            _ => bail!("Only one file can be checked"),
+        }
+    }
+    if options.path.is_empty() {
+        bail!("No file to check");
+    }
+    Ok(options)
+}
+
+// Whether the arguments ask for a headless check instead of the window
+pub fn is_headless(args: &[String]) -> bool {
+    args.iter().any(|arg| arg == "--check")
+}
+
+// Check a capture against a policy without opening a window, printing what
+// violates it. Exits with 1 if anything of the severity or worse was found:
+// violations are errors, except deprecated usage under the rules of a
+// standard, and events the decoders don't recognize, which thus can't be
+// checked, are warnings. Exits with 2 if the capture can't be checked at all.
+pub fn run(args: &[String]) -> glib::ExitCode {
+    match check(args) {
+        Ok(failed) => glib::ExitCode::from(if failed { EXIT_VIOLATIONS } else { EXIT_OK }),
+        Err(e) => {
+            eprintln!("{}", e);
+            eprintln!("{}", USAGE);
+            glib::ExitCode::from(EXIT_FAILURE)
+        }
+    }
+}
+
+fn check(args: &[String]) -> Result<bool> {
+    let options = parse_options(args)?;
+    let profile = match &options.profile {
+        // A policy file by its path, or a built-in or custom profile by its name
+        Some(name) if Path::new(name).is_file() => Profile::load(Path::new(name))?,
+        Some(name) => Profile::builtin(&name.to_uppercase())
+            .or_else(|| Profile::named(name))
+            .ok_or_else(|| anyhow!("Unknown profile \"{}\"", name))?,
+        None => Profile::system()
+            .or_else(|| Profile::builtin("DEFAULT"))
+            .ok_or_else(|| anyhow!("No profile to check against"))?,
+    };
+
+    let content = fs::read_to_string(&options.path)
+        .map_err(|e| anyhow!("Failed to read {}: {}", options.path, e))?;
+    let mut events = Capture::parse(&content)
+        .map_err(|e| anyhow!("Failed to parse {}: {}", options.path, e))?
+        .events;
+    profiles::normalize(&mut events);
+    if let Some(query) = &options.query {
+        events = Query::parse(query)?.filter(&events);
+    }
+
+    let violations = profile.check(&events);
+    for violation in &violations {
+        println!(
+            "{}\t{}\t{}\t{}",
+            violation.severity.name(),
+            violation.origin,
+            violation.count,
+            violation.description
+        );
+    }
+    let unknown = decoders::collect_unknown(&events);
+    for code in &unknown {
+        println!(
+            "{}\t{}\t{}\tunknown {}",
+            Severity::Warning.name(),
+            code.field,
+            code.count,
+            code.code
+        );
+    }
+    eprintln!(
+        "{} violations of {}, {} unknown codes",
+        violations.len(),
+        profile.name,
+        unknown.len()
+    );
+    if !profile.rules.is_empty() {
+        let counts = profile.count_usages(&events);
+        let count = |severity| counts.get(&severity).copied().unwrap_or(0);
+        eprintln!(
+            "{} acceptable, {} deprecated, {} disallowed usages",
+            count(Severity::Ok),
+            count(Severity::Warning),
+            count(Severity::Error)
+        );
+    }
+
+    let worst = if let Some(worst) = violations.iter().map(|v| v.severity).max() {
+        worst
+    } else if !unknown.is_empty() {
+        Severity::Warning
+    } else {
+        Severity::Ok
+    };
+    Ok(worst >= options.fail_on)
+}
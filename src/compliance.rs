@@ -0,0 +1,935 @@
+use crate::data::{AuditEvent, NodeKind, TreeNode};
+use crate::decoders;
+use crate::ike;
+use crate::standards;
+use anyhow::{anyhow, Result};
+use gtk4::glib;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+// File written by update-crypto-policies naming the active policy, e.g. "DEFAULT:SHA1"
+const CURRENT_POLICY_PATH: &str = "/etc/crypto-policies/state/current";
+
+// How problematic an observed usage is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Ok,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "ok" => Some(Self::Ok),
+            "warning" => Some(Self::Warning),
+            "error" => Some(Self::Error),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Ok => "ok",
+            Self::Warning => "warning",
+            Self::Error => "error",
+        }
+    }
+}
+
+// Algorithm restrictions of a policy level, modelled after the
+// Fedora/RHEL crypto-policies definitions. Custom profiles are saved as JSON
+// of this in the profiles directory, where missing fields restrict nothing.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Profile {
+    pub name: String,
+    // Set when the restrictions only approximate the named policy
+    #[serde(skip)]
+    pub approximated: bool,
+    pub min_tls_version: u64,
+    pub min_rsa_bits: u64,
+    pub denied_groups: Vec<String>,
+    pub denied_signatures: Vec<String>,
+    // Cipher weaknesses as reported by decoders::cipher_weaknesses
+    pub denied_ciphers: Vec<String>,
+    // Hash functions as reported by decoders::hash_algorithm
+    pub denied_hashes: Vec<String>,
+    // Lowest acceptable PBKDF2 iteration count
+    pub min_kdf_iterations: u64,
+    // Only allow SP 800-90A DRBGs as random generators
+    pub require_drbg: bool,
+    // Rules of a standard, checked on top of the restrictions above
+    pub rules: Vec<Rule>,
+}
+
+// Kind of algorithm a rule restricts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Target {
+    Protocol,
+    KeyExchange,
+    Signature,
+    Cipher,
+    Hash,
+    Mac,
+    PublicKey,
+    Random,
+}
+
+impl Target {
+    pub const ALL: [Target; 8] = [
+        Self::Protocol,
+        Self::KeyExchange,
+        Self::Signature,
+        Self::Cipher,
+        Self::Hash,
+        Self::Mac,
+        Self::PublicKey,
+        Self::Random,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Protocol => "Protocol Version",
+            Self::KeyExchange => "Key Exchange",
+            Self::Signature => "Signature",
+            Self::Cipher => "Cipher",
+            Self::Hash => "Hash",
+            Self::Mac => "MAC",
+            Self::PublicKey => "Public Key",
+            Self::Random => "Random Generator",
+        }
+    }
+
+    // What a usage of the kind is called in findings, e.g. "RSA-1024 key"
+    fn noun(&self) -> &'static str {
+        match self {
+            Self::Protocol => "protocol",
+            Self::KeyExchange => "key exchange",
+            Self::Signature => "signature",
+            Self::Cipher => "cipher",
+            Self::Hash => "hash",
+            Self::Mac => "MAC",
+            Self::PublicKey => "key",
+            Self::Random => "random generator",
+        }
+    }
+}
+
+// A rule of a standard. Deny rules flag the usages they match; allow rules
+// flag every usage of their kind that no allow rule of the kind matches.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Rule {
+    pub target: Target,
+    #[serde(default)]
+    pub allow: bool,
+    // Name the rule matches, by its tokens, e.g. "SHA1" matches
+    // "rsa_pkcs1_sha1" and "TLS 1.0" matches "TLS 1.0"; "*" matches any
+    pub pattern: String,
+    // Size in bits denied below, or allowed from
+    #[serde(default)]
+    pub min_bits: Option<u64>,
+    pub severity: Severity,
+    // Year from which the rule applies, for scheduled transitions
+    #[serde(default)]
+    pub from_year: Option<i16>,
+    // Shown with the usages the rule flags, e.g. "disallowed after 2023"
+    #[serde(default)]
+    pub note: String,
+}
+
+// An algorithm an event uses, as rules are checked against
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Usage {
+    pub target: Target,
+    pub name: String,
+    pub bits: Option<u64>,
+}
+
+impl Usage {
+    fn new(target: Target, name: impl Into<String>, bits: Option<u64>) -> Self {
+        Self {
+            target,
+            name: name.into(),
+            bits,
+        }
+    }
+
+    // Name with the size, e.g. "RSA-2048"; sizes already in the name aren't
+    // repeated, as in "AES-256"
+    pub fn label(&self) -> String {
+        match self.bits {
+            Some(bits) if !tokens(&self.name).contains(&bits.to_string()) => {
+                format!("{}-{}", self.name, bits)
+            }
+            _ => self.name.clone(),
+        }
+    }
+
+    pub fn description(&self) -> String {
+        format!("{} {}", self.label(), self.target.noun())
+    }
+}
+
+// Letters and digits of a name in runs, uppercased, e.g. "ECDSA-SHA1" gives
+// ["ECDSA", "SHA1"]
+pub fn tokens(name: &str) -> Vec<String> {
+    name.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|token| !token.is_empty())
+        .map(str::to_uppercase)
+        .collect()
+}
+
+impl Rule {
+    pub fn in_force(&self, year: i16) -> bool {
+        self.from_year.is_none_or(|from| year >= from)
+    }
+
+    // Whether a usage has the name and, for deny rules, is too small
+    fn matches(&self, usage: &Usage) -> bool {
+        if self.target != usage.target {
+            return false;
+        }
+        if self.pattern != "*" {
+            let pattern = tokens(&self.pattern);
+            let name = tokens(&usage.name);
+            if pattern.is_empty() || !name.windows(pattern.len()).any(|run| run == pattern) {
+                return false;
+            }
+        }
+        match (self.min_bits, usage.bits) {
+            (Some(min), Some(bits)) if self.allow => bits >= min,
+            (Some(min), Some(bits)) => bits < min,
+            // Rules on sizes only apply to sizes that are known
+            (Some(_), None) => false,
+            (None, _) => true,
+        }
+    }
+}
+
+// Policy levels of crypto-policies that have built-in restrictions
+pub const POLICIES: [&str; 4] = ["LEGACY", "DEFAULT", "FUTURE", "FIPS"];
+
+// Names of all built-in profiles: the policy levels, then the standards
+pub fn builtin_names() -> Vec<&'static str> {
+    POLICIES
+        .iter()
+        .chain(standards::NAMES.iter())
+        .copied()
+        .collect()
+}
+
+// Profile checked instead of the system policy, as chosen in the preferences
+static SELECTED: RwLock<Option<Profile>> = RwLock::new(None);
+
+// Usage observed in the capture that the profile should have prevented
+#[derive(Debug, Clone)]
+pub struct Violation {
+    pub severity: Severity,
+    pub origin: String,
+    pub description: String,
+    pub count: usize,
+}
+
+impl Profile {
+    // Load the policy currently active on this system, if crypto-policies is installed.
+    // Subpolicy modifiers (":SHA1", ":NO-ENFORCE-EMS", ...) are ignored and custom
+    // policies are checked with the DEFAULT restrictions.
+    pub fn system() -> Option<Self> {
+        let content = fs::read_to_string(CURRENT_POLICY_PATH).ok()?;
+        let base = content.trim().split(':').next()?.to_string();
+        Self::builtin(&base).or(Some(Self {
+            name: base,
+            approximated: true,
+            ..Self::builtin("DEFAULT")?
+        }))
+    }
+
+    // Check against the profile from now on, or the system policy for None
+    pub fn select(profile: Option<Profile>) {
+        *SELECTED.write().unwrap() = profile;
+    }
+
+    // Profile the views check against: the selected one, if any, or the
+    // policy of the system
+    pub fn active() -> Option<Self> {
+        SELECTED.read().unwrap().clone().or_else(Self::system)
+    }
+
+    // Restrictions nothing violates, which profiles of standards add rules to
+    pub fn permissive(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ..Self::default()
+        }
+    }
+
+    // A built-in profile or a custom one of the name
+    pub fn named(name: &str) -> Option<Self> {
+        Self::builtin(name).or_else(|| {
+            Self::custom_profiles()
+                .into_iter()
+                .find(|profile| profile.name == name)
+        })
+    }
+
+    fn custom_dir() -> PathBuf {
+        glib::user_config_dir()
+            .join("crypto-usage-analyzer")
+            .join("profiles")
+    }
+
+    // Profiles saved with the editor, by name; unreadable files are skipped
+    pub fn custom_profiles() -> Vec<Self> {
+        let Ok(entries) = fs::read_dir(Self::custom_dir()) else {
+            return Vec::new();
+        };
+        let mut profiles: Vec<Self> = entries
+            .filter_map(Result::ok)
+            .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|entry| Self::load(&entry.path()).ok())
+            .collect();
+        profiles.sort_by(|a, b| a.name.cmp(&b.name));
+        profiles
+    }
+
+    // Read a profile from a policy file
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+        let profile: Self = serde_json::from_str(&content)
+            .map_err(|e| anyhow!("Invalid profile {}: {}", path.display(), e))?;
+        if profile.name.is_empty() {
+            return Err(anyhow!("Profile {} has no name", path.display()));
+        }
+        Ok(profile)
+    }
+
+    // Save a custom profile in the profiles directory, replacing the one of
+    // the same name
+    pub fn save_custom(&self) -> Result<PathBuf> {
+        if Self::builtin(&self.name).is_some() {
+            return Err(anyhow!("{} is the name of a built-in profile", self.name));
+        }
+        let file_name: String = self
+            .name
+            .chars()
+            .map(|c| {
+                if c.is_alphanumeric() || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        let dir = Self::custom_dir();
+        fs::create_dir_all(&dir)?;
+        let path = dir.join(format!("{}.json", file_name));
+        fs::write(&path, serde_json::to_string_pretty(self)?)?;
+        Ok(path)
+    }
+
+    pub fn builtin(name: &str) -> Option<Self> {
+        if let Some(profile) = standards::profile(name) {
+            return Some(profile);
+        }
+        let sha1_signatures = ["rsa_pkcs1_sha1", "ecdsa_sha1"];
+        let draft_groups = ["X25519Kyber768Draft00"];
+        let broken_ciphers = ["NULL", "RC4", "DES"];
+        let broken_hashes = ["MD2", "MD4", "MD5"];
+
+        // SP 800-132 asks for at least 1000 PBKDF2 iterations
+        let (
+            min_tls_version,
+            min_rsa_bits,
+            min_kdf_iterations,
+            denied_groups,
+            denied_signatures,
+            denied_ciphers,
+        ): (u64, u64, u64, Vec<&str>, Vec<&str>, Vec<&str>) = match name {
+            "LEGACY" => (
+                0x0303,
+                2048,
+                1000,
+                draft_groups.to_vec(),
+                Vec::new(),
+                broken_ciphers.to_vec(),
+            ),
+            "DEFAULT" => (
+                0x0303,
+                2048,
+                1000,
+                draft_groups.to_vec(),
+                sha1_signatures.to_vec(),
+                [&broken_ciphers[..], &["3DES"]].concat(),
+            ),
+            "FUTURE" => (
+                0x0303,
+                3072,
+                10000,
+                [&draft_groups[..], &["ffdhe2048"]].concat(),
+                [&sha1_signatures[..], &["rsa_pkcs1_sha256"]].concat(),
+                [&broken_ciphers[..], &["3DES", "CBC-SHA1"]].concat(),
+            ),
+            "FIPS" => (
+                0x0303,
+                2048,
+                1000,
+                [&draft_groups[..], &["x25519", "x448", "X25519MLKEM768"]].concat(),
+                [&sha1_signatures[..], &["ed25519", "ed448"]].concat(),
+                [&broken_ciphers[..], &["3DES", "CBC-SHA1"]].concat(),
+            ),
+            _ => return None,
+        };
+        let denied_hashes = match name {
+            "LEGACY" => broken_hashes.to_vec(),
+            "FUTURE" => [&broken_hashes[..], &["SHA1", "SHA224", "SHA3-224"]].concat(),
+            _ => [&broken_hashes[..], &["SHA1"]].concat(),
+        };
+
+        Some(Self {
+            name: name.to_string(),
+            approximated: false,
+            min_tls_version,
+            min_rsa_bits,
+            min_kdf_iterations,
+            denied_groups: denied_groups.iter().map(|s| s.to_string()).collect(),
+            denied_signatures: denied_signatures.iter().map(|s| s.to_string()).collect(),
+            denied_ciphers: denied_ciphers.iter().map(|s| s.to_string()).collect(),
+            denied_hashes: denied_hashes.iter().map(|s| s.to_string()).collect(),
+            require_drbg: name == "FIPS",
+            rules: Vec::new(),
+        })
+    }
+
+    // Check all events (including nested spans) against the profile.
+    // Violations are aggregated per origin and sorted by severity, then
+    // count, descending.
+    pub fn check(&self, events: &[AuditEvent]) -> Vec<Violation> {
+        let mut counts: HashMap<(Severity, String, String), usize> = HashMap::new();
+
+        fn visit(
+            profile: &Profile,
+            event: &AuditEvent,
+            counts: &mut HashMap<(Severity, String, String), usize>,
+        ) {
+            for (severity, description) in profile.check_event(event) {
+                *counts
+                    .entry((severity, event.origin.clone(), description))
+                    .or_insert(0) += 1;
+            }
+            for span in &event.spans {
+                visit(profile, span, counts);
+            }
+        }
+
+        for event in events {
+            visit(self, event, &mut counts);
+        }
+
+        let mut violations: Vec<Violation> = counts
+            .into_iter()
+            .map(|((severity, origin, description), count)| Violation {
+                severity,
+                origin,
+                description,
+                count,
+            })
+            .collect();
+        violations.sort_by(|a, b| {
+            b.severity
+                .cmp(&a.severity)
+                .then_with(|| b.count.cmp(&a.count))
+                .then_with(|| a.description.cmp(&b.description))
+        });
+        violations
+    }
+
+    // Set the severity of every node in the tree and return the root's.
+    // Events violating the profile take the severity of the worst violation,
+    // events the decoders don't recognize (and thus can't be checked) are
+    // warnings; branches take the worst severity below them.
+    pub fn classify(&self, node: &mut TreeNode) -> Severity {
+        let mut severity = match node.event_name() {
+            Some(name) if node.kind == NodeKind::Event => {
+                let worst = self
+                    .check_fields(name, &node.fields)
+                    .into_iter()
+                    .map(|(severity, _)| severity)
+                    .max();
+                match worst {
+                    Some(severity) => severity,
+                    None if !decoders::is_known_event(name) => Severity::Warning,
+                    None => Severity::Ok,
+                }
+            }
+            _ => Severity::Ok,
+        };
+        for child in &mut node.children {
+            severity = severity.max(self.classify(child));
+        }
+        node.severity = Some(severity);
+        severity
+    }
+
+    fn check_event(&self, event: &AuditEvent) -> Vec<(Severity, String)> {
+        self.check_fields(&event.name(), &event.events)
+    }
+
+    // Count the usages of all events by the severity of the worst rule they
+    // match, Ok for those no rule flags, e.g. acceptable, deprecated and
+    // disallowed usages for a transition schedule
+    pub fn count_usages(&self, events: &[AuditEvent]) -> HashMap<Severity, usize> {
+        fn visit(profile: &Profile, event: &AuditEvent, counts: &mut HashMap<Severity, usize>) {
+            for usage in usages(&event.name(), &event.events) {
+                let severity = profile
+                    .judge(&usage)
+                    .map_or(Severity::Ok, |rule| rule.severity);
+                *counts.entry(severity).or_insert(0) += 1;
+            }
+            for span in &event.spans {
+                visit(profile, span, counts);
+            }
+        }
+
+        let mut counts = HashMap::new();
+        for event in events {
+            visit(self, event, &mut counts);
+        }
+        counts
+    }
+
+    // Worst rule in force a usage breaks, if any
+    fn judge(&self, usage: &Usage) -> Option<&Rule> {
+        let year = jiff::Zoned::now().year();
+        let rules: Vec<&Rule> = self
+            .rules
+            .iter()
+            .filter(|rule| rule.target == usage.target && rule.in_force(year))
+            .collect();
+
+        let mut broken: Vec<&Rule> = rules
+            .iter()
+            .filter(|rule| !rule.allow && rule.matches(usage))
+            .copied()
+            .collect();
+        let allows: Vec<&Rule> = rules.iter().filter(|rule| rule.allow).copied().collect();
+        if !allows.is_empty() && !allows.iter().any(|rule| rule.matches(usage)) {
+            broken.extend(allows);
+        }
+        broken.into_iter().max_by_key(|rule| rule.severity)
+    }
+
+    // Describe every restriction of the profile an event with these fields
+    // violates, with how severe it is
+    fn check_fields(&self, name: &str, fields: &HashMap<String, Value>) -> Vec<(Severity, String)> {
+        let mut violations = Vec::new();
+        let get_u64 = |key: &str| fields.get(key).and_then(|v| v.as_u64());
+
+        if let Some(version) = get_u64("tls::protocol_version") {
+            if version < self.min_tls_version {
+                let name = decoders::protocol_version_name(version)
+                    .map(|n| n.to_string())
+                    .unwrap_or_else(|| format!("version {}", version));
+                violations.push(format!("{} handshake", name));
+            }
+        }
+
+        if let Some(group) = get_u64("tls::group").and_then(decoders::group_name) {
+            if self.denied_groups.iter().any(|g| g == group) {
+                violations.push(format!("{} key exchange", group));
+            }
+        }
+
+        if let Some(sig) =
+            get_u64("tls::signature_algorithm").and_then(decoders::signature_scheme_name)
+        {
+            if self.denied_signatures.iter().any(|s| s == sig) {
+                violations.push(format!("{} signature", sig));
+            }
+        }
+
+        let cipher = get_u64("tls::ciphersuite")
+            .and_then(decoders::ciphersuite_name)
+            .map(|name| name.to_string())
+            .or_else(|| decoders::cipher_name(fields));
+        if let Some(cipher) = cipher {
+            let denied: Vec<&str> = decoders::cipher_weaknesses(&cipher)
+                .into_iter()
+                .filter(|w| self.denied_ciphers.iter().any(|d| d == w))
+                .collect();
+            if !denied.is_empty() {
+                violations.push(format!("{} cipher ({})", cipher, denied.join(", ")));
+            }
+        }
+
+        if decoders::is_hash_event(name) {
+            if let Some(hash) = decoders::hash_algorithm(fields) {
+                if self.denied_hashes.contains(&hash) {
+                    violations.push(format!("{} hash", hash));
+                }
+            }
+        }
+
+        if decoders::is_ssh_event(name) {
+            for (field, algorithm) in decoders::ssh_algorithms(fields) {
+                let what = match field {
+                    "ssh::kex_algorithm" => "SSH key exchange",
+                    "ssh::host_key_algorithm" => "SSH host key",
+                    "ssh::cipher" => "SSH cipher",
+                    _ => "SSH MAC",
+                };
+                if let Some(hash) = decoders::ssh_weak_hash(&algorithm) {
+                    if self.denied_hashes.iter().any(|h| h == hash) {
+                        violations.push(format!("{} {} ({})", algorithm, what, hash));
+                    }
+                }
+                // OpenSSH calls RC4 arcfour, no encryption none and 1024-bit DH group1
+                let mut cipher = algorithm.to_lowercase().replace("arcfour", "rc4-");
+                if field == "ssh::cipher" && cipher == "none" {
+                    cipher = "null".to_string();
+                }
+                let mut weaknesses: Vec<&str> = decoders::cipher_weaknesses(&cipher)
+                    .into_iter()
+                    .filter(|w| self.denied_ciphers.iter().any(|d| d == w))
+                    .collect();
+                if field == "ssh::kex_algorithm" && cipher.contains("group1-") {
+                    weaknesses.push("1024-bit group");
+                }
+                if field != "ssh::mac" && !weaknesses.is_empty() {
+                    violations.push(format!(
+                        "{} {} ({})",
+                        algorithm,
+                        what,
+                        weaknesses.join(", ")
+                    ));
+                }
+            }
+        }
+
+        if let Some(namespace) = decoders::ike_namespace(name) {
+            let protocol = if namespace == "ike::" { "IKE" } else { "IPsec" };
+            for (kind, transform) in ike::transforms(namespace, fields) {
+                let mut weaknesses: Vec<&str> = decoders::cipher_weaknesses(&transform)
+                    .into_iter()
+                    .filter(|w| self.denied_ciphers.iter().any(|d| d == w))
+                    .collect();
+                if transform.contains("MD5") {
+                    weaknesses.push("MD5");
+                }
+                if !weaknesses.is_empty() {
+                    violations.push(format!(
+                        "{} {} {} ({})",
+                        transform,
+                        protocol,
+                        kind,
+                        weaknesses.join(", ")
+                    ));
+                }
+            }
+            // Finite field groups are held to the size of RSA keys
+            if let Some(bits) = ike::dh_group_bits(namespace, fields) {
+                if bits < self.min_rsa_bits {
+                    violations.push(format!("{}-bit {} DH group", bits, protocol));
+                }
+            }
+        }
+
+        let algorithm = fields.get("pk::algorithm").and_then(|v| v.as_str());
+        if let (Some(algorithm), Some(bits)) = (algorithm, get_u64("pk::bits")) {
+            if algorithm.to_lowercase().starts_with("rsa") && bits < self.min_rsa_bits {
+                violations.push(format!("RSA-{} key", bits));
+            }
+        }
+
+        if let Some(iterations) = get_u64("kdf::iterations") {
+            if iterations < self.min_kdf_iterations {
+                let algorithm =
+                    decoders::kdf_algorithm(name, fields).unwrap_or_else(|| "KDF".to_string());
+                violations.push(format!("{} with {} iterations", algorithm, iterations));
+            }
+        }
+
+        if self.require_drbg && decoders::is_random_event(name) {
+            if let Some(generator) = decoders::random_generator(fields) {
+                if !decoders::is_drbg(&generator) {
+                    violations.push(format!("non-DRBG random generator {}", generator));
+                }
+            }
+        }
+
+        let mut violations: Vec<(Severity, String)> = violations
+            .into_iter()
+            .map(|description| (Severity::Error, description))
+            .collect();
+        if !self.rules.is_empty() {
+            for usage in usages(name, fields) {
+                if let Some(rule) = self.judge(&usage) {
+                    let description = match rule.note.as_str() {
+                        "" => usage.description(),
+                        note => format!("{} ({})", usage.description(), note),
+                    };
+                    violations.push((rule.severity, description));
+                }
+            }
+        }
+        violations
+    }
+}
+
+// The algorithms an event with these fields uses, as rules of standards are
+// checked against
+pub fn usages(name: &str, fields: &HashMap<String, Value>) -> Vec<Usage> {
+    let mut usages = Vec::new();
+    let get_u64 = |key: &str| fields.get(key).and_then(Value::as_u64);
+    let get_str = |key: &str| fields.get(key).and_then(Value::as_str);
+
+    if let Some(version) =
+        get_u64("tls::protocol_version").and_then(decoders::protocol_version_name)
+    {
+        usages.push(Usage::new(Target::Protocol, version, None));
+    }
+    if let Some(group) = get_u64("tls::group").and_then(decoders::group_name) {
+        usages.push(Usage::new(Target::KeyExchange, group, None));
+    }
+    if let Some(signature) =
+        get_u64("tls::signature_algorithm").and_then(decoders::signature_scheme_name)
+    {
+        usages.push(Usage::new(Target::Signature, signature, None));
+    }
+    let cipher = get_u64("tls::ciphersuite")
+        .and_then(decoders::ciphersuite_name)
+        .map(str::to_string)
+        .or_else(|| decoders::cipher_name(fields));
+    if let Some(cipher) = cipher {
+        let bits = get_u64("cipher::key_length");
+        usages.push(Usage::new(Target::Cipher, cipher, bits));
+    }
+    if decoders::is_ktls_event(name) {
+        if let (Some(cipher), _) = decoders::ktls_offload(fields) {
+            usages.push(Usage::new(Target::Cipher, cipher, None));
+        }
+    }
+
+    if decoders::is_hash_event(name) {
+        if let Some(hash) = decoders::hash_algorithm(fields) {
+            usages.push(Usage::new(Target::Hash, hash, None));
+        }
+    }
+    if let Some(hash) = get_str("kdf::hash") {
+        usages.push(Usage::new(Target::Hash, hash.to_uppercase(), None));
+    }
+
+    if decoders::is_certificate_event(name) {
+        if let Some(signature) = decoders::certificate_signature_algorithm(fields) {
+            usages.push(Usage::new(Target::Signature, signature, None));
+        }
+    }
+    for (algorithm, bits) in [
+        ("pk::algorithm", "pk::bits"),
+        ("x509::public_key_algorithm", "x509::public_key_bits"),
+    ] {
+        if let Some(algorithm) = get_str(algorithm) {
+            usages.push(Usage::new(Target::PublicKey, algorithm, get_u64(bits)));
+        }
+    }
+
+    if decoders::is_random_event(name) {
+        if let Some(generator) = decoders::random_generator(fields) {
+            usages.push(Usage::new(Target::Random, generator, None));
+        }
+    }
+
+    if decoders::is_ssh_event(name) {
+        for (field, algorithm) in decoders::ssh_algorithms(fields) {
+            let target = match field {
+                "ssh::kex_algorithm" => Target::KeyExchange,
+                "ssh::host_key_algorithm" => Target::Signature,
+                "ssh::cipher" => Target::Cipher,
+                _ => Target::Mac,
+            };
+            usages.push(Usage::new(target, algorithm, None));
+        }
+    }
+
+    if let Some(namespace) = decoders::ike_namespace(name) {
+        for (kind, transform) in ike::transforms(namespace, fields) {
+            let (target, bits) = match kind {
+                "encryption" => (Target::Cipher, get_u64(&format!("{}key_length", namespace))),
+                "DH group" => (Target::KeyExchange, ike::dh_group_bits(namespace, fields)),
+                _ => (Target::Mac, None),
+            };
+            usages.push(Usage::new(target, transform, bits));
+        }
+    }
+    usages
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn event(origin: &str, fields: Value, spans: Vec<AuditEvent>) -> AuditEvent {
+        let mut event: AuditEvent = serde_json::from_value(json!({
+            "context": "c",
+            "origin": origin,
+            "start": 0,
+            "end": 0,
+            "events": fields,
+        }))
+        .unwrap();
+        event.spans = spans;
+        event
+    }
+
+    #[test]
+    fn check_aggregates_violations() {
+        let tls10 = json!({"name": "tls::handshake_client", "tls::protocol_version": 0x0301});
+        let events = [
+            event(
+                "curl",
+                tls10.clone(),
+                vec![event("curl", tls10.clone(), vec![])],
+            ),
+            event(
+                "wget",
+                json!({"name": "tls::handshake_client", "tls::signature_algorithm": 0x0201}),
+                vec![],
+            ),
+            event(
+                "wget",
+                json!({"name": "tls::handshake_client", "tls::protocol_version": 0x0304}),
+                vec![],
+            ),
+        ];
+        let violations: Vec<_> = Profile::builtin("DEFAULT")
+            .unwrap()
+            .check(&events)
+            .into_iter()
+            .map(|v| (v.severity, v.origin, v.description, v.count))
+            .collect();
+        assert_eq!(
+            violations,
+            [
+                (
+                    Severity::Error,
+                    "curl".to_string(),
+                    "TLS 1.0 handshake".to_string(),
+                    2
+                ),
+                (
+                    Severity::Error,
+                    "wget".to_string(),
+                    "rsa_pkcs1_sha1 signature".to_string(),
+                    1
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn legacy_allows_sha1_signatures() {
+        let events = [event(
+            "wget",
+            json!({"name": "tls::handshake_client", "tls::signature_algorithm": 0x0201}),
+            vec![],
+        )];
+        assert!(Profile::builtin("LEGACY")
+            .unwrap()
+            .check(&events)
+            .is_empty());
+    }
+
+    fn rule(target: Target, allow: bool, pattern: &str, min_bits: Option<u64>) -> Rule {
+        Rule {
+            target,
+            allow,
+            pattern: pattern.to_string(),
+            min_bits,
+            severity: Severity::Error,
+            from_year: None,
+            note: String::new(),
+        }
+    }
+
+    #[test]
+    fn rules_match_by_tokens() {
+        let sha1 = rule(Target::Signature, false, "SHA1", None);
+        assert!(sha1.matches(&Usage::new(Target::Signature, "rsa_pkcs1_sha1", None)));
+        assert!(!sha1.matches(&Usage::new(Target::Signature, "rsa_pkcs1_sha1x", None)));
+        assert!(!sha1.matches(&Usage::new(Target::Hash, "SHA1", None)));
+
+        let tls10 = rule(Target::Protocol, false, "TLS 1.0", None);
+        assert!(tls10.matches(&Usage::new(Target::Protocol, "TLS 1.0", None)));
+        assert!(!tls10.matches(&Usage::new(Target::Protocol, "TLS 1.1", None)));
+
+        let any = rule(Target::Cipher, false, "*", None);
+        assert!(any.matches(&Usage::new(Target::Cipher, "AES-128-GCM", None)));
+    }
+
+    #[test]
+    fn rules_match_by_size() {
+        let deny = rule(Target::PublicKey, false, "RSA", Some(2048));
+        assert!(deny.matches(&Usage::new(Target::PublicKey, "RSA", Some(1024))));
+        assert!(!deny.matches(&Usage::new(Target::PublicKey, "RSA", Some(2048))));
+        assert!(!deny.matches(&Usage::new(Target::PublicKey, "RSA", None)));
+
+        let allow = rule(Target::Cipher, true, "AES", Some(256));
+        assert!(allow.matches(&Usage::new(Target::Cipher, "AES", Some(256))));
+        assert!(!allow.matches(&Usage::new(Target::Cipher, "AES", Some(128))));
+    }
+
+    #[test]
+    fn rules_in_force() {
+        let rule = Rule {
+            from_year: Some(2031),
+            ..rule(Target::Hash, false, "SHA1", None)
+        };
+        assert!(!rule.in_force(2030));
+        assert!(rule.in_force(2031));
+    }
+
+    #[test]
+    fn judge_takes_the_worst_rule() {
+        let profile = Profile {
+            rules: vec![
+                Rule {
+                    severity: Severity::Warning,
+                    ..rule(Target::Hash, false, "SHA1", None)
+                },
+                rule(Target::Hash, false, "*", None),
+                rule(Target::Cipher, true, "AES", None),
+                // Not in force yet
+                Rule {
+                    from_year: Some(i16::MAX),
+                    ..rule(Target::Protocol, false, "TLS", None)
+                },
+            ],
+            ..Profile::permissive("test")
+        };
+        let judge = |target, name| {
+            profile
+                .judge(&Usage::new(target, name, None))
+                .map(|rule| (rule.severity, rule.pattern.clone()))
+        };
+        assert_eq!(
+            judge(Target::Hash, "SHA1"),
+            Some((Severity::Error, "*".to_string()))
+        );
+        // Usages no allow rule of their kind matches break them
+        assert_eq!(judge(Target::Cipher, "AES-128-GCM"), None);
+        assert_eq!(
+            judge(Target::Cipher, "CHACHA20-POLY1305"),
+            Some((Severity::Error, "AES".to_string()))
+        );
+        assert_eq!(judge(Target::Protocol, "TLS 1.0"), None);
+        assert_eq!(judge(Target::KeyExchange, "x25519"), None);
+    }
+}
@@ -0,0 +1,164 @@
+use crate::annotations::Annotation;
+use crate::filters::ExcludeRule;
+use crate::live::Retention;
+use crate::palette::Palette;
+use crate::saved_queries::SavedQuery;
+use crate::sunburst::TooltipOptions;
+use crate::timezone::DisplayZone;
+use anyhow::{anyhow, Result};
+use gtk4::glib;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+// User preferences persisted across sessions
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub palette: Palette,
+    // Children listed in segment tooltips and their order
+    pub tooltip: TooltipOptions,
+    // Applied when building the tree of every capture
+    pub exclude_rules: Vec<ExcludeRule>,
+    // Limits of the events kept in memory while following a file
+    pub live_retention: Retention,
+    // Saved zoom locations, keyed by the SHA-256 of the capture they belong to
+    pub bookmarks: HashMap<String, Vec<Bookmark>>,
+    // Notes on tree nodes, keyed like the bookmarks
+    pub annotations: HashMap<String, Vec<Annotation>>,
+    // HTML template of generated reports, instead of the built-in one
+    pub report_template: Option<PathBuf>,
+    // Draw frame timings over the chart
+    pub performance_overlay: bool,
+    // Named filter expressions offered in the filter bar
+    pub saved_queries: Vec<SavedQuery>,
+    // Zone displayed times are in
+    pub time_zone: DisplayZone,
+    // Show times as offsets from the start of the capture
+    pub relative_times: bool,
+    // Secret key of the pseudonyms of anonymized identifiers, generated on first use
+    pub anonymization_key: Option<String>,
+    // Built-in profile checked instead of the system crypto policy
+    pub compliance_profile: Option<String>,
+    // Set when the file on disk could be neither read nor moved aside, so
+    // saving would overwrite the bookmarks and notes it still holds
+    #[serde(skip)]
+    read_only: bool,
+}
+
+// A zoom location, as the names from the root's children down to the zoomed node
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bookmark {
+    pub path: Vec<String>,
+}
+
+impl Bookmark {
+    pub fn label(&self) -> String {
+        self.path.join(" / ")
+    }
+}
+
+impl Config {
+    pub fn annotation(&self, hash: &str, path: &[String]) -> Option<&Annotation> {
+        self.annotations.get(hash)?.iter().find(|a| a.path == path)
+    }
+
+    // Add or replace the annotation of a node
+    pub fn set_annotation(&mut self, hash: &str, annotation: Annotation) {
+        let annotations = self.annotations.entry(hash.to_string()).or_default();
+        annotations.retain(|a| a.path != annotation.path);
+        annotations.push(annotation);
+    }
+
+    pub fn remove_annotation(&mut self, hash: &str, path: &[String]) {
+        if let Some(annotations) = self.annotations.get_mut(hash) {
+            annotations.retain(|a| a.path != path);
+        }
+    }
+
+    // Saved query with the expression, if any
+    pub fn saved_query_for(&self, query: &str) -> Option<&SavedQuery> {
+        self.saved_queries.iter().find(|saved| saved.query == query)
+    }
+
+    // Add a saved query, replacing one of the same name
+    pub fn save_query(&mut self, saved: SavedQuery) {
+        self.saved_queries
+            .retain(|existing| existing.name != saved.name);
+        self.saved_queries.push(saved);
+    }
+
+    pub fn remove_saved_query(&mut self, name: &str) {
+        self.saved_queries.retain(|saved| saved.name != name);
+    }
+
+    fn path() -> PathBuf {
+        glib::user_config_dir()
+            .join("crypto-usage-analyzer")
+            .join("config.json")
+    }
+
+    // Load the configuration, falling back to defaults if it is missing. A
+    // file that can't be parsed is moved aside to config.json.bak rather than
+    // overwritten by the next save; the error is returned along with the defaults.
+    pub fn load() -> (Self, Option<anyhow::Error>) {
+        let path = Self::path();
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return (Self::default(), None),
+            Err(e) => {
+                let config = Self {
+                    read_only: true,
+                    ..Self::default()
+                };
+                return (
+                    config,
+                    Some(anyhow!("Failed to read {}: {}", path.display(), e)),
+                );
+            }
+        };
+        let e = match serde_json::from_str(&content) {
+            Ok(config) => return (config, None),
+            Err(e) => e,
+        };
+
+        let backup = path.with_extension("json.bak");
+        match fs::rename(&path, &backup) {
+            Ok(()) => (
+                Self::default(),
+                Some(anyhow!(
+                    "Preferences were reset, as {} is invalid ({}); it was kept as {}",
+                    path.display(),
+                    e,
+                    backup.display()
+                )),
+            ),
+            Err(rename_error) => (
+                Self {
+                    read_only: true,
+                    ..Self::default()
+                },
+                Some(anyhow!(
+                    "Preferences won't be saved, as {} is invalid ({}) and couldn't be moved aside: {}",
+                    path.display(),
+                    e,
+                    rename_error
+                )),
+            ),
+        }
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path();
+        if self.read_only {
+            return Err(anyhow!("{} couldn't be read", path.display()));
+        }
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,314 @@
+use crate::data::{system_boot_time, AuditEvent};
+use adw::prelude::*;
+use adw::{AlertDialog, ComboRow, PreferencesGroup, ResponseAppearance};
+use anyhow::{anyhow, Result};
+use gtk4::StringList;
+use std::collections::HashMap;
+
+// Event fields a CSV column can be mapped to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Field {
+    Context,
+    Origin,
+    Start,
+    End,
+    Name,
+    Detail,
+}
+
+impl Field {
+    pub const ALL: [Field; 6] = [
+        Field::Context,
+        Field::Origin,
+        Field::Start,
+        Field::End,
+        Field::Name,
+        Field::Detail,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Context => "Context",
+            Self::Origin => "Origin",
+            Self::Start => "Start",
+            Self::End => "End",
+            Self::Name => "Name",
+            Self::Detail => "Detail",
+        }
+    }
+}
+
+// Column index of each mapped field
+pub type ColumnMapping = HashMap<Field, usize>;
+
+// Split CSV content into rows of fields. Fields may be quoted, with doubled
+// quotes inside; the delimiter is whichever of comma, semicolon and tab occurs
+// most often in the first line.
+pub fn parse(content: &str) -> Vec<Vec<String>> {
+    let first_line = content.lines().next().unwrap_or("");
+    let delimiter = [',', ';', '\t']
+        .into_iter()
+        .max_by_key(|d| first_line.matches(*d).count())
+        .unwrap_or(',');
+
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = content.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' if quoted => quoted = false,
+            '"' if field.is_empty() => quoted = true,
+            '\r' if !quoted => {}
+            '\n' if !quoted => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            c if c == delimiter && !quoted => row.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+
+    // Blank lines carry no events
+    rows.retain(|row| row.iter().any(|field| !field.trim().is_empty()));
+    rows
+}
+
+// Guess the mapping from the header, matching the field labels ignoring case
+pub fn guess_mapping(header: &[String]) -> ColumnMapping {
+    Field::ALL
+        .iter()
+        .filter_map(|field| {
+            let column = header
+                .iter()
+                .position(|title| title.trim().eq_ignore_ascii_case(field.label()))?;
+            Some((*field, column))
+        })
+        .collect()
+}
+
+// Nanoseconds since boot, as events record them, from either such a number or
+// an RFC 3339 timestamp such as "2024-05-01T12:00:00Z"
+fn parse_time(value: &str) -> Result<u64> {
+    let value = value.trim();
+    if let Ok(ns) = value.parse() {
+        return Ok(ns);
+    }
+    let timestamp: jiff::Timestamp = value
+        .parse()
+        .map_err(|_| anyhow!("Invalid time “{}”", value))?;
+    // Imported events have no metadata, so they count from this machine's boot
+    let since_boot = timestamp.as_nanosecond() - system_boot_time() as i128 * 1_000_000_000;
+    Ok(since_boot.max(0) as u64)
+}
+
+// Numbers become numeric fields, so decoders recognize codes such as
+// "tls::protocol_version"
+fn field_value(value: &str) -> serde_json::Value {
+    match value.trim().parse::<u64>() {
+        Ok(number) => number.into(),
+        Err(_) => value.into(),
+    }
+}
+
+// Build one top-level event per data row. Columns not mapped to a field are
+// kept as event fields named by their header.
+pub fn events_from_rows(rows: &[Vec<String>], mapping: &ColumnMapping) -> Result<Vec<AuditEvent>> {
+    let Some((header, data)) = rows.split_first() else {
+        return Err(anyhow!("The file has no header row"));
+    };
+    if !mapping.contains_key(&Field::Name) {
+        return Err(anyhow!("No column is mapped to the event name"));
+    }
+
+    let mut events = Vec::new();
+    for (i, row) in data.iter().enumerate() {
+        let get = |field: Field| -> &str {
+            mapping
+                .get(&field)
+                .and_then(|column| row.get(*column))
+                .map_or("", |value| value.trim())
+        };
+        // Line numbers count the header as line 1
+        let line = i + 2;
+        let start = match get(Field::Start) {
+            "" => 0,
+            value => parse_time(value).map_err(|e| anyhow!("Line {}: {}", line, e))?,
+        };
+        let end = match get(Field::End) {
+            "" => start,
+            value => parse_time(value).map_err(|e| anyhow!("Line {}: {}", line, e))?,
+        };
+
+        let mut fields = HashMap::new();
+        for (column, value) in row.iter().enumerate() {
+            let mapped = mapping.values().any(|c| *c == column);
+            if let Some(title) = header.get(column).filter(|_| !mapped) {
+                if !value.trim().is_empty() {
+                    fields.insert(title.trim().to_string(), field_value(value));
+                }
+            }
+        }
+        fields.insert("name".to_string(), get(Field::Name).into());
+        if !get(Field::Detail).is_empty() {
+            fields.insert("detail".to_string(), get(Field::Detail).into());
+        }
+
+        events.push(AuditEvent {
+            context: match get(Field::Context) {
+                "" => format!("csv-{}", line),
+                context => context.to_string(),
+            },
+            origin: get(Field::Origin).to_string(),
+            start,
+            end,
+            events: fields,
+            spans: Vec::new(),
+            boot_id: None,
+        });
+    }
+    Ok(events)
+}
+
+// Ask which column each event field is read from, preselecting columns whose
+// header matches. Returns None when the dialog was cancelled.
+pub async fn ask_mapping(
+    parent: &impl IsA<gtk4::Widget>,
+    header: &[String],
+) -> Option<ColumnMapping> {
+    let guessed = guess_mapping(header);
+    let mut choices = vec!["(None)".to_string()];
+    choices.extend(header.iter().map(|title| title.trim().to_string()));
+    let choices: Vec<&str> = choices.iter().map(String::as_str).collect();
+
+    let group = PreferencesGroup::new();
+    let rows: Vec<(Field, ComboRow)> = Field::ALL
+        .iter()
+        .map(|field| {
+            let row = ComboRow::builder()
+                .title(field.label())
+                .model(&StringList::new(&choices))
+                .selected(guessed.get(field).map_or(0, |column| *column as u32 + 1))
+                .build();
+            group.add(&row);
+            (*field, row)
+        })
+        .collect();
+
+    let dialog = AlertDialog::builder()
+        .heading("Import CSV")
+        .body("Choose the column each event field is read from. Times are nanoseconds since boot or RFC 3339 timestamps; other columns are kept as event fields.")
+        .extra_child(&group)
+        .close_response("cancel")
+        .default_response("import")
+        .build();
+    dialog.add_response("cancel", "Cancel");
+    dialog.add_response("import", "Import");
+    dialog.set_response_appearance("import", ResponseAppearance::Suggested);
+
+    // Events can't be shown without a name
+    let name_row = rows
+        .iter()
+        .find(|(field, _)| *field == Field::Name)
+        .map(|(_, row)| row.clone())?;
+    dialog.set_response_enabled("import", name_row.selected() > 0);
+    let dialog_clone = dialog.clone();
+    name_row.connect_selected_notify(move |row| {
+        dialog_clone.set_response_enabled("import", row.selected() > 0);
+    });
+
+    if dialog.clone().choose_future(parent).await != "import" {
+        return None;
+    }
+
+    Some(
+        rows.iter()
+            .filter(|(_, row)| row.selected() > 0)
+            .map(|(field, row)| (*field, row.selected() as usize - 1))
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows(rows: &[&[&str]]) -> Vec<Vec<String>> {
+        rows.iter()
+            .map(|row| row.iter().map(|field| field.to_string()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn parse_delimiters() {
+        let expected = rows(&[&["name", "origin"], &["tls::sign", "gnutls"]]);
+        assert_eq!(parse("name,origin\ntls::sign,gnutls\n"), expected);
+        assert_eq!(parse("name;origin\ntls::sign;gnutls\n"), expected);
+        assert_eq!(parse("name\torigin\ntls::sign\tgnutls\n"), expected);
+        // The first line decides, commas in later lines are data
+        assert_eq!(
+            parse("name;detail\ntls::sign;a,b\n"),
+            rows(&[&["name", "detail"], &["tls::sign", "a,b"]])
+        );
+    }
+
+    #[test]
+    fn parse_quoted_fields() {
+        assert_eq!(
+            parse("name,detail\n\"tls::sign\",\"a, \"\"b\"\"\nc\"\n"),
+            rows(&[&["name", "detail"], &["tls::sign", "a, \"b\"\nc"]])
+        );
+        // Quotes inside an unquoted field are kept
+        assert_eq!(parse("a,b\"c\n"), rows(&[&["a", "b\"c"]]));
+    }
+
+    #[test]
+    fn parse_line_endings() {
+        let expected = rows(&[&["name", "origin"], &["tls::sign", "gnutls"]]);
+        assert_eq!(parse("name,origin\r\ntls::sign,gnutls\r\n"), expected);
+        assert_eq!(parse("name,origin\ntls::sign,gnutls"), expected);
+        assert_eq!(parse("name,origin\n\n, \ntls::sign,gnutls\n\n"), expected);
+    }
+
+    #[test]
+    fn parse_empty_fields() {
+        assert_eq!(parse("a,,b,\n"), rows(&[&["a", "", "b", ""]]));
+        assert_eq!(parse(""), Vec::<Vec<String>>::new());
+    }
+
+    #[test]
+    fn events_from_mapped_rows() {
+        let rows = parse("Name,Start,End,tls::protocol_version,note\ntls::sign,10,20,772,slow\n");
+        let mapping = guess_mapping(&rows[0]);
+        assert_eq!(mapping.get(&Field::Name), Some(&0));
+        assert_eq!(mapping.get(&Field::Start), Some(&1));
+        assert_eq!(mapping.get(&Field::End), Some(&2));
+
+        let events = events_from_rows(&rows, &mapping).unwrap();
+        assert_eq!(events.len(), 1);
+        let event = &events[0];
+        assert_eq!(event.name(), "tls::sign");
+        assert_eq!((event.start, event.end), (10, 20));
+        assert_eq!(event.context, "csv-2");
+        assert_eq!(event.events["tls::protocol_version"], 772);
+        assert_eq!(event.events["note"], "slow");
+        assert!(!event.events.contains_key("Start"));
+    }
+
+    #[test]
+    fn events_need_a_name_column() {
+        let rows = parse("start\n10\n");
+        assert!(events_from_rows(&rows, &guess_mapping(&rows[0])).is_err());
+        let rows = parse("name,start\ntls::sign,soon\n");
+        assert!(events_from_rows(&rows, &guess_mapping(&rows[0])).is_err());
+    }
+}
@@ -1,5 +1,38 @@
+use crate::compliance::Severity;
+use crate::decoders;
+use crate::ike;
+use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, UNIX_EPOCH};
+use sysinfo::System;
+
+// Boot time of the loaded capture in seconds since the Unix epoch, 0 while
+// it doesn't record one and this machine's boot time is assumed
+static BOOT_TIME: AtomicU64 = AtomicU64::new(0);
+
+// Count event timestamps from the boot at this time from now on, or from
+// this machine's boot for None
+pub fn set_boot_time(boot_time: Option<u64>) {
+    BOOT_TIME.store(boot_time.unwrap_or(0), Ordering::Relaxed);
+}
+
+// Boot time of this machine, in seconds since the Unix epoch
+pub fn system_boot_time() -> u64 {
+    System::boot_time()
+}
+
+// Wall-clock time of an event timestamp, which counts nanoseconds since boot
+pub fn boot_timestamp(ns: u64) -> jiff::Timestamp {
+    let boot_time = match BOOT_TIME.load(Ordering::Relaxed) {
+        0 => system_boot_time(),
+        boot_time => boot_time,
+    };
+    (UNIX_EPOCH + Duration::from_secs(boot_time) + Duration::from_nanos(ns))
+        .try_into()
+        .unwrap()
+}
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct AuditEvent {
@@ -10,6 +43,11 @@ pub struct AuditEvent {
     pub events: HashMap<String, serde_json::Value>,
     #[serde(default)]
     pub spans: Vec<AuditEvent>,
+    // Boot the timestamps count from, in captures spanning several boots.
+    // Spans without one are of their event's boot, and events without one of
+    // the capture's.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub boot_id: Option<String>,
 }
 
 impl AuditEvent {
@@ -22,42 +60,52 @@ impl AuditEvent {
     }
 
     pub fn format_details(&self) -> String {
+        self.format_details_with_codes(false)
+    }
+
+    // Like format_details, optionally followed by the numeric IANA code of each
+    // decoded name, e.g. "TLS_AES_128_GCM_SHA256 (0x1301)"
+    pub fn format_details_with_codes(&self, show_codes: bool) -> String {
         let name = self.name();
         let mut details = Vec::new();
+        let decoded = |decoded_name: &str, code: u64| {
+            if show_codes {
+                format!("{} ({:#06x})", decoded_name, code)
+            } else {
+                decoded_name.to_string()
+            }
+        };
 
         if name.starts_with("tls::handshake_") {
             if let Some(version) = self.events.get("tls::protocol_version") {
                 if let Some(v) = version.as_u64() {
-                    match v {
-                        772 => details.push("TLS 1.3".to_string()),
-                        771 => details.push("TLS 1.2".to_string()),
-                        _ => details.push(format!("version {}", v)),
+                    match decoders::protocol_version_name(v) {
+                        Some(version_name) => details.push(decoded(version_name, v)),
+                        None => details.push(format!("version {}", v)),
                     }
                 }
             }
             if let Some(cs) = self.events.get("tls::ciphersuite") {
-                details.push(format!("ciphersuite {}", cs));
+                match cs
+                    .as_u64()
+                    .and_then(|c| Some((decoders::ciphersuite_name(c)?, c)))
+                {
+                    Some((cs_name, c)) => details.push(decoded(cs_name, c)),
+                    None => details.push(format!("ciphersuite {}", cs)),
+                }
             }
         } else if name == "tls::sign" || name == "tls::verify" {
             if let Some(sig) = self.events.get("tls::signature_algorithm") {
                 if let Some(s) = sig.as_u64() {
-                    let sig_name = match s {
-                        1027 => "ecdsa_secp256r1_sha256",
-                        2052 => "rsa_pss_rsae_sha256",
-                        _ => "unknown",
-                    };
-                    details.push(sig_name.to_string());
+                    let sig_name = decoders::signature_scheme_name(s).unwrap_or("unknown");
+                    details.push(decoded(sig_name, s));
                 }
             }
         } else if name == "tls::key_exchange" {
             if let Some(group) = self.events.get("tls::group") {
                 if let Some(g) = group.as_u64() {
-                    let group_name = match g {
-                        23 => "secp256r1",
-                        4588 => "X25519MLKEM768",
-                        _ => "unknown",
-                    };
-                    details.push(group_name.to_string());
+                    let group_name = decoders::group_name(g).unwrap_or("unknown");
+                    details.push(decoded(group_name, g));
                 }
             }
         } else if name.starts_with("pk::") {
@@ -69,6 +117,85 @@ impl AuditEvent {
             if let Some(bits) = self.events.get("pk::bits") {
                 details.push(format!("{} bits", bits));
             }
+        } else if decoders::is_certificate_event(&name) {
+            if let Some(sig) = decoders::certificate_signature_algorithm(&self.events) {
+                details.push(sig);
+            }
+            if let Some(algo) = self
+                .events
+                .get("x509::public_key_algorithm")
+                .and_then(|v| v.as_str())
+            {
+                match self.events.get("x509::public_key_bits") {
+                    Some(bits) => details.push(format!("{} {} bits", algo, bits)),
+                    None => details.push(algo.to_string()),
+                }
+            }
+            match self.events.get("x509::key_usage") {
+                Some(serde_json::Value::Number(bits)) => {
+                    let usages = decoders::key_usage_names(bits.as_u64().unwrap_or(0));
+                    if !usages.is_empty() {
+                        details.push(usages.join(" "));
+                    }
+                }
+                Some(serde_json::Value::String(usage)) => details.push(usage.clone()),
+                _ => {}
+            }
+        } else if decoders::is_random_event(&name) {
+            if let Some(generator) = decoders::random_generator(&self.events) {
+                details.push(generator);
+            }
+            if let Some(bytes) = self.events.get("rng::bytes") {
+                details.push(format!("{} bytes", bytes));
+            }
+        } else if decoders::is_kdf_event(&name) {
+            if let Some(algorithm) = decoders::kdf_algorithm(&name, &self.events) {
+                details.push(algorithm);
+            }
+            if let Some(iterations) = self.events.get("kdf::iterations") {
+                details.push(format!("{} iterations", iterations));
+            }
+            if let Some(length) = self.events.get("kdf::length") {
+                details.push(format!("{} bytes", length));
+            }
+        } else if decoders::is_cipher_event(&name) {
+            if let Some(cipher) = decoders::cipher_name(&self.events) {
+                details.push(cipher);
+            }
+        } else if decoders::is_hash_event(&name) {
+            if let Some(hash) = decoders::hash_algorithm(&self.events) {
+                details.push(hash);
+            }
+        } else if decoders::is_ssh_event(&name) {
+            details.extend(
+                decoders::ssh_algorithms(&self.events)
+                    .into_iter()
+                    .map(|(_, algorithm)| algorithm),
+            );
+        } else if let Some(namespace) = decoders::ike_namespace(&name) {
+            details.extend(
+                ike::transforms(namespace, &self.events)
+                    .into_iter()
+                    .map(|(_, transform)| transform),
+            );
+        } else if decoders::is_ktls_event(&name) {
+            let (cipher, direction) = decoders::ktls_offload(&self.events);
+            details.extend(cipher);
+            details.extend(direction);
+            match self.events.get("ktls::socket") {
+                Some(serde_json::Value::String(socket)) => {
+                    details.push(format!("socket {}", socket))
+                }
+                Some(socket) => details.push(format!("socket {}", socket)),
+                None => {}
+            }
+        }
+
+        // Events imported from other formats may carry a free-text detail instead
+        if details.is_empty() {
+            if let Some(detail) = self.events.get("detail").and_then(|v| v.as_str()) {
+                details.push(detail.to_string());
+            }
         }
 
         if details.is_empty() {
@@ -79,14 +206,331 @@ impl AuditEvent {
     }
 }
 
+// Describes where and how a capture was recorded, from the optional header
+// of a capture file
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct CaptureMetadata {
+    pub hostname: Option<String>,
+    pub kernel: Option<String>,
+    pub boot_id: Option<String>,
+    // Time of the boot of boot_id, in seconds since the Unix epoch
+    pub boot_time: Option<u64>,
+    // Times of the boots a capture spanning several was recorded in, by boot ID
+    pub boots: BTreeMap<String, u64>,
+    // Version of the tool that recorded the capture, e.g. "crau-agent 0.2.3"
+    pub tool_version: Option<String>,
+    // Versions of the crypto libraries by name
+    pub libraries: BTreeMap<String, String>,
+}
+
+impl CaptureMetadata {
+    // Boot time the timestamps of the parsed events count from: the earliest
+    // of the boots the capture was recorded in, if it records their times
+    pub fn epoch(&self) -> Option<u64> {
+        self.boots.values().chain(&self.boot_time).min().copied()
+    }
+
+    // Label and value of each known entry, for display
+    pub fn entries(&self) -> Vec<(String, String)> {
+        let mut entries = Vec::new();
+        let mut push = |label: &str, value: &Option<String>| {
+            if let Some(value) = value {
+                entries.push((label.to_string(), value.clone()));
+            }
+        };
+        push("Host", &self.hostname);
+        push("Kernel", &self.kernel);
+        push("Boot ID", &self.boot_id);
+        let boot_time = self
+            .boot_time
+            .and_then(|secs| jiff::Timestamp::from_second(secs as i64).ok())
+            .map(|time| time.strftime("%Y-%m-%d %H:%M:%S UTC").to_string());
+        push("Boot Time", &boot_time);
+        let boots = (self.boots.len() > 1).then(|| self.boots.len().to_string());
+        push("Boots", &boots);
+        push("Capture Tool", &self.tool_version);
+        for (library, version) in &self.libraries {
+            entries.push((library.clone(), version.clone()));
+        }
+        entries
+    }
+}
+
+// Versions of the capture file format. A new upstream format that renames
+// fields or changes their nesting gets its own variant and deserializer
+// converting it into AuditEvents, so files of every version keep loading.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormatVersion {
+    // Events as crau-query writes them, either as a bare array or as an
+    // object with a metadata header
+    V1,
+}
+
+impl FormatVersion {
+    pub const LATEST: FormatVersion = FormatVersion::V1;
+
+    fn from_number(number: u64) -> Option<Self> {
+        match number {
+            1 => Some(Self::V1),
+            _ => None,
+        }
+    }
+
+    pub fn number(&self) -> u64 {
+        match self {
+            Self::V1 => 1,
+        }
+    }
+
+    // Version of a capture file object, from its "format_version" field;
+    // objects without the field are version 1
+    pub fn detect(capture: &serde_json::Value) -> Result<Self> {
+        let number = match capture.get("format_version") {
+            Some(value) => value
+                .as_u64()
+                .ok_or_else(|| anyhow!("Capture format version {} is not a number", value))?,
+            None => 1,
+        };
+        Self::from_number(number).ok_or_else(|| {
+            anyhow!(
+                "Capture format version {} is not supported; this version reads up to {}",
+                number,
+                Self::LATEST.number()
+            )
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct CaptureV1 {
+    #[serde(default)]
+    metadata: Option<CaptureMetadata>,
+    events: Vec<AuditEvent>,
+}
+
+pub struct Capture {
+    pub metadata: Option<CaptureMetadata>,
+    pub events: Vec<AuditEvent>,
+}
+
+impl Capture {
+    // Parse a capture file of any supported format version. Objects are read
+    // once into a JSON value, then converted according to their version;
+    // bare arrays are version 1
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut capture = if content.trim_start().starts_with('{') {
+            let value: serde_json::Value = serde_json::from_str(content)?;
+            match FormatVersion::detect(&value)? {
+                FormatVersion::V1 => Self::parse_v1(value)?,
+            }
+        } else {
+            Self {
+                metadata: None,
+                events: serde_json::from_str(content)?,
+            }
+        };
+        capture.resolve_boots();
+        Ok(capture)
+    }
+
+    // Put the events of all boots of a capture spanning several on the
+    // timeline of the earliest one, shifting the timestamps of the events of
+    // later boots by the time between the boots
+    pub fn resolve_boots(&mut self) {
+        let Some(metadata) = self.metadata.as_ref() else {
+            return;
+        };
+        let Some(earliest) = metadata.epoch() else {
+            return;
+        };
+        // Events of unknown boots are taken to be of the capture's
+        let default = metadata
+            .boot_time
+            .or_else(|| metadata.boots.get(metadata.boot_id.as_ref()?).copied())
+            .unwrap_or(earliest);
+
+        fn shift(
+            events: &mut [AuditEvent],
+            boots: &BTreeMap<String, u64>,
+            boot_time: u64,
+            earliest: u64,
+        ) {
+            for event in events {
+                let boot_time = event
+                    .boot_id
+                    .as_ref()
+                    .and_then(|boot_id| boots.get(boot_id))
+                    .copied()
+                    .unwrap_or(boot_time);
+                let offset = (boot_time - earliest) * 1_000_000_000;
+                event.start += offset;
+                event.end += offset;
+                shift(&mut event.spans, boots, boot_time, earliest);
+            }
+        }
+        shift(&mut self.events, &metadata.boots, default, earliest);
+
+        // Events of the capture's boot are tagged too, so all events of a
+        // capture spanning several boots segment into boot sessions
+        if let (false, Some(boot_id)) = (metadata.boots.is_empty(), &metadata.boot_id) {
+            for event in self
+                .events
+                .iter_mut()
+                .filter(|event| event.boot_id.is_none())
+            {
+                event.boot_id = Some(boot_id.clone());
+            }
+        }
+    }
+
+    fn parse_v1(value: serde_json::Value) -> Result<Self> {
+        let capture: CaptureV1 = serde_json::from_value(value)?;
+        Ok(Self {
+            metadata: capture.metadata,
+            events: capture.events,
+        })
+    }
+}
+
+// What a tree node stands for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeKind {
+    Root,
+    Context,
+    // Synthetic branch grouping related events, e.g. "certificates"
+    Category,
+    // Contexts of one process, when grouping by process
+    Process,
+    Event,
+}
+
 #[derive(Debug, Clone)]
 pub struct TreeNode {
     pub name: String,
+    // Human-readable name to show instead, e.g. the connection of a context
+    pub label: Option<String>,
+    // Number of structurally identical contexts collapsed into this one
+    pub multiplier: usize,
+    pub kind: NodeKind,
+    // Value according to the selected ValueMode
     pub value: usize,
+    // Number of records the node itself stands for: the merged events for an
+    // event node, the top-level events for a context, the contexts for a process
+    // and the top-level nodes for the root
+    pub count: usize,
+    // Total time spent in the node's events, in nanoseconds
+    pub duration: u64,
+    // Earliest start and latest end of the node's events, in nanoseconds since boot
+    pub time_range: Option<(u64, u64)>,
+    // Raw fields of the event an event node was built from
+    pub fields: HashMap<String, serde_json::Value>,
+    // Process the node's events were recorded in, for contexts and events
+    pub process: Option<String>,
+    // Compliance classification of the subtree, if it was checked against a profile
+    pub severity: Option<Severity>,
     pub children: Vec<TreeNode>,
 }
 
+// How node values are derived from event counts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ValueMode {
+    // The node's own count only
+    SelfCount,
+    // The number of leaf events in the subtree
+    #[default]
+    LeafCount,
+    // The node's own events plus all events below it
+    InclusiveSum,
+}
+
+impl ValueMode {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "self" => Some(Self::SelfCount),
+            "leaf" => Some(Self::LeafCount),
+            "inclusive" => Some(Self::InclusiveSum),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::SelfCount => "self",
+            Self::LeafCount => "leaf",
+            Self::InclusiveSum => "inclusive",
+        }
+    }
+}
+
+// Order of sibling nodes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortOrder {
+    // Descending by value, ties broken by name
+    #[default]
+    Size,
+    // Alphabetical by name
+    Name,
+}
+
+impl SortOrder {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "size" => Some(Self::Size),
+            "name" => Some(Self::Name),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Size => "size",
+            Self::Name => "name",
+        }
+    }
+}
+
+// Options controlling how the tree is built from events
+#[derive(Debug, Clone)]
+pub struct TreeOptions {
+    // Merge structurally identical sibling events into one node, summing counts
+    pub aggregate: bool,
+    pub value_mode: ValueMode,
+    pub sort_order: SortOrder,
+    // Insert a level grouping contexts by the process they belong to
+    pub group_by_process: bool,
+    // Cluster contexts with identical event structure into one node
+    pub collapse_contexts: bool,
+    // Append the numeric IANA codes to decoded names in labels
+    pub show_codes: bool,
+}
+
+impl Default for TreeOptions {
+    fn default() -> Self {
+        Self {
+            aggregate: true,
+            value_mode: ValueMode::default(),
+            sort_order: SortOrder::default(),
+            group_by_process: false,
+            collapse_contexts: false,
+            show_codes: false,
+        }
+    }
+}
+
 impl AuditEvent {
+    // Events overlapping the window, with their spans restricted to it as well
+    pub fn filter_time_window(events: &[AuditEvent], start: u64, end: u64) -> Vec<AuditEvent> {
+        events
+            .iter()
+            .filter(|event| event.start <= end && event.end >= start)
+            .map(|event| AuditEvent {
+                spans: Self::filter_time_window(&event.spans, start, end),
+                ..event.clone()
+            })
+            .collect()
+    }
+
     pub fn get_time_range(events: &[AuditEvent]) -> Option<(u64, u64)> {
         if events.is_empty() {
             return None;
@@ -116,13 +560,105 @@ impl AuditEvent {
     }
 }
 
+// Shortest prefix length, of at least 8 characters, telling all the contexts
+// apart, as git abbreviates commit hashes. It's taken over the whole capture so
+// the short forms don't change with the filters.
+pub fn short_context_length<'a>(contexts: impl IntoIterator<Item = &'a str>) -> usize {
+    let mut contexts: Vec<&str> = contexts.into_iter().collect();
+    contexts.sort_unstable();
+    contexts.dedup();
+    contexts
+        .windows(2)
+        .map(|pair| {
+            let common = pair[0]
+                .chars()
+                .zip(pair[1].chars())
+                .take_while(|(a, b)| a == b)
+                .count();
+            common + 1
+        })
+        .fold(8, usize::max)
+}
+
+// Prefix of a context ID of the length, e.g. "7f3a09c1…", unless it's hardly
+// shorter than the ID itself
+pub fn short_context(context: &str, length: usize) -> Option<String> {
+    context.chars().nth(length + 2)?;
+    let (end, _) = context.char_indices().nth(length)?;
+    Some(format!("{}…", &context[..end]))
+}
+
+fn union_time_ranges(a: Option<(u64, u64)>, b: Option<(u64, u64)>) -> Option<(u64, u64)> {
+    match (a, b) {
+        (Some((a_start, a_end)), Some((b_start, b_end))) => {
+            Some((a_start.min(b_start), a_end.max(b_end)))
+        }
+        (range, None) | (None, range) => range,
+    }
+}
+
 impl TreeNode {
-    pub fn from_events(events: &[AuditEvent]) -> Self {
-        let mut root = TreeNode {
-            name: "all".to_string(),
+    pub fn new(kind: NodeKind, name: &str, count: usize) -> Self {
+        TreeNode {
+            name: name.to_string(),
+            label: None,
+            multiplier: 1,
+            kind,
             value: 0,
+            count,
+            duration: 0,
+            time_range: None,
+            fields: HashMap::new(),
+            process: None,
+            severity: None,
             children: Vec::new(),
-        };
+        }
+    }
+
+    pub fn display_name(&self) -> String {
+        let name = self.label.as_deref().unwrap_or(&self.name);
+        match self.multiplier {
+            1 => name.to_string(),
+            n => format!("{} ×{}", name, n),
+        }
+    }
+
+    // Label contexts found in the mapping of context IDs to labels
+    pub fn apply_context_labels(&mut self, labels: &HashMap<String, String>) {
+        if self.kind == NodeKind::Context {
+            self.label = labels.get(&self.name).cloned();
+        }
+        for child in &mut self.children {
+            child.apply_context_labels(labels);
+        }
+    }
+
+    // Label unlabeled contexts with their short form, see short_context
+    pub fn abbreviate_contexts(&mut self, length: usize) {
+        if self.kind == NodeKind::Context && self.label.is_none() {
+            self.label = short_context(&self.name, length);
+        }
+        for child in &mut self.children {
+            child.abbreviate_contexts(length);
+        }
+    }
+
+    // Number of levels below the node, zero for leaves
+    pub fn height(&self) -> usize {
+        self.children
+            .iter()
+            .map(|child| child.height() + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    // Name of the event an event node was built from, e.g. "tls::sign"
+    pub fn event_name(&self) -> Option<&str> {
+        self.fields.get("name").and_then(|v| v.as_str())
+    }
+
+    pub fn from_events(events: &[AuditEvent], options: &TreeOptions) -> Self {
+        let mut root = TreeNode::new(NodeKind::Root, "all", 0);
 
         // Group by context
         let mut context_map: HashMap<String, Vec<&AuditEvent>> = HashMap::new();
@@ -133,51 +669,323 @@ impl TreeNode {
                 .push(event);
         }
 
+        root.count = context_map.len();
+
         for (context, context_events) in context_map {
-            let mut context_node = TreeNode {
-                name: context,
-                value: context_events.len(),
-                children: Vec::new(),
-            };
+            let mut context_node = TreeNode::new(NodeKind::Context, &context, context_events.len());
+
+            // Prefer events recording the process themselves over the context ID
+            let no_fields = HashMap::new();
+            let fields = context_events
+                .iter()
+                .map(|event| &event.events)
+                .find(|fields| decoders::process_name(fields, "").is_some())
+                .unwrap_or(&no_fields);
+            context_node.process = decoders::process_name(fields, &context);
 
             for event in context_events {
-                let event_node = Self::build_event_tree(event);
+                let event_node = Self::build_event_tree(event, options.show_codes);
+                context_node.duration += event_node.duration;
                 context_node.children.push(event_node);
             }
 
+            context_node.children = Self::group_categories(context_node.children);
+            if options.aggregate {
+                context_node.children = Self::merge_identical(context_node.children);
+            }
+
+            let process = context_node.process.clone();
+            context_node.set_process(process);
+
+            root.duration += context_node.duration;
             root.children.push(context_node);
         }
 
-        root.update_values();
+        if options.collapse_contexts {
+            root.children = Self::collapse_contexts(root.children);
+        }
+        if options.group_by_process {
+            root.children = Self::group_processes(root.children);
+            root.count = root.children.len();
+        }
+
+        root.update_time_ranges();
+        root.update_values(options.value_mode);
+        root.sort_children(options.sort_order);
         root
     }
 
-    fn build_event_tree(event: &AuditEvent) -> Self {
-        let mut node = TreeNode {
-            name: event.format_details(),
-            value: 1,
-            children: Vec::new(),
-        };
+    fn build_event_tree(event: &AuditEvent, show_codes: bool) -> Self {
+        let mut node = TreeNode::new(NodeKind::Event, &event.format_details(), 1);
+        // Names stay without codes, so paths of bookmarks and annotations don't change
+        if show_codes {
+            node.label = Some(event.format_details_with_codes(true)).filter(|l| *l != node.name);
+        }
+        node.duration = event.end.saturating_sub(event.start);
+        node.time_range = Some((event.start, event.end));
+        node.fields = event.events.clone();
 
         for span in &event.spans {
-            node.children.push(Self::build_event_tree(span));
+            node.children.push(Self::build_event_tree(span, show_codes));
         }
+        node.children = Self::group_categories(node.children);
 
         node
     }
 
-    fn update_values(&mut self) -> usize {
-        if self.children.is_empty() {
-            return self.value;
+    fn set_process(&mut self, process: Option<String>) {
+        for child in &mut self.children {
+            child.set_process(process.clone());
+        }
+        self.process = process;
+    }
+
+    // Move contexts under a node for the process they belong to
+    fn group_processes(contexts: Vec<TreeNode>) -> Vec<TreeNode> {
+        let mut processes: Vec<TreeNode> = Vec::new();
+        let mut index: HashMap<String, usize> = HashMap::new();
+
+        for context in contexts {
+            let name = context
+                .process
+                .clone()
+                .unwrap_or_else(|| "unknown process".to_string());
+            let i = *index.entry(name.clone()).or_insert_with(|| {
+                let mut process = TreeNode::new(NodeKind::Process, &name, 0);
+                process.process = context.process.clone();
+                processes.push(process);
+                processes.len() - 1
+            });
+            let process = &mut processes[i];
+            process.count += 1;
+            process.duration += context.duration;
+            process.children.push(context);
+        }
+
+        processes
+    }
+
+    // Move sibling events belonging to a category (e.g. certificates) under a
+    // synthetic node for that category, placed where its first member was
+    fn group_categories(nodes: Vec<TreeNode>) -> Vec<TreeNode> {
+        let mut grouped: Vec<TreeNode> = Vec::new();
+        let mut index: HashMap<&'static str, usize> = HashMap::new();
+
+        for node in nodes {
+            let Some(category) = node.event_name().and_then(decoders::category) else {
+                grouped.push(node);
+                continue;
+            };
+
+            let i = *index.entry(category).or_insert_with(|| {
+                grouped.push(TreeNode::new(NodeKind::Category, category, 0));
+                grouped.len() - 1
+            });
+            let category_node = &mut grouped[i];
+            category_node.count += 1;
+            category_node.duration += node.duration;
+            category_node.children.push(node);
         }
 
-        let mut total = 0;
+        grouped
+    }
+
+    // Merge siblings with identical structure (name, counts and children),
+    // working bottom-up so that nested spans are merged first
+    fn merge_identical(nodes: Vec<TreeNode>) -> Vec<TreeNode> {
+        let mut merged: Vec<TreeNode> = Vec::new();
+        let mut index: HashMap<String, usize> = HashMap::new();
+
+        for mut node in nodes {
+            node.children = Self::merge_identical(std::mem::take(&mut node.children));
+
+            let key = node.shape_key();
+            match index.get(&key) {
+                Some(&i) => merged[i].add_values(&node),
+                None => {
+                    index.insert(key, merged.len());
+                    merged.push(node);
+                }
+            }
+        }
+
+        merged
+    }
+
+    // Collapse contexts of the same process whose events have identical structure
+    // into the first of them, counting how many were collapsed
+    fn collapse_contexts(contexts: Vec<TreeNode>) -> Vec<TreeNode> {
+        let mut collapsed: Vec<TreeNode> = Vec::new();
+        let mut index: HashMap<String, usize> = HashMap::new();
+
+        for context in contexts {
+            let mut key = context.process.clone().unwrap_or_default();
+            for child in &context.children {
+                key.push('(');
+                key.push_str(&child.shape_key());
+                key.push(')');
+            }
+
+            match index.get(&key) {
+                Some(&i) => {
+                    collapsed[i].add_values(&context);
+                    collapsed[i].multiplier += context.multiplier;
+                }
+                None => {
+                    index.insert(key, collapsed.len());
+                    collapsed.push(context);
+                }
+            }
+        }
+
+        collapsed
+    }
+
+    // Canonical representation of the node's structure, used to detect identical siblings
+    fn shape_key(&self) -> String {
+        let mut key = format!("{}\u{1f}{}", self.name, self.count);
+        for child in &self.children {
+            key.push('(');
+            key.push_str(&child.shape_key());
+            key.push(')');
+        }
+        key
+    }
+
+    // Add the values of a structurally identical node to this one
+    fn add_values(&mut self, other: &TreeNode) {
+        self.count += other.count;
+        self.duration += other.duration;
+        self.time_range = union_time_ranges(self.time_range, other.time_range);
+        for (child, other_child) in self.children.iter_mut().zip(&other.children) {
+            child.add_values(other_child);
+        }
+    }
+
+    // Extend the time range of every node over those of its children
+    fn update_time_ranges(&mut self) -> Option<(u64, u64)> {
         for child in &mut self.children {
-            total += child.update_values();
+            let child_range = child.update_time_ranges();
+            self.time_range = union_time_ranges(self.time_range, child_range);
         }
+        self.time_range
+    }
+
+    fn update_values(&mut self, mode: ValueMode) -> usize {
+        let children_total: usize = self
+            .children
+            .iter_mut()
+            .map(|child| child.update_values(mode))
+            .sum();
 
-        self.value = total;
-        total
+        self.value = match mode {
+            ValueMode::SelfCount => self.count,
+            ValueMode::LeafCount if self.children.is_empty() => self.count,
+            ValueMode::LeafCount => children_total,
+            // Contexts and the root only group their children, so their own
+            // count would count the same events twice
+            ValueMode::InclusiveSum if self.kind == NodeKind::Event => self.count + children_total,
+            ValueMode::InclusiveSum => children_total,
+        };
+
+        self.value
+    }
+
+    // Sort children recursively so the layout is stable across reloads
+    fn sort_children(&mut self, order: SortOrder) {
+        match order {
+            SortOrder::Size => self
+                .children
+                .sort_by(|a, b| b.value.cmp(&a.value).then_with(|| a.name.cmp(&b.name))),
+            SortOrder::Name => self.children.sort_by(|a, b| a.name.cmp(&b.name)),
+        }
+
+        for child in &mut self.children {
+            child.sort_children(order);
+        }
+    }
+
+    // Count event nodes in the subtree by the key the function derives from them.
+    // Nodes for which it returns None are skipped.
+    pub fn extract_stats_by(
+        &self,
+        key: &dyn Fn(&TreeNode) -> Option<String>,
+        stats: &mut HashMap<String, usize>,
+    ) {
+        if self.kind == NodeKind::Event {
+            if let Some(key) = key(self) {
+                *stats.entry(key).or_insert(0) += self.value;
+            }
+        }
+
+        for child in &self.children {
+            child.extract_stats_by(key, stats);
+        }
+    }
+
+    // Count certificate signature algorithms in the subtree
+    pub fn extract_certificate_stats(&self, stats: &mut HashMap<String, usize>) {
+        self.extract_stats_by(
+            &|node| {
+                node.event_name()
+                    .filter(|name| decoders::is_certificate_event(name))
+                    .and_then(|_| decoders::certificate_signature_algorithm(&node.fields))
+            },
+            stats,
+        );
+    }
+
+    // Count random generator types in the subtree
+    pub fn extract_random_stats(&self, stats: &mut HashMap<String, usize>) {
+        self.extract_stats_by(
+            &|node| {
+                node.event_name()
+                    .filter(|name| decoders::is_random_event(name))
+                    .and_then(|_| decoders::random_generator(&node.fields))
+            },
+            stats,
+        );
+    }
+
+    // Count key derivation functions in the subtree
+    pub fn extract_kdf_stats(&self, stats: &mut HashMap<String, usize>) {
+        self.extract_stats_by(
+            &|node| {
+                node.event_name()
+                    .filter(|name| decoders::is_kdf_event(name))
+                    .and_then(|name| decoders::kdf_algorithm(name, &node.fields))
+            },
+            stats,
+        );
+    }
+
+    // Count symmetric ciphers in the subtree
+    pub fn extract_cipher_stats(&self, stats: &mut HashMap<String, usize>) {
+        self.extract_stats_by(
+            &|node| {
+                node.event_name()
+                    .filter(|name| decoders::is_cipher_event(name))
+                    .and_then(|_| decoders::cipher_name(&node.fields))
+            },
+            stats,
+        );
+    }
+
+    // Count hash functions in the subtree
+    pub fn extract_hash_stats(&self, stats: &mut HashMap<String, usize>) {
+        self.extract_stats_by(
+            &|node| {
+                node.event_name()
+                    .filter(|name| decoders::is_hash_event(name))
+                    .and_then(|_| decoders::hash_algorithm(&node.fields))
+            },
+            stats,
+        );
+    }
+
+    // Count events in the subtree by the process they were recorded in
+    pub fn extract_process_stats(&self, stats: &mut HashMap<String, usize>) {
+        self.extract_stats_by(&|node| node.process.clone(), stats);
     }
 
     pub fn extract_algorithm_stats(&self, stats: &mut HashMap<String, usize>) {
@@ -206,3 +1014,87 @@ impl TreeNode {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(name: &str, count: usize, children: Vec<TreeNode>) -> TreeNode {
+        let mut node = TreeNode::new(NodeKind::Event, name, count);
+        node.duration = 10 * count as u64;
+        node.children = children;
+        node
+    }
+
+    #[test]
+    fn shape_key_separates_names_and_counts() {
+        assert_ne!(
+            node("a1", 1, vec![]).shape_key(),
+            node("a", 11, vec![]).shape_key()
+        );
+        assert_ne!(
+            node("a", 1, vec![node("b", 1, vec![])]).shape_key(),
+            node("a", 1, vec![node("b", 2, vec![])]).shape_key()
+        );
+        assert_eq!(
+            node("a", 1, vec![node("b", 1, vec![])]).shape_key(),
+            node("a", 1, vec![node("b", 1, vec![])]).shape_key()
+        );
+    }
+
+    #[test]
+    fn merge_identical_siblings() {
+        let merged = TreeNode::merge_identical(vec![
+            node("a", 1, vec![node("b", 1, vec![])]),
+            node("c", 1, vec![]),
+            node("a", 1, vec![node("b", 1, vec![])]),
+            node("a", 1, vec![node("b", 2, vec![])]),
+        ]);
+        let shapes: Vec<_> = merged
+            .iter()
+            .map(|node| (node.name.as_str(), node.count, node.duration))
+            .collect();
+        assert_eq!(shapes, [("a", 2, 20), ("c", 1, 10), ("a", 1, 10)]);
+        assert_eq!(merged[0].children[0].count, 2);
+        assert_eq!(merged[2].children[0].count, 2);
+    }
+
+    #[test]
+    fn merge_identical_bottom_up() {
+        // The children of the first are merged before it is compared
+        let merged = TreeNode::merge_identical(vec![
+            node("a", 1, vec![node("b", 1, vec![]), node("b", 1, vec![])]),
+            node("a", 1, vec![node("b", 2, vec![])]),
+        ]);
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].count, 2);
+        assert_eq!(merged[0].children.len(), 1);
+        assert_eq!(merged[0].children[0].count, 4);
+        assert_eq!(merged[0].children[0].duration, 40);
+    }
+
+    fn context(name: &str, process: &str, children: Vec<TreeNode>) -> TreeNode {
+        let mut context = TreeNode::new(NodeKind::Context, name, children.len());
+        context.process = Some(process.to_string());
+        context.children = children;
+        context
+    }
+
+    #[test]
+    fn collapse_contexts_of_one_process() {
+        let collapsed = TreeNode::collapse_contexts(vec![
+            context("1", "curl", vec![node("a", 1, vec![])]),
+            context("2", "wget", vec![node("a", 1, vec![])]),
+            context("3", "curl", vec![node("a", 1, vec![])]),
+            context("4", "curl", vec![node("a", 2, vec![])]),
+            context("5", "curl", vec![node("a", 1, vec![])]),
+        ]);
+        let shapes: Vec<_> = collapsed
+            .iter()
+            .map(|context| (context.name.as_str(), context.multiplier, context.count))
+            .collect();
+        assert_eq!(shapes, [("1", 3, 3), ("2", 1, 1), ("4", 1, 1)]);
+        assert_eq!(collapsed[0].children[0].count, 3);
+        assert_eq!(collapsed[0].display_name(), "1 ×3");
+    }
+}
@@ -0,0 +1,650 @@
+use crate::data::AuditEvent;
+use crate::gcrypt;
+use crate::ike;
+use serde_json::Value;
+use std::collections::HashMap;
+
+// Lookup tables mapping numeric IANA codepoints found in audit events
+// to their registered names
+
+pub fn protocol_version_name(code: u64) -> Option<&'static str> {
+    let name = match code {
+        0x0300 => "SSL 3.0",
+        0x0301 => "TLS 1.0",
+        0x0302 => "TLS 1.1",
+        0x0303 => "TLS 1.2",
+        0x0304 => "TLS 1.3",
+        _ => return None,
+    };
+    Some(name)
+}
+
+pub fn signature_scheme_name(code: u64) -> Option<&'static str> {
+    let name = match code {
+        0x0201 => "rsa_pkcs1_sha1",
+        0x0203 => "ecdsa_sha1",
+        0x0401 => "rsa_pkcs1_sha256",
+        0x0403 => "ecdsa_secp256r1_sha256",
+        0x0501 => "rsa_pkcs1_sha384",
+        0x0503 => "ecdsa_secp384r1_sha384",
+        0x0601 => "rsa_pkcs1_sha512",
+        0x0603 => "ecdsa_secp521r1_sha512",
+        0x0804 => "rsa_pss_rsae_sha256",
+        0x0805 => "rsa_pss_rsae_sha384",
+        0x0806 => "rsa_pss_rsae_sha512",
+        0x0807 => "ed25519",
+        0x0808 => "ed448",
+        0x0809 => "rsa_pss_pss_sha256",
+        0x080a => "rsa_pss_pss_sha384",
+        0x080b => "rsa_pss_pss_sha512",
+        0x0904 => "mldsa44",
+        0x0905 => "mldsa65",
+        0x0906 => "mldsa87",
+        _ => return None,
+    };
+    Some(name)
+}
+
+pub fn group_name(code: u64) -> Option<&'static str> {
+    let name = match code {
+        0x0017 => "secp256r1",
+        0x0018 => "secp384r1",
+        0x0019 => "secp521r1",
+        0x001d => "x25519",
+        0x001e => "x448",
+        0x0100 => "ffdhe2048",
+        0x0101 => "ffdhe3072",
+        0x0102 => "ffdhe4096",
+        0x0103 => "ffdhe6144",
+        0x0104 => "ffdhe8192",
+        0x0200 => "MLKEM512",
+        0x0201 => "MLKEM768",
+        0x0202 => "MLKEM1024",
+        0x11eb => "SecP256r1MLKEM768",
+        0x11ec => "X25519MLKEM768",
+        0x11ed => "SecP384r1MLKEM1024",
+        0x6399 => "X25519Kyber768Draft00",
+        _ => return None,
+    };
+    Some(name)
+}
+
+pub fn ciphersuite_name(code: u64) -> Option<&'static str> {
+    let name = match code {
+        0x0000 => "TLS_NULL_WITH_NULL_NULL",
+        0x0004 => "TLS_RSA_WITH_RC4_128_MD5",
+        0x0005 => "TLS_RSA_WITH_RC4_128_SHA",
+        0x000a => "TLS_RSA_WITH_3DES_EDE_CBC_SHA",
+        0x0016 => "TLS_DHE_RSA_WITH_3DES_EDE_CBC_SHA",
+        0x002f => "TLS_RSA_WITH_AES_128_CBC_SHA",
+        0x0033 => "TLS_DHE_RSA_WITH_AES_128_CBC_SHA",
+        0x0035 => "TLS_RSA_WITH_AES_256_CBC_SHA",
+        0x0039 => "TLS_DHE_RSA_WITH_AES_256_CBC_SHA",
+        0x003c => "TLS_RSA_WITH_AES_128_CBC_SHA256",
+        0x003d => "TLS_RSA_WITH_AES_256_CBC_SHA256",
+        0x009c => "TLS_RSA_WITH_AES_128_GCM_SHA256",
+        0x009d => "TLS_RSA_WITH_AES_256_GCM_SHA384",
+        0x009e => "TLS_DHE_RSA_WITH_AES_128_GCM_SHA256",
+        0x009f => "TLS_DHE_RSA_WITH_AES_256_GCM_SHA384",
+        0x1301 => "TLS_AES_128_GCM_SHA256",
+        0x1302 => "TLS_AES_256_GCM_SHA384",
+        0x1303 => "TLS_CHACHA20_POLY1305_SHA256",
+        0x1304 => "TLS_AES_128_CCM_SHA256",
+        0x1305 => "TLS_AES_128_CCM_8_SHA256",
+        0xc009 => "TLS_ECDHE_ECDSA_WITH_AES_128_CBC_SHA",
+        0xc00a => "TLS_ECDHE_ECDSA_WITH_AES_256_CBC_SHA",
+        0xc011 => "TLS_ECDHE_RSA_WITH_RC4_128_SHA",
+        0xc012 => "TLS_ECDHE_RSA_WITH_3DES_EDE_CBC_SHA",
+        0xc013 => "TLS_ECDHE_RSA_WITH_AES_128_CBC_SHA",
+        0xc014 => "TLS_ECDHE_RSA_WITH_AES_256_CBC_SHA",
+        0xc023 => "TLS_ECDHE_ECDSA_WITH_AES_128_CBC_SHA256",
+        0xc024 => "TLS_ECDHE_ECDSA_WITH_AES_256_CBC_SHA384",
+        0xc027 => "TLS_ECDHE_RSA_WITH_AES_128_CBC_SHA256",
+        0xc028 => "TLS_ECDHE_RSA_WITH_AES_256_CBC_SHA384",
+        0xc02b => "TLS_ECDHE_ECDSA_WITH_AES_128_GCM_SHA256",
+        0xc02c => "TLS_ECDHE_ECDSA_WITH_AES_256_GCM_SHA384",
+        0xc02f => "TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256",
+        0xc030 => "TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384",
+        0xc0ac => "TLS_ECDHE_ECDSA_WITH_AES_128_CCM",
+        0xc0ad => "TLS_ECDHE_ECDSA_WITH_AES_256_CCM",
+        0xcca8 => "TLS_ECDHE_RSA_WITH_CHACHA20_POLY1305_SHA256",
+        0xcca9 => "TLS_ECDHE_ECDSA_WITH_CHACHA20_POLY1305_SHA256",
+        0xccaa => "TLS_DHE_RSA_WITH_CHACHA20_POLY1305_SHA256",
+        _ => return None,
+    };
+    Some(name)
+}
+
+// Whether a ciphersuite's key exchange is ephemeral, so that recorded traffic
+// can't be decrypted with the server key later. TLS 1.3 suites always are.
+// None for unknown codes.
+pub fn ciphersuite_forward_secrecy(code: u64) -> Option<bool> {
+    let name = ciphersuite_name(code)?;
+    Some((0x1301..=0x1305).contains(&code) || name.contains("_DHE_") || name.contains("_ECDHE_"))
+}
+
+// Forward secrecy of the connection a handshake event negotiated, None for
+// other events and unknown ciphersuites
+pub fn handshake_forward_secrecy(name: &str, fields: &HashMap<String, Value>) -> Option<bool> {
+    if !name.starts_with("tls::handshake_") {
+        return None;
+    }
+    fields
+        .get("tls::ciphersuite")
+        .and_then(Value::as_u64)
+        .and_then(ciphersuite_forward_secrecy)
+}
+
+// X.509 key usage bits, in the DER bit string order used by GnuTLS and OpenSSL
+const KEY_USAGE_BITS: [(u64, &str); 9] = [
+    (0x0080, "digitalSignature"),
+    (0x0040, "nonRepudiation"),
+    (0x0020, "keyEncipherment"),
+    (0x0010, "dataEncipherment"),
+    (0x0008, "keyAgreement"),
+    (0x0004, "keyCertSign"),
+    (0x0002, "cRLSign"),
+    (0x0001, "encipherOnly"),
+    (0x8000, "decipherOnly"),
+];
+
+pub fn key_usage_names(bits: u64) -> Vec<&'static str> {
+    KEY_USAGE_BITS
+        .iter()
+        .filter(|(bit, _)| bits & bit != 0)
+        .map(|(_, name)| *name)
+        .collect()
+}
+
+// Signature algorithm of a certificate, given either as a name or a TLS SignatureScheme
+pub fn certificate_signature_algorithm(fields: &HashMap<String, Value>) -> Option<String> {
+    match fields.get("x509::signature_algorithm")? {
+        Value::String(name) => Some(name.clone()),
+        Value::Number(code) => Some(
+            code.as_u64()
+                .and_then(signature_scheme_name)
+                .unwrap_or("unknown")
+                .to_string(),
+        ),
+        _ => None,
+    }
+}
+
+pub fn is_certificate_event(name: &str) -> bool {
+    name.starts_with("x509::") || name.starts_with("cert::")
+}
+
+pub fn is_random_event(name: &str) -> bool {
+    name.starts_with("rng::") || name.starts_with("entropy::")
+}
+
+// Generator type of an RNG event, e.g. "CTR-DRBG" or "getrandom"
+pub fn random_generator(fields: &HashMap<String, Value>) -> Option<String> {
+    fields
+        .get("rng::generator")
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string())
+}
+
+// Whether a generator is a NIST SP 800-90A deterministic random bit generator
+pub fn is_drbg(generator: &str) -> bool {
+    generator.to_lowercase().contains("drbg")
+}
+
+pub fn is_kdf_event(name: &str) -> bool {
+    let name = name.to_lowercase();
+    name.starts_with("kdf::") || name.contains("hkdf") || name.contains("pbkdf2")
+}
+
+// KDF of a key derivation event with its hash, e.g. "PBKDF2-SHA256".
+// The algorithm is taken from the event name when the event doesn't record it.
+pub fn kdf_algorithm(name: &str, fields: &HashMap<String, Value>) -> Option<String> {
+    let lower = name.to_lowercase();
+    let algorithm = match fields.get("kdf::algorithm").and_then(|v| v.as_str()) {
+        Some(algorithm) => algorithm.to_string(),
+        None if lower.contains("pbkdf2") => "PBKDF2".to_string(),
+        None if lower.contains("hkdf") => "HKDF".to_string(),
+        None => return None,
+    };
+    match fields.get("kdf::hash").and_then(|v| v.as_str()) {
+        Some(hash) => Some(format!("{}-{}", algorithm, hash.to_uppercase())),
+        None => Some(algorithm),
+    }
+}
+
+pub fn is_cipher_event(name: &str) -> bool {
+    name.starts_with("cipher::") || name == "tls::cipher"
+}
+
+// Cipher of a symmetric cipher event, e.g. "AES-256-GCM" or "AES-128-CBC-SHA1"
+pub fn cipher_name(fields: &HashMap<String, Value>) -> Option<String> {
+    let mut parts = vec![fields.get("cipher::algorithm")?.as_str()?.to_string()];
+    if let Some(bits) = fields.get("cipher::key_length").and_then(|v| v.as_u64()) {
+        parts.push(bits.to_string());
+    }
+    for key in ["cipher::mode", "cipher::mac"] {
+        if let Some(value) = fields.get(key).and_then(|v| v.as_str()) {
+            parts.push(value.to_string());
+        }
+    }
+    Some(parts.join("-").to_uppercase())
+}
+
+// Known weaknesses of a cipher or TLS ciphersuite, identified by name:
+// "NULL", "RC4", "DES", "3DES" and "CBC-SHA1" (CBC mode with HMAC-SHA1)
+pub fn cipher_weaknesses(name: &str) -> Vec<&'static str> {
+    let upper = name.to_uppercase();
+    let tokens: Vec<&str> = upper
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|t| !t.is_empty())
+        .collect();
+    let has = |token: &str| tokens.contains(&token);
+
+    let mut weaknesses = Vec::new();
+    if has("NULL") {
+        weaknesses.push("NULL");
+    }
+    if has("RC4") {
+        weaknesses.push("RC4");
+    }
+    if has("3DES") || has("TDES") || (has("DES") && has("EDE")) {
+        weaknesses.push("3DES");
+    } else if has("DES") {
+        weaknesses.push("DES");
+    }
+    if has("CBC") && (has("SHA") || has("SHA1")) {
+        weaknesses.push("CBC-SHA1");
+    }
+    weaknesses
+}
+
+pub fn is_ktls_event(name: &str) -> bool {
+    name.starts_with("ktls::")
+}
+
+// Cipher of a kTLS offload, from the TLS_CIPHER_* constants of linux/tls.h
+pub fn ktls_cipher_name(code: u64) -> Option<&'static str> {
+    let name = match code {
+        51 => "AES-GCM-128",
+        52 => "AES-GCM-256",
+        53 => "AES-CCM-128",
+        54 => "CHACHA20-POLY1305",
+        55 => "SM4-GCM",
+        56 => "SM4-CCM",
+        251 => "ARIA-GCM-128",
+        252 => "ARIA-GCM-256",
+        _ => return None,
+    };
+    Some(name)
+}
+
+// Cipher and direction of a kTLS event, e.g. ("AES-GCM-128", "transmit").
+// The cipher and direction may be given by name or by their linux/tls.h
+// constant, TLS_TX (1) or TLS_RX (2) for the direction.
+pub fn ktls_offload(fields: &HashMap<String, Value>) -> (Option<String>, Option<String>) {
+    let cipher = match fields.get("ktls::cipher") {
+        Some(Value::Number(code)) => Some(
+            code.as_u64()
+                .and_then(ktls_cipher_name)
+                .map_or_else(|| format!("cipher {}", code), str::to_string),
+        ),
+        Some(Value::String(name)) => Some(name.to_uppercase()),
+        _ => None,
+    };
+    let direction = match fields.get("ktls::direction") {
+        Some(Value::Number(code)) if code.as_u64() == Some(1) => Some("transmit".to_string()),
+        Some(Value::Number(code)) if code.as_u64() == Some(2) => Some("receive".to_string()),
+        Some(Value::String(direction)) => Some(match direction.to_lowercase().as_str() {
+            "tx" | "transmit" | "send" => "transmit".to_string(),
+            "rx" | "receive" | "recv" => "receive".to_string(),
+            other => other.to_string(),
+        }),
+        _ => None,
+    };
+    (cipher, direction)
+}
+
+pub fn is_ssh_event(name: &str) -> bool {
+    name.starts_with("ssh::")
+}
+
+// Algorithms an SSH event negotiated, as (field, name), in the order of the
+// key exchange, host key, cipher and MAC. The MAC is left out for AEAD
+// ciphers, which OpenSSH reports as "<implicit>".
+pub fn ssh_algorithms(fields: &HashMap<String, Value>) -> Vec<(&'static str, String)> {
+    const FIELDS: [&str; 4] = [
+        "ssh::kex_algorithm",
+        "ssh::host_key_algorithm",
+        "ssh::cipher",
+        "ssh::mac",
+    ];
+    FIELDS
+        .iter()
+        .filter_map(|field| {
+            let value = fields.get(*field)?.as_str()?;
+            (!value.is_empty() && value != "<implicit>").then(|| (*field, value.to_string()))
+        })
+        .collect()
+}
+
+// Hash an SSH algorithm is built on, where it's a broken or deprecated one:
+// "ssh-rsa" and "ssh-dss" host keys sign with SHA1
+pub fn ssh_weak_hash(algorithm: &str) -> Option<&'static str> {
+    let lower = algorithm.to_lowercase();
+    if lower.contains("md5") {
+        Some("MD5")
+    } else if lower.ends_with("sha1")
+        || lower.contains("sha1-")
+        || lower.contains("sha1@")
+        || lower == "ssh-rsa"
+        || lower == "ssh-dss"
+    {
+        Some("SHA1")
+    } else {
+        None
+    }
+}
+
+// Namespace of an IKE or IPsec event, e.g. "ike::"
+pub fn ike_namespace(name: &str) -> Option<&'static str> {
+    ike::NAMESPACES
+        .iter()
+        .copied()
+        .find(|namespace| name.starts_with(namespace))
+}
+
+pub fn is_hash_event(name: &str) -> bool {
+    name.starts_with("hash::")
+}
+
+// Hash function of a message digest event, e.g. "SHA256"
+pub fn hash_algorithm(fields: &HashMap<String, Value>) -> Option<String> {
+    fields
+        .get("hash::algorithm")
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_uppercase())
+}
+
+// Whether a name shown in the stats, such as an algorithm, cipher or signature
+// scheme, stands for something considered weak regardless of the policy
+pub fn is_weak_algorithm(name: &str) -> bool {
+    let lower = name.to_lowercase();
+    !cipher_weaknesses(name).is_empty()
+        || ["md2", "md4", "md5", "sha1", "sha-1", "sha224"]
+            .iter()
+            .any(|weak| lower.contains(weak))
+}
+
+// Process an event was recorded in, e.g. "nginx (1234)", taken from the event's
+// fields or else from its context ID. Context IDs created by the crypto-auditing
+// agent start with the BPF pid_tgid of the thread, in little-endian byte order,
+// whose upper half is the process ID.
+pub fn process_name(fields: &HashMap<String, Value>, context: &str) -> Option<String> {
+    let get = |keys: [&str; 2]| keys.iter().find_map(|key| fields.get(*key));
+    let comm = get(["process::comm", "comm"]).and_then(|v| v.as_str());
+    let pid = get(["process::pid", "pid"])
+        .and_then(|v| v.as_u64())
+        .or_else(|| context_pid(context).map(u64::from));
+
+    match (comm, pid) {
+        (Some(comm), Some(pid)) => Some(format!("{} ({})", comm, pid)),
+        (Some(comm), None) => Some(comm.to_string()),
+        (None, Some(pid)) => Some(format!("pid {}", pid)),
+        (None, None) => None,
+    }
+}
+
+fn context_pid(context: &str) -> Option<u32> {
+    let hex = context.get(..16)?;
+    let mut bytes = [0u8; 8];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(hex.get(i * 2..i * 2 + 2)?, 16).ok()?;
+    }
+    let pid = (u64::from_le_bytes(bytes) >> 32) as u32;
+    (pid != 0).then_some(pid)
+}
+
+// Synthetic tree branch grouping events of the same category among their siblings
+pub fn category(name: &str) -> Option<&'static str> {
+    if is_certificate_event(name) {
+        Some("certificates")
+    } else if is_random_event(name) {
+        Some("random")
+    } else if is_kdf_event(name) {
+        Some("key derivation")
+    } else if is_cipher_event(name) {
+        Some("ciphers")
+    } else if is_hash_event(name) {
+        Some("hashes")
+    } else if is_ktls_event(name) {
+        Some("kernel TLS")
+    } else {
+        None
+    }
+}
+
+// Whether format_details knows how to describe events of this name
+pub fn is_known_event(name: &str) -> bool {
+    name.starts_with("tls::handshake_")
+        || name == "tls::sign"
+        || name == "tls::verify"
+        || name == "tls::key_exchange"
+        || name.starts_with("pk::")
+        || is_certificate_event(name)
+        || is_random_event(name)
+        || is_kdf_event(name)
+        || is_cipher_event(name)
+        || is_hash_event(name)
+        || is_ktls_event(name)
+        || is_ssh_event(name)
+        || ike_namespace(name).is_some()
+}
+
+// A codepoint or event name the decoders did not recognize
+#[derive(Debug, Clone)]
+pub struct UnknownCode {
+    pub field: String,
+    pub code: String,
+    pub count: usize,
+}
+
+// Collect all unrecognized codepoints and event names in the events,
+// sorted by occurrence count, descending
+pub fn collect_unknown(events: &[AuditEvent]) -> Vec<UnknownCode> {
+    type Decoder = fn(u64) -> Option<&'static str>;
+    const NUMERIC_FIELDS: [(&str, Decoder); 17] = [
+        ("tls::protocol_version", protocol_version_name),
+        ("tls::ciphersuite", ciphersuite_name),
+        ("tls::group", group_name),
+        ("tls::signature_algorithm", signature_scheme_name),
+        ("x509::signature_algorithm", signature_scheme_name),
+        ("gcry::md_algo", gcrypt::md_name),
+        ("gcry::cipher_algo", |id| Some(gcrypt::cipher_name(id)?.0)),
+        ("gcry::cipher_mode", gcrypt::mode_name),
+        ("gcry::pk_algo", gcrypt::pk_name),
+        ("ike::encryption", ike::encryption_name),
+        ("ike::prf", ike::prf_name),
+        ("ike::integrity", ike_integrity_name),
+        ("ike::dh_group", |id| Some(ike::dh_group_name(id)?.0)),
+        ("ipsec::encryption", ike::encryption_name),
+        ("ipsec::prf", ike::prf_name),
+        ("ipsec::integrity", ike_integrity_name),
+        ("ipsec::dh_group", |id| Some(ike::dh_group_name(id)?.0)),
+    ];
+
+    // AEAD ciphers need no separate integrity algorithm, which is then NONE (0)
+    fn ike_integrity_name(id: u64) -> Option<&'static str> {
+        if id == 0 {
+            Some("NONE")
+        } else {
+            ike::integrity_name(id)
+        }
+    }
+
+    fn visit(event: &AuditEvent, counts: &mut HashMap<(String, String), usize>) {
+        let name = event.name();
+        if !is_known_event(&name) {
+            *counts.entry(("event name".to_string(), name)).or_insert(0) += 1;
+        }
+
+        for (field, decode) in NUMERIC_FIELDS {
+            if let Some(code) = event.events.get(field).and_then(|v| v.as_u64()) {
+                if decode(code).is_none() {
+                    *counts
+                        .entry((field.to_string(), format!("0x{:04x}", code)))
+                        .or_insert(0) += 1;
+                }
+            }
+        }
+
+        for span in &event.spans {
+            visit(span, counts);
+        }
+    }
+
+    let mut counts = HashMap::new();
+    for event in events {
+        visit(event, &mut counts);
+    }
+
+    let mut unknown: Vec<UnknownCode> = counts
+        .into_iter()
+        .map(|((field, code), count)| UnknownCode { field, code, count })
+        .collect();
+    unknown.sort_by(|a, b| {
+        b.count
+            .cmp(&a.count)
+            .then_with(|| a.field.cmp(&b.field))
+            .then_with(|| a.code.cmp(&b.code))
+    });
+    unknown
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn event(fields: Value) -> AuditEvent {
+        AuditEvent {
+            context: "0a1b".to_string(),
+            origin: "libgcrypt".to_string(),
+            start: 0,
+            end: 0,
+            events: serde_json::from_value(fields).unwrap(),
+            spans: Vec::new(),
+            boot_id: None,
+        }
+    }
+
+    fn unknown(events: &[AuditEvent]) -> Vec<(String, String, usize)> {
+        collect_unknown(events)
+            .into_iter()
+            .map(|unknown| (unknown.field, unknown.code, unknown.count))
+            .collect()
+    }
+
+    #[test]
+    fn unknown_gcrypt_identifiers() {
+        let events = [
+            event(json!({"name": "hash::digest", "gcry::md_algo": 2})),
+            event(json!({"name": "hash::digest", "gcry::md_algo": 999})),
+            event(
+                json!({"name": "cipher::encrypt", "gcry::cipher_algo": 999, "gcry::cipher_mode": 3}),
+            ),
+            event(json!({"name": "cipher::encrypt", "gcry::cipher_mode": 999})),
+            event(json!({"name": "pk::sign", "gcry::pk_algo": 999})),
+        ];
+        let mut found = unknown(&events);
+        found.sort();
+        assert_eq!(
+            found,
+            vec![
+                ("gcry::cipher_algo".to_string(), "0x03e7".to_string(), 1),
+                ("gcry::cipher_mode".to_string(), "0x03e7".to_string(), 1),
+                ("gcry::md_algo".to_string(), "0x03e7".to_string(), 1),
+                ("gcry::pk_algo".to_string(), "0x03e7".to_string(), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_ike_transforms() {
+        let events = [
+            event(
+                json!({"name": "ike::sa_established", "ike::encryption": 20, "ike::integrity": 0, "ike::prf": 5, "ike::dh_group": 31}),
+            ),
+            event(
+                json!({"name": "ipsec::sa_established", "ipsec::encryption": 1024, "ipsec::dh_group": 1024}),
+            ),
+        ];
+        let mut found = unknown(&events);
+        found.sort();
+        assert_eq!(
+            found,
+            vec![
+                ("ipsec::dh_group".to_string(), "0x0400".to_string(), 1),
+                ("ipsec::encryption".to_string(), "0x0400".to_string(), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_tls_codepoints() {
+        let events = [
+            event(json!({"name": "tls::handshake_client", "tls::ciphersuite": 0x1301})),
+            event(json!({"name": "tls::handshake_client", "tls::ciphersuite": 0xabcd})),
+            event(json!({"name": "tls::handshake_client", "tls::ciphersuite": 0xabcd})),
+            event(json!({"name": "made::up"})),
+        ];
+        assert_eq!(
+            unknown(&events),
+            vec![
+                ("tls::ciphersuite".to_string(), "0xabcd".to_string(), 2),
+                ("event name".to_string(), "made::up".to_string(), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn weak_ciphers() {
+        assert_eq!(cipher_weaknesses("TLS_RSA_WITH_NULL_SHA256"), ["NULL"]);
+        assert_eq!(cipher_weaknesses("tls_rsa_with_rc4_128_md5"), ["RC4"]);
+        assert_eq!(
+            cipher_weaknesses("TLS_RSA_WITH_3DES_EDE_CBC_SHA"),
+            ["3DES", "CBC-SHA1"]
+        );
+        assert_eq!(cipher_weaknesses("DES-CBC"), ["DES"]);
+        assert_eq!(cipher_weaknesses("AES-128-CBC-SHA1"), ["CBC-SHA1"]);
+    }
+
+    #[test]
+    fn strong_ciphers() {
+        assert!(cipher_weaknesses("AES-256-GCM").is_empty());
+        assert!(cipher_weaknesses("TLS_ECDHE_RSA_WITH_AES_128_CBC_SHA256").is_empty());
+        // Names are matched by whole tokens
+        assert!(cipher_weaknesses("NULLIFIED-CIPHER4").is_empty());
+    }
+
+    #[test]
+    fn weak_algorithms() {
+        for name in [
+            "MD5",
+            "sha1WithRSAEncryption",
+            "SHA-1",
+            "SHA224",
+            "RC4-128",
+            "TLS_RSA_WITH_3DES_EDE_CBC_SHA",
+        ] {
+            assert!(is_weak_algorithm(name), "{}", name);
+        }
+        for name in [
+            "SHA256",
+            "ecdsa_secp256r1_sha256",
+            "rsa_pss_rsae_sha384",
+            "AES-256-GCM",
+        ] {
+            assert!(!is_weak_algorithm(name), "{}", name);
+        }
+    }
+}
@@ -0,0 +1,192 @@
+use crate::chains;
+use crate::data::{NodeKind, TreeNode};
+use crate::help;
+use crate::models::StatsObject;
+use crate::navigation;
+use crate::references;
+use crate::sunburst::SunburstChart;
+use adw::prelude::*;
+use adw::{ActionRow, HeaderBar, ToolbarView, ViewStack, ViewSwitcher, ViewSwitcherPolicy};
+use gtk4::{
+    gio, ColumnView, Label, ListBox, Orientation, ScrolledWindow, SingleSelection, TextView,
+};
+use std::rc::Rc;
+
+// Open a window for a subtree with its own sunburst, stats, handshakes and raw events, leaving
+// the main view as it is. Double-clicking a segment there opens another one.
+pub fn show_detail_window(
+    parent: &impl IsA<gtk4::Window>,
+    node: &TreeNode,
+    source: &SunburstChart,
+) {
+    let chart = SunburstChart::new();
+    chart.set_palette(source.palette());
+    chart.set_tooltip_options(source.tooltip_options());
+    chart.set_weighting(source.weighting());
+    chart.set_layout(source.layout());
+    chart.set_percent_base(source.percent_base());
+
+    // Zooming within the window
+    let sunburst_box = gtk4::Box::new(Orientation::Vertical, 0);
+    sunburst_box.append(&navigation::create_zoom_navigation(
+        Rc::new(chart.clone()),
+        &node.display_name(),
+    ));
+
+    // Algorithm stats of the subtree
+    let stats_store = gio::ListStore::new::<StatsObject>();
+    let stats_view = ColumnView::new(Some(SingleSelection::new(Some(stats_store.clone()))));
+    stats_view.add_css_class("data-table");
+    stats_view.append_column(&crate::create_term_column(
+        "Algorithm",
+        |obj: &StatsObject| obj.algorithm(),
+    ));
+    stats_view.append_column(&crate::create_text_column(
+        "Count",
+        gtk4::Align::End,
+        false,
+        |obj: &StatsObject| obj.count(),
+    ));
+    stats_view.append_column(&crate::create_text_column(
+        "Percentage",
+        gtk4::Align::End,
+        false,
+        |obj: &StatsObject| obj.percentage(),
+    ));
+    chart.set_stats_store(stats_store);
+    let stats_scroll = ScrolledWindow::new();
+    stats_scroll.set_child(Some(&stats_view));
+
+    // Raw fields of the events below the node
+    let mut events = Vec::new();
+    collect_event_fields(node, &mut events);
+    let events_view = TextView::new();
+    events_view.set_editable(false);
+    events_view.set_monospace(true);
+    events_view.set_left_margin(12);
+    events_view.set_top_margin(12);
+    events_view
+        .buffer()
+        .set_text(&serde_json::to_string_pretty(&events).unwrap_or_default());
+    let events_scroll = ScrolledWindow::new();
+    events_scroll.set_child(Some(&events_view));
+
+    // Handshakes with the operations they triggered, shown if there are any
+    let chains = chains::handshake_chains(node);
+    let chains_page = (!chains.is_empty()).then(|| create_chains_page(&chains));
+
+    // Specifications of the codepoints, shown if there are any
+    let references_page = references::create_references_page(node);
+
+    let view_stack = ViewStack::new();
+    view_stack.set_vexpand(true);
+    view_stack
+        .add_titled(&sunburst_box, Some("sunburst"), "Sunburst")
+        .set_icon_name(Some("view-pie-symbolic"));
+    view_stack
+        .add_titled(&stats_scroll, Some("stats"), "Statistics")
+        .set_icon_name(Some("view-list-symbolic"));
+    if let Some(chains_page) = &chains_page {
+        view_stack
+            .add_titled(chains_page, Some("handshakes"), "Handshakes")
+            .set_icon_name(Some("network-transmit-receive-symbolic"));
+    }
+    if let Some(references_page) = &references_page {
+        view_stack
+            .add_titled(references_page, Some("references"), "References")
+            .set_icon_name(Some("web-browser-symbolic"));
+    }
+    view_stack
+        .add_titled(&events_scroll, Some("events"), "Raw Events")
+        .set_icon_name(Some("text-x-generic-symbolic"));
+
+    let header_bar = HeaderBar::new();
+    header_bar.set_title_widget(Some(
+        &ViewSwitcher::builder()
+            .stack(&view_stack)
+            .policy(ViewSwitcherPolicy::Wide)
+            .build(),
+    ));
+    let help_button = gtk4::Button::from_icon_name("help-browser-symbolic");
+    help_button.set_tooltip_text(Some("Event Types"));
+    header_bar.pack_end(&help_button);
+
+    let toolbar_view = ToolbarView::new();
+    toolbar_view.add_top_bar(&header_bar);
+    toolbar_view.set_content(Some(&view_stack));
+
+    let window = adw::Window::builder()
+        .title(node.display_name())
+        .transient_for(parent)
+        .default_width(800)
+        .default_height(700)
+        .content(&toolbar_view)
+        .build();
+
+    let window_clone = window.clone();
+    let help_node = node.clone();
+    help_button.connect_clicked(move |_| {
+        help::show_event_help(&window_clone, &help_node);
+    });
+
+    let window_clone = window.clone();
+    let chart_clone = chart.clone();
+    chart.connect_segment_activated(move |node| {
+        show_detail_window(&window_clone, node, &chart_clone);
+    });
+
+    chart.set_tree(node.clone());
+    window.present();
+}
+
+// A boxed list per handshake, with its operations connected below it
+fn create_chains_page(chains: &[chains::Chain]) -> ScrolledWindow {
+    let content = gtk4::Box::new(Orientation::Vertical, 18);
+    content.set_margin_start(12);
+    content.set_margin_end(12);
+    content.set_margin_top(12);
+    content.set_margin_bottom(12);
+
+    for chain in chains {
+        let list = ListBox::new();
+        list.set_selection_mode(gtk4::SelectionMode::None);
+        list.add_css_class("boxed-list");
+
+        let handshake_row = ActionRow::builder()
+            .title(&chain.handshake)
+            .use_markup(false)
+            .build();
+        handshake_row.add_css_class("heading");
+        list.append(&handshake_row);
+
+        if chain.steps.is_empty() {
+            let row = ActionRow::builder().title("No operations recorded").build();
+            row.add_css_class("dim-label");
+            list.append(&row);
+        }
+        for step in &chain.steps {
+            let row = ActionRow::builder().title(step).use_markup(false).build();
+            let connector = Label::new(Some("↳"));
+            connector.add_css_class("dim-label");
+            row.add_prefix(&connector);
+            list.append(&row);
+        }
+        content.append(&list);
+    }
+
+    let scroll = ScrolledWindow::new();
+    scroll.set_child(Some(&content));
+    scroll
+}
+
+fn collect_event_fields<'a>(
+    node: &'a TreeNode,
+    events: &mut Vec<&'a std::collections::HashMap<String, serde_json::Value>>,
+) {
+    if node.kind == NodeKind::Event {
+        events.push(&node.fields);
+    }
+    for child in &node.children {
+        collect_event_fields(child, events);
+    }
+}
@@ -0,0 +1,180 @@
+use crate::data::TreeNode;
+use crate::filters::TlsVersion;
+use crate::sunburst::SunburstChart;
+use adw::prelude::*;
+use gtk4::{DrawingArea, GestureClick};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::f64::consts::PI;
+use std::rc::Rc;
+
+const SIZE: f64 = 120.0;
+const OUTER_RADIUS: f64 = 56.0;
+const INNER_RADIUS: f64 = 34.0;
+const LEGEND_X: f64 = SIZE + 12.0;
+const LEGEND_ROW_HEIGHT: f64 = 22.0;
+const FONT_SIZE: f64 = 12.0;
+
+fn version_color(version: TlsVersion) -> (f64, f64, f64) {
+    match version {
+        TlsVersion::Tls13 => (0.30, 0.69, 0.31),
+        TlsVersion::Tls12 => (0.13, 0.59, 0.95),
+        TlsVersion::Legacy => (0.90, 0.30, 0.24),
+    }
+}
+
+// Handshakes in the view by version, in the order of TlsVersion::ALL
+fn count_versions(root: &TreeNode) -> Vec<(TlsVersion, usize)> {
+    let mut stats = HashMap::new();
+    root.extract_stats_by(
+        &|node| {
+            let version = TlsVersion::of_event(node.event_name()?, &node.fields)?;
+            Some(version.label().to_string())
+        },
+        &mut stats,
+    );
+    TlsVersion::ALL
+        .iter()
+        .map(|version| (*version, stats.get(version.label()).copied().unwrap_or(0)))
+        .collect()
+}
+
+// Version under a point of the donut or its legend
+fn version_at(counts: &[(TlsVersion, usize)], x: f64, y: f64) -> Option<TlsVersion> {
+    if x >= LEGEND_X {
+        let row = (y / LEGEND_ROW_HEIGHT) as usize;
+        return counts.get(row).map(|(version, _)| *version);
+    }
+
+    let (dx, dy) = (x - SIZE / 2.0, y - SIZE / 2.0);
+    let distance = (dx * dx + dy * dy).sqrt();
+    if !(INNER_RADIUS..=OUTER_RADIUS).contains(&distance) {
+        return None;
+    }
+    // Slices start at the top and go clockwise
+    let angle = (dy.atan2(dx) + PI / 2.0).rem_euclid(2.0 * PI);
+    let total: usize = counts.iter().map(|(_, count)| count).sum();
+    let mut start = 0.0;
+    for (version, count) in counts {
+        let end = start + *count as f64 / total as f64 * 2.0 * PI;
+        if angle < end {
+            return Some(*version);
+        }
+        start = end;
+    }
+    None
+}
+
+// Donut of the TLS 1.3 / 1.2 / legacy split of the handshakes in the view.
+// Clicking a version shows only connections of it; clicking it again shows all.
+pub fn create_version_donut(chart: Rc<SunburstChart>) -> gtk4::Box {
+    let section = gtk4::Box::new(gtk4::Orientation::Vertical, 6);
+    section.set_margin_start(12);
+    section.set_margin_end(12);
+    section.set_margin_top(12);
+    section.set_margin_bottom(12);
+    section.set_visible(false);
+
+    let title = gtk4::Label::new(Some("TLS Versions"));
+    title.set_halign(gtk4::Align::Start);
+    title.add_css_class("title-4");
+    section.append(&title);
+
+    let counts: Rc<RefCell<Vec<(TlsVersion, usize)>>> = Rc::new(RefCell::new(Vec::new()));
+
+    let drawing_area = DrawingArea::new();
+    drawing_area.set_content_width((LEGEND_X + 120.0) as i32);
+    drawing_area.set_content_height(SIZE as i32);
+    drawing_area.set_tooltip_text(Some(
+        "Click a version to show only its connections, and again to show all",
+    ));
+    section.append(&drawing_area);
+
+    let counts_clone = counts.clone();
+    let chart_clone = chart.clone();
+    drawing_area.set_draw_func(move |_, cr, _, _| {
+        let counts = counts_clone.borrow();
+        let total: usize = counts.iter().map(|(_, count)| count).sum();
+        if total == 0 {
+            return;
+        }
+        let active = chart_clone.tls_version();
+        let (cx, cy) = (SIZE / 2.0, SIZE / 2.0);
+
+        let mut start = -PI / 2.0;
+        for (version, count) in counts.iter() {
+            let end = start + *count as f64 / total as f64 * 2.0 * PI;
+            let (r, g, b) = version_color(*version);
+            cr.set_source_rgb(r, g, b);
+            cr.arc(cx, cy, OUTER_RADIUS, start, end);
+            cr.arc_negative(cx, cy, INNER_RADIUS, end, start);
+            cr.close_path();
+            cr.fill().unwrap();
+            start = end;
+        }
+
+        // Number of handshakes in the hole
+        cr.set_font_size(FONT_SIZE * 1.5);
+        let text = total.to_string();
+        if let Ok(extents) = cr.text_extents(&text) {
+            cr.set_source_rgb(0.3, 0.3, 0.3);
+            cr.move_to(
+                cx - extents.width() / 2.0 - extents.x_bearing(),
+                cy - extents.height() / 2.0 - extents.y_bearing(),
+            );
+            cr.show_text(&text).unwrap();
+        }
+
+        cr.set_font_size(FONT_SIZE);
+        for (i, (version, count)) in counts.iter().enumerate() {
+            let y = i as f64 * LEGEND_ROW_HEIGHT;
+            let (r, g, b) = version_color(*version);
+            cr.set_source_rgb(r, g, b);
+            cr.rectangle(LEGEND_X, y + 5.0, 12.0, 12.0);
+            cr.fill().unwrap();
+
+            // The version filtered to is marked in bold
+            let weight = if active == Some(*version) {
+                cairo::FontWeight::Bold
+            } else {
+                cairo::FontWeight::Normal
+            };
+            cr.select_font_face("Sans", cairo::FontSlant::Normal, weight);
+            cr.set_source_rgb(0.3, 0.3, 0.3);
+            cr.move_to(
+                LEGEND_X + 18.0,
+                y + LEGEND_ROW_HEIGHT / 2.0 + FONT_SIZE / 3.0,
+            );
+            let share = (*count as f64 / total as f64 * 100.0).round() as u32;
+            cr.show_text(&format!("{} {}%", version.label(), share))
+                .unwrap();
+        }
+    });
+
+    let click = GestureClick::new();
+    let counts_clone = counts.clone();
+    let chart_clone = chart.clone();
+    click.connect_released(move |_, _, x, y| {
+        let Some(version) = version_at(&counts_clone.borrow(), x, y) else {
+            return;
+        };
+        if chart_clone.tls_version() == Some(version) {
+            chart_clone.set_tls_version(None);
+        } else {
+            chart_clone.set_tls_version(Some(version));
+        }
+    });
+    drawing_area.add_controller(click);
+
+    let section_clone = section.clone();
+    let chart_clone = chart.clone();
+    chart.connect_view_changed(move |root| {
+        let new_counts = count_versions(root);
+        let has_handshakes = new_counts.iter().any(|(_, count)| *count > 0);
+        *counts.borrow_mut() = new_counts;
+        section_clone.set_visible(has_handshakes || chart_clone.tls_version().is_some());
+        drawing_area.queue_draw();
+    });
+
+    section
+}
@@ -0,0 +1,342 @@
+use crate::bars;
+use crate::data::AuditEvent;
+use crate::models::StatsObject;
+use crate::sunburst::SunburstChart;
+use crate::timezone::display_time;
+use adw::prelude::*;
+use anyhow::Result;
+use cairo::{Context, FontSlant, FontWeight, Format, ImageSurface, PdfSurface, SvgSurface};
+use gtk4::gio;
+use std::fs::File;
+use std::path::Path;
+use sysinfo::System;
+
+// Size of exported images, in pixels for PNG at 1× and points for SVG
+const SIZE: f64 = 1000.0;
+const WATERMARK_FONT_SIZE: f64 = 12.0;
+const WATERMARK_MARGIN: f64 = 12.0;
+// Width of the exported statistics, without the margins around them
+const STATS_WIDTH: f64 = 560.0;
+const STATS_MARGIN: f64 = 20.0;
+const STATS_TITLE_HEIGHT: f64 = 36.0;
+const STATS_FONT_SIZE: f64 = 12.0;
+
+// Pixel densities offered for PNG exports, as choices of the export dialogs.
+// SVG exports are vectors, sharp at any density.
+pub const SCALES: [(&str, &str); 3] = [
+    ("1", "1× (96 DPI)"),
+    ("2", "2× (192 DPI)"),
+    ("3", "3× (288 DPI)"),
+];
+
+// Choice of SCALES matching a display's scale factor, so exports look as sharp
+// as the chart on it
+pub fn scale_choice(scale_factor: i32) -> String {
+    scale_factor.clamp(1, SCALES.len() as i32).to_string()
+}
+
+// Describes where an exported image came from, so report images remain
+// traceable to their source data
+pub fn watermark_lines(chart: &SunburstChart) -> Vec<String> {
+    let source = chart
+        .source_path()
+        .and_then(|path| Some(path.file_name()?.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| "unsaved capture".to_string());
+    let metadata = chart.metadata().unwrap_or_default();
+    let host = chart
+        .source_host()
+        .or_else(|| metadata.hostname.clone())
+        .or_else(System::host_name)
+        .unwrap_or_else(|| "unknown host".to_string());
+
+    // File names often name the host too
+    let mut lines = vec![match chart.anonymizer() {
+        Some(anonymizer) => format!(
+            "{} on {}",
+            anonymizer.pseudonym("capture", &source),
+            anonymizer.pseudonym("host", &host)
+        ),
+        None => format!("{} on {}", source, host),
+    }];
+    // Recording environment from the capture's metadata header
+    let mut environment = Vec::new();
+    if let Some(kernel) = &metadata.kernel {
+        environment.push(format!("Kernel {}", kernel));
+    }
+    environment.extend(
+        metadata
+            .libraries
+            .iter()
+            .map(|(library, version)| format!("{} {}", library, version)),
+    );
+    if let Some(tool) = &metadata.tool_version {
+        environment.push(format!("Recorded with {}", tool));
+    }
+    if !environment.is_empty() {
+        lines.push(environment.join(" · "));
+    }
+    let events = chart.events();
+    if let Some((start, end)) = chart
+        .time_window()
+        .or_else(|| AuditEvent::get_time_range(&events))
+    {
+        lines.push(format!(
+            "Captured {} – {}",
+            display_time(start).strftime("%F %T"),
+            display_time(end).strftime("%T %Z")
+        ));
+    }
+    // The filter expression, so the view can be reproduced
+    if let Some(query) = chart.filter_query() {
+        lines.push(match query.name() {
+            Some(name) => format!("Filtered by “{}”: {}", name, query.text()),
+            None => format!("Filtered by {}", query.text()),
+        });
+    }
+    lines.push(format!(
+        "Generated {}",
+        jiff::Zoned::now().strftime("%F %T %Z")
+    ));
+    lines
+}
+
+// Stamp the lines into the bottom left corner of an image of the height
+fn draw_watermark(cr: &Context, lines: &[String], height: f64) -> Result<()> {
+    cr.select_font_face("Sans", FontSlant::Normal, FontWeight::Normal);
+    cr.set_font_size(WATERMARK_FONT_SIZE);
+    cr.set_source_rgb(0.4, 0.4, 0.4);
+
+    let line_height = WATERMARK_FONT_SIZE * 1.4;
+    let mut y = height - WATERMARK_MARGIN - line_height * (lines.len() as f64 - 1.0);
+    for line in lines {
+        cr.move_to(WATERMARK_MARGIN, y);
+        cr.show_text(line)?;
+        y += line_height;
+    }
+    Ok(())
+}
+
+// Export the visible chart as SVG if the path ends in ".svg", and as PNG at
+// the scale otherwise
+pub fn export_chart(chart: &SunburstChart, path: &Path, watermark: bool, scale: f64) -> Result<()> {
+    let lines = if watermark {
+        watermark_lines(chart)
+    } else {
+        Vec::new()
+    };
+    let draw = |cr: &Context| -> Result<()> {
+        chart.render(cr, SIZE, SIZE);
+        if !lines.is_empty() {
+            draw_watermark(cr, &lines, SIZE)?;
+        }
+        Ok(())
+    };
+
+    write_image(path, SIZE, SIZE, scale, draw)
+}
+
+// Write what the function draws as SVG if the path ends in ".svg", and as PNG
+// otherwise. PNGs have the scale times as many pixels in each direction, with
+// the drawing scaled up to match.
+fn write_image(
+    path: &Path,
+    width: f64,
+    height: f64,
+    scale: f64,
+    draw: impl Fn(&Context) -> Result<()>,
+) -> Result<()> {
+    let is_svg = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("svg"));
+    if is_svg {
+        let surface = SvgSurface::new(width, height, Some(path))?;
+        draw(&Context::new(&surface)?)?;
+        surface.finish();
+    } else {
+        let surface = ImageSurface::create(
+            Format::ARgb32,
+            (width * scale).ceil() as i32,
+            (height * scale).ceil() as i32,
+        )?;
+        surface.set_device_scale(scale, scale);
+        draw(&Context::new(&surface)?)?;
+        surface.write_to_png(&mut File::create(path)?)?;
+    }
+    Ok(())
+}
+
+// The algorithm statistics as tab-separated values with a header row, for
+// pasting into spreadsheets
+pub fn stats_tsv(store: &gio::ListStore) -> String {
+    // Tabs and line breaks in a value would shift the cells
+    let cell = |value: String| value.replace(['\t', '\n'], " ");
+    let mut tsv = String::from("Algorithm\tCount\tPercentage\n");
+    for obj in store.iter::<StatsObject>().filter_map(Result::ok) {
+        tsv.push_str(&format!(
+            "{}\t{}\t{}\n",
+            cell(obj.algorithm()),
+            cell(obj.count()),
+            cell(obj.percentage())
+        ));
+    }
+    tsv
+}
+
+// How the statistics are drawn in an exported image
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatsForm {
+    Table,
+    Bars,
+}
+
+impl StatsForm {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "table" => Some(Self::Table),
+            "bars" => Some(Self::Bars),
+            _ => None,
+        }
+    }
+}
+
+fn draw_stats_table(cr: &Context, store: &gio::ListStore) -> Result<()> {
+    let text_y = bars::ROW_HEIGHT / 2.0 + STATS_FONT_SIZE / 3.0;
+    let columns = [
+        (0.0, "Algorithm"),
+        (STATS_WIDTH * 0.6, "Count"),
+        (STATS_WIDTH * 0.8, "Percentage"),
+    ];
+
+    cr.set_font_size(STATS_FONT_SIZE);
+    cr.select_font_face("Sans", FontSlant::Normal, FontWeight::Bold);
+    cr.set_source_rgb(0.2, 0.2, 0.2);
+    for (x, heading) in columns {
+        cr.move_to(x, text_y);
+        cr.show_text(heading)?;
+    }
+
+    cr.select_font_face("Sans", FontSlant::Normal, FontWeight::Normal);
+    for (i, obj) in store
+        .iter::<StatsObject>()
+        .filter_map(Result::ok)
+        .enumerate()
+    {
+        let y = (i + 1) as f64 * bars::ROW_HEIGHT;
+        // Rule between the rows, like the data tables of the sidebar
+        cr.set_source_rgb(0.85, 0.85, 0.85);
+        cr.rectangle(0.0, y, STATS_WIDTH, 1.0);
+        cr.fill()?;
+
+        cr.set_source_rgb(0.3, 0.3, 0.3);
+        let values = [obj.algorithm(), obj.count(), obj.percentage()];
+        for ((x, _), value) in columns.iter().zip(values) {
+            cr.move_to(*x, y + text_y);
+            cr.show_text(&value)?;
+        }
+    }
+    Ok(())
+}
+
+// Size of the statistics image, with room for the watermark lines
+fn stats_size(store: &gio::ListStore, form: StatsForm, lines: &[String]) -> (f64, f64) {
+    let rows = match form {
+        StatsForm::Table => store.n_items() as usize + 1,
+        StatsForm::Bars => bars::share_entries(store).len(),
+    };
+    let watermark_height = if lines.is_empty() {
+        0.0
+    } else {
+        WATERMARK_MARGIN + lines.len() as f64 * WATERMARK_FONT_SIZE * 1.4
+    };
+    let width = STATS_WIDTH + 2.0 * STATS_MARGIN;
+    let height =
+        2.0 * STATS_MARGIN + STATS_TITLE_HEIGHT + rows as f64 * bars::ROW_HEIGHT + watermark_height;
+    (width, height)
+}
+
+// Draw the algorithm statistics with a title above them, as a table or as bars
+fn draw_stats(
+    cr: &Context,
+    chart: &SunburstChart,
+    store: &gio::ListStore,
+    form: StatsForm,
+    lines: &[String],
+    height: f64,
+) -> Result<()> {
+    cr.set_source_rgb(1.0, 1.0, 1.0);
+    cr.paint()?;
+
+    cr.select_font_face("Sans", FontSlant::Normal, FontWeight::Bold);
+    cr.set_font_size(STATS_FONT_SIZE * 1.4);
+    cr.set_source_rgb(0.2, 0.2, 0.2);
+    cr.move_to(STATS_MARGIN, STATS_MARGIN + STATS_FONT_SIZE * 1.4);
+    cr.show_text("Most Used Algorithms")?;
+
+    cr.save()?;
+    cr.translate(STATS_MARGIN, STATS_MARGIN + STATS_TITLE_HEIGHT);
+    match form {
+        StatsForm::Table => draw_stats_table(cr, store)?,
+        StatsForm::Bars => {
+            cr.select_font_face("Sans", FontSlant::Normal, FontWeight::Normal);
+            let entries = bars::share_entries(store);
+            bars::draw_share_bars(cr, &entries, STATS_WIDTH, chart.palette(), usize::MAX);
+        }
+    }
+    cr.restore()?;
+
+    if !lines.is_empty() {
+        draw_watermark(cr, lines, height)?;
+    }
+    Ok(())
+}
+
+// Export the algorithm statistics with a title above them, as a table or as bars
+pub fn export_stats(
+    chart: &SunburstChart,
+    store: &gio::ListStore,
+    form: StatsForm,
+    path: &Path,
+    watermark: bool,
+    scale: f64,
+) -> Result<()> {
+    let lines = if watermark {
+        watermark_lines(chart)
+    } else {
+        Vec::new()
+    };
+    let (width, height) = stats_size(store, form, &lines);
+    write_image(path, width, height, scale, |cr| {
+        draw_stats(cr, chart, store, form, &lines, height)
+    })
+}
+
+// Export the visible chart to a vector PDF for print, followed by a page with
+// the statistics table if a store is given
+pub fn export_pdf(
+    chart: &SunburstChart,
+    stats: Option<&gio::ListStore>,
+    path: &Path,
+    watermark: bool,
+) -> Result<()> {
+    let lines = if watermark {
+        watermark_lines(chart)
+    } else {
+        Vec::new()
+    };
+    let surface = PdfSurface::new(SIZE, SIZE, path)?;
+    let cr = Context::new(&surface)?;
+    chart.render(&cr, SIZE, SIZE);
+    if !lines.is_empty() {
+        draw_watermark(&cr, &lines, SIZE)?;
+    }
+
+    if let Some(store) = stats {
+        cr.show_page()?;
+        let (width, height) = stats_size(store, StatsForm::Table, &lines);
+        surface.set_size(width, height)?;
+        draw_stats(&cr, chart, store, StatsForm::Table, &lines, height)?;
+    }
+    drop(cr);
+    surface.finish();
+    Ok(())
+}
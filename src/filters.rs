@@ -0,0 +1,255 @@
+use crate::data::AuditEvent;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+// Which part of an event an exclusion rule is matched against
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RuleTarget {
+    #[default]
+    EventName,
+    Context,
+    Origin,
+}
+
+impl RuleTarget {
+    pub const ALL: [RuleTarget; 3] = [
+        RuleTarget::EventName,
+        RuleTarget::Context,
+        RuleTarget::Origin,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::EventName => "Event Name",
+            Self::Context => "Context",
+            Self::Origin => "Origin",
+        }
+    }
+
+    fn value(&self, event: &AuditEvent) -> String {
+        match self {
+            Self::EventName => event.name(),
+            Self::Context => event.context.clone(),
+            Self::Origin => event.origin.clone(),
+        }
+    }
+}
+
+// Events matching the pattern are left out of the tree, together with their spans
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExcludeRule {
+    pub target: RuleTarget,
+    pub pattern: String,
+}
+
+// Exclusion rules with their patterns compiled. Rules with invalid patterns
+// are ignored; the preferences only accept valid ones.
+pub struct Exclusions(Vec<(RuleTarget, Regex)>);
+
+impl Exclusions {
+    pub fn new(rules: &[ExcludeRule]) -> Self {
+        Self(
+            rules
+                .iter()
+                .filter_map(|rule| Some((rule.target, Regex::new(&rule.pattern).ok()?)))
+                .collect(),
+        )
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    fn excludes(&self, event: &AuditEvent) -> bool {
+        self.0
+            .iter()
+            .any(|(target, regex)| regex.is_match(&target.value(event)))
+    }
+
+    pub fn apply(&self, events: &[AuditEvent]) -> Vec<AuditEvent> {
+        events
+            .iter()
+            .filter(|event| !self.excludes(event))
+            .map(|event| AuditEvent {
+                spans: self.apply(&event.spans),
+                ..event.clone()
+            })
+            .collect()
+    }
+}
+
+// Whether the description of the event or of one of its spans contains the
+// query, ignoring case. Codes are included, so "0x1301" finds a ciphersuite too.
+fn matches_search(event: &AuditEvent, query: &str) -> bool {
+    event
+        .format_details_with_codes(true)
+        .to_lowercase()
+        .contains(query)
+        || event.spans.iter().any(|span| matches_search(span, query))
+}
+
+// Top-level events matching a search query, together with all of their spans
+pub fn search(events: &[AuditEvent], query: &str) -> Vec<AuditEvent> {
+    let query = query.to_lowercase();
+    events
+        .iter()
+        .filter(|event| matches_search(event, &query))
+        .cloned()
+        .collect()
+}
+
+// Events of other origins, leaving out those of hidden ones together with their spans
+pub fn filter_origins(events: &[AuditEvent], hidden: &HashSet<String>) -> Vec<AuditEvent> {
+    events
+        .iter()
+        .filter(|event| !hidden.contains(&event.origin))
+        .map(|event| AuditEvent {
+            spans: filter_origins(&event.spans, hidden),
+            ..event.clone()
+        })
+        .collect()
+}
+
+// Distinct origins of the events and their spans, in order
+pub fn origins(events: &[AuditEvent]) -> BTreeSet<String> {
+    fn visit(events: &[AuditEvent], origins: &mut BTreeSet<String>) {
+        for event in events {
+            origins.insert(event.origin.clone());
+            visit(&event.spans, origins);
+        }
+    }
+    let mut origins = BTreeSet::new();
+    visit(events, &mut origins);
+    origins
+}
+
+// Whether a field tells how an operation went: "outcome", "result" or "error",
+// optionally namespaced as in "tls::error"
+fn outcome_key(key: &str) -> Option<&str> {
+    let name = key.rsplit("::").next().unwrap_or(key);
+    ["outcome", "result", "error"]
+        .into_iter()
+        .find(|outcome| name.eq_ignore_ascii_case(outcome))
+}
+
+// Whether an event records how it went, with one of the outcome fields
+pub fn has_outcome(event: &AuditEvent) -> bool {
+    event.events.keys().any(|key| outcome_key(key).is_some())
+}
+
+// Whether an event failed: it has an error other than none, or an outcome or
+// result that is false, negative like a GnuTLS error code, or a word other
+// than one for success
+pub fn is_failure(event: &AuditEvent) -> bool {
+    event
+        .events
+        .iter()
+        .any(|(key, value)| match (outcome_key(key), value) {
+            (None, _) => false,
+            (Some(_), serde_json::Value::Null) => false,
+            (Some("error"), serde_json::Value::Bool(error)) => *error,
+            (Some("error"), serde_json::Value::Number(code)) => code.as_i64() != Some(0),
+            (Some("error"), serde_json::Value::String(error)) => !error.is_empty(),
+            (Some(_), serde_json::Value::Bool(success)) => !success,
+            (Some(_), serde_json::Value::Number(code)) => code.as_i64().is_some_and(|c| c < 0),
+            (Some(_), serde_json::Value::String(outcome)) => !matches!(
+                outcome.to_lowercase().as_str(),
+                "ok" | "success" | "succeeded" | "pass" | "passed"
+            ),
+            (Some(_), _) => false,
+        })
+}
+
+// Whether any of the events or their spans record an outcome
+pub fn has_outcomes(events: &[AuditEvent]) -> bool {
+    events
+        .iter()
+        .any(|event| has_outcome(event) || has_outcomes(&event.spans))
+}
+
+fn has_failure(event: &AuditEvent) -> bool {
+    is_failure(event) || event.spans.iter().any(has_failure)
+}
+
+// Top-level events with a failed operation, together with all of their spans
+pub fn filter_failures(events: &[AuditEvent]) -> Vec<AuditEvent> {
+    events
+        .iter()
+        .filter(|event| has_failure(event))
+        .cloned()
+        .collect()
+}
+
+// Protocol versions grouped the way the version donut shows them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TlsVersion {
+    Tls13,
+    Tls12,
+    // TLS 1.1 and older, including unknown versions
+    Legacy,
+}
+
+impl TlsVersion {
+    pub const ALL: [TlsVersion; 3] = [TlsVersion::Tls13, TlsVersion::Tls12, TlsVersion::Legacy];
+
+    pub fn from_code(code: u64) -> Self {
+        match code {
+            0x0304 => Self::Tls13,
+            0x0303 => Self::Tls12,
+            _ => Self::Legacy,
+        }
+    }
+
+    // Version negotiated by a handshake event, None for other events
+    pub fn of_event(name: &str, fields: &HashMap<String, serde_json::Value>) -> Option<Self> {
+        if !name.starts_with("tls::handshake_") {
+            return None;
+        }
+        fields
+            .get("tls::protocol_version")
+            .and_then(|v| v.as_u64())
+            .map(Self::from_code)
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Tls13 => "TLS 1.3",
+            Self::Tls12 => "TLS 1.2",
+            Self::Legacy => "Legacy",
+        }
+    }
+}
+
+// Handshakes among the events and their spans negotiating a deprecated version:
+// SSL 3.0, TLS 1.0 and TLS 1.1 (RFC 8996) or an unknown one, as the legacy
+// filter shows them
+pub fn count_deprecated_handshakes(events: &[AuditEvent]) -> usize {
+    events
+        .iter()
+        .map(|event| {
+            let deprecated =
+                TlsVersion::of_event(&event.name(), &event.events) == Some(TlsVersion::Legacy);
+            usize::from(deprecated) + count_deprecated_handshakes(&event.spans)
+        })
+        .sum()
+}
+
+fn has_tls_version(event: &AuditEvent, version: TlsVersion) -> bool {
+    TlsVersion::of_event(&event.name(), &event.events) == Some(version)
+        || event
+            .spans
+            .iter()
+            .any(|span| has_tls_version(span, version))
+}
+
+// Top-level events with a handshake of the version, together with all of their spans
+pub fn filter_tls_version(events: &[AuditEvent], version: TlsVersion) -> Vec<AuditEvent> {
+    events
+        .iter()
+        .filter(|event| has_tls_version(event, version))
+        .cloned()
+        .collect()
+}
@@ -0,0 +1,180 @@
+use serde_json::Value;
+use std::collections::HashMap;
+
+// Lookup tables mapping the algorithm identifiers of libgcrypt (GCRY_MD_*,
+// GCRY_CIPHER_*, GCRY_CIPHER_MODE_* and GCRY_PK_* in gcrypt.h) to names
+
+// Names as gcry_md_algo_name gives them
+pub fn md_name(id: u64) -> Option<&'static str> {
+    let name = match id {
+        1 => "MD5",
+        2 => "SHA1",
+        3 => "RIPEMD160",
+        5 => "MD2",
+        6 => "TIGER",
+        8 => "SHA256",
+        9 => "SHA384",
+        10 => "SHA512",
+        11 => "SHA224",
+        301 => "MD4",
+        305 => "WHIRLPOOL",
+        309 => "STRIBOG256",
+        310 => "STRIBOG512",
+        312 => "SHA3-224",
+        313 => "SHA3-256",
+        314 => "SHA3-384",
+        315 => "SHA3-512",
+        316 => "SHAKE128",
+        317 => "SHAKE256",
+        318 => "BLAKE2B_512",
+        319 => "BLAKE2B_384",
+        320 => "BLAKE2B_256",
+        321 => "BLAKE2B_160",
+        322 => "BLAKE2S_256",
+        323 => "BLAKE2S_224",
+        324 => "BLAKE2S_160",
+        325 => "BLAKE2S_128",
+        326 => "SM3",
+        327 => "SHA512_256",
+        328 => "SHA512_224",
+        _ => return None,
+    };
+    Some(name)
+}
+
+// Cipher names as the cipher decoder spells them, with their key length in
+// bits where the identifier implies one
+pub fn cipher_name(id: u64) -> Option<(&'static str, Option<u64>)> {
+    let cipher = match id {
+        1 => ("IDEA", Some(128)),
+        2 => ("3DES", Some(192)),
+        3 => ("CAST5", Some(128)),
+        4 => ("BLOWFISH", None),
+        7 => ("AES", Some(128)),
+        8 => ("AES", Some(192)),
+        9 => ("AES", Some(256)),
+        10 => ("TWOFISH", Some(256)),
+        301 => ("RC4", None),
+        302 => ("DES", Some(56)),
+        303 => ("TWOFISH", Some(128)),
+        304 => ("SERPENT", Some(128)),
+        305 => ("SERPENT", Some(192)),
+        306 => ("SERPENT", Some(256)),
+        309 => ("SEED", Some(128)),
+        310 => ("CAMELLIA", Some(128)),
+        311 => ("CAMELLIA", Some(192)),
+        312 => ("CAMELLIA", Some(256)),
+        313 => ("SALSA20", Some(256)),
+        314 => ("SALSA20R12", Some(256)),
+        315 => ("GOST28147", Some(256)),
+        316 => ("CHACHA20", Some(256)),
+        318 => ("SM4", Some(128)),
+        _ => return None,
+    };
+    Some(cipher)
+}
+
+// None for GCRY_CIPHER_MODE_NONE and STREAM, which stream ciphers use
+pub fn mode_name(id: u64) -> Option<&'static str> {
+    let name = match id {
+        1 => "ECB",
+        2 => "CFB",
+        3 => "CBC",
+        5 => "OFB",
+        6 => "CTR",
+        7 => "AESWRAP",
+        8 => "CCM",
+        9 => "GCM",
+        10 => "POLY1305",
+        11 => "OCB",
+        12 => "CFB8",
+        13 => "XTS",
+        14 => "EAX",
+        15 => "SIV",
+        16 => "GCM-SIV",
+        _ => return None,
+    };
+    Some(name)
+}
+
+// Public key algorithm names as the pk decoder spells them
+pub fn pk_name(id: u64) -> Option<&'static str> {
+    let name = match id {
+        1..=3 => "RSA",
+        16 | 20 => "ELG",
+        17 => "DSA",
+        18 | 301 => "ECDSA",
+        302 => "ECDH",
+        303 => "EdDSA",
+        _ => return None,
+    };
+    Some(name)
+}
+
+// Shared namespaces libgcrypt events go to, by the prefix of their names,
+// e.g. "gcry::cipher_encrypt" becomes "cipher::encrypt"
+const NAMESPACES: [(&str, &str); 3] = [
+    ("gcry::md_", "hash::"),
+    ("gcry::cipher_", "cipher::"),
+    ("gcry::pk_", "pk::"),
+];
+
+// Convert the fields of a libgcrypt event to those of the shared namespaces,
+// so its digests, ciphers and public key operations are decoded, counted and
+// checked like those of other libraries. Identifiers may also be given by
+// name, which is kept.
+pub fn decode(fields: &mut HashMap<String, Value>) {
+    let Some(name) = fields.get("name").and_then(Value::as_str) else {
+        return;
+    };
+    let Some((prefix, namespace)) = NAMESPACES
+        .iter()
+        .find(|(prefix, _)| name.starts_with(prefix))
+    else {
+        return;
+    };
+    let renamed = format!("{}{}", namespace, &name[prefix.len()..]);
+
+    // Name of the identifier in a field, looked up in the table or as given
+    let id = |key: &str, name_of: fn(u64) -> Option<&'static str>| match fields.get(key) {
+        Some(Value::Number(number)) => number.as_u64().and_then(name_of).map(Value::from),
+        Some(Value::String(name)) => Some(Value::from(name.as_str())),
+        _ => None,
+    };
+    let mut decoded: Vec<(&str, Option<Value>)> = Vec::new();
+    match *namespace {
+        "hash::" => decoded.push(("hash::algorithm", id("gcry::md_algo", md_name))),
+        "cipher::" => {
+            let cipher = fields
+                .get("gcry::cipher_algo")
+                .and_then(Value::as_u64)
+                .and_then(cipher_name);
+            decoded.push((
+                "cipher::algorithm",
+                id("gcry::cipher_algo", |code| Some(cipher_name(code)?.0)),
+            ));
+            decoded.push((
+                "cipher::key_length",
+                cipher.and_then(|(_, bits)| bits).map(Value::from),
+            ));
+            decoded.push(("cipher::mode", id("gcry::cipher_mode", mode_name)));
+        }
+        _ => {
+            decoded.push(("pk::algorithm", id("gcry::pk_algo", pk_name)));
+            decoded.push((
+                "pk::bits",
+                fields
+                    .get("gcry::pk_nbits")
+                    .filter(|bits| bits.is_u64())
+                    .cloned(),
+            ));
+        }
+    }
+
+    fields.insert("name".to_string(), Value::from(renamed));
+    for (key, value) in decoded {
+        if let Some(value) = value {
+            fields.entry(key.to_string()).or_insert(value);
+        }
+    }
+}
@@ -0,0 +1,265 @@
+use gtk4::glib;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+// One-line explanations of algorithm names as the decoders show them
+const BUILTIN: &[(&str, &str)] = &[
+    (
+        "RSA",
+        "Public key algorithm based on factoring, for signatures and key transport",
+    ),
+    ("ECDSA", "Elliptic curve digital signature algorithm"),
+    (
+        "EdDSA",
+        "Edwards curve digital signature algorithm, deterministic by design",
+    ),
+    (
+        "DSA",
+        "Digital Signature Algorithm over finite fields, deprecated",
+    ),
+    ("DH", "Finite field Diffie-Hellman key agreement"),
+    ("ECDH", "Elliptic curve Diffie-Hellman key agreement"),
+    (
+        "ML-KEM",
+        "Module-lattice key encapsulation (FIPS 203), post-quantum",
+    ),
+    (
+        "ML-DSA",
+        "Module-lattice digital signatures (FIPS 204), post-quantum",
+    ),
+    ("x25519", "Elliptic curve Diffie-Hellman over Curve25519"),
+    ("x448", "Elliptic curve Diffie-Hellman over Curve448"),
+    ("secp256r1", "Elliptic curve Diffie-Hellman over NIST P-256"),
+    ("secp384r1", "Elliptic curve Diffie-Hellman over NIST P-384"),
+    ("secp521r1", "Elliptic curve Diffie-Hellman over NIST P-521"),
+    (
+        "X25519MLKEM768",
+        "Hybrid classical+post-quantum key exchange: X25519 combined with ML-KEM-768",
+    ),
+    (
+        "SecP256r1MLKEM768",
+        "Hybrid classical+post-quantum key exchange: P-256 combined with ML-KEM-768",
+    ),
+    (
+        "SecP384r1MLKEM1024",
+        "Hybrid classical+post-quantum key exchange: P-384 combined with ML-KEM-1024",
+    ),
+    (
+        "X25519Kyber768Draft00",
+        "Pre-standard hybrid of X25519 and Kyber768, superseded by X25519MLKEM768",
+    ),
+    (
+        "MLKEM512",
+        "Post-quantum key encapsulation ML-KEM-512 (FIPS 203)",
+    ),
+    (
+        "MLKEM768",
+        "Post-quantum key encapsulation ML-KEM-768 (FIPS 203)",
+    ),
+    (
+        "MLKEM1024",
+        "Post-quantum key encapsulation ML-KEM-1024 (FIPS 203)",
+    ),
+    (
+        "ffdhe2048",
+        "Finite field Diffie-Hellman with the 2048-bit RFC 7919 group",
+    ),
+    (
+        "ffdhe3072",
+        "Finite field Diffie-Hellman with the 3072-bit RFC 7919 group",
+    ),
+    (
+        "ffdhe4096",
+        "Finite field Diffie-Hellman with the 4096-bit RFC 7919 group",
+    ),
+    (
+        "ffdhe6144",
+        "Finite field Diffie-Hellman with the 6144-bit RFC 7919 group",
+    ),
+    (
+        "ffdhe8192",
+        "Finite field Diffie-Hellman with the 8192-bit RFC 7919 group",
+    ),
+    (
+        "rsa_pkcs1_sha1",
+        "RSA PKCS#1 v1.5 signature with SHA-1, deprecated",
+    ),
+    (
+        "rsa_pkcs1_sha256",
+        "RSA PKCS#1 v1.5 signature with SHA-256, kept for certificates",
+    ),
+    (
+        "rsa_pkcs1_sha384",
+        "RSA PKCS#1 v1.5 signature with SHA-384, kept for certificates",
+    ),
+    (
+        "rsa_pkcs1_sha512",
+        "RSA PKCS#1 v1.5 signature with SHA-512, kept for certificates",
+    ),
+    (
+        "rsa_pss_rsae_sha256",
+        "RSA-PSS signature with SHA-256 by a key of an ordinary RSA certificate",
+    ),
+    (
+        "rsa_pss_rsae_sha384",
+        "RSA-PSS signature with SHA-384 by a key of an ordinary RSA certificate",
+    ),
+    (
+        "rsa_pss_rsae_sha512",
+        "RSA-PSS signature with SHA-512 by a key of an ordinary RSA certificate",
+    ),
+    (
+        "rsa_pss_pss_sha256",
+        "RSA-PSS signature with SHA-256 by a PSS-only key",
+    ),
+    (
+        "rsa_pss_pss_sha384",
+        "RSA-PSS signature with SHA-384 by a PSS-only key",
+    ),
+    (
+        "rsa_pss_pss_sha512",
+        "RSA-PSS signature with SHA-512 by a PSS-only key",
+    ),
+    (
+        "ecdsa_secp256r1_sha256",
+        "ECDSA signature over P-256 with SHA-256",
+    ),
+    (
+        "ecdsa_secp384r1_sha384",
+        "ECDSA signature over P-384 with SHA-384",
+    ),
+    (
+        "ecdsa_secp521r1_sha512",
+        "ECDSA signature over P-521 with SHA-512",
+    ),
+    ("ecdsa_sha1", "ECDSA signature with SHA-1, deprecated"),
+    ("ed25519", "EdDSA signature over Curve25519"),
+    ("ed448", "EdDSA signature over Curve448"),
+    ("mldsa44", "Post-quantum ML-DSA-44 signature (FIPS 204)"),
+    ("mldsa65", "Post-quantum ML-DSA-65 signature (FIPS 204)"),
+    ("mldsa87", "Post-quantum ML-DSA-87 signature (FIPS 204)"),
+    (
+        "TLS 1.3",
+        "Current TLS version, with forward secrecy and AEAD ciphers only",
+    ),
+    ("TLS 1.2", "Previous TLS version, still widely used"),
+    ("TLS 1.1", "Deprecated TLS version (RFC 8996)"),
+    ("TLS 1.0", "Deprecated TLS version (RFC 8996)"),
+    ("SSL 3.0", "Broken predecessor of TLS (RFC 7568)"),
+    ("AES", "Advanced Encryption Standard block cipher"),
+    ("GCM", "Galois/Counter Mode, authenticated encryption"),
+    ("CCM", "Counter with CBC-MAC, authenticated encryption"),
+    (
+        "CBC",
+        "Cipher block chaining, unauthenticated and needing a separate MAC",
+    ),
+    (
+        "ChaCha20",
+        "Stream cipher, usually paired with the Poly1305 authenticator",
+    ),
+    (
+        "3DES",
+        "Triple DES block cipher with 64-bit blocks, deprecated",
+    ),
+    ("RC4", "Broken stream cipher (RFC 7465)"),
+    ("MD5", "Broken hash function"),
+    (
+        "SHA1",
+        "Hash function with practical collisions, deprecated for signatures",
+    ),
+    (
+        "SHA256",
+        "Hash function of the SHA-2 family with 256-bit output",
+    ),
+    ("HKDF", "HMAC-based key derivation (RFC 5869)"),
+    (
+        "PBKDF2",
+        "Password-based key derivation, slowed down by its iteration count",
+    ),
+    (
+        "DRBG",
+        "Deterministic random bit generator (NIST SP 800-90A)",
+    ),
+];
+
+// Explanations by lower-cased term: the built-in ones, extended or replaced
+// by those of the user's glossary file
+pub struct Glossary(HashMap<String, (String, String)>);
+
+impl Glossary {
+    // JSON object mapping names to explanations
+    fn user_path() -> PathBuf {
+        glib::user_config_dir()
+            .join("crypto-usage-analyzer")
+            .join("glossary.json")
+    }
+
+    fn load() -> Self {
+        let mut entries: HashMap<String, (String, String)> = BUILTIN
+            .iter()
+            .map(|(term, text)| (term.to_lowercase(), (term.to_string(), text.to_string())))
+            .collect();
+        let user: HashMap<String, String> = fs::read_to_string(Self::user_path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        for (term, text) in user {
+            entries.insert(term.to_lowercase(), (term, text));
+        }
+        Self(entries)
+    }
+
+    fn explain(&self, term: &str) -> Option<String> {
+        if let Some((term, text)) = self.0.get(&term.to_lowercase()) {
+            return Some(format!("{}: {}", term, text));
+        }
+        ciphersuite_explanation(term)
+    }
+
+    // Explanations of the terms of a name, e.g. "pk::sign [RSA, 2048 bits]"
+    // or "AES-256-GCM", one per line
+    pub fn tooltip(&self, text: &str) -> Option<String> {
+        if let Some(line) = self.explain(text.trim()) {
+            return Some(line);
+        }
+        let mut lines: Vec<String> = Vec::new();
+        for token in text.split(|c: char| c.is_whitespace() || "[](),=/-".contains(c)) {
+            if let Some(line) = self.explain(token) {
+                if !lines.contains(&line) {
+                    lines.push(line);
+                }
+            }
+        }
+        (!lines.is_empty()).then(|| lines.join("\n"))
+    }
+}
+
+// TLS ciphersuites are named after their parts, so they are explained from them
+fn ciphersuite_explanation(name: &str) -> Option<String> {
+    let rest = name.strip_prefix("TLS_")?;
+    Some(match rest.split_once("_WITH_") {
+        Some((kex, cipher)) => {
+            let (cipher, hash) = cipher.rsplit_once('_')?;
+            format!(
+                "{}: TLS 1.2 ciphersuite with {} key exchange, {} encryption and {}",
+                name, kex, cipher, hash
+            )
+        }
+        None => {
+            let (cipher, hash) = rest.rsplit_once('_')?;
+            format!(
+                "{}: TLS 1.3 ciphersuite with {} encryption and {} for key derivation",
+                name, cipher, hash
+            )
+        }
+    })
+}
+
+static GLOSSARY: OnceLock<Glossary> = OnceLock::new();
+
+// The glossary, loaded on first use
+pub fn glossary() -> &'static Glossary {
+    GLOSSARY.get_or_init(Glossary::load)
+}
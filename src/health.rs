@@ -0,0 +1,286 @@
+use crate::data::AuditEvent;
+use crate::sunburst::SunburstChart;
+use adw::prelude::*;
+use adw::{ActionRow, PreferencesGroup, PreferencesPage, ViewStack};
+use serde_json::Value;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+// Checks of the capture, with their descriptions, in the order they're shown
+const CHECKS: [(&str, &str); 4] = [
+    (
+        "Missing Names",
+        "Events without a name, which show as “unknown”",
+    ),
+    (
+        "Inverted Spans",
+        "Events and spans ending before they start, which count no duration",
+    ),
+    (
+        "Unexpected Field Types",
+        "Fields the decoders can't read, as they hold another type of value",
+    ),
+    (
+        "Unknown Namespaces",
+        "Namespaces of event names and fields no decoder knows, perhaps from a newer collector",
+    ),
+];
+const MISSING_NAMES: usize = 0;
+const INVERTED_SPANS: usize = 1;
+const UNEXPECTED_TYPES: usize = 2;
+const UNKNOWN_NAMESPACES: usize = 3;
+
+// Namespaces of the event names and fields the decoders know
+const KNOWN_NAMESPACES: &[&str] = &[
+    "tls", "pk", "x509", "cert", "rng", "entropy", "kdf", "cipher", "hash", "ktls", "ssh",
+    "process",
+];
+
+#[derive(Clone, Copy, PartialEq)]
+enum Kind {
+    Number,
+    String,
+    NumberOrString,
+}
+
+impl Kind {
+    fn accepts(&self, value: &Value) -> bool {
+        match self {
+            Self::Number => value.is_u64(),
+            Self::String => value.is_string(),
+            Self::NumberOrString => value.is_u64() || value.is_string(),
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Number => "a number",
+            Self::String => "a string",
+            Self::NumberOrString => "a number or a string",
+        }
+    }
+}
+
+// Fields the decoders read, with the type of value they expect
+const FIELD_KINDS: &[(&str, Kind)] = &[
+    ("tls::protocol_version", Kind::Number),
+    ("tls::ciphersuite", Kind::Number),
+    ("tls::group", Kind::Number),
+    ("tls::signature_algorithm", Kind::Number),
+    ("tls::server_name", Kind::String),
+    ("tls::sni", Kind::String),
+    ("tls::peer", Kind::String),
+    ("tls::peer_address", Kind::String),
+    ("pk::algorithm", Kind::String),
+    ("pk::bits", Kind::Number),
+    ("x509::signature_algorithm", Kind::NumberOrString),
+    ("x509::public_key_algorithm", Kind::String),
+    ("x509::public_key_bits", Kind::Number),
+    ("x509::key_usage", Kind::NumberOrString),
+    ("rng::generator", Kind::String),
+    ("rng::bytes", Kind::Number),
+    ("kdf::algorithm", Kind::String),
+    ("kdf::hash", Kind::String),
+    ("kdf::iterations", Kind::Number),
+    ("kdf::length", Kind::Number),
+    ("cipher::algorithm", Kind::String),
+    ("cipher::key_length", Kind::Number),
+    ("cipher::mode", Kind::String),
+    ("cipher::mac", Kind::String),
+    ("hash::algorithm", Kind::String),
+    ("ktls::cipher", Kind::NumberOrString),
+    ("ktls::direction", Kind::NumberOrString),
+    ("ktls::socket", Kind::NumberOrString),
+    ("ssh::kex_algorithm", Kind::String),
+    ("ssh::host_key_algorithm", Kind::String),
+    ("ssh::cipher", Kind::String),
+    ("ssh::mac", Kind::String),
+    ("ike::encryption", Kind::NumberOrString),
+    ("ike::key_length", Kind::Number),
+    ("ike::prf", Kind::NumberOrString),
+    ("ike::integrity", Kind::NumberOrString),
+    ("ike::dh_group", Kind::NumberOrString),
+    ("ipsec::encryption", Kind::NumberOrString),
+    ("ipsec::key_length", Kind::Number),
+    ("ipsec::prf", Kind::NumberOrString),
+    ("ipsec::integrity", Kind::NumberOrString),
+    ("ipsec::dh_group", Kind::NumberOrString),
+    ("process::comm", Kind::String),
+    ("process::pid", Kind::Number),
+];
+
+fn value_type(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(number) if number.is_u64() => "a number",
+        Value::Number(_) => "a negative or fractional number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+// A problem found in the capture, with the events it was found in and the
+// context of the first
+#[derive(PartialEq)]
+pub struct Issue {
+    pub check: usize,
+    pub description: String,
+    pub count: usize,
+    pub example: String,
+}
+
+fn visit(event: &AuditEvent, issues: &mut HashMap<(usize, String), (usize, String)>) {
+    let mut report = |check: usize, description: String| {
+        issues
+            .entry((check, description))
+            .or_insert((0, event.context.clone()))
+            .0 += 1;
+    };
+
+    let name = match event.events.get("name") {
+        Some(Value::String(name)) => Some(name.as_str()),
+        None => {
+            report(MISSING_NAMES, "No name field".to_string());
+            None
+        }
+        Some(other) => {
+            report(
+                MISSING_NAMES,
+                format!("Name field is {}", value_type(other)),
+            );
+            None
+        }
+    };
+
+    if event.end < event.start {
+        report(
+            INVERTED_SPANS,
+            format!("{} ends before it starts", name.unwrap_or("Unnamed event")),
+        );
+    }
+
+    for (field, kind) in FIELD_KINDS {
+        if let Some(value) = event.events.get(*field) {
+            if !kind.accepts(value) {
+                report(
+                    UNEXPECTED_TYPES,
+                    format!(
+                        "{} is {}, expected {}",
+                        field,
+                        value_type(value),
+                        kind.label()
+                    ),
+                );
+            }
+        }
+    }
+
+    if let Some((namespace, _)) = name.and_then(|name| name.split_once("::")) {
+        if !KNOWN_NAMESPACES.contains(&namespace) {
+            report(
+                UNKNOWN_NAMESPACES,
+                format!("{}:: in event names", namespace),
+            );
+        }
+    }
+    for key in event.events.keys() {
+        if let Some((namespace, _)) = key.split_once("::") {
+            if !KNOWN_NAMESPACES.contains(&namespace) {
+                report(UNKNOWN_NAMESPACES, format!("{}:: in fields", namespace));
+            }
+        }
+    }
+
+    for span in &event.spans {
+        visit(span, issues);
+    }
+}
+
+// Problems of the capture, most frequent first within each check
+pub fn check_events(events: &[AuditEvent]) -> Vec<Issue> {
+    let mut issues = HashMap::new();
+    for event in events {
+        visit(event, &mut issues);
+    }
+    let mut issues: Vec<Issue> = issues
+        .into_iter()
+        .map(|((check, description), (count, example))| Issue {
+            check,
+            description,
+            count,
+            example,
+        })
+        .collect();
+    issues.sort_by(|a, b| {
+        a.check
+            .cmp(&b.check)
+            .then_with(|| b.count.cmp(&a.count))
+            .then_with(|| a.description.cmp(&b.description))
+    });
+    issues
+}
+
+// Add a page reporting data quality problems of the whole capture, so
+// collector bugs are noticed rather than folded into "unknown". Its badge
+// tells the number of distinct problems.
+pub fn add_health_page(stack: &ViewStack, chart: Rc<SunburstChart>) {
+    let page = PreferencesPage::new();
+    let groups: Vec<PreferencesGroup> = CHECKS
+        .iter()
+        .map(|(title, description)| {
+            let group = PreferencesGroup::builder()
+                .title(*title)
+                .description(*description)
+                .build();
+            page.add(&group);
+            group
+        })
+        .collect();
+    // Rows added to the groups, to remove on the next update
+    let rows: Rc<RefCell<Vec<(PreferencesGroup, ActionRow)>>> = Rc::default();
+    let last_issues: Rc<RefCell<Option<Vec<Issue>>>> = Rc::default();
+
+    let stack_page = stack.add_titled(&page, Some("health"), "Data Health");
+    stack_page.set_icon_name(Some("dialog-warning-symbolic"));
+
+    let chart_clone = chart.clone();
+    chart.connect_view_changed(move |_| {
+        // The checks cover the whole capture, so the rows only change with it
+        let issues = check_events(&chart_clone.events());
+        if last_issues.borrow().as_ref() == Some(&issues) {
+            return;
+        }
+
+        for (group, row) in rows.borrow_mut().drain(..) {
+            group.remove(&row);
+        }
+
+        stack_page.set_badge_number(issues.len() as u32);
+        for (check, group) in groups.iter().enumerate() {
+            let mut found = false;
+            for issue in issues.iter().filter(|issue| issue.check == check) {
+                let row = ActionRow::builder()
+                    .title(&issue.description)
+                    .subtitle(format!("First in context {}", issue.example))
+                    .subtitle_lines(1)
+                    .build();
+                let count = gtk4::Label::new(Some(&issue.count.to_string()));
+                count.add_css_class("dim-label");
+                row.add_suffix(&count);
+                group.add(&row);
+                rows.borrow_mut().push((group.clone(), row));
+                found = true;
+            }
+            if !found {
+                let row = ActionRow::builder().title("None found").build();
+                row.add_prefix(&gtk4::Image::from_icon_name("emblem-ok-symbolic"));
+                group.add(&row);
+                rows.borrow_mut().push((group.clone(), row));
+            }
+        }
+        *last_issues.borrow_mut() = Some(issues);
+    });
+}
@@ -0,0 +1,213 @@
+use crate::data::AuditEvent;
+use crate::sunburst::SunburstChart;
+use crate::timezone::format_time;
+use adw::prelude::*;
+use gtk4::{DrawingArea, EventControllerMotion};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+const BUCKETS: usize = 48;
+// Rows beyond these are the least used algorithms, left out
+const MAX_ROWS: usize = 24;
+const ROW_HEIGHT: f64 = 22.0;
+const LABEL_WIDTH: f64 = 200.0;
+const AXIS_HEIGHT: f64 = 28.0;
+const MARGIN: f64 = 12.0;
+const FONT_SIZE: f64 = 11.0;
+
+// Operation counts of the top algorithms by time bucket
+#[derive(Default)]
+struct Grid {
+    // Start and end of the period, in nanoseconds since boot
+    range: (u64, u64),
+    rows: Vec<(String, [usize; BUCKETS])>,
+    max_count: usize,
+}
+
+impl Grid {
+    fn bucket_duration(&self) -> u64 {
+        ((self.range.1 - self.range.0) / BUCKETS as u64).max(1)
+    }
+
+    fn bucket_start(&self, bucket: usize) -> u64 {
+        self.range.0 + bucket as u64 * self.bucket_duration()
+    }
+}
+
+// What an event is counted as: the first decoded detail, e.g. "RSA" for
+// "pk::sign [RSA, 2048 bits]", or the event name if it has none
+fn algorithm_of(event: &AuditEvent) -> String {
+    let details = event.format_details();
+    details
+        .split_once(" [")
+        .and_then(|(_, rest)| rest.trim_end_matches(']').split(", ").next())
+        .map(str::to_string)
+        .unwrap_or(details)
+}
+
+fn count_events(events: &[AuditEvent], counts: &mut HashMap<String, Vec<u64>>) {
+    for event in events {
+        counts
+            .entry(algorithm_of(event))
+            .or_default()
+            .push(event.start);
+        count_events(&event.spans, counts);
+    }
+}
+
+fn build_grid(events: &[AuditEvent]) -> Grid {
+    let Some(range) = AuditEvent::get_time_range(events) else {
+        return Grid::default();
+    };
+    let mut starts = HashMap::new();
+    count_events(events, &mut starts);
+
+    let mut grid = Grid {
+        range,
+        ..Grid::default()
+    };
+    let bucket_duration = grid.bucket_duration();
+    let mut rows: Vec<(String, [usize; BUCKETS])> = starts
+        .into_iter()
+        .map(|(algorithm, starts)| {
+            let mut buckets = [0; BUCKETS];
+            for start in starts {
+                let bucket = (start.saturating_sub(range.0) / bucket_duration) as usize;
+                buckets[bucket.min(BUCKETS - 1)] += 1;
+            }
+            (algorithm, buckets)
+        })
+        .collect();
+    rows.sort_by(|a, b| {
+        let total = |buckets: &[usize; BUCKETS]| buckets.iter().sum::<usize>();
+        total(&b.1).cmp(&total(&a.1)).then_with(|| a.0.cmp(&b.0))
+    });
+    rows.truncate(MAX_ROWS);
+
+    grid.max_count = rows
+        .iter()
+        .flat_map(|(_, buckets)| buckets.iter().copied())
+        .max()
+        .unwrap_or(0);
+    grid.rows = rows;
+    grid
+}
+
+// From white for no operations to dark blue for the busiest bucket, on a
+// logarithmic scale so rare operations still show up
+fn cell_color(count: usize, max_count: usize) -> (f64, f64, f64) {
+    if count == 0 || max_count == 0 {
+        return (0.97, 0.97, 0.97);
+    }
+    let t = (count as f64).ln_1p() / (max_count as f64).ln_1p();
+    (0.85 - 0.75 * t, 0.92 - 0.6 * t, 1.0 - 0.45 * t)
+}
+
+// Bucket and row under a point of the drawing area
+fn cell_at(grid: &Grid, width: f64, x: f64, y: f64) -> Option<(usize, usize)> {
+    let cell_width = (width - LABEL_WIDTH - 2.0 * MARGIN) / BUCKETS as f64;
+    let column = ((x - LABEL_WIDTH - MARGIN) / cell_width).floor();
+    let row = ((y - MARGIN) / ROW_HEIGHT).floor();
+    if column < 0.0 || row < 0.0 {
+        return None;
+    }
+    let (column, row) = (column as usize, row as usize);
+    (column < BUCKETS && row < grid.rows.len()).then_some((column, row))
+}
+
+// Heatmap of the visible events with time buckets across and the most used
+// algorithms down, colored by operation count, showing periodic patterns such
+// as hourly key generation
+pub fn create_heatmap(chart: Rc<SunburstChart>) -> gtk4::ScrolledWindow {
+    let grid = Rc::new(RefCell::new(Grid::default()));
+
+    let drawing_area = DrawingArea::new();
+    drawing_area.set_hexpand(true);
+    drawing_area.set_has_tooltip(true);
+
+    let grid_clone = grid.clone();
+    drawing_area.set_draw_func(move |_, cr, width, _| {
+        let grid = grid_clone.borrow();
+        if grid.rows.is_empty() {
+            return;
+        }
+        let width = width as f64;
+        let cell_width = (width - LABEL_WIDTH - 2.0 * MARGIN) / BUCKETS as f64;
+        cr.set_font_size(FONT_SIZE);
+
+        for (i, (algorithm, buckets)) in grid.rows.iter().enumerate() {
+            let y = MARGIN + i as f64 * ROW_HEIGHT;
+
+            // Names are cut to the space left of the cells
+            cr.save().unwrap();
+            cr.rectangle(MARGIN, y, LABEL_WIDTH - 6.0, ROW_HEIGHT);
+            cr.clip();
+            cr.set_source_rgb(0.3, 0.3, 0.3);
+            cr.move_to(MARGIN, y + ROW_HEIGHT / 2.0 + FONT_SIZE / 3.0);
+            cr.show_text(algorithm).unwrap();
+            cr.restore().unwrap();
+
+            for (bucket, count) in buckets.iter().enumerate() {
+                let (r, g, b) = cell_color(*count, grid.max_count);
+                cr.set_source_rgb(r, g, b);
+                cr.rectangle(
+                    LABEL_WIDTH + MARGIN + bucket as f64 * cell_width,
+                    y + 1.0,
+                    cell_width - 1.0,
+                    ROW_HEIGHT - 2.0,
+                );
+                cr.fill().unwrap();
+            }
+        }
+
+        // Wall-clock times under every eighth bucket
+        let axis_y = MARGIN + grid.rows.len() as f64 * ROW_HEIGHT + FONT_SIZE + 6.0;
+        cr.set_source_rgb(0.4, 0.4, 0.4);
+        for bucket in (0..=BUCKETS).step_by(8) {
+            let x = LABEL_WIDTH + MARGIN + bucket as f64 * cell_width;
+            let time = format_time(grid.bucket_start(bucket), "%T");
+            let text = time.to_string();
+            if let Ok(extents) = cr.text_extents(&text) {
+                cr.move_to(x - extents.width() / 2.0, axis_y);
+                cr.show_text(&text).unwrap();
+            }
+        }
+    });
+
+    // Tell the algorithm, period and count of the hovered cell
+    let motion = EventControllerMotion::new();
+    let grid_clone = grid.clone();
+    let drawing_area_clone = drawing_area.clone();
+    motion.connect_motion(move |_, x, y| {
+        let grid = grid_clone.borrow();
+        let width = drawing_area_clone.width() as f64;
+        let tooltip = cell_at(&grid, width, x, y).map(|(bucket, row)| {
+            let (algorithm, buckets) = &grid.rows[row];
+            format!(
+                "{}\n{} – {}\n{} operations",
+                algorithm,
+                format_time(grid.bucket_start(bucket), "%T"),
+                format_time(grid.bucket_start(bucket + 1), "%T"),
+                buckets[bucket]
+            )
+        });
+        drawing_area_clone.set_tooltip_text(tooltip.as_deref());
+    });
+    drawing_area.add_controller(motion);
+
+    let drawing_area_clone = drawing_area.clone();
+    let chart_clone = chart.clone();
+    chart.connect_view_changed(move |_| {
+        let new_grid = build_grid(&chart_clone.visible_events());
+        let height = 2.0 * MARGIN + new_grid.rows.len() as f64 * ROW_HEIGHT + AXIS_HEIGHT;
+        *grid.borrow_mut() = new_grid;
+        drawing_area_clone.set_content_height(height as i32);
+        drawing_area_clone.queue_draw();
+    });
+
+    let scroll = gtk4::ScrolledWindow::new();
+    scroll.set_hscrollbar_policy(gtk4::PolicyType::Never);
+    scroll.set_child(Some(&drawing_area));
+    scroll
+}
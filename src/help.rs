@@ -0,0 +1,233 @@
+use crate::data::{NodeKind, TreeNode};
+use adw::prelude::*;
+use adw::{ActionRow, PreferencesDialog, PreferencesGroup, PreferencesPage};
+use std::collections::HashSet;
+
+// Event namespaces with a title and what their events are about
+const NAMESPACES: [(&str, &str, &str); 14] = [
+    (
+        "tls::",
+        "TLS",
+        "Operations of TLS connections: handshakes and the key exchanges, signatures and verifications done in them, with the negotiated protocol version, ciphersuite and group",
+    ),
+    (
+        "ssh::",
+        "SSH",
+        "Key exchanges of SSH connections, as recorded by OpenSSH or libssh, with the negotiated key exchange, host key, cipher and MAC algorithms",
+    ),
+    (
+        "ike::",
+        "IKE",
+        "IKEv2 security associations of VPN connections, with the negotiated encryption, PRF, integrity and Diffie-Hellman group transforms",
+    ),
+    (
+        "ipsec::",
+        "IPsec",
+        "IPsec (child) security associations protecting VPN traffic, with the same transforms as IKE",
+    ),
+    (
+        "pk::",
+        "Public Key",
+        "Public key operations such as RSA, ECDSA or EdDSA signing and verification, encryption and key generation, with the algorithm and key size",
+    ),
+    (
+        "kex::",
+        "Key Exchange",
+        "Key agreement and encapsulation, e.g. ECDH, finite field DH or ML-KEM, establishing a secret shared by both ends",
+    ),
+    (
+        "hash::",
+        "Hashing",
+        "Message digest computations, e.g. SHA-256, including those done for signatures and MACs",
+    ),
+    (
+        "cipher::",
+        "Symmetric Ciphers",
+        "Encryption and decryption of data with a symmetric cipher, with the algorithm, key length, mode and MAC",
+    ),
+    (
+        "ktls::",
+        "Kernel TLS",
+        "Record protection of TLS connections offloaded to the kernel (kTLS) after the handshake, with the cipher, direction and socket",
+    ),
+    (
+        "kdf::",
+        "Key Derivation",
+        "Derivation of keys from a secret or a password, e.g. with HKDF or PBKDF2",
+    ),
+    (
+        "rng::",
+        "Random Numbers",
+        "Random bytes drawn from a generator, which should be a DRBG seeded from the system's entropy",
+    ),
+    (
+        "entropy::",
+        "Entropy",
+        "Gathering of entropy from the system to seed random generators",
+    ),
+    (
+        "x509::",
+        "Certificates",
+        "Parsing and verification of X.509 certificates, with the signature algorithm, public key and key usage; older recorders use cert::",
+    ),
+    (
+        "process::",
+        "Process",
+        "The process an event was recorded in",
+    ),
+];
+
+// Event names and fields, by the namespace they start with
+const ENTRIES: [(&str, &str); 42] = [
+    ("tls::handshake_client", "TLS handshake started by this host as a client"),
+    ("tls::handshake_server", "TLS handshake answered by this host as a server"),
+    (
+        "tls::protocol_version",
+        "Negotiated protocol version as its wire code, e.g. 772 (0x0304) for TLS 1.3; anything below TLS 1.2 is deprecated",
+    ),
+    (
+        "tls::ciphersuite",
+        "Negotiated ciphersuite as its IANA code, e.g. 0x1301 for TLS_AES_128_GCM_SHA256",
+    ),
+    ("tls::key_exchange", "Key exchange of a handshake, establishing its shared secret"),
+    (
+        "tls::group",
+        "Named group of the key exchange as its IANA code, e.g. 0x001d for X25519",
+    ),
+    (
+        "tls::sign",
+        "Signature made by this host, usually as a server proving it holds its certificate's key",
+    ),
+    (
+        "tls::verify",
+        "Signature checked by this host, usually as a client authenticating the server",
+    ),
+    (
+        "tls::signature_algorithm",
+        "Signature scheme as its IANA code, e.g. 0x0804 for rsa_pss_rsae_sha256",
+    ),
+    ("tls::cipher", "Record protection with a symmetric cipher"),
+    ("tls::error", "Error the operation failed with, if it did"),
+    (
+        "ssh::kex_algorithm",
+        "Key exchange method, e.g. curve25519-sha256 or mlkem768x25519-sha256",
+    ),
+    (
+        "ssh::host_key_algorithm",
+        "Signature algorithm of the server's host key; ssh-rsa signs with SHA1",
+    ),
+    ("ssh::cipher", "Cipher protecting the connection, e.g. aes256-gcm@openssh.com"),
+    (
+        "ssh::mac",
+        "MAC authenticating the packets, <implicit> for AEAD ciphers",
+    ),
+    (
+        "ike::encryption",
+        "Encryption transform as its IANA ID, e.g. 20 for AES-GCM-16",
+    ),
+    ("ike::key_length", "Key length of the encryption in bits"),
+    (
+        "ike::prf",
+        "Pseudorandom function as its IANA ID, e.g. 5 for HMAC-SHA2-256",
+    ),
+    (
+        "ike::integrity",
+        "Integrity transform as its IANA ID, none with AEAD encryption",
+    ),
+    (
+        "ike::dh_group",
+        "Diffie-Hellman group as its IANA ID, e.g. 31 for Curve25519; MODP groups below 2048 bits are weak",
+    ),
+    ("pk::algorithm", "Public key algorithm, e.g. RSA, ECDSA or Ed25519"),
+    ("pk::bits", "Key size in bits; RSA keys below 2048 bits are considered weak"),
+    ("cipher::algorithm", "Cipher, e.g. AES or ChaCha20"),
+    ("cipher::key_length", "Key length in bits"),
+    ("cipher::mode", "Mode of operation, e.g. GCM or CBC"),
+    ("cipher::mac", "MAC authenticating the data, if the mode doesn't"),
+    ("hash::algorithm", "Hash function, e.g. SHA256 or SHA3-256; MD5 and SHA1 are broken"),
+    ("ktls::cipher", "Cipher the kernel protects records with, e.g. AES-GCM-128"),
+    ("ktls::direction", "Whether records sent (transmit) or received (receive) are offloaded"),
+    ("ktls::socket", "Socket the offload is set up on"),
+    ("kdf::algorithm", "Derivation function, e.g. HKDF or PBKDF2"),
+    ("kdf::hash", "Hash the function is built on"),
+    ("kdf::iterations", "Iteration count of password-based derivation; more resist guessing better"),
+    ("kdf::length", "Length of the derived key in bytes"),
+    ("rng::generator", "Type of the generator, e.g. a CTR or hash DRBG"),
+    ("rng::bytes", "Number of random bytes requested"),
+    ("x509::signature_algorithm", "Algorithm the certificate is signed with"),
+    ("x509::public_key_algorithm", "Algorithm of the certificate's public key"),
+    ("x509::public_key_bits", "Size of the certificate's public key in bits"),
+    ("x509::key_usage", "Key usage extension, restricting what the key may be used for"),
+    ("process::pid", "Process ID"),
+    ("process::comm", "Command name of the process"),
+];
+
+// Namespace of an event name or field, with certificate events under x509::
+fn namespace_of(name: &str) -> Option<&'static str> {
+    let name = name
+        .strip_prefix("cert::")
+        .map_or(name.to_string(), |rest| format!("x509::{}", rest));
+    NAMESPACES
+        .iter()
+        .map(|(prefix, _, _)| *prefix)
+        .find(|prefix| name.starts_with(prefix))
+}
+
+// Event names and field keys of the events below a node
+fn collect_names(node: &TreeNode, names: &mut HashSet<String>) {
+    if node.kind == NodeKind::Event {
+        names.extend(node.fields.keys().cloned());
+        if let Some(name) = node.event_name() {
+            names.insert(name.to_string());
+        }
+    }
+    for child in &node.children {
+        collect_names(child, names);
+    }
+}
+
+// Explanations of the event namespaces and their fields, those occurring
+// below the node first, for analysts who aren't experts in every protocol
+pub fn show_event_help(parent: &impl IsA<gtk4::Widget>, node: &TreeNode) {
+    let mut names = HashSet::new();
+    collect_names(node, &mut names);
+    let used: HashSet<&str> = names.iter().filter_map(|name| namespace_of(name)).collect();
+
+    let page = PreferencesPage::builder()
+        .title("Event Types")
+        .icon_name("help-browser-symbolic")
+        .build();
+    let mut namespaces: Vec<_> = NAMESPACES.iter().collect();
+    namespaces.sort_by_key(|(prefix, _, _)| !used.contains(prefix));
+    for (prefix, title, description) in namespaces {
+        let group = PreferencesGroup::builder()
+            .title(format!("{} ({})", title, prefix))
+            .description(*description)
+            .build();
+        if used.contains(prefix) {
+            group.set_header_suffix(Some(&{
+                let label = gtk4::Label::new(Some("In This Subtree"));
+                label.add_css_class("dim-label");
+                label.add_css_class("caption");
+                label
+            }));
+        }
+        for (name, help) in ENTRIES.iter().filter(|(name, _)| name.starts_with(prefix)) {
+            let row = ActionRow::builder()
+                .title(*name)
+                .subtitle(*help)
+                .use_markup(false)
+                .build();
+            row.add_css_class("property");
+            group.add(&row);
+        }
+        page.add(&group);
+    }
+
+    let dialog = PreferencesDialog::builder()
+        .title("Event Types")
+        .search_enabled(true)
+        .build();
+    dialog.add(&page);
+    dialog.present(Some(parent));
+}
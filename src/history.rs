@@ -0,0 +1,161 @@
+use crate::filters::{ExcludeRule, TlsVersion};
+use crate::sunburst::SunburstChart;
+use gtk4::glib;
+use std::cell::{Cell, RefCell};
+use std::collections::HashSet;
+use std::rc::Rc;
+
+// Steps kept for undoing; older ones are dropped
+const MAX_STEPS: usize = 100;
+
+// What undo and redo restore: the filters, exclusions and zoom of the view
+#[derive(Debug, Clone, PartialEq)]
+pub struct ViewState {
+    pub search: String,
+    // Text of the filter expression
+    pub filter_query: Option<String>,
+    pub tls_version: Option<TlsVersion>,
+    pub hidden_origins: HashSet<String>,
+    pub failures_only: bool,
+    pub time_window: Option<(u64, u64)>,
+    pub exclude_rules: Vec<ExcludeRule>,
+    pub zoom_path: Vec<String>,
+}
+
+impl ViewState {
+    // Whether the states differ in nothing but the time window
+    fn same_but_time_window(&self, other: &ViewState) -> bool {
+        *self
+            == ViewState {
+                time_window: self.time_window,
+                ..other.clone()
+            }
+    }
+}
+
+type ChangedHandler = Box<dyn Fn(bool, bool)>;
+
+// Undo and redo of changes to the view's filters, exclusions and zoom. A
+// change is recorded once the view settles, so that the steps of one change,
+// e.g. rebuilding the tree and zooming back in, make a single step. Loading
+// another capture starts over.
+pub struct History {
+    chart: Rc<SunburstChart>,
+    // State of the view as last recorded
+    current: RefCell<Option<ViewState>>,
+    // Capture the recorded states belong to
+    source_hash: RefCell<Option<String>>,
+    undo: RefCell<Vec<ViewState>>,
+    redo: RefCell<Vec<ViewState>>,
+    pending: Cell<bool>,
+    // Called with whether there is something to undo and to redo
+    changed_handlers: RefCell<Vec<ChangedHandler>>,
+}
+
+impl History {
+    pub fn new(chart: Rc<SunburstChart>) -> Rc<Self> {
+        let history = Rc::new(Self {
+            chart: chart.clone(),
+            current: RefCell::new(None),
+            source_hash: RefCell::new(None),
+            undo: RefCell::new(Vec::new()),
+            redo: RefCell::new(Vec::new()),
+            pending: Cell::new(false),
+            changed_handlers: RefCell::new(Vec::new()),
+        });
+
+        let weak = Rc::downgrade(&history);
+        chart.connect_view_changed(move |_| {
+            let Some(history) = weak.upgrade() else {
+                return;
+            };
+            if history.pending.replace(true) {
+                return;
+            }
+            let weak = Rc::downgrade(&history);
+            glib::idle_add_local_once(move || {
+                if let Some(history) = weak.upgrade() {
+                    history.pending.set(false);
+                    history.record();
+                }
+            });
+        });
+        history
+    }
+
+    pub fn connect_changed<F: Fn(bool, bool) + 'static>(&self, f: F) {
+        self.changed_handlers.borrow_mut().push(Box::new(f));
+    }
+
+    fn notify_changed(&self) {
+        let can_undo = !self.undo.borrow().is_empty();
+        let can_redo = !self.redo.borrow().is_empty();
+        for handler in self.changed_handlers.borrow().iter() {
+            handler(can_undo, can_redo);
+        }
+    }
+
+    fn record(&self) {
+        let state = self.chart.view_state();
+        let hash = self.chart.source_hash();
+        if *self.source_hash.borrow() != hash {
+            *self.source_hash.borrow_mut() = hash;
+            *self.current.borrow_mut() = Some(state);
+            self.undo.borrow_mut().clear();
+            self.redo.borrow_mut().clear();
+            self.notify_changed();
+            return;
+        }
+
+        let Some(current) = self.current.replace(Some(state.clone())) else {
+            return;
+        };
+        if current == state {
+            return;
+        }
+        // Dragging the time scrubber changes the window many times over; the
+        // moves of one window make a single step
+        let mut undo = self.undo.borrow_mut();
+        let continues_window = state.same_but_time_window(&current)
+            && undo
+                .last()
+                .is_some_and(|last| last.same_but_time_window(&current) && *last != current);
+        if !continues_window {
+            undo.push(current);
+            if undo.len() > MAX_STEPS {
+                undo.remove(0);
+            }
+        }
+        drop(undo);
+        self.redo.borrow_mut().clear();
+        self.notify_changed();
+    }
+
+    // Restore the state on top of `from`, keeping the current one on `to`
+    fn step(
+        &self,
+        from: &RefCell<Vec<ViewState>>,
+        to: &RefCell<Vec<ViewState>>,
+    ) -> Option<ViewState> {
+        let state = from.borrow_mut().pop()?;
+        if let Some(current) = self.current.borrow().clone() {
+            to.borrow_mut().push(current);
+        }
+        self.chart.restore_view_state(&state);
+        // The zoom location may not exist anymore, so keep what was restored
+        let restored = self.chart.view_state();
+        *self.current.borrow_mut() = Some(restored.clone());
+        self.notify_changed();
+        Some(restored)
+    }
+
+    // Undo the last change, returning the restored state
+    pub fn undo(&self) -> Option<ViewState> {
+        self.step(&self.undo, &self.redo)
+    }
+
+    // Redo the last undone change, returning the restored state
+    pub fn redo(&self) -> Option<ViewState> {
+        self.step(&self.redo, &self.undo)
+    }
+}
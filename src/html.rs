@@ -0,0 +1,134 @@
+use crate::data::TreeNode;
+use crate::export;
+use crate::palette::Palette;
+use crate::sunburst::SunburstChart;
+use anyhow::{anyhow, Result};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::Path;
+
+// Page drawing the chart with SVG, with {{title}}, {{watermark}} and {{data}}
+// placeholders
+const TEMPLATE: &str = include_str!("sunburst.html");
+
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// Substitute the {{name}} placeholders of a template in a single pass, so
+// placeholders in the substituted text are left as they are. Unknown
+// placeholders are kept too.
+pub fn render_template(template: &str, values: &[(&str, &str)]) -> String {
+    let mut page = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        page.push_str(&rest[..start]);
+        let value = rest[start + 2..].find("}}").and_then(|end| {
+            let name = &rest[start + 2..start + 2 + end];
+            let (_, value) = values.iter().find(|(key, _)| *key == name)?;
+            Some((value, start + 2 + end + 2))
+        });
+        match value {
+            Some((value, end)) => {
+                page.push_str(value);
+                rest = &rest[end..];
+            }
+            // Keep the brace, as a placeholder may start right after it
+            None => {
+                page.push('{');
+                rest = &rest[start + 1..];
+            }
+        }
+    }
+    page.push_str(rest);
+    page
+}
+
+fn node_json(node: &TreeNode, palette: Palette, depth: usize) -> Value {
+    let (r, g, b) = palette.node_color(&node.name, node.kind, depth);
+    let channel = |c: f64| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+    json!({
+        "key": node.name,
+        "name": node.display_name(),
+        "value": node.value,
+        "duration": node.duration,
+        "color": format!("#{:02x}{:02x}{:02x}", channel(r), channel(g), channel(b)),
+        "children": node
+            .children
+            .iter()
+            .map(|child| node_json(child, palette, depth + 1))
+            .collect::<Vec<_>>(),
+    })
+}
+
+// Export the chart as a standalone HTML page that can be zoomed and hovered
+// in any browser, starting where the chart is zoomed, with the source
+// information of image exports above it
+pub fn export_html(chart: &SunburstChart, path: &Path) -> Result<()> {
+    let root = chart
+        .root()
+        .ok_or_else(|| anyhow!("No capture is loaded"))?;
+    let data = json!({
+        "root": node_json(&root, chart.palette(), 0),
+        "weighting": chart.weighting().name(),
+        "zoom": chart.zoom_path(),
+    });
+    // Keep a "</script>" in the data from ending the script
+    let data = serde_json::to_string(&data)?.replace("</", "<\\/");
+    let watermark: String = export::watermark_lines(chart)
+        .iter()
+        .map(|line| format!("<p>{}</p>", escape_html(line)))
+        .collect();
+
+    let page = render_template(
+        TEMPLATE,
+        &[
+            ("title", "Crypto Usage"),
+            ("watermark", &watermark),
+            ("data", &data),
+        ],
+    );
+    fs::write(path, page)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_placeholders() {
+        assert_eq!(
+            render_template(
+                "<h1>{{title}}</h1>{{data}}",
+                &[("title", "A"), ("data", "1")]
+            ),
+            "<h1>A</h1>1"
+        );
+        assert_eq!(render_template("{{a}}{{a}}", &[("a", "x")]), "xx");
+    }
+
+    #[test]
+    fn render_leaves_substituted_placeholders() {
+        assert_eq!(
+            render_template(
+                "{{watermark}}|{{data}}",
+                &[("watermark", "{{data}}"), ("data", "{}")]
+            ),
+            "{{data}}|{}"
+        );
+    }
+
+    #[test]
+    fn render_keeps_unknown_placeholders() {
+        assert_eq!(
+            render_template("{{other}} {{a}}", &[("a", "x")]),
+            "{{other}} x"
+        );
+        assert_eq!(render_template("{{{a}}", &[("a", "x")]), "{x");
+        assert_eq!(render_template("{{a", &[("a", "x")]), "{{a");
+    }
+}
@@ -0,0 +1,146 @@
+use serde_json::Value;
+use std::collections::HashMap;
+
+// Lookup tables mapping the IKEv2 transform IDs of the IANA registry
+// (RFC 7296 and its updates) to names
+
+// Encryption algorithms (transform type 1)
+pub fn encryption_name(id: u64) -> Option<&'static str> {
+    let name = match id {
+        1 => "DES-IV64",
+        2 => "DES",
+        3 => "3DES",
+        11 => "NULL",
+        12 => "AES-CBC",
+        13 => "AES-CTR",
+        14 => "AES-CCM-8",
+        15 => "AES-CCM-12",
+        16 => "AES-CCM-16",
+        18 => "AES-GCM-8",
+        19 => "AES-GCM-12",
+        20 => "AES-GCM-16",
+        21 => "NULL-AUTH-AES-GMAC",
+        23 => "CAMELLIA-CBC",
+        24 => "CAMELLIA-CTR",
+        28 => "CHACHA20-POLY1305",
+        _ => return None,
+    };
+    Some(name)
+}
+
+// Pseudorandom functions (transform type 2)
+pub fn prf_name(id: u64) -> Option<&'static str> {
+    let name = match id {
+        1 => "HMAC-MD5",
+        2 => "HMAC-SHA1",
+        3 => "HMAC-TIGER",
+        4 => "AES128-XCBC",
+        5 => "HMAC-SHA2-256",
+        6 => "HMAC-SHA2-384",
+        7 => "HMAC-SHA2-512",
+        8 => "AES128-CMAC",
+        _ => return None,
+    };
+    Some(name)
+}
+
+// Integrity algorithms (transform type 3), none for AEAD encryption
+pub fn integrity_name(id: u64) -> Option<&'static str> {
+    let name = match id {
+        1 => "HMAC-MD5-96",
+        2 => "HMAC-SHA1-96",
+        3 => "DES-MAC",
+        5 => "AES-XCBC-96",
+        6 => "HMAC-MD5-128",
+        7 => "HMAC-SHA1-160",
+        8 => "AES-CMAC-96",
+        12 => "HMAC-SHA2-256-128",
+        13 => "HMAC-SHA2-384-192",
+        14 => "HMAC-SHA2-512-256",
+        _ => return None,
+    };
+    Some(name)
+}
+
+// Key exchange methods (transform type 4), with their size in bits for
+// finite field groups
+pub fn dh_group_name(id: u64) -> Option<(&'static str, Option<u64>)> {
+    let group = match id {
+        1 => ("MODP-768", Some(768)),
+        2 => ("MODP-1024", Some(1024)),
+        5 => ("MODP-1536", Some(1536)),
+        14 => ("MODP-2048", Some(2048)),
+        15 => ("MODP-3072", Some(3072)),
+        16 => ("MODP-4096", Some(4096)),
+        17 => ("MODP-6144", Some(6144)),
+        18 => ("MODP-8192", Some(8192)),
+        19 => ("ECP-256", None),
+        20 => ("ECP-384", None),
+        21 => ("ECP-521", None),
+        22 => ("MODP-1024-S160", Some(1024)),
+        23 => ("MODP-2048-S224", Some(2048)),
+        24 => ("MODP-2048-S256", Some(2048)),
+        31 => ("Curve25519", None),
+        32 => ("Curve448", None),
+        35 => ("ML-KEM-512", None),
+        36 => ("ML-KEM-768", None),
+        37 => ("ML-KEM-1024", None),
+        _ => return None,
+    };
+    Some(group)
+}
+
+// Namespaces of IKE SAs and of the IPsec (child) SAs they set up, whose
+// events have the same fields in their own namespace
+pub const NAMESPACES: [&str; 2] = ["ike::", "ipsec::"];
+
+// A transform of an IKE or IPsec SA, named from its ID or as given
+fn transform(
+    fields: &HashMap<String, Value>,
+    key: &str,
+    name_of: fn(u64) -> Option<&'static str>,
+) -> Option<String> {
+    match fields.get(key)? {
+        Value::Number(id) => Some(
+            id.as_u64()
+                .and_then(name_of)
+                .map_or_else(|| format!("transform {}", id), str::to_string),
+        ),
+        Value::String(name) => Some(name.to_uppercase()),
+        _ => None,
+    }
+}
+
+// Transforms an event of the namespace negotiated, as (type, name):
+// encryption with its key length, e.g. "AES-GCM-16-256", then PRF, integrity
+// and DH group
+pub fn transforms(namespace: &str, fields: &HashMap<String, Value>) -> Vec<(&'static str, String)> {
+    let key = |name: &str| format!("{}{}", namespace, name);
+    let mut transforms = Vec::new();
+    if let Some(mut encryption) = transform(fields, &key("encryption"), encryption_name) {
+        if let Some(bits) = fields.get(&key("key_length")).and_then(Value::as_u64) {
+            encryption = format!("{}-{}", encryption, bits);
+        }
+        transforms.push(("encryption", encryption));
+    }
+    if let Some(prf) = transform(fields, &key("prf"), prf_name) {
+        transforms.push(("PRF", prf));
+    }
+    // AEAD ciphers need no separate integrity algorithm, which is then NONE (0)
+    if let Some(integrity) = transform(fields, &key("integrity"), integrity_name)
+        .filter(|integrity| integrity != "NONE" && integrity != "transform 0")
+    {
+        transforms.push(("integrity", integrity));
+    }
+    if let Some(group) = transform(fields, &key("dh_group"), |id| Some(dh_group_name(id)?.0)) {
+        transforms.push(("DH group", group));
+    }
+    transforms
+}
+
+// Size of the finite field DH group of an event of the namespace, None for
+// elliptic curves, KEMs and groups given by name
+pub fn dh_group_bits(namespace: &str, fields: &HashMap<String, Value>) -> Option<u64> {
+    let id = fields.get(&format!("{}dh_group", namespace))?.as_u64()?;
+    dh_group_name(id)?.1
+}
@@ -0,0 +1,240 @@
+use crate::data::AuditEvent;
+use crate::decoders;
+use crate::palette::SERIES_COLORS;
+use crate::sunburst::SunburstChart;
+use crate::timezone::format_time;
+use adw::prelude::*;
+use gtk4::{DrawingArea, EventControllerMotion};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+const BUCKETS: usize = 24;
+// Groups beyond these are combined into "Other"
+const MAX_GROUPS: usize = SERIES_COLORS.len() - 1;
+const COLUMNS_HEIGHT: f64 = 240.0;
+const AXIS_HEIGHT: f64 = 24.0;
+const LEGEND_ROW_HEIGHT: f64 = 22.0;
+const MARGIN: f64 = 12.0;
+const FONT_SIZE: f64 = 11.0;
+
+// Key exchanges of the most used groups by time bucket
+#[derive(Default)]
+struct Shares {
+    // Start and end of the period, in nanoseconds since boot
+    range: (u64, u64),
+    groups: Vec<String>,
+    // Key exchanges of each group in each bucket, in the order of groups
+    buckets: Vec<Vec<usize>>,
+}
+
+impl Shares {
+    fn bucket_duration(&self) -> u64 {
+        ((self.range.1 - self.range.0) / BUCKETS as u64).max(1)
+    }
+
+    fn bucket_start(&self, bucket: usize) -> u64 {
+        self.range.0 + bucket as u64 * self.bucket_duration()
+    }
+
+    fn total(&self, group: usize) -> usize {
+        self.buckets.iter().map(|counts| counts[group]).sum()
+    }
+}
+
+// Named group of a key exchange event, with the code of unknown groups
+fn group_of(event: &AuditEvent) -> Option<String> {
+    if event.name() != "tls::key_exchange" {
+        return None;
+    }
+    let code = event.events.get("tls::group")?.as_u64()?;
+    Some(
+        decoders::group_name(code)
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("0x{:04x}", code)),
+    )
+}
+
+fn collect_key_exchanges(events: &[AuditEvent], exchanges: &mut Vec<(String, u64)>) {
+    for event in events {
+        if let Some(group) = group_of(event) {
+            exchanges.push((group, event.start));
+        }
+        collect_key_exchanges(&event.spans, exchanges);
+    }
+}
+
+fn build_shares(events: &[AuditEvent]) -> Shares {
+    let mut exchanges = Vec::new();
+    collect_key_exchanges(events, &mut exchanges);
+    let (Some(first), Some(last)) = (
+        exchanges.iter().map(|(_, start)| *start).min(),
+        exchanges.iter().map(|(_, start)| *start).max(),
+    ) else {
+        return Shares::default();
+    };
+
+    let mut totals: HashMap<&str, usize> = HashMap::new();
+    for (group, _) in &exchanges {
+        *totals.entry(group).or_default() += 1;
+    }
+    let mut groups: Vec<(&str, usize)> = totals.into_iter().collect();
+    groups.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+    let mut names: Vec<String> = groups
+        .iter()
+        .take(MAX_GROUPS)
+        .map(|(group, _)| group.to_string())
+        .collect();
+    if groups.len() > MAX_GROUPS {
+        names.push("Other".to_string());
+    }
+
+    let mut shares = Shares {
+        range: (first, last),
+        buckets: vec![vec![0; names.len()]; BUCKETS],
+        ..Shares::default()
+    };
+    let bucket_duration = shares.bucket_duration();
+    for (group, start) in &exchanges {
+        let index = names
+            .iter()
+            .position(|name| name == group)
+            .unwrap_or(MAX_GROUPS);
+        let bucket = ((start - first) / bucket_duration) as usize;
+        shares.buckets[bucket.min(BUCKETS - 1)][index] += 1;
+    }
+    shares.groups = names;
+    shares
+}
+
+// Bucket of the column under a point of the drawing area
+fn bucket_at(width: f64, x: f64, y: f64) -> Option<usize> {
+    let column_width = (width - 2.0 * MARGIN) / BUCKETS as f64;
+    let column = ((x - MARGIN) / column_width).floor();
+    if column < 0.0 || !(MARGIN..MARGIN + COLUMNS_HEIGHT).contains(&y) {
+        return None;
+    }
+    let column = column as usize;
+    (column < BUCKETS).then_some(column)
+}
+
+// Columns of the key exchanges in each period of the visible events split by
+// named group, each scaled to 100% so a rollout of e.g. hybrid ML-KEM groups
+// shows as their share growing across the capture
+pub fn create_group_share_chart(chart: Rc<SunburstChart>) -> gtk4::ScrolledWindow {
+    let shares = Rc::new(RefCell::new(Shares::default()));
+
+    let drawing_area = DrawingArea::new();
+    drawing_area.set_hexpand(true);
+    drawing_area.set_has_tooltip(true);
+
+    let shares_clone = shares.clone();
+    drawing_area.set_draw_func(move |_, cr, width, _| {
+        let shares = shares_clone.borrow();
+        if shares.groups.is_empty() {
+            return;
+        }
+        let width = width as f64;
+        let column_width = (width - 2.0 * MARGIN) / BUCKETS as f64;
+        cr.set_font_size(FONT_SIZE);
+
+        for (bucket, counts) in shares.buckets.iter().enumerate() {
+            let x = MARGIN + bucket as f64 * column_width;
+            let total: usize = counts.iter().sum();
+            if total == 0 {
+                cr.set_source_rgb(0.97, 0.97, 0.97);
+                cr.rectangle(x, MARGIN, column_width - 1.0, COLUMNS_HEIGHT);
+                cr.fill().unwrap();
+                continue;
+            }
+            // Stack the groups from the bottom, most used first
+            let mut y = MARGIN + COLUMNS_HEIGHT;
+            for (group, count) in counts.iter().enumerate() {
+                let height = *count as f64 / total as f64 * COLUMNS_HEIGHT;
+                let (r, g, b) = SERIES_COLORS[group];
+                cr.set_source_rgb(r, g, b);
+                cr.rectangle(x, y - height, column_width - 1.0, height);
+                cr.fill().unwrap();
+                y -= height;
+            }
+        }
+
+        // Times under every sixth bucket
+        let axis_y = MARGIN + COLUMNS_HEIGHT + FONT_SIZE + 6.0;
+        cr.set_source_rgb(0.4, 0.4, 0.4);
+        for bucket in (0..=BUCKETS).step_by(6) {
+            let x = MARGIN + bucket as f64 * column_width;
+            let text = format_time(shares.bucket_start(bucket), "%T");
+            if let Ok(extents) = cr.text_extents(&text) {
+                cr.move_to(x - extents.width() / 2.0, axis_y);
+                cr.show_text(&text).unwrap();
+            }
+        }
+
+        // Legend with each group's share of the whole capture
+        let exchanges: usize = (0..shares.groups.len()).map(|i| shares.total(i)).sum();
+        let legend_y = MARGIN + COLUMNS_HEIGHT + AXIS_HEIGHT;
+        for (i, group) in shares.groups.iter().enumerate() {
+            let y = legend_y + i as f64 * LEGEND_ROW_HEIGHT;
+            let (r, g, b) = SERIES_COLORS[i];
+            cr.set_source_rgb(r, g, b);
+            cr.rectangle(MARGIN, y + 5.0, 12.0, 12.0);
+            cr.fill().unwrap();
+
+            cr.set_source_rgb(0.3, 0.3, 0.3);
+            cr.move_to(MARGIN + 18.0, y + LEGEND_ROW_HEIGHT / 2.0 + FONT_SIZE / 3.0);
+            let share = (shares.total(i) as f64 / exchanges as f64 * 100.0).round() as u32;
+            cr.show_text(&format!("{} {}%", group, share)).unwrap();
+        }
+    });
+
+    // Tell the period and group split of the hovered column
+    let motion = EventControllerMotion::new();
+    let shares_clone = shares.clone();
+    let drawing_area_clone = drawing_area.clone();
+    motion.connect_motion(move |_, x, y| {
+        let shares = shares_clone.borrow();
+        let width = drawing_area_clone.width() as f64;
+        let tooltip = bucket_at(width, x, y)
+            .filter(|_| !shares.groups.is_empty())
+            .map(|bucket| {
+                let counts = &shares.buckets[bucket];
+                let total: usize = counts.iter().sum();
+                let mut lines = vec![format!(
+                    "{} – {}",
+                    format_time(shares.bucket_start(bucket), "%T"),
+                    format_time(shares.bucket_start(bucket + 1), "%T")
+                )];
+                for (group, count) in shares.groups.iter().zip(counts) {
+                    if *count > 0 {
+                        let share = (*count as f64 / total as f64 * 100.0).round() as u32;
+                        lines.push(format!("{}: {} ({}%)", group, count, share));
+                    }
+                }
+                if total == 0 {
+                    lines.push("No key exchanges".to_string());
+                }
+                lines.join("\n")
+            });
+        drawing_area_clone.set_tooltip_text(tooltip.as_deref());
+    });
+    drawing_area.add_controller(motion);
+
+    let drawing_area_clone = drawing_area.clone();
+    let chart_clone = chart.clone();
+    chart.connect_view_changed(move |_| {
+        let new_shares = build_shares(&chart_clone.visible_events());
+        let height = 2.0 * MARGIN
+            + COLUMNS_HEIGHT
+            + AXIS_HEIGHT
+            + new_shares.groups.len() as f64 * LEGEND_ROW_HEIGHT;
+        *shares.borrow_mut() = new_shares;
+        drawing_area_clone.set_content_height(height as i32);
+        drawing_area_clone.queue_draw();
+    });
+
+    let scroll = gtk4::ScrolledWindow::new();
+    scroll.set_hscrollbar_policy(gtk4::PolicyType::Never);
+    scroll.set_child(Some(&drawing_area));
+    scroll
+}
@@ -0,0 +1,107 @@
+use crate::data::AuditEvent;
+use crate::decoders;
+use crate::sunburst::SunburstChart;
+use adw::prelude::*;
+use adw::ActionRow;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+// Record protection of the view, by whether the kernel or a library did it
+#[derive(Default)]
+struct Offload {
+    // kTLS offloads by cipher and direction
+    kernel: HashMap<(String, String), usize>,
+    userspace: usize,
+}
+
+impl Offload {
+    fn kernel_total(&self) -> usize {
+        self.kernel.values().sum()
+    }
+}
+
+fn collect(events: &[AuditEvent], offload: &mut Offload) {
+    for event in events {
+        let name = event.name();
+        if decoders::is_ktls_event(&name) {
+            let (cipher, direction) = decoders::ktls_offload(&event.events);
+            *offload
+                .kernel
+                .entry((
+                    cipher.unwrap_or_else(|| "unknown cipher".to_string()),
+                    direction.unwrap_or_else(|| "unknown direction".to_string()),
+                ))
+                .or_insert(0) += 1;
+        } else if decoders::is_cipher_event(&name) {
+            offload.userspace += 1;
+        }
+        collect(&event.spans, offload);
+    }
+}
+
+// Share of the record protection in the view offloaded to kernel TLS rather
+// than done by a userspace library, with the offloads by cipher and direction
+pub fn create_ktls_section(chart: Rc<SunburstChart>) -> gtk4::Box {
+    let section = gtk4::Box::new(gtk4::Orientation::Vertical, 6);
+    section.set_margin_start(12);
+    section.set_margin_end(12);
+    section.set_margin_top(12);
+    section.set_margin_bottom(12);
+    section.set_visible(false);
+
+    let title = gtk4::Label::new(Some("Kernel TLS"));
+    title.set_halign(gtk4::Align::Start);
+    title.add_css_class("title-4");
+    section.append(&title);
+
+    let overall = gtk4::Label::new(None);
+    overall.set_halign(gtk4::Align::Start);
+    overall.set_tooltip_text(Some(
+        "kTLS offloads against cipher operations done by crypto libraries in userspace",
+    ));
+    section.append(&overall);
+
+    let list = gtk4::ListBox::new();
+    list.set_selection_mode(gtk4::SelectionMode::None);
+    list.add_css_class("boxed-list");
+    section.append(&list);
+
+    let section_clone = section.clone();
+    let chart_clone = chart.clone();
+    chart.connect_view_changed(move |_| {
+        let mut offload = Offload::default();
+        collect(&chart_clone.visible_events(), &mut offload);
+
+        while let Some(row) = list.first_child() {
+            list.remove(&row);
+        }
+        let kernel = offload.kernel_total();
+        section_clone.set_visible(kernel > 0);
+        if kernel == 0 {
+            return;
+        }
+        let total = kernel + offload.userspace;
+        overall.set_text(&format!(
+            "{} of {} cipher operations offloaded ({}%)",
+            kernel,
+            total,
+            (kernel as f64 / total as f64 * 100.0).round() as u32
+        ));
+
+        let mut offloads: Vec<((String, String), usize)> = offload.kernel.into_iter().collect();
+        offloads.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        for ((cipher, direction), count) in offloads {
+            let row = ActionRow::builder()
+                .title(&cipher)
+                .subtitle(&direction)
+                .use_markup(false)
+                .build();
+            let count = gtk4::Label::new(Some(&count.to_string()));
+            count.add_css_class("dim-label");
+            row.add_suffix(&count);
+            list.append(&row);
+        }
+    });
+
+    section
+}
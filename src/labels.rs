@@ -0,0 +1,51 @@
+use anyhow::Result;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+// An entry of a context label mapping file: either a ready-made label or a
+// description of the connection the context belongs to
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Entry {
+    Label(String),
+    Connection {
+        #[serde(default)]
+        peer: Option<String>,
+        #[serde(default)]
+        sni: Option<String>,
+        #[serde(default)]
+        process: Option<String>,
+    },
+}
+
+impl Entry {
+    // e.g. "example.com (203.0.113.5:443) – curl"
+    fn label(self) -> Option<String> {
+        match self {
+            Self::Label(label) => Some(label),
+            Self::Connection { peer, sni, process } => {
+                let connection = match (sni, peer) {
+                    (Some(sni), Some(peer)) => Some(format!("{} ({})", sni, peer)),
+                    (sni, peer) => sni.or(peer),
+                };
+                match (connection, process) {
+                    (Some(connection), Some(process)) => {
+                        Some(format!("{} – {}", connection, process))
+                    }
+                    (connection, process) => connection.or(process),
+                }
+            }
+        }
+    }
+}
+
+// Load a JSON object mapping context IDs to labels or connection descriptions
+pub fn load_context_labels(path: &Path) -> Result<HashMap<String, String>> {
+    let entries: HashMap<String, Entry> = serde_json::from_str(&fs::read_to_string(path)?)?;
+    Ok(entries
+        .into_iter()
+        .filter_map(|(context, entry)| Some((context, entry.label()?)))
+        .collect())
+}
@@ -0,0 +1,500 @@
+use crate::config::Config;
+use crate::data::AuditEvent;
+use crate::sunburst::SunburstChart;
+use anyhow::{anyhow, Result};
+use gtk4::prelude::*;
+use gtk4::{gio, glib};
+use serde::de::{DeserializeOwned, IgnoredAny};
+use serde::{Deserialize, Serialize};
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::{Rc, Weak};
+
+// How many events a followed file keeps in memory. Zero means no limit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Retention {
+    pub max_events: usize,
+    // Events ending this many seconds before the latest one are evicted
+    pub max_age_secs: u64,
+}
+
+impl Retention {
+    // Drop the oldest events beyond the limits, returning how many were dropped
+    fn evict(&self, retained: &mut VecDeque<AuditEvent>) -> usize {
+        let before = retained.len();
+        if self.max_events > 0 {
+            while retained.len() > self.max_events {
+                retained.pop_front();
+            }
+        }
+        if self.max_age_secs > 0 {
+            let latest = retained.iter().map(|event| event.end).max().unwrap_or(0);
+            let oldest = latest.saturating_sub(self.max_age_secs * 1_000_000_000);
+            while retained.front().is_some_and(|event| event.end < oldest) {
+                retained.pop_front();
+            }
+        }
+        before - retained.len()
+    }
+}
+
+// Running totals since following began, including evicted events
+#[derive(Debug, Default)]
+pub struct Counters {
+    pub ingested: usize,
+    pub evicted: usize,
+    // Events and their spans by name
+    pub by_name: HashMap<String, usize>,
+}
+
+impl Counters {
+    fn count(&mut self, event: &AuditEvent) {
+        *self.by_name.entry(event.name()).or_default() += 1;
+        for span in &event.spans {
+            self.count(span);
+        }
+    }
+}
+
+// The part of a followed file already parsed
+#[derive(Debug, Default)]
+struct Tail {
+    // End of the last complete top-level event
+    offset: usize,
+    // SHA-256 of the file up to the offset, to notice when the file was
+    // rewritten rather than appended to
+    hash: Option<String>,
+}
+
+impl Tail {
+    fn of_file(bytes: &[u8]) -> Result<Self> {
+        let start = events_offset(bytes)?;
+        let (_, consumed) = parse_elements::<IgnoredAny>(&bytes[start..], true, usize::MAX)?;
+        Ok(Self::at(bytes, start + consumed))
+    }
+
+    fn at(bytes: &[u8], offset: usize) -> Self {
+        Self {
+            offset,
+            hash: hash_of(&bytes[..offset]),
+        }
+    }
+
+    // Whether the file still starts with the part parsed before
+    fn is_prefix_of(&self, bytes: &[u8]) -> bool {
+        bytes.len() >= self.offset && hash_of(&bytes[..self.offset]) == self.hash
+    }
+}
+
+fn hash_of(bytes: &[u8]) -> Option<String> {
+    glib::compute_checksum_for_data(glib::ChecksumType::Sha256, bytes).map(|hash| hash.to_string())
+}
+
+// Where the array of events of a capture file starts: the file itself for a
+// bare array, or the "events" member of a capture with a metadata header
+pub fn events_offset(bytes: &[u8]) -> Result<usize> {
+    let skip = |pos: &mut usize, separators: &[u8]| {
+        while bytes
+            .get(*pos)
+            .is_some_and(|b| b.is_ascii_whitespace() || separators.contains(b))
+        {
+            *pos += 1;
+        }
+    };
+
+    let mut pos = 0;
+    skip(&mut pos, b"");
+    match bytes.get(pos) {
+        Some(b'[') => return Ok(pos),
+        Some(b'{') => pos += 1,
+        _ => return Err(anyhow!("Not a JSON array of events")),
+    }
+    // Skip the members before "events", e.g. the metadata header
+    loop {
+        skip(&mut pos, b",");
+        let mut stream = serde_json::Deserializer::from_slice(&bytes[pos..]).into_iter::<String>();
+        let key = match stream.next() {
+            Some(Ok(key)) => key,
+            _ => return Err(anyhow!("The capture has no array of events")),
+        };
+        pos += stream.byte_offset();
+        skip(&mut pos, b":");
+        if key == "events" {
+            return Ok(pos);
+        }
+        let mut stream =
+            serde_json::Deserializer::from_slice(&bytes[pos..]).into_iter::<IgnoredAny>();
+        match stream.next() {
+            Some(Ok(_)) => pos += stream.byte_offset(),
+            Some(Err(e)) => return Err(e.into()),
+            None => return Err(anyhow!("The capture has no array of events")),
+        }
+    }
+}
+
+// Parse the complete elements of a JSON array, from its start or from the end
+// of an element, until one ends past `max_bytes`. Returns them with the number
+// of bytes up to the end of the last one; an element still being written is
+// left for the next change.
+pub fn parse_elements<T: DeserializeOwned>(
+    bytes: &[u8],
+    at_start: bool,
+    max_bytes: usize,
+) -> Result<(Vec<T>, usize)> {
+    let skip = |pos: &mut usize, separators: &[u8]| {
+        while bytes
+            .get(*pos)
+            .is_some_and(|b| b.is_ascii_whitespace() || separators.contains(b))
+        {
+            *pos += 1;
+        }
+    };
+
+    let mut pos = 0;
+    if at_start {
+        skip(&mut pos, b"");
+        if bytes.get(pos) != Some(&b'[') {
+            return Err(anyhow!("Not a JSON array of events"));
+        }
+        pos += 1;
+    }
+
+    let mut elements = Vec::new();
+    let mut end = pos;
+    while end < max_bytes {
+        skip(&mut pos, b",");
+        if matches!(bytes.get(pos), None | Some(b']')) {
+            break;
+        }
+        let mut stream = serde_json::Deserializer::from_slice(&bytes[pos..]).into_iter::<T>();
+        match stream.next() {
+            Some(Ok(element)) => {
+                pos += stream.byte_offset();
+                end = pos;
+                elements.push(element);
+            }
+            Some(Err(e)) if e.is_eof() => break,
+            Some(Err(e)) => return Err(e.into()),
+            None => break,
+        }
+    }
+    Ok((elements, end))
+}
+
+// What the follower reports back to the window
+pub enum Status<'a> {
+    // Number of updates held back while paused
+    Buffered(usize),
+    Counters {
+        retained: usize,
+        counters: &'a Counters,
+    },
+    // Another capture was opened, so the file is no longer followed
+    Stopped,
+}
+
+// Live mode: follows a capture file that is being rewritten or appended to,
+// reloading the chart whenever the file changes. Events appended to the file
+// are kept in a ring buffer bounded by the retention preferences; only the
+// appended part of the file is parsed on each change. Updates are
+// held back while paused and applied on resume.
+pub struct LiveFollow {
+    inner: Rc<Inner>,
+}
+
+struct Inner {
+    path: PathBuf,
+    chart: Rc<SunburstChart>,
+    config: Rc<RefCell<Config>>,
+    monitor: gio::FileMonitor,
+    paused: Cell<bool>,
+    buffered_updates: Cell<usize>,
+    // Where the events already taken into the buffer end in the file
+    tail: RefCell<Tail>,
+    retained: RefCell<VecDeque<AuditEvent>>,
+    counters: RefCell<Counters>,
+    status: Box<dyn Fn(Status)>,
+}
+
+impl LiveFollow {
+    pub fn new(
+        path: &Path,
+        chart: Rc<SunburstChart>,
+        config: Rc<RefCell<Config>>,
+        status: impl Fn(Status) + 'static,
+    ) -> Result<Self> {
+        let monitor = gio::File::for_path(path)
+            .monitor_file(gio::FileMonitorFlags::NONE, None::<&gio::Cancellable>)?;
+
+        let events = chart.source_events();
+        let tail = Tail::of_file(&fs::read(path)?)?;
+        let inner = Rc::new(Inner {
+            path: path.to_path_buf(),
+            chart,
+            config,
+            monitor: monitor.clone(),
+            paused: Cell::new(false),
+            buffered_updates: Cell::new(0),
+            tail: RefCell::new(tail),
+            retained: RefCell::new(VecDeque::new()),
+            counters: RefCell::new(Counters::default()),
+            status: Box::new(status),
+        });
+
+        // The events already shown are the start of the buffer
+        inner.ingest(events);
+        if inner.counters.borrow().evicted > 0 {
+            inner.show();
+        }
+        inner.report_counters();
+
+        // The monitor lives as long as the follower, so only keep a weak reference here
+        let weak: Weak<Inner> = Rc::downgrade(&inner);
+        monitor.connect_changed(move |_, _, _, event| {
+            if !matches!(
+                event,
+                gio::FileMonitorEvent::ChangesDoneHint | gio::FileMonitorEvent::Created
+            ) {
+                return;
+            }
+            if let Some(inner) = weak.upgrade() {
+                inner.reload();
+            }
+        });
+
+        Ok(Self { inner })
+    }
+
+    // Freeze the view; changes of the file are buffered until resumed
+    pub fn pause(&self) {
+        self.inner.paused.set(true);
+    }
+
+    // Apply the buffered changes and follow the file again
+    pub fn resume(&self) {
+        let inner = &self.inner;
+        inner.paused.set(false);
+        if inner.buffered_updates.replace(0) > 0 {
+            inner.show();
+        }
+        (inner.status)(Status::Buffered(0));
+    }
+
+    // Drop everything seen so far, keeping only events that arrive later
+    pub fn clear(&self) {
+        let inner = &self.inner;
+        inner.retained.borrow_mut().clear();
+        *inner.counters.borrow_mut() = Counters::default();
+        inner.show();
+        inner.report_counters();
+    }
+}
+
+impl Drop for LiveFollow {
+    fn drop(&mut self) {
+        self.inner.monitor.cancel();
+    }
+}
+
+impl Inner {
+    fn reload(&self) {
+        if self.chart.source_path().as_deref() != Some(self.path.as_path()) {
+            (self.status)(Status::Stopped);
+            return;
+        }
+
+        let (new_events, rewritten) = match self.read_appended() {
+            Ok(change) => change,
+            Err(e) => {
+                eprintln!("Failed to reload {}: {}", self.path.display(), e);
+                return;
+            }
+        };
+        if rewritten {
+            // The events seen before may have changed, so start over from the file
+            self.retained.borrow_mut().clear();
+            *self.counters.borrow_mut() = Counters::default();
+        } else if new_events.is_empty() {
+            return;
+        }
+        self.ingest(new_events);
+        self.report_counters();
+
+        if self.paused.get() {
+            self.buffered_updates.set(self.buffered_updates.get() + 1);
+            (self.status)(Status::Buffered(self.buffered_updates.get()));
+        } else {
+            self.show();
+        }
+    }
+
+    // Events appended to the file since the last read, and whether the file
+    // was rewritten instead. Only the grown tail is parsed, unless the file was
+    // truncated or its content before the tail changed, in which case all of
+    // its events are parsed again.
+    fn read_appended(&self) -> Result<(Vec<AuditEvent>, bool)> {
+        let mut tail = self.tail.borrow_mut();
+        let bytes = fs::read(&self.path)?;
+
+        if tail.is_prefix_of(&bytes) {
+            let (events, consumed) = parse_elements(&bytes[tail.offset..], false, usize::MAX)?;
+            *tail = Tail::at(&bytes, tail.offset + consumed);
+            return Ok((events, false));
+        }
+
+        let start = events_offset(&bytes)?;
+        let (events, consumed) = parse_elements(&bytes[start..], true, usize::MAX)?;
+        *tail = Tail::at(&bytes, start + consumed);
+        Ok((events, true))
+    }
+
+    // Add events to the buffer, evicting the oldest ones beyond the retention limits
+    fn ingest(&self, events: Vec<AuditEvent>) {
+        let retention = self.config.borrow().live_retention;
+        let mut retained = self.retained.borrow_mut();
+        let mut counters = self.counters.borrow_mut();
+
+        for event in events {
+            counters.ingested += 1;
+            counters.count(&event);
+            retained.push_back(event);
+        }
+
+        counters.evicted += retention.evict(&mut retained);
+    }
+
+    fn show(&self) {
+        let events = self.retained.borrow().iter().cloned().collect();
+        self.chart.update_data(events);
+    }
+
+    fn report_counters(&self) {
+        (self.status)(Status::Counters {
+            retained: self.retained.borrow().len(),
+            counters: &self.counters.borrow(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    fn parse(bytes: &str, at_start: bool, max_bytes: usize) -> Result<(Vec<Value>, usize)> {
+        parse_elements(bytes.as_bytes(), at_start, max_bytes)
+    }
+
+    #[test]
+    fn parse_complete_array() {
+        let (elements, end) = parse(" [{\"a\":1}, {\"a\":2}]\n", true, usize::MAX).unwrap();
+        assert_eq!(elements, vec![json!({"a": 1}), json!({"a": 2})]);
+        assert_eq!(end, 18);
+
+        let (elements, end) = parse("[ ]", true, usize::MAX).unwrap();
+        assert!(elements.is_empty());
+        assert_eq!(end, 1);
+    }
+
+    #[test]
+    fn parse_leaves_partial_element() {
+        let bytes = "[{\"a\":1},{\"a\":2},{\"a\":";
+        let (elements, end) = parse(bytes, true, usize::MAX).unwrap();
+        assert_eq!(elements, vec![json!({"a": 1}), json!({"a": 2})]);
+        assert_eq!(&bytes[end..], ",{\"a\":");
+    }
+
+    #[test]
+    fn parse_continues_after_element() {
+        let (elements, end) = parse(",\n{\"a\":3}]", false, usize::MAX).unwrap();
+        assert_eq!(elements, vec![json!({"a": 3})]);
+        assert_eq!(end, 9);
+    }
+
+    #[test]
+    fn parse_stops_past_max_bytes() {
+        let bytes = "[{\"a\":1},{\"a\":2},{\"a\":3}]";
+        // The element crossing the limit is the last one parsed
+        let (elements, end) = parse(bytes, true, 10).unwrap();
+        assert_eq!(elements, vec![json!({"a": 1}), json!({"a": 2})]);
+        assert_eq!(end, 16);
+
+        let (elements, end) = parse(bytes, true, 1).unwrap();
+        assert!(elements.is_empty());
+        assert_eq!(end, 1);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(parse("{\"a\":1}", true, usize::MAX).is_err());
+        assert!(parse("[{\"a\":1}, x]", true, usize::MAX).is_err());
+        assert!(parse_elements::<AuditEvent>(b"[{\"a\":1}]", true, usize::MAX).is_err());
+    }
+
+    #[test]
+    fn offset_of_events() {
+        assert_eq!(events_offset(b"  [{\"a\":1}]").unwrap(), 2);
+        let bytes = b"{\"metadata\": {\"events\": [1]}, \"events\": [{\"a\":1}]}";
+        let offset = events_offset(bytes).unwrap();
+        assert_eq!(&bytes[offset..offset + 2], b"[{");
+        assert!(events_offset(b"{\"metadata\": {}}").is_err());
+        assert!(events_offset(b"\"events\"").is_err());
+    }
+
+    fn events(ends_secs: &[u64]) -> VecDeque<AuditEvent> {
+        ends_secs
+            .iter()
+            .map(|end| {
+                serde_json::from_value(json!({
+                    "context": "c",
+                    "origin": "o",
+                    "start": 0,
+                    "end": end * 1_000_000_000,
+                    "events": {"name": "tls::handshake_client"},
+                }))
+                .unwrap()
+            })
+            .collect()
+    }
+
+    fn ends_secs(retained: &VecDeque<AuditEvent>) -> Vec<u64> {
+        retained
+            .iter()
+            .map(|event| event.end / 1_000_000_000)
+            .collect()
+    }
+
+    #[test]
+    fn retention_without_limits() {
+        let mut retained = events(&[1, 2, 3]);
+        assert_eq!(Retention::default().evict(&mut retained), 0);
+        assert_eq!(ends_secs(&retained), [1, 2, 3]);
+    }
+
+    #[test]
+    fn retention_by_count() {
+        let retention = Retention {
+            max_events: 2,
+            max_age_secs: 0,
+        };
+        let mut retained = events(&[1, 2, 3, 4]);
+        assert_eq!(retention.evict(&mut retained), 2);
+        assert_eq!(ends_secs(&retained), [3, 4]);
+    }
+
+    #[test]
+    fn retention_by_age() {
+        let retention = Retention {
+            max_events: 0,
+            max_age_secs: 10,
+        };
+        // Ages count from the latest end, which needn't be the last event's,
+        // and eviction stops at the first event recent enough
+        let mut retained = events(&[1, 5, 30, 25, 40, 35]);
+        assert_eq!(retention.evict(&mut retained), 2);
+        assert_eq!(ends_secs(&retained), [30, 25, 40, 35]);
+    }
+}
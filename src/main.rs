@@ -1,26 +1,101 @@
+mod annotations;
+mod anonymize;
+mod bars;
+mod boots;
+mod bundle;
+mod cbom;
+mod chains;
+mod cli;
+mod compliance;
+mod config;
+mod csv_import;
 mod data;
+mod decoders;
+mod detail;
+mod donut;
+mod export;
+mod filters;
+mod gcrypt;
+mod glossary;
+mod health;
+mod heatmap;
+mod help;
+mod history;
+mod html;
+mod ike;
+mod kex;
+mod ktls;
+mod labels;
+mod live;
+mod matrix;
+mod memory;
 mod models;
+mod navigation;
+mod origins;
+mod outcomes;
+mod palette;
+mod parquet;
+mod parse_summary;
+mod perf;
+mod preferences;
+mod profile_editor;
+mod profiles;
+mod progressive;
+mod query;
+mod references;
+mod remote;
+mod repeats;
+mod report;
+mod resumption;
+mod saved_queries;
+mod scrubber;
+mod secrecy;
+mod session;
+mod settings;
+mod signatures;
+mod sparkline;
+mod sql;
+mod standards;
+mod summary;
 mod sunburst;
+mod timestamps;
+mod timezone;
+mod xlsx;
 
 use adw::prelude::*;
 use adw::{
     glib, AboutWindow, Application, ApplicationWindow, Banner, HeaderBar, NavigationPage,
-    NavigationSplitView, StatusPage, ToolbarView, ViewStack, ViewSwitcherBar,
+    NavigationSplitView, StatusPage, Toast, ToastOverlay, ToolbarView, ViewStack, ViewSwitcherBar,
 };
+use annotations::Annotation;
 use anyhow::Result;
-use data::{AuditEvent, TreeNode};
+use compliance::{Profile, Severity};
+use config::Config;
+use data::{AuditEvent, Capture, NodeKind, SortOrder, TreeNode, ValueMode};
 use gtk4::{
-    gio, Button, ColumnView, ColumnViewColumn, Label, ListItem, Orientation, ScrolledWindow,
+    gio, Button, ColumnView, ColumnViewColumn, Image, Label, ListItem, Orientation, ScrolledWindow,
     SignalListItemFactory, SingleSelection, Stack, TreeListModel, TreeListRow,
 };
-use models::{StatsObject, TreeNodeObject};
+use history::{History, ViewState};
+use live::LiveFollow;
+use models::{StatsObject, TreeNodeObject, UnknownCodeObject};
+use scrubber::TimeScrubber;
+use session::Session;
+use std::cell::RefCell;
+use std::collections::HashSet;
 use std::fs;
 use std::rc::Rc;
-use sunburst::SunburstChart;
+use std::time::{Duration, Instant};
+use sunburst::{Layout, PercentBase, StatsExtract, SunburstChart, Weighting};
 
 const APP_ID: &str = "org.gnome.CryptoUsageAnalyzer";
 
 fn main() -> glib::ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if cli::is_headless(&args) {
+        return cli::run(&args);
+    }
+
     let app = Application::builder().application_id(APP_ID).build();
 
     app.connect_activate(build_ui);
@@ -33,14 +108,126 @@ fn build_ui(app: &Application) {
 
     // Create hamburger menu
     let menu = gio::Menu::new();
-    menu.append(Some("Open File"), Some("app.open"));
-    menu.append(Some("About Crypto Usage Analyzer"), Some("app.about"));
+
+    let edit_section = gio::Menu::new();
+    edit_section.append(Some("Undo"), Some("app.undo"));
+    edit_section.append(Some("Redo"), Some("app.redo"));
+    menu.append_section(None, &edit_section);
+
+    let file_section = gio::Menu::new();
+    file_section.append(Some("Open File"), Some("app.open"));
+    file_section.append(
+        Some("Collect from Remote Host…"),
+        Some("app.collect-remote"),
+    );
+    file_section.append(
+        Some("Load Context Labels…"),
+        Some("app.load-context-labels"),
+    );
+    file_section.append(Some("Import CSV…"), Some("app.import-csv"));
+    file_section.append(Some("Cross-Check CBOM…"), Some("app.import-cbom"));
+    file_section.append(Some("Follow File"), Some("app.follow"));
+    file_section.append(Some("Use as Baseline"), Some("app.set-baseline"));
+    file_section.append(Some("Clear Baseline"), Some("app.clear-baseline"));
+    file_section.append(Some("Export Chart Image…"), Some("app.export-image"));
+    file_section.append(Some("Export Statistics Image…"), Some("app.export-stats"));
+    file_section.append(Some("Export PDF…"), Some("app.export-pdf"));
+    file_section.append(Some("Export Interactive HTML…"), Some("app.export-html"));
+    file_section.append(Some("Generate Report…"), Some("app.generate-report"));
+    file_section.append(Some("Export Workbook…"), Some("app.export-workbook"));
+    file_section.append(Some("Export Parquet…"), Some("app.export-parquet"));
+    file_section.append(Some("Export SQLite Database…"), Some("app.export-database"));
+    file_section.append(Some("Open Session…"), Some("app.open-session"));
+    file_section.append(Some("Save Session…"), Some("app.save-session"));
+    menu.append_section(None, &file_section);
+
+    let view_section = gio::Menu::new();
+    view_section.append(Some("Merge Identical Events"), Some("app.aggregate"));
+    view_section.append(Some("Group by Process"), Some("app.group-by-process"));
+    view_section.append(
+        Some("Collapse Identical Contexts"),
+        Some("app.collapse-contexts"),
+    );
+    view_section.append(Some("Show IANA Codes"), Some("app.show-codes"));
+    view_section.append(Some("Color by Severity"), Some("app.severity-colors"));
+    view_section.append(Some("Anonymize Identifiers"), Some("app.anonymize"));
+
+    let value_menu = gio::Menu::new();
+    value_menu.append(Some("Self Count"), Some("app.value-mode::self"));
+    value_menu.append(Some("Leaf Count"), Some("app.value-mode::leaf"));
+    value_menu.append(Some("Inclusive Sum"), Some("app.value-mode::inclusive"));
+    view_section.append_submenu(Some("Event Counts"), &value_menu);
+
+    let sort_menu = gio::Menu::new();
+    sort_menu.append(Some("By Size"), Some("app.sort::size"));
+    sort_menu.append(Some("By Name"), Some("app.sort::name"));
+    view_section.append_submenu(Some("Sort Segments"), &sort_menu);
+
+    let percent_menu = gio::Menu::new();
+    percent_menu.append(Some("Of Parent"), Some("app.percent-base::parent"));
+    percent_menu.append(Some("Of Current View"), Some("app.percent-base::view"));
+    percent_menu.append(Some("Of Whole Capture"), Some("app.percent-base::capture"));
+    view_section.append_submenu(Some("Percentages"), &percent_menu);
+
+    // Bookmarks of the loaded capture are filled in whenever the view changes
+    let bookmarks_menu = gio::Menu::new();
+    let bookmark_actions = gio::Menu::new();
+    bookmark_actions.append(Some("Bookmark This View"), Some("app.add-bookmark"));
+    bookmark_actions.append(Some("Clear Bookmarks"), Some("app.clear-bookmarks"));
+    bookmarks_menu.append_section(None, &bookmark_actions);
+    let bookmark_items = gio::Menu::new();
+    bookmarks_menu.append_section(None, &bookmark_items);
+    view_section.append_submenu(Some("Bookmarks"), &bookmarks_menu);
+    view_section.append(Some("Annotate Selected Node…"), Some("app.annotate"));
+    view_section.append(Some("Copy Selected Node as Query"), Some("app.copy-query"));
+    view_section.append(Some("Copy Context ID"), Some("app.copy-context"));
+    menu.append_section(None, &view_section);
+
+    let weighting_section = gio::Menu::new();
+    weighting_section.append(Some("Size by Event Count"), Some("app.weighting::count"));
+    weighting_section.append(Some("Size by Duration"), Some("app.weighting::duration"));
+    weighting_section.append(
+        Some("Arrange One After Another"),
+        Some("app.layout::proportional"),
+    );
+    weighting_section.append(
+        Some("Arrange by Wall-Clock Time"),
+        Some("app.layout::wall-time"),
+    );
+    menu.append_section(None, &weighting_section);
+
+    let about_section = gio::Menu::new();
+    about_section.append(Some("Preferences"), Some("app.preferences"));
+    about_section.append(
+        Some("Copy Performance Report"),
+        Some("app.copy-performance-report"),
+    );
+    about_section.append(Some("About Crypto Usage Analyzer"), Some("app.about"));
+    menu.append_section(None, &about_section);
 
     let menu_button = gtk4::MenuButton::new();
     menu_button.set_icon_name("open-menu-symbolic");
     menu_button.set_menu_model(Some(&menu));
     header_bar.pack_end(&menu_button);
 
+    // Live mode controls, shown only while the capture file is followed
+    let pause_button = gtk4::ToggleButton::new();
+    pause_button.set_icon_name("media-playback-pause-symbolic");
+    pause_button.set_tooltip_text(Some("Pause Live Updates"));
+    pause_button.set_visible(false);
+    header_bar.pack_start(&pause_button);
+
+    let clear_button = gtk4::Button::from_icon_name("edit-clear-all-symbolic");
+    clear_button.set_tooltip_text(Some("Clear Events Seen So Far"));
+    clear_button.set_visible(false);
+    header_bar.pack_start(&clear_button);
+
+    let live_counters_label = Label::new(None);
+    live_counters_label.add_css_class("dim-label");
+    live_counters_label.add_css_class("numeric");
+    live_counters_label.set_visible(false);
+    header_bar.pack_start(&live_counters_label);
+
     // Create stack for switching between empty state and split view
     let stack = Stack::new();
     stack.set_transition_type(gtk4::StackTransitionType::Crossfade);
@@ -68,9 +255,7 @@ fn build_ui(app: &Application) {
         true,  // autoexpand
         |item| {
             let tree_node = item.downcast_ref::<TreeNodeObject>().unwrap();
-            tree_node
-                .children()
-                .map(gio::ListModel::from)
+            tree_node.children().map(gio::ListModel::from)
         },
     );
 
@@ -78,6 +263,52 @@ fn build_ui(app: &Application) {
     let column_view = ColumnView::new(Some(selection_model.clone()));
     column_view.add_css_class("data-table");
 
+    // Create leading compliance severity column
+    let severity_factory = SignalListItemFactory::new();
+    severity_factory.connect_setup(|_, list_item| {
+        let image = Image::new();
+        image.set_margin_start(4);
+        image.set_margin_end(4);
+        list_item.set_child(Some(&image));
+    });
+    severity_factory.connect_bind(|_, list_item| {
+        let list_item = list_item.downcast_ref::<ListItem>().unwrap();
+        let tree_list_row = list_item.item().and_downcast::<TreeListRow>().unwrap();
+        let tree_node = tree_list_row
+            .item()
+            .and_downcast::<TreeNodeObject>()
+            .unwrap();
+        let image = list_item.child().and_downcast::<Image>().unwrap();
+
+        for class in ["success", "warning", "error"] {
+            image.remove_css_class(class);
+        }
+        let (icon, class, tooltip) = match Severity::from_name(&tree_node.severity()) {
+            Some(Severity::Ok) => ("emblem-ok-symbolic", "success", "Complies with the policy"),
+            Some(Severity::Warning) => (
+                "dialog-warning-symbolic",
+                "warning",
+                "Contains events that could not be checked",
+            ),
+            Some(Severity::Error) => (
+                "dialog-error-symbolic",
+                "error",
+                "Contains usage violating the policy",
+            ),
+            None => {
+                image.set_icon_name(None);
+                image.set_tooltip_text(None);
+                return;
+            }
+        };
+        image.set_icon_name(Some(icon));
+        image.add_css_class(class);
+        image.set_tooltip_text(Some(tooltip));
+    });
+
+    let severity_column = ColumnViewColumn::new(None, Some(severity_factory));
+    column_view.append_column(&severity_column);
+
     // Create "Operation" column
     let name_factory = SignalListItemFactory::new();
     name_factory.connect_setup(|_, list_item| {
@@ -99,7 +330,13 @@ fn build_ui(app: &Application) {
         // Add indentation based on depth
         let depth = tree_list_row.depth();
         let indent = "  ".repeat(depth as usize);
-        label.set_text(&format!("{}{}", indent, tree_node.name()));
+        label.set_text(&format!("{}{}", indent, tree_node.label()));
+        // Nodes shown by a short form, like contexts, tell their full name
+        let name = tree_node.property::<String>("name");
+        let tooltip = glossary::glossary()
+            .tooltip(&tree_node.label())
+            .or_else(|| (name != tree_node.label()).then_some(name));
+        label.set_tooltip_text(tooltip.as_deref());
     });
 
     let name_column = ColumnViewColumn::new(Some("Operation"), Some(name_factory));
@@ -165,6 +402,24 @@ fn build_ui(app: &Application) {
     separator.set_margin_top(6);
     sampling_period_box.append(&separator);
 
+    // Create capture info section, shown when the capture has a metadata header
+    let capture_info_box = gtk4::Box::new(Orientation::Vertical, 6);
+    capture_info_box.set_margin_start(12);
+    capture_info_box.set_margin_end(12);
+    capture_info_box.set_margin_top(12);
+    capture_info_box.set_margin_bottom(12);
+    capture_info_box.set_visible(false);
+
+    let capture_info_title = gtk4::Label::new(Some("Capture Info"));
+    capture_info_title.set_halign(gtk4::Align::Start);
+    capture_info_title.add_css_class("title-4");
+    capture_info_box.append(&capture_info_title);
+
+    let capture_info_list = gtk4::ListBox::new();
+    capture_info_list.set_selection_mode(gtk4::SelectionMode::None);
+    capture_info_list.add_css_class("boxed-list");
+    capture_info_box.append(&capture_info_list);
+
     // Create algorithms section
     let algorithms_box = gtk4::Box::new(Orientation::Vertical, 6);
     algorithms_box.set_margin_start(12);
@@ -174,12 +429,32 @@ fn build_ui(app: &Application) {
 
     let algorithms_title = gtk4::Label::new(Some("Most Used Algorithms"));
     algorithms_title.set_halign(gtk4::Align::Start);
+    algorithms_title.set_hexpand(true);
     algorithms_title.add_css_class("title-4");
-    algorithms_box.append(&algorithms_title);
+    let copy_stats_button = Button::from_icon_name("edit-copy-symbolic");
+    copy_stats_button.set_tooltip_text(Some("Copy Table"));
+    copy_stats_button.add_css_class("flat");
+    copy_stats_button.set_action_name(Some("app.copy-stats"));
+    let algorithms_header = gtk4::Box::new(Orientation::Horizontal, 6);
+    algorithms_header.append(&algorithms_title);
+    algorithms_header.append(&copy_stats_button);
+    algorithms_box.append(&algorithms_header);
+
+    // Create sunburst chart, which also keeps the stats tables up to date
+    let chart = Rc::new(SunburstChart::new());
 
     // Create statistics view
     let stats_store = gio::ListStore::new::<StatsObject>();
-    let stats_selection = SingleSelection::new(Some(stats_store.clone()));
+    // Filtered by the entry above the table, matching any part of the algorithm, ignoring case
+    let stats_filter = gtk4::StringFilter::new(Some(gtk4::PropertyExpression::new(
+        StatsObject::static_type(),
+        None::<&gtk4::Expression>,
+        "algorithm",
+    )));
+    stats_filter.set_match_mode(gtk4::StringFilterMatchMode::Substring);
+    let stats_filter_model =
+        gtk4::FilterListModel::new(Some(stats_store.clone()), Some(stats_filter.clone()));
+    let stats_selection = SingleSelection::new(Some(stats_filter_model));
     let stats_view = ColumnView::new(Some(stats_selection));
     stats_view.add_css_class("data-table");
 
@@ -197,6 +472,11 @@ fn build_ui(app: &Application) {
         let stats_obj = list_item.item().and_downcast::<StatsObject>().unwrap();
         let label = list_item.child().and_downcast::<Label>().unwrap();
         label.set_text(&stats_obj.algorithm());
+        label.set_tooltip_text(
+            glossary::glossary()
+                .tooltip(&stats_obj.algorithm())
+                .as_deref(),
+        );
     });
     let algo_column = ColumnViewColumn::new(Some("Algorithm"), Some(algo_factory));
     algo_column.set_expand(true);
@@ -238,6 +518,11 @@ fn build_ui(app: &Application) {
     let percent_column = ColumnViewColumn::new(Some("Percentage"), Some(percent_factory));
     stats_view.append_column(&percent_column);
 
+    // Create "Δ" column, shown while a baseline is set
+    let delta_column = create_delta_column();
+    stats_view.append_column(&delta_column);
+    chart.add_stats_columns(percent_column, delta_column);
+
     // Wrap statistics view in scrolled window
     let stats_scroll = ScrolledWindow::new();
     stats_scroll.set_child(Some(&stats_view));
@@ -245,29 +530,112 @@ fn build_ui(app: &Application) {
     stats_scroll.set_min_content_height(200);
     stats_scroll.set_max_content_height(400);
 
+    // Bars of the top algorithms, which show dominance quicker than the table
+    algorithms_box.append(&bars::create_share_bars(&stats_store, chart.clone()));
+
+    let stats_filter_entry = gtk4::SearchEntry::new();
+    stats_filter_entry.set_placeholder_text(Some("Filter algorithms"));
+    stats_filter_entry.connect_search_changed(move |entry| {
+        stats_filter.set_search(Some(&entry.text()));
+    });
+    algorithms_box.append(&stats_filter_entry);
+
     // Add the scrolled window to algorithms box
     algorithms_box.append(&stats_scroll);
 
-    // Create stats container with period section and algorithms section
+    // Create per-category sections, hidden unless the view contains such events
+    let (certificates_box, cert_stats_store) =
+        create_stats_section(&chart, "Certificate Signatures", "Algorithm");
+    let (random_box, random_stats_store) =
+        create_stats_section(&chart, "Random Generators", "Generator");
+    let (kdf_box, kdf_stats_store) = create_stats_section(&chart, "Key Derivation", "Function");
+    let (cipher_box, cipher_stats_store) = create_stats_section(&chart, "Ciphers", "Cipher");
+    let (hash_box, hash_stats_store) = create_stats_section(&chart, "Hashes", "Hash");
+    let (process_box, process_stats_store) = create_stats_section(&chart, "Processes", "Process");
+
+    // Create system crypto policy section
+    let policy_box = gtk4::Box::new(Orientation::Vertical, 6);
+    policy_box.set_margin_start(12);
+    policy_box.set_margin_end(12);
+    policy_box.set_margin_top(12);
+    policy_box.set_margin_bottom(12);
+
+    let policy_title = gtk4::Label::new(Some("System Crypto Policy"));
+    policy_title.set_halign(gtk4::Align::Start);
+    policy_title.add_css_class("title-4");
+    policy_box.append(&policy_title);
+
+    let policy_status = gtk4::Box::new(Orientation::Horizontal, 6);
+    let policy_label = gtk4::Label::new(Some("Not loaded"));
+    policy_label.set_halign(gtk4::Align::Start);
+    policy_label.set_wrap(true);
+    policy_label.set_xalign(0.0);
+    policy_label.add_css_class("dim-label");
+    policy_status.append(&policy_label);
+    policy_box.append(&policy_status);
+
+    let policy_list = gtk4::ListBox::new();
+    policy_list.set_selection_mode(gtk4::SelectionMode::None);
+    policy_list.add_css_class("boxed-list");
+    policy_list.set_visible(false);
+    policy_box.append(&policy_list);
+
+    // Create stats container with period, algorithms and policy sections
     let stats_container = gtk4::Box::new(Orientation::Vertical, 0);
+    let sparkline = sparkline::create_density_sparkline(chart.clone());
+    sampling_period_box.insert_child_after(&sparkline, Some(&period_duration_label));
+    sampling_period_box
+        .insert_child_after(&boots::create_boot_picker(chart.clone()), Some(&sparkline));
     stats_container.append(&sampling_period_box);
+    stats_container.append(&capture_info_box);
+    stats_container.append(&parse_summary::create_parse_summary(chart.clone()));
     stats_container.append(&algorithms_box);
+    stats_container.append(&donut::create_version_donut(chart.clone()));
+    stats_container.append(&resumption::create_resumption_section(chart.clone()));
+    stats_container.append(&repeats::create_repeats_section(chart.clone()));
+    stats_container.append(&secrecy::create_forward_secrecy_card(chart.clone()));
+    stats_container.append(&signatures::create_signature_breakdown(chart.clone()));
+    stats_container.append(&outcomes::create_error_rate_section(chart.clone()));
+    stats_container.append(&certificates_box);
+    stats_container.append(&random_box);
+    stats_container.append(&kdf_box);
+    stats_container.append(&cipher_box);
+    stats_container.append(&hash_box);
+    stats_container.append(&ktls::create_ktls_section(chart.clone()));
+    let cbom_section = Rc::new(cbom::create_cbom_section(chart.clone()));
+    stats_container.append(&cbom_section.widget);
+    stats_container.append(&process_box);
+    stats_container.append(&policy_box);
+
+    // Let the sidebar scroll when its sections don't fit
+    let sidebar_scroll = ScrolledWindow::new();
+    sidebar_scroll.set_hscrollbar_policy(gtk4::PolicyType::Never);
+    sidebar_scroll.set_child(Some(&stats_container));
 
     // Create sidebar page with statistics only
     let sidebar_page = NavigationPage::builder()
         .title("Statistics")
-        .child(&stats_container)
+        .child(&sidebar_scroll)
         .build();
 
-    // Create banner for zoom notification
-    let banner = Banner::new("");
-    banner.set_title("Click to reset the zoom");
-    banner.set_button_label(Some("Reset"));
-    banner.set_revealed(false);
-
-    // Create sunburst chart
-    let chart = Rc::new(SunburstChart::new());
-    chart.set_zoom_banner(banner.clone());
+    // Load persisted preferences
+    let (config, config_error) = Config::load();
+    let config = Rc::new(RefCell::new(config));
+    chart.set_palette(config.borrow().palette);
+    chart.set_tooltip_options(config.borrow().tooltip);
+    chart.set_exclude_rules(&config.borrow().exclude_rules);
+    chart.set_performance_overlay(config.borrow().performance_overlay);
+    if let Err(e) = timezone::set_display_zone(&config.borrow().time_zone) {
+        eprintln!("Failed to apply preferences: {}", e);
+    }
+    timezone::set_relative(config.borrow().relative_times);
+    Profile::select(
+        config
+            .borrow()
+            .compliance_profile
+            .as_deref()
+            .and_then(Profile::named),
+    );
     chart.set_tree_store(root_store.clone());
     chart.set_column_view(column_view.clone());
     chart.set_stats_store(stats_store.clone());
@@ -276,35 +644,132 @@ fn build_ui(app: &Application) {
         period_end_label.clone(),
         period_duration_label.clone(),
     );
+    chart.set_capture_info_widgets(capture_info_box.clone(), capture_info_list.clone());
+    chart.set_policy_widgets(
+        policy_status.clone(),
+        policy_label.clone(),
+        policy_list.clone(),
+    );
+
+    // Create trailing annotation column, showing an icon for annotated nodes
+    let annotation_factory = SignalListItemFactory::new();
+    annotation_factory.connect_setup(|_, list_item| {
+        let image = Image::new();
+        image.set_margin_start(4);
+        image.set_margin_end(4);
+        list_item.set_child(Some(&image));
+    });
+    let chart_clone = chart.clone();
+    let config_clone = config.clone();
+    annotation_factory.connect_bind(move |_, list_item| {
+        let list_item = list_item.downcast_ref::<ListItem>().unwrap();
+        let tree_list_row = list_item.item().and_downcast::<TreeListRow>().unwrap();
+        let image = list_item.child().and_downcast::<Image>().unwrap();
+
+        // Tree rows are relative to the zoomed node, annotations to the root
+        let mut path = chart_clone.zoom_path();
+        path.extend(tree_row_path(&tree_list_row));
+        let config = config_clone.borrow();
+        let annotation = chart_clone
+            .source_hash()
+            .and_then(|hash| config.annotation(&hash, &path));
+        match annotation {
+            Some(annotation) => {
+                image.set_icon_name(Some("document-edit-symbolic"));
+                image.set_tooltip_text(Some(&annotation.summary()));
+            }
+            None => {
+                image.set_icon_name(None);
+                image.set_tooltip_text(None);
+            }
+        }
+    });
+    let annotation_column = ColumnViewColumn::new(None, Some(annotation_factory));
+    column_view.append_column(&annotation_column);
 
-    // Create sunburst view container (banner + chart)
+    // Activating a tree row annotates it
+    let app_clone = app.clone();
+    column_view.connect_activate(move |_, _| {
+        app_clone.activate_action("annotate", None);
+    });
+
+    // Keep the per-category stats in sync with the visible subtree
+    let category_sections: [(gtk4::Box, gio::ListStore, StatsExtract); 6] = [
+        (
+            certificates_box,
+            cert_stats_store,
+            TreeNode::extract_certificate_stats,
+        ),
+        (
+            random_box,
+            random_stats_store,
+            TreeNode::extract_random_stats,
+        ),
+        (kdf_box, kdf_stats_store, TreeNode::extract_kdf_stats),
+        (
+            cipher_box,
+            cipher_stats_store,
+            TreeNode::extract_cipher_stats,
+        ),
+        (hash_box, hash_stats_store, TreeNode::extract_hash_stats),
+        (
+            process_box,
+            process_stats_store,
+            TreeNode::extract_process_stats,
+        ),
+    ];
+    let chart_clone = chart.clone();
+    chart.connect_view_changed(move |node| {
+        for (section, store, extract) in &category_sections {
+            let shown = chart_clone.fill_stats_for(store, node, *extract);
+            section.set_visible(shown);
+        }
+    });
+
+    // Share of a capture parsed so far, while it is being loaded
+    let loading_label = gtk4::Label::new(None);
+    loading_label.set_halign(gtk4::Align::End);
+    loading_label.set_margin_start(12);
+    loading_label.set_margin_end(12);
+    loading_label.set_margin_top(6);
+    loading_label.add_css_class("caption");
+    loading_label.add_css_class("dim-label");
+    loading_label.set_visible(false);
+
+    // Create sunburst view container (origin chips + loading indicator + summary
+    // cards + zoom levels of the chart)
     let sunburst_box = gtk4::Box::new(Orientation::Vertical, 0);
-    sunburst_box.append(&banner);
-    sunburst_box.append(chart.widget());
+    sunburst_box.append(&origins::create_origin_chips(chart.clone()));
+    sunburst_box.append(&loading_label);
+    sunburst_box.append(&summary::create_summary_cards(chart.clone()));
+    sunburst_box.append(&navigation::create_zoom_navigation(
+        chart.clone(),
+        "All Events",
+    ));
+
+    // Time scrubber restricting the view to part of the sampling period
+    let time_scrubber = TimeScrubber::new();
+    sunburst_box.append(time_scrubber.widget());
+    chart.set_time_scrubber(time_scrubber);
 
     // Connect tree selection to chart highlighting
     let chart_clone = chart.clone();
     selection_model.connect_selection_changed(move |selection, _, _| {
         if let Some(selected_item) = selection.selected_item() {
             if let Some(tree_list_row) = selected_item.downcast_ref::<TreeListRow>() {
-                // Build path from root to selected node
-                let mut path = Vec::new();
-                let mut current_row = Some(tree_list_row.clone());
-
-                while let Some(row) = current_row {
-                    if let Some(node) = row.item().and_downcast::<TreeNodeObject>() {
-                        path.insert(0, node.name());
-                    }
-                    current_row = row.parent();
-                }
-
-                chart_clone.set_selected_path(path);
+                chart_clone.set_selected_path(tree_row_path(tree_list_row));
             }
         } else {
             chart_clone.set_selected_path(Vec::new());
         }
     });
 
+    // Create toast overlay for transient notifications
+    let toast_overlay = ToastOverlay::new();
+    if let Some(e) = config_error {
+        toast_overlay.add_toast(Toast::builder().title(e.to_string()).timeout(0).build());
+    }
+
     // Create view stack for content area
     let content_view_stack = ViewStack::new();
     content_view_stack.set_vexpand(true);
@@ -315,14 +780,319 @@ fn build_ui(app: &Application) {
     let tree_page = content_view_stack.add_titled(&tree_scroll, Some("tree"), "Event Tree");
     tree_page.set_icon_name(Some("view-list-symbolic"));
 
+    // Create heatmap of operations over time, to spot periodic patterns
+    let heatmap_page = content_view_stack.add_titled(
+        &heatmap::create_heatmap(chart.clone()),
+        Some("heatmap"),
+        "Heatmap",
+    );
+    heatmap_page.set_icon_name(Some("view-grid-symbolic"));
+
+    // Create chart of key exchange groups over time, to follow a rollout of new ones
+    let kex_page = content_view_stack.add_titled(
+        &kex::create_group_share_chart(chart.clone()),
+        Some("key-exchange"),
+        "Key Exchange",
+    );
+    kex_page.set_icon_name(Some("network-transmit-receive-symbolic"));
+
+    // Create matrix of contexts by algorithm family, showing who uses what
+    let (matrix_scroll, matrix_model) = matrix::create_matrix_view(chart.clone());
+    matrix_scroll.set_vexpand(true);
+    let export_matrix_button = gtk4::Button::with_label("Export CSV…");
+    export_matrix_button.set_halign(gtk4::Align::End);
+    export_matrix_button.set_margin_top(6);
+    export_matrix_button.set_margin_bottom(6);
+    export_matrix_button.set_margin_end(12);
+    export_matrix_button.set_action_name(Some("app.export-matrix"));
+    let matrix_box = gtk4::Box::new(Orientation::Vertical, 0);
+    matrix_box.append(&matrix_scroll);
+    matrix_box.append(&export_matrix_button);
+    let matrix_page = content_view_stack.add_titled(&matrix_box, Some("matrix"), "Matrix");
+    matrix_page.set_icon_name(Some("view-app-grid-symbolic"));
+
+    // Create SQL panel for ad-hoc queries on the events in the view
+    let sql_page = content_view_stack.add_titled(
+        &sql::create_sql_panel(chart.clone(), toast_overlay.clone()),
+        Some("sql"),
+        "SQL",
+    );
+    sql_page.set_icon_name(Some("utilities-terminal-symbolic"));
+
+    // Create unknown codes view listing everything the decoders didn't recognize
+    let unknown_store = gio::ListStore::new::<UnknownCodeObject>();
+    let unknown_view = ColumnView::new(Some(SingleSelection::new(Some(unknown_store.clone()))));
+    unknown_view.add_css_class("data-table");
+    unknown_view.append_column(&create_text_column(
+        "Field",
+        gtk4::Align::Start,
+        true,
+        |obj: &UnknownCodeObject| obj.field(),
+    ));
+    unknown_view.append_column(&create_text_column(
+        "Code",
+        gtk4::Align::Start,
+        true,
+        |obj: &UnknownCodeObject| obj.code(),
+    ));
+    unknown_view.append_column(&create_text_column(
+        "Occurrences",
+        gtk4::Align::End,
+        false,
+        |obj: &UnknownCodeObject| obj.count(),
+    ));
+
+    let unknown_scroll = ScrolledWindow::new();
+    unknown_scroll.set_child(Some(&unknown_view));
+
+    let unknown_empty = StatusPage::builder()
+        .icon_name("emblem-ok-symbolic")
+        .title("All Codes Recognized")
+        .description("Every event name and codepoint in this capture could be decoded")
+        .build();
+
+    let unknown_stack = Stack::new();
+    unknown_stack.add_named(&unknown_empty, Some("empty"));
+    unknown_stack.add_named(&unknown_scroll, Some("list"));
+
+    let unknown_page =
+        content_view_stack.add_titled(&unknown_stack, Some("unknown"), "Unknown Codes");
+    unknown_page.set_icon_name(Some("dialog-question-symbolic"));
+
+    // Show the list and a badge with the number of distinct unknown codes
+    let unknown_stack_clone = unknown_stack.clone();
+    let unknown_page_clone = unknown_page.clone();
+    unknown_store.connect_items_changed(move |store, _, _, _| {
+        let n_items = store.n_items();
+        unknown_stack_clone.set_visible_child_name(if n_items == 0 { "empty" } else { "list" });
+        unknown_page_clone.set_badge_number(n_items);
+    });
+    chart.set_unknown_store(unknown_store.clone());
+
+    // Create data health page reporting malformed events of the capture
+    health::add_health_page(&content_view_stack, chart.clone());
+
     // Create view switcher bar for content
     let content_view_switcher = ViewSwitcherBar::new();
     content_view_switcher.set_stack(Some(&content_view_stack));
     content_view_switcher.set_reveal(true);
 
     // Create content container with view stack and switcher
+    // Search filtering the events by their description
+    let search_entry = gtk4::SearchEntry::new();
+    search_entry.set_placeholder_text(Some("Search events"));
+    search_entry.set_margin_start(12);
+    search_entry.set_margin_end(12);
+    search_entry.set_margin_top(6);
+    search_entry.set_margin_bottom(6);
+    let chart_clone = chart.clone();
+    search_entry.connect_search_changed(move |entry| {
+        // Undo sets the text of the search it restores
+        if chart_clone.search() != entry.text().trim() {
+            chart_clone.set_search(&entry.text());
+        }
+    });
+    search_entry.set_hexpand(true);
+
+    // Only offered when the capture records outcomes of its operations
+    let failures_button = gtk4::ToggleButton::with_label("Failures Only");
+    failures_button.set_tooltip_text(Some("Show only connections with a failed operation"));
+    failures_button.set_margin_end(12);
+    failures_button.set_valign(gtk4::Align::Center);
+    failures_button.set_visible(false);
+    let chart_clone = chart.clone();
+    failures_button.connect_toggled(move |button| {
+        if chart_clone.failures_only() != button.is_active() {
+            chart_clone.set_failures_only(button.is_active());
+        }
+    });
+    let chart_clone = chart.clone();
+    let failures_button_clone = failures_button.clone();
+    chart.connect_view_changed(move |_| {
+        failures_button_clone.set_visible(
+            chart_clone.failures_only() || filters::has_outcomes(&chart_clone.events()),
+        );
+        // Follow the filters being cleared
+        failures_button_clone.set_active(chart_clone.failures_only());
+    });
+
+    // Filter expression, applied on Enter, behind a toggle in the search bar
+    let query_entry = gtk4::Entry::new();
+    query_entry.set_placeholder_text(Some(
+        "Filter expression, e.g. origin == \"gnutls\" && name =~ \"tls::sign\" && bits < 2048",
+    ));
+    query_entry.set_primary_icon_name(Some("system-search-symbolic"));
+    query_entry.set_margin_start(12);
+    query_entry.set_margin_end(12);
+    query_entry.set_margin_bottom(6);
+    let chart_clone = chart.clone();
+    let config_clone = config.clone();
+    query_entry.connect_activate(move |entry| {
+        let text = entry.text();
+        if text.trim().is_empty() {
+            entry.remove_css_class("error");
+            entry.set_tooltip_text(None);
+            chart_clone.set_filter_query(None);
+            return;
+        }
+        match query::Query::parse(&text) {
+            Ok(query) => {
+                entry.remove_css_class("error");
+                entry.set_tooltip_text(None);
+                // Exports name the saved query the expression is
+                let name = config_clone
+                    .borrow()
+                    .saved_query_for(query.text())
+                    .map(|saved| saved.name.clone());
+                chart_clone.set_filter_query(Some(query.with_name(name)));
+            }
+            Err(e) => {
+                entry.add_css_class("error");
+                entry.set_tooltip_text(Some(&e.to_string()));
+            }
+        }
+    });
+    query_entry.set_hexpand(true);
+
+    // Saved queries, with actions saving and removing the current one
+    let saved_query_items = gio::Menu::new();
+    saved_queries::update_menu(&saved_query_items, &config.borrow());
+    let saved_queries_menu = gio::Menu::new();
+    saved_queries_menu.append_section(None, &saved_query_items);
+    let saved_query_actions = gio::Menu::new();
+    saved_query_actions.append(Some("Save Query…"), Some("app.save-query"));
+    saved_query_actions.append(Some("Remove Saved Query"), Some("app.remove-saved-query"));
+    saved_queries_menu.append_section(None, &saved_query_actions);
+    let saved_queries_button = gtk4::MenuButton::builder()
+        .icon_name("folder-saved-search-symbolic")
+        .tooltip_text("Saved Queries")
+        .menu_model(&saved_queries_menu)
+        .valign(gtk4::Align::Center)
+        .margin_end(12)
+        .margin_bottom(6)
+        .build();
+
+    let query_row = gtk4::Box::new(Orientation::Horizontal, 0);
+    query_row.append(&query_entry);
+    query_row.append(&saved_queries_button);
+    let query_revealer = gtk4::Revealer::new();
+    query_revealer.set_child(Some(&query_row));
+
+    let query_button = gtk4::ToggleButton::new();
+    query_button.set_icon_name("view-more-horizontal-symbolic");
+    query_button.set_tooltip_text(Some("Filter Expression"));
+    query_button.set_margin_end(12);
+    query_button.set_valign(gtk4::Align::Center);
+    let query_revealer_clone = query_revealer.clone();
+    let query_entry_clone = query_entry.clone();
+    query_button.connect_toggled(move |button| {
+        query_revealer_clone.set_reveal_child(button.is_active());
+        if button.is_active() {
+            query_entry_clone.grab_focus();
+        }
+    });
+    // Stay open while an expression filters the view
+    let chart_clone = chart.clone();
+    let query_button_clone = query_button.clone();
+    chart.connect_view_changed(move |_| {
+        if chart_clone.filter_query().is_some() {
+            query_button_clone.set_active(true);
+        }
+    });
+
+    let search_row = gtk4::Box::new(Orientation::Horizontal, 0);
+    search_row.append(&search_entry);
+    search_row.append(&failures_button);
+    search_row.append(&query_button);
+    let search_bar = gtk4::Box::new(Orientation::Vertical, 0);
+    search_bar.append(&search_row);
+    search_bar.append(&query_revealer);
+
+    // Shown instead of the views when the filters leave no events
+    let no_match_page = StatusPage::builder()
+        .icon_name("edit-find-symbolic")
+        .build();
+    let clear_filters_button = gtk4::Button::with_label("Clear Filters");
+    clear_filters_button.set_halign(gtk4::Align::Center);
+    clear_filters_button.add_css_class("pill");
+    clear_filters_button.add_css_class("suggested-action");
+    no_match_page.set_child(Some(&clear_filters_button));
+
+    let search_entry_clone = search_entry.clone();
+    let query_entry_clone = query_entry.clone();
+    let chart_clone = chart.clone();
+    clear_filters_button.connect_clicked(move |_| {
+        search_entry_clone.set_text("");
+        chart_clone.set_search("");
+        query_entry_clone.set_text("");
+        chart_clone.set_filter_query(None);
+        chart_clone.set_tls_version(None);
+        chart_clone.set_hidden_origins(HashSet::new());
+        chart_clone.set_failures_only(false);
+        chart_clone.set_time_window(None);
+    });
+
+    let results_stack = Stack::new();
+    results_stack.set_vexpand(true);
+    results_stack.add_named(&content_view_stack, Some("results"));
+    results_stack.add_named(&no_match_page, Some("no-match"));
+
+    let chart_clone = chart.clone();
+    let results_stack_clone = results_stack.clone();
+    chart.connect_view_changed(move |_| {
+        let no_match = chart_clone.has_events()
+            && chart_clone
+                .root()
+                .is_some_and(|root| root.children.is_empty());
+        if no_match {
+            let search = chart_clone.search();
+            no_match_page.set_title(&if search.is_empty() {
+                "No Events Match the Filters".to_string()
+            } else {
+                format!("No events match “{}”", search)
+            });
+            no_match_page.set_description(Some(if chart_clone.has_exclude_rules() {
+                "Try another search, filter expression, time window, TLS version, origin or failure filter, or review the exclusion rules in the preferences"
+            } else {
+                "Try another search, filter expression, time window, TLS version, origin or failure filter"
+            }));
+        }
+        results_stack_clone.set_visible_child_name(if no_match { "no-match" } else { "results" });
+    });
+
+    // Warn whenever the capture has handshakes of deprecated protocol versions,
+    // whatever the filters show, with a button showing only legacy connections
+    let deprecated_banner = Banner::new("");
+    deprecated_banner.add_css_class("error");
+    deprecated_banner.set_button_label(Some("Show"));
+    deprecated_banner.set_revealed(false);
+    let chart_clone = chart.clone();
+    deprecated_banner.connect_button_clicked(move |_| {
+        chart_clone.set_tls_version(Some(filters::TlsVersion::Legacy));
+    });
+    let chart_clone = chart.clone();
+    let deprecated_banner_clone = deprecated_banner.clone();
+    chart.connect_view_changed(move |_| {
+        let count = chart_clone.deprecated_handshakes();
+        if count > 0 {
+            deprecated_banner_clone.set_title(&format!(
+                "Deprecated protocol versions detected — {} {}",
+                count,
+                if count == 1 {
+                    "handshake"
+                } else {
+                    "handshakes"
+                }
+            ));
+        }
+        deprecated_banner_clone.set_revealed(count > 0);
+    });
+
     let content_container = gtk4::Box::new(Orientation::Vertical, 0);
-    content_container.append(&content_view_stack);
+    content_container.append(&deprecated_banner);
+    content_container.append(&timestamps::create_timestamp_banner(chart.clone()));
+    content_container.append(&search_bar);
+    content_container.append(&results_stack);
     content_container.append(&content_view_switcher);
 
     // Create content page
@@ -344,10 +1114,12 @@ fn build_ui(app: &Application) {
     // Set initial page
     stack.set_visible_child_name("empty");
 
+    toast_overlay.set_child(Some(&stack));
+
     // Create toolbar view (modern Adwaita pattern)
     let toolbar_view = ToolbarView::new();
     toolbar_view.add_top_bar(&header_bar);
-    toolbar_view.set_content(Some(&stack));
+    toolbar_view.set_content(Some(&toast_overlay));
 
     // Create window
     let window = ApplicationWindow::builder()
@@ -357,11 +1129,21 @@ fn build_ui(app: &Application) {
         .default_height(800)
         .content(&toolbar_view)
         .build();
+    settings::bind_window_state(&window, &split_view, &content_view_stack);
+
+    // Double-clicking a segment opens it in a window of its own
+    let window_clone = window.clone();
+    let chart_clone = chart.clone();
+    chart.connect_segment_activated(move |node| {
+        detail::show_detail_window(&window_clone, node, &chart_clone);
+    });
 
     // Set up "open" action
     let window_clone = window.clone();
     let chart_clone = chart.clone();
     let stack_clone = stack.clone();
+    let toast_overlay_clone = toast_overlay.clone();
+    let loading_label_clone = loading_label.clone();
 
     let open_action = gio::SimpleAction::new("open", None);
     open_action.connect_activate(move |_, _| {
@@ -381,21 +1163,83 @@ fn build_ui(app: &Application) {
         filter.add_pattern("*.json");
         dialog.add_filter(&filter);
 
+        // Archives with one audit file per host, as the collection script produces
+        let bundle_filter = gtk4::FileFilter::new();
+        bundle_filter.set_name(Some("Capture Bundles"));
+        for pattern in [
+            "*.tar",
+            "*.tar.gz",
+            "*.tgz",
+            "*.tar.xz",
+            "*.tar.zst",
+            "*.zip",
+        ] {
+            bundle_filter.add_pattern(pattern);
+        }
+        dialog.add_filter(&bundle_filter);
+
         let all_filter = gtk4::FileFilter::new();
         all_filter.set_name(Some("All Files"));
         all_filter.add_pattern("*");
         dialog.add_filter(&all_filter);
 
+        let window = window_clone.clone();
         let chart = chart_clone.clone();
         let stack = stack_clone.clone();
+        let toast_overlay = toast_overlay_clone.clone();
+        let loading_label = loading_label_clone.clone();
 
         dialog.connect_response(move |dialog, response| {
             if response == gtk4::ResponseType::Accept {
                 if let Some(file) = dialog.file() {
-                    if let Some(path) = file.path() {
-                        if load_and_display(&path.to_string_lossy(), &chart).is_ok() {
-                            stack.set_visible_child_name("content");
-                        }
+                    if let Some(path) = file.path().filter(|path| bundle::is_bundle(path)) {
+                        let chart = chart.clone();
+                        let stack = stack.clone();
+                        let toast_overlay = toast_overlay.clone();
+                        glib::spawn_future_local(async move {
+                            let start = Instant::now();
+                            match bundle::read_bundle(&path).await {
+                                Ok((events, content)) => {
+                                    load_events(events, &content, Some(start.elapsed()), &chart);
+                                    stack.set_visible_child_name("content");
+                                }
+                                Err(e) => toast_overlay.add_toast(Toast::new(&format!(
+                                    "Failed to open bundle: {}",
+                                    e
+                                ))),
+                            }
+                        });
+                    } else if let Some(path) = file.path() {
+                        let window = window.clone();
+                        let chart = chart.clone();
+                        let stack = stack.clone();
+                        let toast_overlay = toast_overlay.clone();
+                        let loading_label = loading_label.clone();
+                        glib::spawn_future_local(async move {
+                            if !memory::confirm_load(&window, &path).await {
+                                return;
+                            }
+                            // Switch to the chart as soon as its first events are shown
+                            let result = progressive::load(&path, &chart, |progress| {
+                                if let Some(progress) = progress {
+                                    loading_label.set_text(&format!(
+                                        "Loading… {}%",
+                                        (progress * 100.0) as u32
+                                    ));
+                                    if chart.has_events() {
+                                        stack.set_visible_child_name("content");
+                                    }
+                                }
+                                loading_label.set_visible(progress.is_some());
+                            })
+                            .await;
+                            if let Err(e) = result {
+                                toast_overlay
+                                    .add_toast(Toast::new(&format!("Failed to open file: {}", e)));
+                            } else {
+                                stack.set_visible_child_name("content");
+                            }
+                        });
                     }
                 }
             }
@@ -406,48 +1250,1869 @@ fn build_ui(app: &Application) {
     });
     app.add_action(&open_action);
 
-    // Set up "about" action
+    // Set up "import-csv" action reading events from a CSV file with mapped columns
     let window_clone = window.clone();
-    let about_action = gio::SimpleAction::new("about", None);
-    about_action.connect_activate(move |_, _| {
-        let about = AboutWindow::builder()
-            .application_name("Crypto Usage Analyzer")
-            .application_icon("org.gnome.CryptoUsageAnalyzer")
-            .developer_name("Crypto Auditing Project")
-            .version("0.1.0")
-            .comments("Visualize cryptographic operations with interactive sunburst charts")
-            .website("https://github.com/simo5/crypto-auditing")
-            .issue_url("https://github.com/simo5/crypto-auditing/issues")
-            .license_type(gtk4::License::Gpl30)
-            .build();
+    let chart_clone = chart.clone();
+    let stack_clone = stack.clone();
+    let toast_overlay_clone = toast_overlay.clone();
 
-        about.set_transient_for(Some(&window_clone));
-        about.present();
+    let import_csv_action = gio::SimpleAction::new("import-csv", None);
+    import_csv_action.connect_activate(move |_, _| {
+        let dialog = gtk4::FileChooserDialog::new(
+            Some("Import CSV"),
+            Some(&window_clone),
+            gtk4::FileChooserAction::Open,
+            &[
+                ("Cancel", gtk4::ResponseType::Cancel),
+                ("Import", gtk4::ResponseType::Accept),
+            ],
+        );
+
+        let filter = gtk4::FileFilter::new();
+        filter.set_name(Some("CSV Files"));
+        filter.add_pattern("*.csv");
+        filter.add_pattern("*.tsv");
+        dialog.add_filter(&filter);
+
+        let window = window_clone.clone();
+        let chart = chart_clone.clone();
+        let stack = stack_clone.clone();
+        let toast_overlay = toast_overlay_clone.clone();
+        dialog.connect_response(move |dialog, response| {
+            let path = dialog.file().and_then(|file| file.path());
+            dialog.close();
+            let Some(path) = path.filter(|_| response == gtk4::ResponseType::Accept) else {
+                return;
+            };
+
+            let window = window.clone();
+            let chart = chart.clone();
+            let stack = stack.clone();
+            let toast_overlay = toast_overlay.clone();
+            glib::spawn_future_local(async move {
+                let content = match fs::read_to_string(&path) {
+                    Ok(content) => content,
+                    Err(e) => {
+                        toast_overlay.add_toast(Toast::new(&format!("Failed to read CSV: {}", e)));
+                        return;
+                    }
+                };
+                let start = Instant::now();
+                let rows = csv_import::parse(&content);
+                let parse = start.elapsed();
+                let Some(header) = rows.first() else {
+                    toast_overlay.add_toast(Toast::new("The CSV file is empty"));
+                    return;
+                };
+                let Some(mapping) = csv_import::ask_mapping(&window, header).await else {
+                    return;
+                };
+
+                let start = Instant::now();
+                match csv_import::events_from_rows(&rows, &mapping) {
+                    Ok(events) => {
+                        let message = format!("Imported {} events", events.len());
+                        let parse = parse + start.elapsed();
+                        load_events(events, &content, Some(parse), &chart);
+                        stack.set_visible_child_name("content");
+                        toast_overlay.add_toast(Toast::new(&message));
+                    }
+                    Err(e) => {
+                        toast_overlay
+                            .add_toast(Toast::new(&format!("Failed to import CSV: {}", e)));
+                    }
+                }
+            });
+        });
+
+        dialog.show();
     });
-    app.add_action(&about_action);
+    app.add_action(&import_csv_action);
 
-    // Connect empty state button to open action
-    let app_clone = app.clone();
-    empty_button.connect_clicked(move |_| {
-        app_clone.activate_action("open", None);
+    // Set up "collect-remote" action
+    let window_clone = window.clone();
+    let chart_clone = chart.clone();
+    let stack_clone = stack.clone();
+    let toast_overlay_clone = toast_overlay.clone();
+
+    let collect_action = gio::SimpleAction::new("collect-remote", None);
+    collect_action.connect_activate(move |action, _| {
+        let window = window_clone.clone();
+        let chart = chart_clone.clone();
+        let stack = stack_clone.clone();
+        let toast_overlay = toast_overlay_clone.clone();
+        let action = action.clone();
+
+        glib::spawn_future_local(async move {
+            let Some((host, command)) = remote::ask_remote_source(&window).await else {
+                return;
+            };
+
+            // Prevent starting a second collection while this one is running
+            action.set_enabled(false);
+            let progress = Toast::builder()
+                .title(format!("Collecting audit data from {}…", host))
+                .timeout(0)
+                .build();
+            toast_overlay.add_toast(progress.clone());
+
+            let result = remote::collect(&host, &command, |received| {
+                progress.set_title(&format!(
+                    "Collecting audit data from {}… {} received",
+                    host,
+                    memory::format_size(received)
+                ));
+            })
+            .await
+            .and_then(|content| load_from_str(&content, &chart));
+            progress.dismiss();
+            match result {
+                Ok(()) => {
+                    chart.set_source_host(Some(host.clone()));
+                    stack.set_visible_child_name("content");
+                    toast_overlay
+                        .add_toast(Toast::new(&format!("Loaded audit data from {}", host)));
+                }
+                Err(e) => {
+                    toast_overlay.add_toast(Toast::new(&e.to_string()));
+                }
+            }
+
+            action.set_enabled(true);
+        });
     });
+    app.add_action(&collect_action);
 
-    // Try to load default file if it exists
-    let default_path = "audit.json";
-    if std::path::Path::new(default_path).exists()
-        && load_and_display(default_path, &chart).is_ok() {
-            stack.set_visible_child_name("content");
+    // Set up "annotate" action editing the note of the selected tree node
+    let window_clone = window.clone();
+    let chart_clone = chart.clone();
+    let config_clone = config.clone();
+    let selection_model_clone = selection_model.clone();
+
+    let annotate_action = gio::SimpleAction::new("annotate", None);
+    annotate_action.connect_activate(move |_, _| {
+        let Some(hash) = chart_clone.source_hash() else {
+            return;
+        };
+        let position = selection_model_clone.selected();
+        let Some(row) = selection_model_clone
+            .selected_item()
+            .and_downcast::<TreeListRow>()
+        else {
+            return;
+        };
+        let Some(node) = row.item().and_downcast::<TreeNodeObject>() else {
+            return;
+        };
+
+        let mut path = chart_clone.zoom_path();
+        path.extend(tree_row_path(&row));
+
+        let window = window_clone.clone();
+        let config = config_clone.clone();
+        let selection_model = selection_model_clone.clone();
+
+        glib::spawn_future_local(async move {
+            let current = config.borrow().annotation(&hash, &path).cloned();
+            let Some(edit) =
+                annotations::edit_annotation(&window, &node.label(), current.as_ref()).await
+            else {
+                return;
+            };
+
+            {
+                let mut config = config.borrow_mut();
+                match edit {
+                    annotations::Edit::Save { note, state } => {
+                        config.set_annotation(&hash, Annotation { path, note, state })
+                    }
+                    annotations::Edit::Remove => config.remove_annotation(&hash, &path),
+                }
+                if let Err(e) = config.save() {
+                    eprintln!("Failed to save annotations: {}", e);
+                }
+            }
+
+            // Rebind the row so its annotation icon is updated
+            selection_model.items_changed(position, 1, 1);
+        });
+    });
+    app.add_action(&annotate_action);
+    app.set_accels_for_action("app.annotate", &["<Ctrl>e"]);
+
+    // Set up "copy-query" action placing a filter expression selecting the
+    // subtree of the selected tree node, or segment, on the clipboard
+    let window_clone = window.clone();
+    let chart_clone = chart.clone();
+    let selection_model_clone = selection_model.clone();
+    let toast_overlay_clone = toast_overlay.clone();
+
+    let copy_query_action = gio::SimpleAction::new("copy-query", None);
+    copy_query_action.connect_activate(move |_, _| {
+        let (Some(root), Some(row)) = (
+            chart_clone.root(),
+            selection_model_clone
+                .selected_item()
+                .and_downcast::<TreeListRow>(),
+        ) else {
+            return;
+        };
+        let mut names = chart_clone.zoom_path();
+        names.extend(tree_row_path(&row));
+
+        let mut path = Vec::new();
+        let mut node = &root;
+        for name in &names {
+            let Some(child) = node.children.iter().find(|child| &child.name == name) else {
+                return;
+            };
+            path.push(child);
+            node = child;
         }
+        window_clone
+            .clipboard()
+            .set_text(&query::subtree_query(&path));
+        toast_overlay_clone.add_toast(Toast::new("Query copied"));
+    });
+    app.add_action(&copy_query_action);
+    app.set_accels_for_action("app.copy-query", &["<Ctrl><Shift>c"]);
 
-    window.present();
-}
+    // Set up "copy-context" action placing the full ID of the context the
+    // selected tree node belongs to on the clipboard
+    let window_clone = window.clone();
+    let chart_clone = chart.clone();
+    let selection_model_clone = selection_model.clone();
+    let toast_overlay_clone = toast_overlay.clone();
+
+    let copy_context_action = gio::SimpleAction::new("copy-context", None);
+    copy_context_action.connect_activate(move |_, _| {
+        let (Some(root), Some(row)) = (
+            chart_clone.root(),
+            selection_model_clone
+                .selected_item()
+                .and_downcast::<TreeListRow>(),
+        ) else {
+            return;
+        };
+        let mut names = chart_clone.zoom_path();
+        names.extend(tree_row_path(&row));
+
+        let mut node = &root;
+        for name in &names {
+            let Some(child) = node.children.iter().find(|child| &child.name == name) else {
+                return;
+            };
+            if child.kind == NodeKind::Context {
+                window_clone.clipboard().set_text(&child.name);
+                toast_overlay_clone.add_toast(Toast::new("Context ID copied"));
+                return;
+            }
+            node = child;
+        }
+    });
+    app.add_action(&copy_context_action);
 
-fn load_and_display(path: &str, chart: &SunburstChart) -> Result<()> {
-    let content = fs::read_to_string(path)?;
-    let events: Vec<AuditEvent> = serde_json::from_str(&content)?;
+    // Set up "apply-saved-query" action, taking the index of the saved query
+    let chart_clone = chart.clone();
+    let config_clone = config.clone();
+    let query_entry_clone = query_entry.clone();
+    let query_button_clone = query_button.clone();
+    let toast_overlay_clone = toast_overlay.clone();
+
+    let apply_saved_query_action =
+        gio::SimpleAction::new("apply-saved-query", Some(glib::VariantTy::UINT32));
+    apply_saved_query_action.connect_activate(move |_, parameter| {
+        let Some(index) = parameter.and_then(|p| p.get::<u32>()) else {
+            return;
+        };
+        let Some(saved) = config_clone
+            .borrow()
+            .saved_queries
+            .get(index as usize)
+            .cloned()
+        else {
+            return;
+        };
+        match query::Query::parse(&saved.query) {
+            Ok(query) => {
+                query_entry_clone.set_text(&saved.query);
+                query_entry_clone.remove_css_class("error");
+                query_button_clone.set_active(true);
+                chart_clone.set_filter_query(Some(query.with_name(Some(saved.name.clone()))));
+                if !saved.description.is_empty() {
+                    toast_overlay_clone.add_toast(Toast::new(&saved.description));
+                }
+            }
+            Err(e) => toast_overlay_clone.add_toast(Toast::new(&format!(
+                "Failed to apply “{}”: {}",
+                saved.name, e
+            ))),
+        }
+    });
+    app.add_action(&apply_saved_query_action);
+
+    // Set up "save-query" action naming the current filter expression
+    let window_clone = window.clone();
+    let chart_clone = chart.clone();
+    let config_clone = config.clone();
+    let saved_query_items_clone = saved_query_items.clone();
+    let toast_overlay_clone = toast_overlay.clone();
+
+    let save_query_action = gio::SimpleAction::new("save-query", None);
+    save_query_action.connect_activate(move |_, _| {
+        let Some(query) = chart_clone.filter_query() else {
+            toast_overlay_clone.add_toast(Toast::new("Apply a filter expression to save it"));
+            return;
+        };
+        let window = window_clone.clone();
+        let chart = chart_clone.clone();
+        let config = config_clone.clone();
+        let saved_query_items = saved_query_items_clone.clone();
+
+        glib::spawn_future_local(async move {
+            let current = config.borrow().saved_query_for(query.text()).cloned();
+            let Some(saved) =
+                saved_queries::save_query(&window, query.text(), current.as_ref()).await
+            else {
+                return;
+            };
+            let name = saved.name.clone();
+            {
+                let mut config = config.borrow_mut();
+                config.save_query(saved);
+                if let Err(e) = config.save() {
+                    eprintln!("Failed to save queries: {}", e);
+                }
+            }
+            saved_queries::update_menu(&saved_query_items, &config.borrow());
+            chart.set_filter_query(Some(query.with_name(Some(name))));
+        });
+    });
+    app.add_action(&save_query_action);
 
-    let tree = TreeNode::from_events(&events);
-    chart.set_data(tree, events);
+    // Set up "remove-saved-query" action forgetting the saved query applied
+    let chart_clone = chart.clone();
+    let config_clone = config.clone();
+    let saved_query_items_clone = saved_query_items.clone();
+    let toast_overlay_clone = toast_overlay.clone();
+
+    let remove_saved_query_action = gio::SimpleAction::new("remove-saved-query", None);
+    remove_saved_query_action.connect_activate(move |_, _| {
+        let Some(query) = chart_clone.filter_query() else {
+            return;
+        };
+        let Some(name) = query.name().map(str::to_string) else {
+            toast_overlay_clone.add_toast(Toast::new("The filter expression isn't saved"));
+            return;
+        };
+        {
+            let mut config = config_clone.borrow_mut();
+            config.remove_saved_query(&name);
+            if let Err(e) = config.save() {
+                eprintln!("Failed to save queries: {}", e);
+            }
+        }
+        saved_queries::update_menu(&saved_query_items_clone, &config_clone.borrow());
+        chart_clone.set_filter_query(Some(query.with_name(None)));
+        toast_overlay_clone.add_toast(Toast::new(&format!("Removed “{}”", name)));
+    });
+    app.add_action(&remove_saved_query_action);
 
-    Ok(())
+    // Set up "load-context-labels" action reading a context ID mapping file
+    let window_clone = window.clone();
+    let chart_clone = chart.clone();
+    let toast_overlay_clone = toast_overlay.clone();
+
+    let load_labels_action = gio::SimpleAction::new("load-context-labels", None);
+    load_labels_action.connect_activate(move |_, _| {
+        let dialog = gtk4::FileChooserDialog::new(
+            Some("Load Context Labels"),
+            Some(&window_clone),
+            gtk4::FileChooserAction::Open,
+            &[
+                ("Cancel", gtk4::ResponseType::Cancel),
+                ("Open", gtk4::ResponseType::Accept),
+            ],
+        );
+
+        let filter = gtk4::FileFilter::new();
+        filter.set_name(Some("JSON Files"));
+        filter.add_pattern("*.json");
+        dialog.add_filter(&filter);
+
+        let chart = chart_clone.clone();
+        let toast_overlay = toast_overlay_clone.clone();
+
+        dialog.connect_response(move |dialog, response| {
+            if response == gtk4::ResponseType::Accept {
+                if let Some(path) = dialog.file().and_then(|file| file.path()) {
+                    let message = match labels::load_context_labels(&path) {
+                        Ok(labels) => {
+                            let count = labels.len();
+                            chart.set_context_labels(labels);
+                            format!("Loaded {} context labels", count)
+                        }
+                        Err(e) => format!("Failed to load context labels: {}", e),
+                    };
+                    toast_overlay.add_toast(Toast::new(&message));
+                }
+            }
+            dialog.close();
+        });
+
+        dialog.show();
+    });
+    app.add_action(&load_labels_action);
+
+    // Set up "import-cbom" action comparing a declared CBOM with observed usage
+    let window_clone = window.clone();
+    let toast_overlay_clone = toast_overlay.clone();
+
+    let import_cbom_action = gio::SimpleAction::new("import-cbom", None);
+    import_cbom_action.connect_activate(move |_, _| {
+        let dialog = gtk4::FileChooserDialog::new(
+            Some("Cross-Check CBOM"),
+            Some(&window_clone),
+            gtk4::FileChooserAction::Open,
+            &[
+                ("Cancel", gtk4::ResponseType::Cancel),
+                ("Open", gtk4::ResponseType::Accept),
+            ],
+        );
+
+        let filter = gtk4::FileFilter::new();
+        filter.set_name(Some("CycloneDX JSON"));
+        filter.add_pattern("*.json");
+        dialog.add_filter(&filter);
+
+        let cbom_section = cbom_section.clone();
+        let toast_overlay = toast_overlay_clone.clone();
+
+        dialog.connect_response(move |dialog, response| {
+            if response == gtk4::ResponseType::Accept {
+                if let Some(path) = dialog.file().and_then(|file| file.path()) {
+                    let message = match cbom::load_cbom(&path) {
+                        Ok(declared) => {
+                            let count = declared.len();
+                            cbom_section.set_declared(Some(declared));
+                            format!("Comparing with {} declared assets", count)
+                        }
+                        Err(e) => format!("Failed to load CBOM: {}", e),
+                    };
+                    toast_overlay.add_toast(Toast::new(&message));
+                }
+            }
+            dialog.close();
+        });
+
+        dialog.show();
+    });
+    app.add_action(&import_cbom_action);
+
+    // Set up "save-session" action
+    let window_clone = window.clone();
+    let chart_clone = chart.clone();
+    let config_clone = config.clone();
+    let toast_overlay_clone = toast_overlay.clone();
+
+    let save_session_action = gio::SimpleAction::new("save-session", None);
+    save_session_action.connect_activate(move |_, _| {
+        let session = match Session::capture(&chart_clone, &config_clone.borrow()) {
+            Ok(session) => session,
+            Err(e) => {
+                toast_overlay_clone.add_toast(Toast::new(&e.to_string()));
+                return;
+            }
+        };
+
+        let dialog = gtk4::FileChooserDialog::new(
+            Some("Save Session"),
+            Some(&window_clone),
+            gtk4::FileChooserAction::Save,
+            &[
+                ("Cancel", gtk4::ResponseType::Cancel),
+                ("Save", gtk4::ResponseType::Accept),
+            ],
+        );
+        dialog.set_current_name(&format!("analysis.{}", session::EXTENSION));
+
+        let toast_overlay = toast_overlay_clone.clone();
+        dialog.connect_response(move |dialog, response| {
+            if response == gtk4::ResponseType::Accept {
+                if let Some(path) = dialog.file().and_then(|file| file.path()) {
+                    let message = match session.save(&path) {
+                        Ok(()) => "Session saved".to_string(),
+                        Err(e) => format!("Failed to save session: {}", e),
+                    };
+                    toast_overlay.add_toast(Toast::new(&message));
+                }
+            }
+            dialog.close();
+        });
+
+        dialog.show();
+    });
+    app.add_action(&save_session_action);
+
+    // Set up "export-image" action saving the visible chart as PNG or SVG
+    let window_clone = window.clone();
+    let chart_clone = chart.clone();
+    let toast_overlay_clone = toast_overlay.clone();
+
+    let export_image_action = gio::SimpleAction::new("export-image", None);
+    export_image_action.connect_activate(move |_, _| {
+        let dialog = gtk4::FileChooserDialog::new(
+            Some("Export Chart Image"),
+            Some(&window_clone),
+            gtk4::FileChooserAction::Save,
+            &[
+                ("Cancel", gtk4::ResponseType::Cancel),
+                ("Export", gtk4::ResponseType::Accept),
+            ],
+        );
+        dialog.set_current_name("chart.png");
+
+        let filter = gtk4::FileFilter::new();
+        filter.set_name(Some("Images (PNG, SVG)"));
+        filter.add_pattern("*.png");
+        filter.add_pattern("*.svg");
+        dialog.add_filter(&filter);
+
+        // A boolean choice, stamping where the image came from into a corner
+        dialog.add_choice("watermark", "Stamp source information", &[]);
+        dialog.set_choice("watermark", "true");
+        dialog.add_choice("scale", "PNG resolution", &export::SCALES);
+        dialog.set_choice("scale", &export::scale_choice(window_clone.scale_factor()));
+
+        let chart = chart_clone.clone();
+        let toast_overlay = toast_overlay_clone.clone();
+        dialog.connect_response(move |dialog, response| {
+            if response == gtk4::ResponseType::Accept {
+                if let Some(path) = dialog.file().and_then(|file| file.path()) {
+                    let watermark = dialog.choice("watermark").as_deref() == Some("true");
+                    let scale = export_scale(dialog);
+                    let message = match export::export_chart(&chart, &path, watermark, scale) {
+                        Ok(()) => "Chart image exported".to_string(),
+                        Err(e) => format!("Failed to export chart image: {}", e),
+                    };
+                    toast_overlay.add_toast(Toast::new(&message));
+                }
+            }
+            dialog.close();
+        });
+
+        dialog.show();
+    });
+    app.add_action(&export_image_action);
+
+    // Set up "export-pdf" action saving the chart, and optionally the statistics, as vector PDF
+    let window_clone = window.clone();
+    let chart_clone = chart.clone();
+    let stats_store_clone = stats_store.clone();
+    let toast_overlay_clone = toast_overlay.clone();
+
+    let export_pdf_action = gio::SimpleAction::new("export-pdf", None);
+    export_pdf_action.connect_activate(move |_, _| {
+        let dialog = gtk4::FileChooserDialog::new(
+            Some("Export PDF"),
+            Some(&window_clone),
+            gtk4::FileChooserAction::Save,
+            &[
+                ("Cancel", gtk4::ResponseType::Cancel),
+                ("Export", gtk4::ResponseType::Accept),
+            ],
+        );
+        dialog.set_current_name("chart.pdf");
+
+        let filter = gtk4::FileFilter::new();
+        filter.set_name(Some("PDF Documents"));
+        filter.add_pattern("*.pdf");
+        dialog.add_filter(&filter);
+
+        dialog.add_choice("stats", "Add statistics table page", &[]);
+        dialog.set_choice("stats", "true");
+        dialog.add_choice("watermark", "Stamp source information", &[]);
+        dialog.set_choice("watermark", "true");
+
+        let chart = chart_clone.clone();
+        let stats_store = stats_store_clone.clone();
+        let toast_overlay = toast_overlay_clone.clone();
+        dialog.connect_response(move |dialog, response| {
+            if response == gtk4::ResponseType::Accept {
+                if let Some(path) = dialog.file().and_then(|file| file.path()) {
+                    let stats =
+                        (dialog.choice("stats").as_deref() == Some("true")).then_some(&stats_store);
+                    let watermark = dialog.choice("watermark").as_deref() == Some("true");
+                    let message = match export::export_pdf(&chart, stats, &path, watermark) {
+                        Ok(()) => "PDF exported".to_string(),
+                        Err(e) => format!("Failed to export PDF: {}", e),
+                    };
+                    toast_overlay.add_toast(Toast::new(&message));
+                }
+            }
+            dialog.close();
+        });
+
+        dialog.show();
+    });
+    app.add_action(&export_pdf_action);
+
+    // Set up "export-html" action saving the chart as a standalone interactive page
+    let window_clone = window.clone();
+    let chart_clone = chart.clone();
+    let toast_overlay_clone = toast_overlay.clone();
+
+    let export_html_action = gio::SimpleAction::new("export-html", None);
+    export_html_action.connect_activate(move |_, _| {
+        let dialog = gtk4::FileChooserDialog::new(
+            Some("Export Interactive HTML"),
+            Some(&window_clone),
+            gtk4::FileChooserAction::Save,
+            &[
+                ("Cancel", gtk4::ResponseType::Cancel),
+                ("Export", gtk4::ResponseType::Accept),
+            ],
+        );
+        dialog.set_current_name("chart.html");
+
+        let filter = gtk4::FileFilter::new();
+        filter.set_name(Some("HTML Files"));
+        filter.add_pattern("*.html");
+        dialog.add_filter(&filter);
+
+        let chart = chart_clone.clone();
+        let toast_overlay = toast_overlay_clone.clone();
+        dialog.connect_response(move |dialog, response| {
+            if response == gtk4::ResponseType::Accept {
+                if let Some(path) = dialog.file().and_then(|file| file.path()) {
+                    let message = match html::export_html(&chart, &path) {
+                        Ok(()) => "Interactive chart exported".to_string(),
+                        Err(e) => format!("Failed to export interactive chart: {}", e),
+                    };
+                    toast_overlay.add_toast(Toast::new(&message));
+                }
+            }
+            dialog.close();
+        });
+
+        dialog.show();
+    });
+    app.add_action(&export_html_action);
+
+    // Set up "generate-report" action filling the report template with the view
+    let window_clone = window.clone();
+    let chart_clone = chart.clone();
+    let stats_store_clone = stats_store.clone();
+    let config_clone = config.clone();
+    let toast_overlay_clone = toast_overlay.clone();
+
+    let generate_report_action = gio::SimpleAction::new("generate-report", None);
+    generate_report_action.connect_activate(move |_, _| {
+        let dialog = gtk4::FileChooserDialog::new(
+            Some("Generate Report"),
+            Some(&window_clone),
+            gtk4::FileChooserAction::Save,
+            &[
+                ("Cancel", gtk4::ResponseType::Cancel),
+                ("Generate", gtk4::ResponseType::Accept),
+            ],
+        );
+        dialog.set_current_name("report.html");
+
+        let filter = gtk4::FileFilter::new();
+        filter.set_name(Some("HTML Files"));
+        filter.add_pattern("*.html");
+        dialog.add_filter(&filter);
+
+        let chart = chart_clone.clone();
+        let stats_store = stats_store_clone.clone();
+        let config = config_clone.clone();
+        let toast_overlay = toast_overlay_clone.clone();
+        dialog.connect_response(move |dialog, response| {
+            if response == gtk4::ResponseType::Accept {
+                if let Some(path) = dialog.file().and_then(|file| file.path()) {
+                    let template = config.borrow().report_template.clone();
+                    let notes = chart
+                        .source_hash()
+                        .and_then(|hash| config.borrow().annotations.get(&hash).cloned())
+                        .unwrap_or_default();
+                    let message = match report::generate_report(
+                        &chart,
+                        &stats_store,
+                        &notes,
+                        template.as_deref(),
+                        &path,
+                    ) {
+                        Ok(()) => "Report generated".to_string(),
+                        Err(e) => format!("Failed to generate report: {}", e),
+                    };
+                    toast_overlay.add_toast(Toast::new(&message));
+                }
+            }
+            dialog.close();
+        });
+
+        dialog.show();
+    });
+    app.add_action(&generate_report_action);
+
+    // Set up "export-workbook" action saving the view as an Excel workbook
+    let window_clone = window.clone();
+    let chart_clone = chart.clone();
+    let stats_store_clone = stats_store.clone();
+    let toast_overlay_clone = toast_overlay.clone();
+
+    let export_workbook_action = gio::SimpleAction::new("export-workbook", None);
+    export_workbook_action.connect_activate(move |_, _| {
+        let dialog = gtk4::FileChooserDialog::new(
+            Some("Export Workbook"),
+            Some(&window_clone),
+            gtk4::FileChooserAction::Save,
+            &[
+                ("Cancel", gtk4::ResponseType::Cancel),
+                ("Export", gtk4::ResponseType::Accept),
+            ],
+        );
+        dialog.set_current_name("crypto-usage.xlsx");
+
+        let filter = gtk4::FileFilter::new();
+        filter.set_name(Some("Excel Workbooks"));
+        filter.add_pattern("*.xlsx");
+        dialog.add_filter(&filter);
+
+        let chart = chart_clone.clone();
+        let stats_store = stats_store_clone.clone();
+        let toast_overlay = toast_overlay_clone.clone();
+        dialog.connect_response(move |dialog, response| {
+            if response == gtk4::ResponseType::Accept {
+                if let Some(path) = dialog.file().and_then(|file| file.path()) {
+                    let message = match xlsx::export_workbook(&chart, &stats_store, &path) {
+                        Ok(()) => "Workbook exported".to_string(),
+                        Err(e) => format!("Failed to export workbook: {}", e),
+                    };
+                    toast_overlay.add_toast(Toast::new(&message));
+                }
+            }
+            dialog.close();
+        });
+
+        dialog.show();
+    });
+    app.add_action(&export_workbook_action);
+
+    // Set up "export-parquet" action saving the visible events as a Parquet table
+    let window_clone = window.clone();
+    let chart_clone = chart.clone();
+    let toast_overlay_clone = toast_overlay.clone();
+
+    let export_parquet_action = gio::SimpleAction::new("export-parquet", None);
+    export_parquet_action.connect_activate(move |_, _| {
+        let dialog = gtk4::FileChooserDialog::new(
+            Some("Export Parquet"),
+            Some(&window_clone),
+            gtk4::FileChooserAction::Save,
+            &[
+                ("Cancel", gtk4::ResponseType::Cancel),
+                ("Export", gtk4::ResponseType::Accept),
+            ],
+        );
+        dialog.set_current_name("events.parquet");
+
+        let filter = gtk4::FileFilter::new();
+        filter.set_name(Some("Parquet Files"));
+        filter.add_pattern("*.parquet");
+        dialog.add_filter(&filter);
+
+        let chart = chart_clone.clone();
+        let toast_overlay = toast_overlay_clone.clone();
+        dialog.connect_response(move |dialog, response| {
+            if response == gtk4::ResponseType::Accept {
+                if let Some(path) = dialog.file().and_then(|file| file.path()) {
+                    let message = match parquet::export_events(&chart.visible_events(), &path) {
+                        Ok(()) => "Events exported".to_string(),
+                        Err(e) => format!("Failed to export events: {}", e),
+                    };
+                    toast_overlay.add_toast(Toast::new(&message));
+                }
+            }
+            dialog.close();
+        });
+
+        dialog.show();
+    });
+    app.add_action(&export_parquet_action);
+
+    // Set up "export-database" action saving the visible events as an SQLite database
+    let window_clone = window.clone();
+    let chart_clone = chart.clone();
+    let toast_overlay_clone = toast_overlay.clone();
+
+    let export_database_action = gio::SimpleAction::new("export-database", None);
+    export_database_action.connect_activate(move |_, _| {
+        let dialog = gtk4::FileChooserDialog::new(
+            Some("Export SQLite Database"),
+            Some(&window_clone),
+            gtk4::FileChooserAction::Save,
+            &[
+                ("Cancel", gtk4::ResponseType::Cancel),
+                ("Export", gtk4::ResponseType::Accept),
+            ],
+        );
+        dialog.set_current_name("events.db");
+
+        let filter = gtk4::FileFilter::new();
+        filter.set_name(Some("SQLite Databases"));
+        filter.add_pattern("*.db");
+        filter.add_pattern("*.sqlite");
+        dialog.add_filter(&filter);
+
+        let chart = chart_clone.clone();
+        let toast_overlay = toast_overlay_clone.clone();
+        dialog.connect_response(move |dialog, response| {
+            if response == gtk4::ResponseType::Accept {
+                if let Some(path) = dialog.file().and_then(|file| file.path()) {
+                    let events = chart.visible_events();
+                    let toast_overlay = toast_overlay.clone();
+                    glib::spawn_future_local(async move {
+                        let message = match sql::save_database(&events, &path).await {
+                            Ok(()) => "Database exported".to_string(),
+                            Err(e) => format!("Failed to export database: {}", e),
+                        };
+                        toast_overlay.add_toast(Toast::new(&message));
+                    });
+                }
+            }
+            dialog.close();
+        });
+
+        dialog.show();
+    });
+    app.add_action(&export_database_action);
+
+    // Set up "copy-stats" action placing the algorithm statistics on the clipboard as TSV
+    let window_clone = window.clone();
+    let stats_store_clone = stats_store.clone();
+    let toast_overlay_clone = toast_overlay.clone();
+
+    let copy_stats_action = gio::SimpleAction::new("copy-stats", None);
+    copy_stats_action.connect_activate(move |_, _| {
+        window_clone
+            .clipboard()
+            .set_text(&export::stats_tsv(&stats_store_clone));
+        toast_overlay_clone.add_toast(Toast::new("Table copied"));
+    });
+    app.add_action(&copy_stats_action);
+
+    // Set up "copy-performance-report" action placing the load timings and the
+    // shape of the capture on the clipboard, without any of its content
+    let window_clone = window.clone();
+    let chart_clone = chart.clone();
+    let toast_overlay_clone = toast_overlay.clone();
+
+    let copy_performance_report_action = gio::SimpleAction::new("copy-performance-report", None);
+    copy_performance_report_action.connect_activate(move |_, _| {
+        window_clone
+            .clipboard()
+            .set_text(&parse_summary::performance_report(&chart_clone));
+        toast_overlay_clone.add_toast(Toast::new("Performance report copied"));
+    });
+    app.add_action(&copy_performance_report_action);
+
+    // Set up "export-matrix" action saving the context × family matrix as CSV
+    let window_clone = window.clone();
+    let toast_overlay_clone = toast_overlay.clone();
+
+    let export_matrix_action = gio::SimpleAction::new("export-matrix", None);
+    export_matrix_action.connect_activate(move |_, _| {
+        let dialog = gtk4::FileChooserDialog::new(
+            Some("Export Matrix"),
+            Some(&window_clone),
+            gtk4::FileChooserAction::Save,
+            &[
+                ("Cancel", gtk4::ResponseType::Cancel),
+                ("Export", gtk4::ResponseType::Accept),
+            ],
+        );
+        dialog.set_current_name("matrix.csv");
+
+        let matrix_model = matrix_model.clone();
+        let toast_overlay = toast_overlay_clone.clone();
+        dialog.connect_response(move |dialog, response| {
+            if response == gtk4::ResponseType::Accept {
+                if let Some(path) = dialog.file().and_then(|file| file.path()) {
+                    let message = match fs::write(&path, matrix::to_csv(&matrix_model)) {
+                        Ok(()) => "Matrix exported".to_string(),
+                        Err(e) => format!("Failed to export matrix: {}", e),
+                    };
+                    toast_overlay.add_toast(Toast::new(&message));
+                }
+            }
+            dialog.close();
+        });
+
+        dialog.show();
+    });
+    app.add_action(&export_matrix_action);
+
+    // Set up "export-stats" action saving the algorithm statistics as PNG or SVG
+    let window_clone = window.clone();
+    let chart_clone = chart.clone();
+    let stats_store_clone = stats_store.clone();
+    let toast_overlay_clone = toast_overlay.clone();
+
+    let export_stats_action = gio::SimpleAction::new("export-stats", None);
+    export_stats_action.connect_activate(move |_, _| {
+        let dialog = gtk4::FileChooserDialog::new(
+            Some("Export Statistics Image"),
+            Some(&window_clone),
+            gtk4::FileChooserAction::Save,
+            &[
+                ("Cancel", gtk4::ResponseType::Cancel),
+                ("Export", gtk4::ResponseType::Accept),
+            ],
+        );
+        dialog.set_current_name("statistics.png");
+
+        let filter = gtk4::FileFilter::new();
+        filter.set_name(Some("Images (PNG, SVG)"));
+        filter.add_pattern("*.png");
+        filter.add_pattern("*.svg");
+        dialog.add_filter(&filter);
+
+        dialog.add_choice(
+            "form",
+            "Draw as",
+            &[("table", "Table"), ("bars", "Bar Chart")],
+        );
+        dialog.set_choice("form", "table");
+        dialog.add_choice("watermark", "Stamp source information", &[]);
+        dialog.set_choice("watermark", "true");
+        dialog.add_choice("scale", "PNG resolution", &export::SCALES);
+        dialog.set_choice("scale", &export::scale_choice(window_clone.scale_factor()));
+
+        let chart = chart_clone.clone();
+        let stats_store = stats_store_clone.clone();
+        let toast_overlay = toast_overlay_clone.clone();
+        dialog.connect_response(move |dialog, response| {
+            if response == gtk4::ResponseType::Accept {
+                if let Some(path) = dialog.file().and_then(|file| file.path()) {
+                    let form = dialog
+                        .choice("form")
+                        .and_then(|name| export::StatsForm::from_name(&name))
+                        .unwrap_or(export::StatsForm::Table);
+                    let watermark = dialog.choice("watermark").as_deref() == Some("true");
+                    let scale = export_scale(dialog);
+                    let message = match export::export_stats(
+                        &chart,
+                        &stats_store,
+                        form,
+                        &path,
+                        watermark,
+                        scale,
+                    ) {
+                        Ok(()) => "Statistics image exported".to_string(),
+                        Err(e) => format!("Failed to export statistics image: {}", e),
+                    };
+                    toast_overlay.add_toast(Toast::new(&message));
+                }
+            }
+            dialog.close();
+        });
+
+        dialog.show();
+    });
+    app.add_action(&export_stats_action);
+
+    // Set up "open-session" action restoring a saved view
+    let window_clone = window.clone();
+    let chart_clone = chart.clone();
+    let config_clone = config.clone();
+    let stack_clone = stack.clone();
+    let toast_overlay_clone = toast_overlay.clone();
+    let app_clone = app.clone();
+    let search_entry_clone = search_entry.clone();
+    let query_entry_clone = query_entry.clone();
+
+    let open_session_action = gio::SimpleAction::new("open-session", None);
+    open_session_action.connect_activate(move |_, _| {
+        let dialog = gtk4::FileChooserDialog::new(
+            Some("Open Session"),
+            Some(&window_clone),
+            gtk4::FileChooserAction::Open,
+            &[
+                ("Cancel", gtk4::ResponseType::Cancel),
+                ("Open", gtk4::ResponseType::Accept),
+            ],
+        );
+
+        let filter = gtk4::FileFilter::new();
+        filter.set_name(Some("Analysis Sessions"));
+        filter.add_pattern(&format!("*.{}", session::EXTENSION));
+        dialog.add_filter(&filter);
+
+        let chart = chart_clone.clone();
+        let config = config_clone.clone();
+        let stack = stack_clone.clone();
+        let toast_overlay = toast_overlay_clone.clone();
+        let app = app_clone.clone();
+        let search_entry = search_entry_clone.clone();
+        let query_entry = query_entry_clone.clone();
+
+        dialog.connect_response(move |dialog, response| {
+            if response == gtk4::ResponseType::Accept {
+                if let Some(path) = dialog.file().and_then(|file| file.path()) {
+                    let entries = (&search_entry, &query_entry);
+                    if let Err(e) =
+                        open_session(&path, &app, &chart, &config, entries, &toast_overlay)
+                    {
+                        toast_overlay
+                            .add_toast(Toast::new(&format!("Failed to open session: {}", e)));
+                    } else {
+                        stack.set_visible_child_name("content");
+                    }
+                }
+            }
+            dialog.close();
+        });
+
+        dialog.show();
+    });
+    app.add_action(&open_session_action);
+
+    // Set up "aggregate" action toggling between merged and per-event trees
+    let chart_clone = chart.clone();
+    let aggregate_action = gio::SimpleAction::new_stateful(
+        "aggregate",
+        None,
+        &chart.tree_options().aggregate.to_variant(),
+    );
+    aggregate_action.connect_activate(move |action, _| {
+        let aggregate = !action
+            .state()
+            .and_then(|state| state.get::<bool>())
+            .unwrap_or(false);
+        action.set_state(&aggregate.to_variant());
+
+        let mut options = chart_clone.tree_options();
+        options.aggregate = aggregate;
+        chart_clone.set_tree_options(options);
+    });
+    app.add_action(&aggregate_action);
+
+    // Set up "group-by-process" action inserting a process level into the tree
+    let chart_clone = chart.clone();
+    let group_by_process_action = gio::SimpleAction::new_stateful(
+        "group-by-process",
+        None,
+        &chart.tree_options().group_by_process.to_variant(),
+    );
+    group_by_process_action.connect_activate(move |action, _| {
+        let group_by_process = !action
+            .state()
+            .and_then(|state| state.get::<bool>())
+            .unwrap_or(false);
+        action.set_state(&group_by_process.to_variant());
+
+        let mut options = chart_clone.tree_options();
+        options.group_by_process = group_by_process;
+        chart_clone.set_tree_options(options);
+    });
+    app.add_action(&group_by_process_action);
+
+    // Set up "collapse-contexts" action clustering identical contexts
+    let chart_clone = chart.clone();
+    let collapse_contexts_action = gio::SimpleAction::new_stateful(
+        "collapse-contexts",
+        None,
+        &chart.tree_options().collapse_contexts.to_variant(),
+    );
+    collapse_contexts_action.connect_activate(move |action, _| {
+        let collapse_contexts = !action
+            .state()
+            .and_then(|state| state.get::<bool>())
+            .unwrap_or(false);
+        action.set_state(&collapse_contexts.to_variant());
+
+        let mut options = chart_clone.tree_options();
+        options.collapse_contexts = collapse_contexts;
+        chart_clone.set_tree_options(options);
+    });
+    app.add_action(&collapse_contexts_action);
+
+    // Set up "show-codes" action appending raw codes to decoded names
+    let chart_clone = chart.clone();
+    let show_codes_action = gio::SimpleAction::new_stateful(
+        "show-codes",
+        None,
+        &chart.tree_options().show_codes.to_variant(),
+    );
+    show_codes_action.connect_activate(move |action, _| {
+        let show_codes = !action
+            .state()
+            .and_then(|state| state.get::<bool>())
+            .unwrap_or(false);
+        action.set_state(&show_codes.to_variant());
+
+        let mut options = chart_clone.tree_options();
+        options.show_codes = show_codes;
+        chart_clone.set_tree_options(options);
+    });
+    app.add_action(&show_codes_action);
+
+    // Set up "severity-colors" action coloring the chart by the worst findings
+    let chart_clone = chart.clone();
+    let severity_colors_action = gio::SimpleAction::new_stateful(
+        "severity-colors",
+        None,
+        &chart.severity_colors().to_variant(),
+    );
+    severity_colors_action.connect_activate(move |action, _| {
+        let enabled = !action
+            .state()
+            .and_then(|state| state.get::<bool>())
+            .unwrap_or(false);
+        action.set_state(&enabled.to_variant());
+        chart_clone.set_severity_colors(enabled);
+    });
+    app.add_action(&severity_colors_action);
+
+    // Set up "anonymize" action masking contexts, origins and hosts with pseudonyms
+    let chart_clone = chart.clone();
+    let config_clone = config.clone();
+    let anonymize_action = gio::SimpleAction::new_stateful("anonymize", None, &false.to_variant());
+    anonymize_action.connect_activate(move |action, _| {
+        let anonymize = !action
+            .state()
+            .and_then(|state| state.get::<bool>())
+            .unwrap_or(false);
+        action.set_state(&anonymize.to_variant());
+
+        if !anonymize {
+            chart_clone.set_anonymizer(None);
+            return;
+        }
+        // Keep the key, so identifiers get the same pseudonyms every time
+        let mut config = config_clone.borrow_mut();
+        let key = match &config.anonymization_key {
+            Some(key) => key.clone(),
+            None => {
+                let key = anonymize::Anonymizer::generate_key();
+                config.anonymization_key = Some(key.clone());
+                if let Err(e) = config.save() {
+                    eprintln!("Failed to save preferences: {}", e);
+                }
+                key
+            }
+        };
+        drop(config);
+        chart_clone.set_anonymizer(Some(anonymize::Anonymizer::new(&key)));
+    });
+    app.add_action(&anonymize_action);
+
+    // Set up "set-baseline" and "clear-baseline" actions comparing the stats
+    // of later captures against the current one
+    let chart_clone = chart.clone();
+    let toast_overlay_clone = toast_overlay.clone();
+    let set_baseline_action = gio::SimpleAction::new("set-baseline", None);
+    set_baseline_action.connect_activate(move |_, _| {
+        let Some(root) = chart_clone.root() else {
+            return;
+        };
+        chart_clone.set_baseline(Some(root));
+        let name = chart_clone
+            .source_path()
+            .and_then(|path| Some(path.file_name()?.to_string_lossy().into_owned()))
+            .unwrap_or_else(|| "the current capture".to_string());
+        toast_overlay_clone.add_toast(Toast::new(&format!("Comparing stats against {}", name)));
+    });
+    app.add_action(&set_baseline_action);
+
+    let chart_clone = chart.clone();
+    let clear_baseline_action = gio::SimpleAction::new("clear-baseline", None);
+    clear_baseline_action.connect_activate(move |_, _| {
+        chart_clone.set_baseline(None);
+    });
+    app.add_action(&clear_baseline_action);
+
+    // Set up "follow" action reloading the capture file whenever it changes
+    let live_follow: Rc<RefCell<Option<LiveFollow>>> = Rc::new(RefCell::new(None));
+    let chart_clone = chart.clone();
+    let toast_overlay_clone = toast_overlay.clone();
+    let live_follow_clone = live_follow.clone();
+    let pause_button_clone = pause_button.clone();
+    let clear_button_clone = clear_button.clone();
+    let live_counters_label_clone = live_counters_label.clone();
+    let config_clone = config.clone();
+    let app_weak = app.downgrade();
+    let follow_action = gio::SimpleAction::new_stateful("follow", None, &false.to_variant());
+    follow_action.connect_activate(move |action, _| {
+        let follow = !action
+            .state()
+            .and_then(|state| state.get::<bool>())
+            .unwrap_or(false);
+
+        if !follow {
+            action.set_state(&false.to_variant());
+            live_follow_clone.borrow_mut().take();
+            pause_button_clone.set_active(false);
+            pause_button_clone.set_visible(false);
+            clear_button_clone.set_visible(false);
+            live_counters_label_clone.set_visible(false);
+            return;
+        }
+
+        let Some(path) = chart_clone.source_path() else {
+            toast_overlay_clone.add_toast(Toast::new(
+                "Only captures opened from a file can be followed",
+            ));
+            return;
+        };
+
+        let pause_button = pause_button_clone.clone();
+        let counters_label = live_counters_label_clone.clone();
+        let app_weak = app_weak.clone();
+        let status = move |status: live::Status| match status {
+            live::Status::Buffered(0) => {
+                pause_button.set_tooltip_text(Some("Pause Live Updates"));
+            }
+            live::Status::Buffered(n) => {
+                pause_button
+                    .set_tooltip_text(Some(&format!("Resume Live Updates ({} buffered)", n)));
+            }
+            live::Status::Counters { retained, counters } => {
+                counters_label.set_text(&format!(
+                    "{} retained, {} evicted",
+                    retained, counters.evicted
+                ));
+                // Totals since following began, which include the evicted events
+                let mut by_name: Vec<_> = counters.by_name.iter().collect();
+                by_name.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+                let mut tooltip = format!("{} events received", counters.ingested);
+                for (name, count) in by_name.iter().take(10) {
+                    tooltip.push_str(&format!("\n{}: {}", name, count));
+                }
+                counters_label.set_tooltip_text(Some(&tooltip));
+            }
+            live::Status::Stopped => {
+                // Stop following once the monitor is done delivering this change
+                let app_weak = app_weak.clone();
+                glib::idle_add_local_once(move || {
+                    if let Some(app) = app_weak.upgrade() {
+                        app.activate_action("follow", None);
+                    }
+                });
+            }
+        };
+
+        match LiveFollow::new(&path, chart_clone.clone(), config_clone.clone(), status) {
+            Ok(follow) => {
+                *live_follow_clone.borrow_mut() = Some(follow);
+                action.set_state(&true.to_variant());
+                pause_button_clone.set_visible(true);
+                clear_button_clone.set_visible(true);
+                live_counters_label_clone.set_visible(true);
+            }
+            Err(e) => {
+                toast_overlay_clone.add_toast(Toast::new(&format!("Failed to follow file: {}", e)));
+            }
+        }
+    });
+    app.add_action(&follow_action);
+
+    let live_follow_clone = live_follow.clone();
+    pause_button.connect_toggled(move |button| {
+        if let Some(follow) = live_follow_clone.borrow().as_ref() {
+            if button.is_active() {
+                follow.pause();
+            } else {
+                follow.resume();
+            }
+        }
+    });
+
+    let live_follow_clone = live_follow.clone();
+    clear_button.connect_clicked(move |_| {
+        if let Some(follow) = live_follow_clone.borrow().as_ref() {
+            follow.clear();
+        }
+    });
+
+    // Set up "value-mode" action choosing how node values are counted
+    let chart_clone = chart.clone();
+    let value_mode_action = gio::SimpleAction::new_stateful(
+        "value-mode",
+        Some(glib::VariantTy::STRING),
+        &chart.tree_options().value_mode.name().to_variant(),
+    );
+    value_mode_action.connect_activate(move |action, parameter| {
+        let Some(name) = parameter.and_then(|p| p.get::<String>()) else {
+            return;
+        };
+        if let Some(value_mode) = ValueMode::from_name(&name) {
+            action.set_state(&name.to_variant());
+            let mut options = chart_clone.tree_options();
+            options.value_mode = value_mode;
+            chart_clone.set_tree_options(options);
+        }
+    });
+    app.add_action(&value_mode_action);
+
+    // Set up "sort" action choosing the order of sibling segments
+    let chart_clone = chart.clone();
+    let sort_action = gio::SimpleAction::new_stateful(
+        "sort",
+        Some(glib::VariantTy::STRING),
+        &chart.tree_options().sort_order.name().to_variant(),
+    );
+    sort_action.connect_activate(move |action, parameter| {
+        let Some(name) = parameter.and_then(|p| p.get::<String>()) else {
+            return;
+        };
+        if let Some(sort_order) = SortOrder::from_name(&name) {
+            action.set_state(&name.to_variant());
+            let mut options = chart_clone.tree_options();
+            options.sort_order = sort_order;
+            chart_clone.set_tree_options(options);
+        }
+    });
+    app.add_action(&sort_action);
+
+    // Set up "weighting" action choosing what the arc angles represent
+    let chart_clone = chart.clone();
+    let weighting_action = gio::SimpleAction::new_stateful(
+        "weighting",
+        Some(glib::VariantTy::STRING),
+        &Weighting::default().name().to_variant(),
+    );
+    weighting_action.connect_activate(move |action, parameter| {
+        let Some(name) = parameter.and_then(|p| p.get::<String>()) else {
+            return;
+        };
+        if let Some(weighting) = Weighting::from_name(&name) {
+            action.set_state(&name.to_variant());
+            chart_clone.set_weighting(weighting);
+        }
+    });
+    app.add_action(&weighting_action);
+
+    // Set up "layout" action choosing how the first ring is arranged
+    let chart_clone = chart.clone();
+    let layout_action = gio::SimpleAction::new_stateful(
+        "layout",
+        Some(glib::VariantTy::STRING),
+        &chart.layout().name().to_variant(),
+    );
+    layout_action.connect_activate(move |action, parameter| {
+        let Some(name) = parameter.and_then(|p| p.get::<String>()) else {
+            return;
+        };
+        if let Some(layout) = Layout::from_name(&name) {
+            action.set_state(&name.to_variant());
+            chart_clone.set_layout(layout);
+        }
+    });
+    app.add_action(&layout_action);
+
+    // Set up "percent-base" action choosing what percentages are relative to
+    let chart_clone = chart.clone();
+    let percent_base_action = gio::SimpleAction::new_stateful(
+        "percent-base",
+        Some(glib::VariantTy::STRING),
+        &chart.percent_base().name().to_variant(),
+    );
+    percent_base_action.connect_activate(move |action, parameter| {
+        let Some(name) = parameter.and_then(|p| p.get::<String>()) else {
+            return;
+        };
+        if let Some(base) = PercentBase::from_name(&name) {
+            action.set_state(&name.to_variant());
+            chart_clone.set_percent_base(base);
+        }
+    });
+    app.add_action(&percent_base_action);
+
+    // Keep the bookmarks menu in sync with the loaded capture
+    let chart_clone = chart.clone();
+    let config_clone = config.clone();
+    let bookmark_items_clone = bookmark_items.clone();
+    chart.connect_view_changed(move |_| {
+        update_bookmarks_menu(&bookmark_items_clone, &chart_clone, &config_clone.borrow());
+    });
+
+    // Set up "add-bookmark" action saving the current zoom location
+    let chart_clone = chart.clone();
+    let config_clone = config.clone();
+    let toast_overlay_clone = toast_overlay.clone();
+    let bookmark_items_clone = bookmark_items.clone();
+    let add_bookmark_action = gio::SimpleAction::new("add-bookmark", None);
+    add_bookmark_action.connect_activate(move |_, _| {
+        let Some(hash) = chart_clone.source_hash() else {
+            return;
+        };
+        let path = chart_clone.zoom_path();
+        if path.is_empty() {
+            toast_overlay_clone.add_toast(Toast::new("Zoom into a segment to bookmark it"));
+            return;
+        }
+
+        let bookmark = config::Bookmark { path };
+        let label = bookmark.label();
+        {
+            let mut config = config_clone.borrow_mut();
+            let bookmarks = config.bookmarks.entry(hash).or_default();
+            if !bookmarks.contains(&bookmark) {
+                bookmarks.push(bookmark);
+            }
+            if let Err(e) = config.save() {
+                eprintln!("Failed to save bookmarks: {}", e);
+            }
+        }
+        update_bookmarks_menu(&bookmark_items_clone, &chart_clone, &config_clone.borrow());
+        toast_overlay_clone.add_toast(Toast::new(&format!("Bookmarked {}", label)));
+    });
+    app.add_action(&add_bookmark_action);
+    app.set_accels_for_action("app.add-bookmark", &["<Ctrl>d"]);
+
+    // Set up "open-bookmark" action, taking the index of the bookmark
+    let chart_clone = chart.clone();
+    let config_clone = config.clone();
+    let toast_overlay_clone = toast_overlay.clone();
+    let open_bookmark_action =
+        gio::SimpleAction::new("open-bookmark", Some(glib::VariantTy::UINT32));
+    open_bookmark_action.connect_activate(move |_, parameter| {
+        let Some(index) = parameter.and_then(|p| p.get::<u32>()) else {
+            return;
+        };
+        let Some(hash) = chart_clone.source_hash() else {
+            return;
+        };
+        let bookmark = config_clone
+            .borrow()
+            .bookmarks
+            .get(&hash)
+            .and_then(|bookmarks| bookmarks.get(index as usize).cloned());
+        if let Some(bookmark) = bookmark {
+            // The location may be gone when the tree is built with other options
+            if !chart_clone.zoom_to(&bookmark.path) {
+                toast_overlay_clone.add_toast(Toast::new(&format!(
+                    "{} is not in the current view",
+                    bookmark.label()
+                )));
+            }
+        }
+    });
+    app.add_action(&open_bookmark_action);
+
+    // Set up "clear-bookmarks" action forgetting the bookmarks of the loaded capture
+    let chart_clone = chart.clone();
+    let config_clone = config.clone();
+    let clear_bookmarks_action = gio::SimpleAction::new("clear-bookmarks", None);
+    clear_bookmarks_action.connect_activate(move |_, _| {
+        let Some(hash) = chart_clone.source_hash() else {
+            return;
+        };
+        {
+            let mut config = config_clone.borrow_mut();
+            config.bookmarks.remove(&hash);
+            if let Err(e) = config.save() {
+                eprintln!("Failed to save bookmarks: {}", e);
+            }
+        }
+        update_bookmarks_menu(&bookmark_items, &chart_clone, &config_clone.borrow());
+    });
+    app.add_action(&clear_bookmarks_action);
+
+    // Set up "undo" and "redo" actions stepping through the changes of filters,
+    // exclusions and zoom
+    let history = History::new(chart.clone());
+    let undo_action = gio::SimpleAction::new("undo", None);
+    let redo_action = gio::SimpleAction::new("redo", None);
+    undo_action.set_enabled(false);
+    redo_action.set_enabled(false);
+    let undo_action_clone = undo_action.clone();
+    let redo_action_clone = redo_action.clone();
+    history.connect_changed(move |can_undo, can_redo| {
+        undo_action_clone.set_enabled(can_undo);
+        redo_action_clone.set_enabled(can_redo);
+    });
+
+    // Keep the preferences and the search entry in line with a restored state
+    let restore = {
+        let config = config.clone();
+        let search_entry = search_entry.clone();
+        let query_entry = query_entry.clone();
+        move |state: ViewState| {
+            show_filter_texts(&search_entry, &query_entry, &state);
+            let mut config = config.borrow_mut();
+            if config.exclude_rules != state.exclude_rules {
+                config.exclude_rules = state.exclude_rules;
+                if let Err(e) = config.save() {
+                    eprintln!("Failed to save preferences: {}", e);
+                }
+            }
+        }
+    };
+    let restore = Rc::new(restore);
+
+    let history_clone = history.clone();
+    let restore_clone = restore.clone();
+    undo_action.connect_activate(move |_, _| {
+        if let Some(state) = history_clone.undo() {
+            restore_clone(state);
+        }
+    });
+    app.add_action(&undo_action);
+    app.set_accels_for_action("app.undo", &["<Ctrl>z"]);
+
+    redo_action.connect_activate(move |_, _| {
+        if let Some(state) = history.redo() {
+            restore(state);
+        }
+    });
+    app.add_action(&redo_action);
+    app.set_accels_for_action("app.redo", &["<Ctrl><Shift>z"]);
+
+    // Set up "preferences" action
+    let window_clone = window.clone();
+    let chart_clone = chart.clone();
+    let config_clone = config.clone();
+    let preferences_action = gio::SimpleAction::new("preferences", None);
+    preferences_action.connect_activate(move |_, _| {
+        preferences::show_preferences(&window_clone, chart_clone.clone(), config_clone.clone());
+    });
+    app.add_action(&preferences_action);
+    app.set_accels_for_action("app.preferences", &["<Ctrl>comma"]);
+
+    // Set up "about" action
+    let window_clone = window.clone();
+    let about_action = gio::SimpleAction::new("about", None);
+    about_action.connect_activate(move |_, _| {
+        let about = AboutWindow::builder()
+            .application_name("Crypto Usage Analyzer")
+            .application_icon("org.gnome.CryptoUsageAnalyzer")
+            .developer_name("Crypto Auditing Project")
+            .version("0.1.0")
+            .comments("Visualize cryptographic operations with interactive sunburst charts")
+            .website("https://github.com/simo5/crypto-auditing")
+            .issue_url("https://github.com/simo5/crypto-auditing/issues")
+            .license_type(gtk4::License::Gpl30)
+            .build();
+
+        about.set_transient_for(Some(&window_clone));
+        about.present();
+    });
+    app.add_action(&about_action);
+
+    // Connect empty state button to open action
+    let app_clone = app.clone();
+    empty_button.connect_clicked(move |_| {
+        app_clone.activate_action("open", None);
+    });
+
+    // Try to load default file if it exists
+    let default_path = "audit.json";
+    if std::path::Path::new(default_path).exists() && load_and_display(default_path, &chart).is_ok()
+    {
+        stack.set_visible_child_name("content");
+    }
+
+    window.present();
+}
+
+// Load the capture of a session and restore its view. Tree options are applied
+// through their actions so the menu reflects them.
+fn open_session(
+    path: &std::path::Path,
+    app: &Application,
+    chart: &SunburstChart,
+    config: &RefCell<Config>,
+    (search_entry, query_entry): (&gtk4::SearchEntry, &gtk4::Entry),
+    toast_overlay: &ToastOverlay,
+) -> Result<()> {
+    let session = Session::load(path)?;
+    load_and_display(&session.resolve_source(path).to_string_lossy(), chart)?;
+
+    let hash = chart.source_hash();
+    if session.source_hash.is_some() && session.source_hash != hash {
+        toast_overlay.add_toast(Toast::new(
+            "The capture has changed since the session was saved",
+        ));
+    }
+
+    // Make the session's bookmarks and annotations available for the capture
+    if let Some(hash) = hash {
+        let mut config = config.borrow_mut();
+        let bookmarks = config.bookmarks.entry(hash.clone()).or_default();
+        for bookmark in &session.bookmarks {
+            if !bookmarks.contains(bookmark) {
+                bookmarks.push(bookmark.clone());
+            }
+        }
+        for annotation in &session.annotations {
+            config.set_annotation(&hash, annotation.clone());
+        }
+        if let Err(e) = config.save() {
+            eprintln!("Failed to save bookmarks: {}", e);
+        }
+    }
+
+    if chart.tree_options().aggregate != session.aggregate {
+        app.activate_action("aggregate", None);
+    }
+    if chart.tree_options().group_by_process != session.group_by_process {
+        app.activate_action("group-by-process", None);
+    }
+    if chart.tree_options().collapse_contexts != session.collapse_contexts {
+        app.activate_action("collapse-contexts", None);
+    }
+    if chart.tree_options().show_codes != session.show_codes {
+        app.activate_action("show-codes", None);
+    }
+    app.activate_action("value-mode", Some(&session.value_mode.to_variant()));
+    app.activate_action("sort", Some(&session.sort_order.to_variant()));
+    app.activate_action("weighting", Some(&session.weighting.to_variant()));
+    app.activate_action("percent-base", Some(&session.percent_base.to_variant()));
+    app.activate_action("layout", Some(&session.layout.to_variant()));
+
+    // The boot session first, as showing one resets the time window
+    chart.set_boot_session(session.boot_session.clone());
+    let state = session.view_state(chart);
+    chart.restore_view_state(&state);
+    show_filter_texts(search_entry, query_entry, &state);
+    // Exclusion rules are preferences, which show those in effect
+    {
+        let mut config = config.borrow_mut();
+        if config.exclude_rules != state.exclude_rules {
+            config.exclude_rules = state.exclude_rules;
+            if let Err(e) = config.save() {
+                eprintln!("Failed to save preferences: {}", e);
+            }
+        }
+    }
+
+    if chart.zoom_path() != session.zoom_path {
+        chart.zoom_to(&[]);
+        toast_overlay.add_toast(Toast::new("The saved zoom location is not in the capture"));
+    }
+
+    Ok(())
+}
+
+// Show the search and filter expression of a restored view in their entries
+fn show_filter_texts(
+    search_entry: &gtk4::SearchEntry,
+    query_entry: &gtk4::Entry,
+    state: &ViewState,
+) {
+    if search_entry.text().trim() != state.search {
+        search_entry.set_text(&state.search);
+    }
+    let query = state.filter_query.as_deref().unwrap_or_default();
+    if query_entry.text().trim() != query {
+        query_entry.set_text(query);
+        query_entry.remove_css_class("error");
+    }
+}
+
+// Names from the visible root's children down to the node of a tree row
+fn tree_row_path(row: &TreeListRow) -> Vec<String> {
+    let mut path = Vec::new();
+    let mut current_row = Some(row.clone());
+
+    while let Some(row) = current_row {
+        if let Some(node) = row.item().and_downcast::<TreeNodeObject>() {
+            path.insert(0, node.name());
+        }
+        current_row = row.parent();
+    }
+
+    path
+}
+
+// List the bookmarks saved for the loaded capture in the menu
+fn update_bookmarks_menu(menu: &gio::Menu, chart: &SunburstChart, config: &Config) {
+    menu.remove_all();
+    let bookmarks = chart
+        .source_hash()
+        .and_then(|hash| config.bookmarks.get(&hash));
+    for (index, bookmark) in bookmarks.into_iter().flatten().enumerate() {
+        let item = gio::MenuItem::new(Some(&bookmark.label()), None);
+        item.set_action_and_target_value(
+            Some("app.open-bookmark"),
+            Some(&(index as u32).to_variant()),
+        );
+        menu.append_item(&item);
+    }
+}
+
+// Create a titled sidebar section with a table of StatsObjects
+fn create_stats_section(
+    chart: &SunburstChart,
+    title: &str,
+    key_title: &str,
+) -> (gtk4::Box, gio::ListStore) {
+    let section = gtk4::Box::new(Orientation::Vertical, 6);
+    section.set_margin_start(12);
+    section.set_margin_end(12);
+    section.set_margin_top(12);
+    section.set_margin_bottom(12);
+    section.set_visible(false);
+
+    let title_label = gtk4::Label::new(Some(title));
+    title_label.set_halign(gtk4::Align::Start);
+    title_label.add_css_class("title-4");
+    section.append(&title_label);
+
+    let store = gio::ListStore::new::<StatsObject>();
+    let view = ColumnView::new(Some(SingleSelection::new(Some(store.clone()))));
+    view.add_css_class("data-table");
+    view.append_column(&create_term_column(key_title, |obj: &StatsObject| {
+        obj.algorithm()
+    }));
+    view.append_column(&create_text_column(
+        "Count",
+        gtk4::Align::End,
+        false,
+        |obj: &StatsObject| obj.count(),
+    ));
+    let percent_column = create_text_column(
+        "Percentage",
+        gtk4::Align::End,
+        false,
+        |obj: &StatsObject| obj.percentage(),
+    );
+    view.append_column(&percent_column);
+    let delta_column = create_delta_column();
+    view.append_column(&delta_column);
+    chart.add_stats_columns(percent_column, delta_column);
+    section.append(&view);
+
+    (section, store)
+}
+
+// Create a hidden column showing the change of each StatsObject against the baseline
+fn create_delta_column() -> ColumnViewColumn {
+    let factory = SignalListItemFactory::new();
+    factory.connect_setup(|_, list_item| {
+        let label = Label::new(None);
+        label.set_halign(gtk4::Align::End);
+        label.set_margin_start(4);
+        label.set_margin_end(4);
+        label.add_css_class("numeric");
+        list_item.set_child(Some(&label));
+    });
+    factory.connect_bind(|_, list_item| {
+        let list_item = list_item.downcast_ref::<ListItem>().unwrap();
+        let obj = list_item.item().and_downcast::<StatsObject>().unwrap();
+        let label = list_item.child().and_downcast::<Label>().unwrap();
+        label.set_text(&obj.delta());
+        // Rows are recycled, so drop the class of the previous row first
+        label.remove_css_class("error");
+        label.remove_css_class("success");
+        let class = obj.delta_class();
+        if !class.is_empty() {
+            label.add_css_class(&class);
+        }
+    });
+
+    let column = ColumnViewColumn::new(Some("Δ"), Some(factory));
+    column.set_visible(false);
+    column
+}
+
+// Create a column displaying a text value of each row object
+fn create_text_column<T: IsA<glib::Object>>(
+    title: &str,
+    halign: gtk4::Align,
+    expand: bool,
+    text: impl Fn(&T) -> String + 'static,
+) -> ColumnViewColumn {
+    let factory = SignalListItemFactory::new();
+    factory.connect_setup(move |_, list_item| {
+        let label = Label::new(None);
+        label.set_halign(halign);
+        label.set_margin_start(4);
+        label.set_margin_end(4);
+        list_item.set_child(Some(&label));
+    });
+    factory.connect_bind(move |_, list_item| {
+        let list_item = list_item.downcast_ref::<ListItem>().unwrap();
+        let obj = list_item.item().and_downcast::<T>().unwrap();
+        let label = list_item.child().and_downcast::<Label>().unwrap();
+        label.set_text(&text(&obj));
+    });
+
+    let column = ColumnViewColumn::new(Some(title), Some(factory));
+    column.set_expand(expand);
+    column
+}
+
+// Expanding column of algorithm names, explained from the glossary on hover
+fn create_term_column<T: IsA<glib::Object>>(
+    title: &str,
+    text: impl Fn(&T) -> String + 'static,
+) -> ColumnViewColumn {
+    let factory = SignalListItemFactory::new();
+    factory.connect_setup(move |_, list_item| {
+        let label = Label::new(None);
+        label.set_halign(gtk4::Align::Start);
+        label.set_margin_start(4);
+        label.set_margin_end(4);
+        list_item.set_child(Some(&label));
+    });
+    factory.connect_bind(move |_, list_item| {
+        let list_item = list_item.downcast_ref::<ListItem>().unwrap();
+        let obj = list_item.item().and_downcast::<T>().unwrap();
+        let label = list_item.child().and_downcast::<Label>().unwrap();
+        let text = text(&obj);
+        label.set_tooltip_text(glossary::glossary().tooltip(&text).as_deref());
+        label.set_text(&text);
+    });
+
+    let column = ColumnViewColumn::new(Some(title), Some(factory));
+    column.set_expand(true);
+    column
+}
+
+// Scale chosen in an image export dialog, 1× if none
+fn export_scale(dialog: &gtk4::FileChooserDialog) -> f64 {
+    dialog
+        .choice("scale")
+        .and_then(|scale| scale.parse().ok())
+        .unwrap_or(1.0)
+}
+
+fn load_and_display(path: &str, chart: &SunburstChart) -> Result<()> {
+    let content = fs::read_to_string(path)?;
+    load_from_str(&content, chart)?;
+    chart.set_source_path(fs::canonicalize(path).ok());
+    Ok(())
+}
+
+fn load_from_str(content: &str, chart: &SunburstChart) -> Result<()> {
+    let start = Instant::now();
+    let capture = Capture::parse(content)?;
+    load_events(capture.events, content, Some(start.elapsed()), chart);
+    chart.set_metadata(capture.metadata);
+    Ok(())
+}
+
+// Show events read from content in another format, identified by the content,
+// which took `parse` to turn into events
+fn load_events(
+    events: Vec<AuditEvent>,
+    content: &str,
+    parse: Option<Duration>,
+    chart: &SunburstChart,
+) {
+    progressive::cancel();
+    chart.set_source_path(None);
+    chart.set_metadata(None);
+    chart.set_source_host(None);
+    chart.set_source_hash(
+        glib::compute_checksum_for_string(glib::ChecksumType::Sha256, content)
+            .map(|hash| hash.to_string()),
+    );
+    chart.set_parse_timing(Some(content.len()), parse);
+    chart.set_data(events);
 }
@@ -0,0 +1,124 @@
+use crate::data::{NodeKind, TreeNode};
+use crate::models::MatrixRowObject;
+use crate::palette::Family;
+use crate::sunburst::SunburstChart;
+use adw::prelude::*;
+use gtk4::{gio, ColumnView, ColumnViewColumn, CustomSorter, SingleSelection, SortListModel};
+use std::collections::HashMap;
+use std::rc::Rc;
+
+// Count the event nodes below a node by algorithm family, in the order of Family::ALL
+pub fn family_counts(node: &TreeNode) -> Vec<usize> {
+    let mut stats = HashMap::new();
+    node.extract_stats_by(
+        &|event| Some(Family::of(&event.name).label().to_string()),
+        &mut stats,
+    );
+    Family::ALL
+        .iter()
+        .map(|family| stats.get(family.label()).copied().unwrap_or(0))
+        .collect()
+}
+
+// Rows for the contexts, or processes when grouping by them, of the view
+fn matrix_rows(root: &TreeNode) -> Vec<MatrixRowObject> {
+    root.children
+        .iter()
+        .filter(|node| matches!(node.kind, NodeKind::Context | NodeKind::Process))
+        .map(|node| MatrixRowObject::new(&node.display_name(), family_counts(node)))
+        .collect()
+}
+
+fn count_column(
+    title: &str,
+    count: impl Fn(&MatrixRowObject) -> usize + Clone + 'static,
+) -> ColumnViewColumn {
+    let text_count = count.clone();
+    let column = crate::create_text_column(
+        title,
+        gtk4::Align::End,
+        false,
+        move |obj: &MatrixRowObject| text_count(obj).to_string(),
+    );
+    // Largest counts first when sorting a column the first time
+    column.set_sorter(Some(&CustomSorter::new(move |a, b| {
+        let count_of = |obj: &gtk4::glib::Object| obj.downcast_ref().map_or(0, &count);
+        count_of(b).cmp(&count_of(a)).into()
+    })));
+    column
+}
+
+// Grid with a row per context and a column per algorithm family, whose cells
+// count the context's events of the family. Columns of families absent from
+// the view are hidden. Returns the view along with its rows in the sorted order.
+pub fn create_matrix_view(chart: Rc<SunburstChart>) -> (gtk4::ScrolledWindow, SortListModel) {
+    let store = gio::ListStore::new::<MatrixRowObject>();
+    let view = ColumnView::new(None::<SingleSelection>);
+    view.add_css_class("data-table");
+
+    let context_column = crate::create_text_column(
+        "Context",
+        gtk4::Align::Start,
+        true,
+        |obj: &MatrixRowObject| obj.label(),
+    );
+    context_column.set_sorter(Some(&CustomSorter::new(|a, b| {
+        let label_of = |obj: &gtk4::glib::Object| {
+            obj.downcast_ref::<MatrixRowObject>()
+                .map(|row| row.label())
+                .unwrap_or_default()
+        };
+        label_of(a).cmp(&label_of(b)).into()
+    })));
+    view.append_column(&context_column);
+
+    let family_columns: Vec<ColumnViewColumn> = Family::ALL
+        .iter()
+        .enumerate()
+        .map(|(i, family)| {
+            let column = count_column(family.label(), move |obj| obj.count(i));
+            view.append_column(&column);
+            column
+        })
+        .collect();
+    view.append_column(&count_column("Total", |obj| obj.total()));
+
+    let sorted = SortListModel::new(Some(store.clone()), view.sorter());
+    view.set_model(Some(&SingleSelection::new(Some(sorted.clone()))));
+
+    let chart_clone = chart.clone();
+    chart.connect_view_changed(move |_| {
+        let rows = chart_clone
+            .root()
+            .map(|root| matrix_rows(&root))
+            .unwrap_or_default();
+        for (i, column) in family_columns.iter().enumerate() {
+            column.set_visible(rows.iter().any(|row| row.count(i) > 0));
+        }
+        store.splice(0, store.n_items(), &rows);
+    });
+
+    let scroll = gtk4::ScrolledWindow::new();
+    scroll.set_child(Some(&view));
+    (scroll, sorted)
+}
+
+// The matrix as CSV, in the order of the model, with a header row
+pub fn to_csv(model: &impl IsA<gio::ListModel>) -> String {
+    // Quote labels, doubling the quotes inside
+    let quote = |text: &str| format!("\"{}\"", text.replace('"', "\"\""));
+
+    let mut header = vec![quote("Context")];
+    header.extend(Family::ALL.iter().map(|family| quote(family.label())));
+    header.push(quote("Total"));
+    let mut csv = header.join(",") + "\n";
+
+    for obj in model.iter::<MatrixRowObject>().filter_map(Result::ok) {
+        let mut cells = vec![quote(&obj.label())];
+        cells.extend((0..Family::ALL.len()).map(|i| obj.count(i).to_string()));
+        cells.push(obj.total().to_string());
+        csv.push_str(&cells.join(","));
+        csv.push('\n');
+    }
+    csv
+}
@@ -0,0 +1,95 @@
+use crate::data::{AuditEvent, TreeNode};
+use adw::prelude::*;
+use adw::{AlertDialog, ResponseAppearance};
+use gtk4::glib;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::mem::size_of;
+use std::path::Path;
+use sysinfo::System;
+
+// Memory of a parsed capture per byte of its file, from the owned strings and
+// maps the JSON is parsed into, the tree built from them and its row objects
+const BYTES_PER_FILE_BYTE: u64 = 6;
+// Memory of a tree row object with its list store, beyond its strings
+const ROW_OBJECT_SIZE: usize = 256;
+
+fn value_size(value: &Value) -> usize {
+    size_of::<Value>()
+        + match value {
+            Value::String(s) => s.capacity(),
+            Value::Array(values) => values.iter().map(value_size).sum(),
+            Value::Object(map) => map.iter().map(|(k, v)| k.capacity() + value_size(v)).sum(),
+            _ => 0,
+        }
+}
+
+fn fields_size(fields: &HashMap<String, Value>) -> usize {
+    fields
+        .iter()
+        .map(|(k, v)| size_of::<String>() + k.capacity() + value_size(v))
+        .sum()
+}
+
+// Approximate memory held by parsed events, with their spans
+pub fn events_size(events: &[AuditEvent]) -> usize {
+    events
+        .iter()
+        .map(|event| {
+            size_of::<AuditEvent>()
+                + event.context.capacity()
+                + event.origin.capacity()
+                + fields_size(&event.events)
+                + events_size(&event.spans)
+        })
+        .sum()
+}
+
+// Approximate memory held by a tree and the row objects listing it
+pub fn tree_size(node: &TreeNode) -> usize {
+    size_of::<TreeNode>()
+        + ROW_OBJECT_SIZE
+        + 2 * node.name.capacity()
+        + node.label.as_ref().map_or(0, String::capacity)
+        + node.process.as_ref().map_or(0, String::capacity)
+        + fields_size(&node.fields)
+        + node.children.iter().map(tree_size).sum::<usize>()
+}
+
+pub fn format_size(bytes: usize) -> String {
+    glib::format_size(bytes as u64).to_string()
+}
+
+// Memory a file is estimated to take once loaded, if it's more than is available
+fn excess_estimate(path: &Path) -> Option<(u64, u64)> {
+    let estimate = fs::metadata(path).ok()?.len() * BYTES_PER_FILE_BYTE;
+    let mut system = System::new();
+    system.refresh_memory();
+    let available = system.available_memory();
+    (available > 0 && estimate > available).then_some((estimate, available))
+}
+
+// Ask before loading a file estimated to exceed the available memory, which
+// would otherwise leave the system swapping or the app killed. True if the
+// file should be loaded.
+pub async fn confirm_load(parent: &impl IsA<gtk4::Widget>, path: &Path) -> bool {
+    let Some((estimate, available)) = excess_estimate(path) else {
+        return true;
+    };
+
+    let dialog = AlertDialog::builder()
+        .heading("Load Large File?")
+        .body(format!(
+            "Loading this file is estimated to take {} of memory, but only {} is available.",
+            glib::format_size(estimate),
+            glib::format_size(available)
+        ))
+        .close_response("cancel")
+        .default_response("cancel")
+        .build();
+    dialog.add_response("cancel", "Cancel");
+    dialog.add_response("load", "Load Anyway");
+    dialog.set_response_appearance("load", ResponseAppearance::Destructive);
+    dialog.choose_future(parent).await == "load"
+}
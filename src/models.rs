@@ -13,10 +13,16 @@ mod imp_tree_node {
     pub struct TreeNodeObject {
         #[property(get, set)]
         pub(super) name: RefCell<String>,
+        // Text shown for the node, which may differ from the name identifying it
+        #[property(get, set)]
+        pub(super) label: RefCell<String>,
         #[property(get, set)]
         pub(super) count: RefCell<String>,
         #[property(get, set)]
         pub(super) value: RefCell<u32>,
+        // Severity name, empty if the node wasn't classified
+        #[property(get, set)]
+        pub(super) severity: RefCell<String>,
         pub(super) children: RefCell<Option<gtk4::gio::ListStore>>,
     }
 
@@ -65,6 +71,12 @@ mod imp_stats {
         pub(super) count: RefCell<String>,
         #[property(get, set)]
         pub(super) percentage: RefCell<String>,
+        // Change against the baseline capture, empty without a baseline
+        #[property(get, set)]
+        pub(super) delta: RefCell<String>,
+        // CSS class coloring the change, e.g. "error" for more use of a weak algorithm
+        #[property(get, set)]
+        pub(super) delta_class: RefCell<String>,
     }
 
     #[glib::object_subclass]
@@ -90,3 +102,85 @@ impl StatsObject {
             .build()
     }
 }
+
+// UnknownCodeObject - GObject wrapper for an unrecognized codepoint
+mod imp_unknown_code {
+    use super::*;
+
+    #[derive(Default, glib::Properties)]
+    #[properties(wrapper_type = super::UnknownCodeObject)]
+    pub struct UnknownCodeObject {
+        #[property(get, set)]
+        pub(super) field: RefCell<String>,
+        #[property(get, set)]
+        pub(super) code: RefCell<String>,
+        #[property(get, set)]
+        pub(super) count: RefCell<String>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for UnknownCodeObject {
+        const NAME: &'static str = "UnknownCodeObject";
+        type Type = super::UnknownCodeObject;
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for UnknownCodeObject {}
+}
+
+glib::wrapper! {
+    pub struct UnknownCodeObject(ObjectSubclass<imp_unknown_code::UnknownCodeObject>);
+}
+
+impl UnknownCodeObject {
+    pub fn new(field: &str, code: &str, count: &str) -> Self {
+        Object::builder()
+            .property("field", field)
+            .property("code", code)
+            .property("count", count)
+            .build()
+    }
+}
+
+// MatrixRowObject - GObject wrapper for a row of the usage matrix or another table of counts
+mod imp_matrix_row {
+    use super::*;
+
+    #[derive(Default, glib::Properties)]
+    #[properties(wrapper_type = super::MatrixRowObject)]
+    pub struct MatrixRowObject {
+        #[property(get, set)]
+        pub(super) label: RefCell<String>,
+        // Count of each column, e.g. of each algorithm family in the order of Family::ALL
+        pub(super) counts: RefCell<Vec<usize>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MatrixRowObject {
+        const NAME: &'static str = "MatrixRowObject";
+        type Type = super::MatrixRowObject;
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for MatrixRowObject {}
+}
+
+glib::wrapper! {
+    pub struct MatrixRowObject(ObjectSubclass<imp_matrix_row::MatrixRowObject>);
+}
+
+impl MatrixRowObject {
+    pub fn new(label: &str, counts: Vec<usize>) -> Self {
+        let obj: Self = Object::builder().property("label", label).build();
+        obj.imp().counts.replace(counts);
+        obj
+    }
+
+    pub fn count(&self, column: usize) -> usize {
+        self.imp().counts.borrow().get(column).copied().unwrap_or(0)
+    }
+
+    pub fn total(&self) -> usize {
+        self.imp().counts.borrow().iter().sum()
+    }
+}
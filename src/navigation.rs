@@ -0,0 +1,112 @@
+use crate::sunburst::SunburstChart;
+use adw::prelude::*;
+use adw::{HeaderBar, NavigationPage, NavigationView, ToolbarView};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+// A drill-down page, with the zoom path it stands for and the box the chart
+// is moved into while it is shown
+#[derive(Clone)]
+struct ZoomPage {
+    path: Vec<String>,
+    page: NavigationPage,
+    holder: gtk4::Box,
+}
+
+impl ZoomPage {
+    fn new(title: &str, path: Vec<String>) -> Self {
+        let holder = gtk4::Box::new(gtk4::Orientation::Vertical, 0);
+        let header_bar = HeaderBar::new();
+        header_bar.set_show_start_title_buttons(false);
+        header_bar.set_show_end_title_buttons(false);
+        header_bar.add_css_class("flat");
+
+        let toolbar_view = ToolbarView::new();
+        toolbar_view.add_top_bar(&header_bar);
+        toolbar_view.set_content(Some(&holder));
+        let page = NavigationPage::builder()
+            .title(title)
+            .child(&toolbar_view)
+            .build();
+        Self { path, page, holder }
+    }
+
+    // There is a single chart widget, so it follows the page being shown
+    fn show_chart(&self, chart: &SunburstChart) {
+        let widget = chart.widget();
+        let parent = widget.parent();
+        if parent.as_ref() == Some(self.holder.upcast_ref()) {
+            return;
+        }
+        if let Some(parent) = parent.and_downcast::<gtk4::Box>() {
+            parent.remove(widget);
+        }
+        self.holder.append(widget);
+    }
+}
+
+// Drill-down of the chart's zoom levels: zooming in pushes a page titled after
+// the zoomed node, and going back with the back button, Escape or a swipe
+// zooms out to the level below it. Zooming straight to an earlier level, e.g.
+// by clicking the center, pops the pages above it.
+pub fn create_zoom_navigation(chart: Rc<SunburstChart>, root_title: &str) -> NavigationView {
+    let view = NavigationView::new();
+    view.set_vexpand(true);
+
+    let root = ZoomPage::new(root_title, Vec::new());
+    root.show_chart(&chart);
+    view.push(&root.page);
+    let pages = Rc::new(RefCell::new(vec![root]));
+
+    let view_clone = view.clone();
+    let pages_clone = pages.clone();
+    let chart_clone = chart.clone();
+    chart.connect_view_changed(move |node| {
+        let path = chart_clone.zoom_path();
+        let existing = pages_clone
+            .borrow()
+            .iter()
+            .position(|page| page.path == path);
+        match existing {
+            Some(index) => {
+                // Forget the pages above before popping them, so the popped
+                // handler leaves the zoom as it is
+                let (page, popped) = {
+                    let mut pages = pages_clone.borrow_mut();
+                    let popped = pages.drain(index + 1..).count();
+                    (pages[index].clone(), popped)
+                };
+                if popped > 0 {
+                    page.show_chart(&chart_clone);
+                    view_clone.pop_to_page(&page.page);
+                }
+            }
+            None => {
+                let page = ZoomPage::new(&node.display_name(), path);
+                page.show_chart(&chart_clone);
+                pages_clone.borrow_mut().push(page.clone());
+                view_clone.push(&page.page);
+            }
+        }
+    });
+
+    view.connect_popped(move |view, popped| {
+        pages.borrow_mut().retain(|page| &page.page != popped);
+        let Some(visible) = view.visible_page() else {
+            return;
+        };
+        let page = pages
+            .borrow()
+            .iter()
+            .find(|page| page.page == visible)
+            .cloned();
+        if let Some(page) = page {
+            page.show_chart(&chart);
+            if chart.zoom_path() != page.path {
+                chart.zoom_to(&page.path);
+            }
+        }
+    });
+
+    view
+}
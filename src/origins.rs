@@ -0,0 +1,81 @@
+use crate::filters;
+use crate::sunburst::SunburstChart;
+use adw::prelude::*;
+use gtk4::{FlowBox, ToggleButton};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+// Row of toggleable chips, one per origin (library) of the capture. Turning a
+// chip off leaves that origin's events out of the chart, tree and stats.
+// Shown when the capture has events of more than one origin.
+pub fn create_origin_chips(chart: Rc<SunburstChart>) -> FlowBox {
+    let flow_box = FlowBox::new();
+    flow_box.set_selection_mode(gtk4::SelectionMode::None);
+    flow_box.set_max_children_per_line(32);
+    flow_box.set_column_spacing(6);
+    flow_box.set_row_spacing(6);
+    flow_box.set_margin_start(12);
+    flow_box.set_margin_end(12);
+    flow_box.set_margin_top(6);
+    flow_box.set_margin_bottom(6);
+    flow_box.set_visible(false);
+
+    // Chips with the origins they were made for, so they're only rebuilt for new ones
+    let chips: Rc<RefCell<Vec<(String, ToggleButton)>>> = Rc::new(RefCell::new(Vec::new()));
+
+    let flow_box_clone = flow_box.clone();
+    let chart_clone = chart.clone();
+    chart.connect_view_changed(move |_| {
+        let origins = filters::origins(&chart_clone.events());
+        let hidden = chart_clone.hidden_origins();
+        let same_origins = chips
+            .borrow()
+            .iter()
+            .map(|(origin, _)| origin)
+            .eq(origins.iter());
+        if same_origins {
+            // Follow the filters being cleared. The chips' handlers leave the
+            // hidden origins as they are, so this doesn't rebuild the view.
+            for (origin, chip) in chips.borrow().iter() {
+                chip.set_active(!hidden.contains(origin));
+            }
+            return;
+        }
+
+        while let Some(child) = flow_box_clone.first_child() {
+            flow_box_clone.remove(&child);
+        }
+        let mut new_chips = Vec::new();
+        for origin in origins {
+            let label = if origin.is_empty() {
+                "(no origin)"
+            } else {
+                origin.as_str()
+            };
+            let chip = ToggleButton::with_label(label);
+            chip.add_css_class("pill");
+            chip.set_active(!hidden.contains(&origin));
+            chip.set_tooltip_text(Some("Show or hide the events of this origin"));
+
+            let chart = chart_clone.clone();
+            let chip_origin = origin.clone();
+            chip.connect_toggled(move |chip| {
+                let mut hidden = chart.hidden_origins();
+                let changed = if chip.is_active() {
+                    hidden.remove(&chip_origin)
+                } else {
+                    hidden.insert(chip_origin.clone())
+                };
+                if changed {
+                    chart.set_hidden_origins(hidden);
+                }
+            });
+            flow_box_clone.insert(&chip, -1);
+            new_chips.push((origin, chip));
+        }
+        flow_box_clone.set_visible(new_chips.len() > 1);
+        *chips.borrow_mut() = new_chips;
+    });
+
+    flow_box
+}
@@ -0,0 +1,115 @@
+use crate::data::AuditEvent;
+use crate::filters;
+use crate::sunburst::SunburstChart;
+use adw::prelude::*;
+use adw::ActionRow;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+// Operation types beyond these have the lowest error rates, left out of the list
+const MAX_OPERATIONS: usize = 10;
+
+// Operations of a type recording their outcome, and how many of them failed
+#[derive(Default, Clone, Copy)]
+struct Outcomes {
+    operations: usize,
+    failed: usize,
+}
+
+impl Outcomes {
+    fn rate(&self) -> f64 {
+        self.failed as f64 / self.operations as f64
+    }
+
+    fn summary(&self) -> String {
+        format!(
+            "{} of {} failed ({}%)",
+            self.failed,
+            self.operations,
+            (self.rate() * 100.0).round() as u32
+        )
+    }
+}
+
+fn count_outcomes(events: &[AuditEvent], outcomes: &mut HashMap<String, Outcomes>) {
+    for event in events {
+        if filters::has_outcome(event) {
+            let counts = outcomes.entry(event.name()).or_default();
+            counts.operations += 1;
+            if filters::is_failure(event) {
+                counts.failed += 1;
+            }
+        }
+        count_outcomes(&event.spans, outcomes);
+    }
+}
+
+// Error rate of each operation type in the view whose events record an
+// outcome, highest first, so failed handshakes and verifications stand out
+pub fn create_error_rate_section(chart: Rc<SunburstChart>) -> gtk4::Box {
+    let section = gtk4::Box::new(gtk4::Orientation::Vertical, 6);
+    section.set_margin_start(12);
+    section.set_margin_end(12);
+    section.set_margin_top(12);
+    section.set_margin_bottom(12);
+    section.set_visible(false);
+
+    let title = gtk4::Label::new(Some("Error Rates"));
+    title.set_halign(gtk4::Align::Start);
+    title.add_css_class("title-4");
+    section.append(&title);
+
+    let overall = gtk4::Label::new(None);
+    overall.set_halign(gtk4::Align::Start);
+    section.append(&overall);
+
+    let list = gtk4::ListBox::new();
+    list.set_selection_mode(gtk4::SelectionMode::None);
+    list.add_css_class("boxed-list");
+    section.append(&list);
+
+    let section_clone = section.clone();
+    let chart_clone = chart.clone();
+    chart.connect_view_changed(move |_| {
+        let mut outcomes = HashMap::new();
+        count_outcomes(&chart_clone.visible_events(), &mut outcomes);
+
+        while let Some(row) = list.first_child() {
+            list.remove(&row);
+        }
+        let total = outcomes
+            .values()
+            .fold(Outcomes::default(), |total, counts| Outcomes {
+                operations: total.operations + counts.operations,
+                failed: total.failed + counts.failed,
+            });
+        section_clone.set_visible(total.operations > 0);
+        if total.operations == 0 {
+            return;
+        }
+        overall.set_text(&total.summary());
+
+        let mut outcomes: Vec<(String, Outcomes)> = outcomes
+            .into_iter()
+            .filter(|(_, counts)| counts.failed > 0)
+            .collect();
+        outcomes.sort_by(|a, b| {
+            b.1.rate()
+                .total_cmp(&a.1.rate())
+                .then_with(|| b.1.failed.cmp(&a.1.failed))
+                .then_with(|| a.0.cmp(&b.0))
+        });
+        for (name, counts) in outcomes.iter().take(MAX_OPERATIONS) {
+            let row = ActionRow::builder()
+                .title(name)
+                .subtitle(counts.summary())
+                .use_markup(false)
+                .css_classes(["property"])
+                .build();
+            list.append(&row);
+        }
+        list.set_visible(!outcomes.is_empty());
+    });
+
+    section
+}
@@ -0,0 +1,241 @@
+use crate::compliance::Severity;
+use crate::data::NodeKind;
+use serde::{Deserialize, Serialize};
+
+pub type Rgb = (f64, f64, f64);
+
+// Okabe-Ito colors for the series of a chart, most used first, then gray for
+// the rest combined
+pub const SERIES_COLORS: [Rgb; 8] = [
+    (0.00, 0.45, 0.70),
+    (0.90, 0.62, 0.00),
+    (0.00, 0.62, 0.45),
+    (0.80, 0.47, 0.65),
+    (0.34, 0.71, 0.91),
+    (0.84, 0.37, 0.00),
+    (0.94, 0.89, 0.26),
+    (0.60, 0.60, 0.60),
+];
+
+// Selectable color schemes for the chart and severity indicators
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Palette {
+    #[default]
+    Standard,
+    // Okabe-Ito colors, distinguishable with deuteranopia and protanopia
+    ColorblindSafe,
+    // Saturated colors with dark borders
+    HighContrast,
+}
+
+impl Palette {
+    pub const ALL: [Palette; 3] = [
+        Palette::Standard,
+        Palette::ColorblindSafe,
+        Palette::HighContrast,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Standard => "Standard",
+            Self::ColorblindSafe => "Colorblind Safe",
+            Self::HighContrast => "High Contrast",
+        }
+    }
+
+    fn family_color(&self, family: Family) -> Rgb {
+        match self {
+            Self::Standard => family.base_color(),
+            Self::ColorblindSafe => match family {
+                Family::Rsa => (0.90, 0.62, 0.00),
+                Family::Ecdsa => (0.00, 0.45, 0.70),
+                Family::EdDsa => (0.34, 0.71, 0.91),
+                Family::Ecdh => (0.00, 0.62, 0.45),
+                Family::FiniteFieldDh => (0.40, 0.40, 0.40),
+                Family::PostQuantum => (0.80, 0.47, 0.65),
+                Family::Hash => (0.94, 0.89, 0.26),
+                Family::Cipher => (0.84, 0.37, 0.00),
+                Family::Protocol => (0.60, 0.60, 0.60),
+                Family::Other => (0.80, 0.80, 0.80),
+            },
+            Self::HighContrast => match family {
+                Family::Rsa => (1.00, 0.50, 0.00),
+                Family::Ecdsa => (0.00, 0.25, 1.00),
+                Family::EdDsa => (0.00, 0.85, 0.90),
+                Family::Ecdh => (0.00, 0.75, 0.00),
+                Family::FiniteFieldDh => (0.50, 0.30, 0.00),
+                Family::PostQuantum => (0.60, 0.00, 0.90),
+                Family::Hash => (1.00, 1.00, 0.00),
+                Family::Cipher => (0.90, 0.00, 0.10),
+                Family::Protocol => (0.25, 0.25, 0.25),
+                Family::Other => (0.55, 0.55, 0.55),
+            },
+        }
+    }
+
+    pub fn severity_color(&self, severity: Severity) -> Rgb {
+        match (self, severity) {
+            (Self::Standard, Severity::Ok) => (0.18, 0.76, 0.49),
+            (Self::Standard, Severity::Warning) => (0.96, 0.66, 0.18),
+            (Self::Standard, Severity::Error) => (0.88, 0.11, 0.14),
+            (Self::ColorblindSafe, Severity::Ok) => (0.00, 0.45, 0.70),
+            (Self::ColorblindSafe, Severity::Warning) => (0.90, 0.62, 0.00),
+            (Self::ColorblindSafe, Severity::Error) => (0.84, 0.37, 0.00),
+            (Self::HighContrast, Severity::Ok) => (0.00, 0.60, 0.00),
+            (Self::HighContrast, Severity::Warning) => (1.00, 0.80, 0.00),
+            (Self::HighContrast, Severity::Error) => (0.90, 0.00, 0.00),
+        }
+    }
+
+    // Color of a node in the severity mode: the severity of its worst finding,
+    // lightened with depth like the family colors
+    pub fn node_severity_color(&self, severity: Severity, depth: usize) -> Rgb {
+        let base = self.severity_color(severity);
+        if *self == Self::HighContrast {
+            return base;
+        }
+        lighten(base, (depth.saturating_sub(2) as f64 * 0.08).min(0.4))
+    }
+
+    // Color of the separating lines between segments
+    pub fn border_color(&self) -> Rgb {
+        match self {
+            Self::HighContrast => (0.0, 0.0, 0.0),
+            _ => (1.0, 1.0, 1.0),
+        }
+    }
+
+    // Color of a node in the chart: contexts and the root are neutral, operations use
+    // their family color, lightened slightly with depth to separate nested rings
+    pub fn node_color(&self, name: &str, kind: NodeKind, depth: usize) -> Rgb {
+        let base = match kind {
+            NodeKind::Root => (0.75, 0.75, 0.75),
+            NodeKind::Context | NodeKind::Category | NodeKind::Process => (0.55, 0.58, 0.62),
+            NodeKind::Event => self.family_color(Family::of(name)),
+        };
+
+        if *self == Self::HighContrast {
+            return base;
+        }
+
+        let tint = (depth.saturating_sub(2) as f64 * 0.08).min(0.4);
+        lighten(base, tint)
+    }
+}
+
+// Algorithm families sharing one color, so e.g. RSA looks the same in every chart
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Family {
+    Rsa,
+    Ecdsa,
+    EdDsa,
+    Ecdh,
+    FiniteFieldDh,
+    PostQuantum,
+    Hash,
+    Cipher,
+    Protocol,
+    Other,
+}
+
+impl Family {
+    pub const ALL: [Family; 10] = [
+        Family::Rsa,
+        Family::Ecdsa,
+        Family::EdDsa,
+        Family::Ecdh,
+        Family::FiniteFieldDh,
+        Family::PostQuantum,
+        Family::Hash,
+        Family::Cipher,
+        Family::Protocol,
+        Family::Other,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Rsa => "RSA",
+            Self::Ecdsa => "ECDSA",
+            Self::EdDsa => "EdDSA",
+            Self::Ecdh => "ECDH",
+            Self::FiniteFieldDh => "FFDH",
+            Self::PostQuantum => "Post-Quantum",
+            Self::Hash => "Hash",
+            Self::Cipher => "Cipher",
+            Self::Protocol => "Protocol",
+            Self::Other => "Other",
+        }
+    }
+
+    // Classify an operation by the algorithm names appearing in its label.
+    // More specific families are checked first, e.g. "rsa_pss_rsae_sha256" is RSA, not a hash.
+    pub fn of(name: &str) -> Self {
+        let name = name.to_lowercase();
+        let has = |tokens: &[&str]| tokens.iter().any(|t| name.contains(t));
+        // Short names are only matched as whole words to avoid accidental hits
+        let has_word = |words: &[&str]| {
+            name.split(|c: char| !c.is_ascii_alphanumeric())
+                .any(|w| words.contains(&w))
+        };
+        // SHA-1, SHA-2, SHA-3 and SHAKE names start a word, unlike "sha" in
+        // "handshake"
+        let has_sha = name
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .filter_map(|w| w.strip_prefix("sha"))
+            .any(|rest| {
+                rest.is_empty()
+                    || rest.starts_with(|c: char| c.is_ascii_digit())
+                    || rest.starts_with("ke")
+            });
+
+        if has(&[
+            "mlkem", "ml-kem", "kyber", "mldsa", "ml-dsa", "slh-dsa", "sphincs",
+        ]) {
+            Self::PostQuantum
+        } else if has(&["ecdsa"]) {
+            Self::Ecdsa
+        } else if has(&["ed25519", "ed448", "eddsa"]) {
+            Self::EdDsa
+        } else if has(&["rsa"]) {
+            Self::Rsa
+        } else if has(&["x25519", "x448", "secp", "ecdh", "prime256v1"]) {
+            Self::Ecdh
+        } else if has(&["ffdhe"]) || has_word(&["dh", "dhe"]) {
+            Self::FiniteFieldDh
+        } else if has(&["aes", "chacha", "camellia", "3des", "cipher::"])
+            || has_word(&["des", "rc4"])
+        {
+            Self::Cipher
+        } else if has_sha || has(&["blake", "hash::"]) || has_word(&["md5"]) {
+            Self::Hash
+        } else if has(&["tls::", "ssl"]) {
+            Self::Protocol
+        } else {
+            Self::Other
+        }
+    }
+
+    fn base_color(&self) -> Rgb {
+        match self {
+            Self::Rsa => (0.90, 0.49, 0.13),
+            Self::Ecdsa => (0.21, 0.52, 0.89),
+            Self::EdDsa => (0.13, 0.63, 0.66),
+            Self::Ecdh => (0.20, 0.70, 0.35),
+            Self::FiniteFieldDh => (0.60, 0.55, 0.20),
+            Self::PostQuantum => (0.57, 0.33, 0.79),
+            Self::Hash => (0.93, 0.78, 0.20),
+            Self::Cipher => (0.85, 0.26, 0.40),
+            Self::Protocol => (0.42, 0.50, 0.62),
+            Self::Other => (0.60, 0.60, 0.60),
+        }
+    }
+}
+
+fn lighten((r, g, b): Rgb, amount: f64) -> Rgb {
+    (
+        r + (1.0 - r) * amount,
+        g + (1.0 - g) * amount,
+        b + (1.0 - b) * amount,
+    )
+}
@@ -0,0 +1,504 @@
+use crate::data::{boot_timestamp, AuditEvent};
+use crate::decoders;
+use anyhow::{anyhow, Result};
+use std::fs;
+use std::path::Path;
+
+// Parquet files are written by hand: one row group with a page per column,
+// PLAIN-encoded and uncompressed, described by Thrift compact metadata
+
+// Parquet physical types, repetitions, converted types and encodings
+const INT64: i32 = 2;
+const BYTE_ARRAY: i32 = 6;
+const REQUIRED: i32 = 0;
+const OPTIONAL: i32 = 1;
+const UTF8: i32 = 0;
+const TIMESTAMP_MICROS: i32 = 10;
+const PLAIN: i32 = 0;
+const RLE: i32 = 3;
+
+// Thrift compact protocol field types
+const T_I32: u8 = 5;
+const T_I64: u8 = 6;
+const T_BINARY: u8 = 8;
+const T_LIST: u8 = 9;
+const T_STRUCT: u8 = 12;
+
+// Writer of Thrift compact structs, tracking the last field ID of each
+// nested struct for the delta encoding of field headers
+#[derive(Default)]
+struct Thrift {
+    buffer: Vec<u8>,
+    last_ids: Vec<i16>,
+    last_id: i16,
+}
+
+impl Thrift {
+    fn varint(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.buffer.push((value as u8) | 0x80);
+            value >>= 7;
+        }
+        self.buffer.push(value as u8);
+    }
+
+    fn zigzag(&mut self, value: i64) {
+        self.varint(((value << 1) ^ (value >> 63)) as u64);
+    }
+
+    fn field(&mut self, id: i16, kind: u8) {
+        let delta = id - self.last_id;
+        if (1..=15).contains(&delta) {
+            self.buffer.push(((delta as u8) << 4) | kind);
+        } else {
+            self.buffer.push(kind);
+            self.zigzag(id as i64);
+        }
+        self.last_id = id;
+    }
+
+    fn i32(&mut self, id: i16, value: i32) {
+        self.field(id, T_I32);
+        self.zigzag(value as i64);
+    }
+
+    fn i64(&mut self, id: i16, value: i64) {
+        self.field(id, T_I64);
+        self.zigzag(value);
+    }
+
+    fn string(&mut self, id: i16, value: &str) {
+        self.field(id, T_BINARY);
+        self.varint(value.len() as u64);
+        self.buffer.extend_from_slice(value.as_bytes());
+    }
+
+    fn list_header(&mut self, id: i16, kind: u8, size: usize) {
+        self.field(id, T_LIST);
+        if size < 15 {
+            self.buffer.push(((size as u8) << 4) | kind);
+        } else {
+            self.buffer.push(0xf0 | kind);
+            self.varint(size as u64);
+        }
+    }
+
+    fn i32_list(&mut self, id: i16, values: &[i32]) {
+        self.list_header(id, T_I32, values.len());
+        for value in values {
+            self.zigzag(*value as i64);
+        }
+    }
+
+    fn string_list(&mut self, id: i16, values: &[&str]) {
+        self.list_header(id, T_BINARY, values.len());
+        for value in values {
+            self.varint(value.len() as u64);
+            self.buffer.extend_from_slice(value.as_bytes());
+        }
+    }
+
+    // Start a struct, as a field or, with no ID, as a list element
+    fn begin(&mut self, id: Option<i16>) {
+        if let Some(id) = id {
+            self.field(id, T_STRUCT);
+        }
+        self.last_ids.push(self.last_id);
+        self.last_id = 0;
+    }
+
+    fn end(&mut self) {
+        self.buffer.push(0);
+        self.last_id = self.last_ids.pop().unwrap_or(0);
+    }
+}
+
+enum Values {
+    Strings(Vec<Option<String>>),
+    Numbers(Vec<Option<i64>>),
+}
+
+struct Column {
+    name: &'static str,
+    converted_type: Option<i32>,
+    optional: bool,
+    values: Values,
+}
+
+impl Column {
+    fn physical_type(&self) -> i32 {
+        match self.values {
+            Values::Strings(_) => BYTE_ARRAY,
+            Values::Numbers(_) => INT64,
+        }
+    }
+
+    // Definition levels, if the column is optional, and PLAIN values of the
+    // non-null entries
+    fn page_data(&self) -> Vec<u8> {
+        let present: Vec<bool> = match &self.values {
+            Values::Strings(values) => values.iter().map(Option::is_some).collect(),
+            Values::Numbers(values) => values.iter().map(Option::is_some).collect(),
+        };
+        let mut data = Vec::new();
+        if self.optional {
+            // RLE runs of the 1-bit levels, prefixed with their length
+            let mut levels = Vec::new();
+            let mut i = 0;
+            while i < present.len() {
+                let run = present[i..]
+                    .iter()
+                    .take_while(|level| **level == present[i])
+                    .count();
+                let mut header = (run as u64) << 1;
+                while header >= 0x80 {
+                    levels.push((header as u8) | 0x80);
+                    header >>= 7;
+                }
+                levels.push(header as u8);
+                levels.push(u8::from(present[i]));
+                i += run;
+            }
+            data.extend_from_slice(&(levels.len() as u32).to_le_bytes());
+            data.extend_from_slice(&levels);
+        }
+        match &self.values {
+            Values::Strings(values) => {
+                for value in values.iter().flatten() {
+                    data.extend_from_slice(&(value.len() as u32).to_le_bytes());
+                    data.extend_from_slice(value.as_bytes());
+                }
+            }
+            Values::Numbers(values) => {
+                for value in values.iter().flatten() {
+                    data.extend_from_slice(&value.to_le_bytes());
+                }
+            }
+        }
+        data
+    }
+}
+
+// Algorithm in the decoded details of an event, or an event node's name: the
+// first that isn't a protocol version or a size, e.g. the ciphersuite of a handshake
+pub fn algorithm_in(details: &str) -> Option<String> {
+    let (_, rest) = details.split_once(" [")?;
+    rest.trim_end_matches(']')
+        .split(", ")
+        .find(|detail| {
+            !detail.starts_with("TLS ")
+                && !detail.starts_with("SSL ")
+                && !detail.starts_with("version ")
+                && !detail.ends_with(" bits")
+                && !detail.ends_with(" bytes")
+                && !detail.ends_with(" iterations")
+        })
+        .map(str::to_string)
+}
+
+pub fn algorithm_of(event: &AuditEvent) -> Option<String> {
+    algorithm_in(&event.format_details())
+}
+
+pub fn bits_of(event: &AuditEvent) -> Option<i64> {
+    ["pk::bits", "x509::public_key_bits"]
+        .iter()
+        .find_map(|key| event.events.get(*key)?.as_i64())
+}
+
+// Microseconds since the epoch of a time in nanoseconds since boot
+fn timestamp_micros(ns: u64) -> i64 {
+    boot_timestamp(ns).as_microsecond()
+}
+
+fn collect_rows<'a>(events: &'a [AuditEvent], rows: &mut Vec<&'a AuditEvent>) {
+    for event in events {
+        rows.push(event);
+        collect_rows(&event.spans, rows);
+    }
+}
+
+// Columns of the flattened event table: a row per event and span
+fn event_columns(events: &[AuditEvent]) -> Vec<Column> {
+    let mut rows = Vec::new();
+    collect_rows(events, &mut rows);
+    let strings = |f: &dyn Fn(&AuditEvent) -> Option<String>| {
+        Values::Strings(rows.iter().map(|e| f(e)).collect())
+    };
+    let numbers = |f: &dyn Fn(&AuditEvent) -> Option<i64>| {
+        Values::Numbers(rows.iter().map(|e| f(e)).collect())
+    };
+    let column = |name, converted_type, optional, values| Column {
+        name,
+        converted_type,
+        optional,
+        values,
+    };
+    vec![
+        column(
+            "context",
+            Some(UTF8),
+            false,
+            strings(&|e| Some(e.context.clone())),
+        ),
+        column(
+            "origin",
+            Some(UTF8),
+            false,
+            strings(&|e| Some(e.origin.clone())),
+        ),
+        column("name", Some(UTF8), false, strings(&|e| Some(e.name()))),
+        column("algorithm", Some(UTF8), true, strings(&algorithm_of)),
+        column("bits", None, true, numbers(&bits_of)),
+        column(
+            "process",
+            Some(UTF8),
+            true,
+            strings(&|e| decoders::process_name(&e.events, &e.context)),
+        ),
+        column(
+            "start",
+            Some(TIMESTAMP_MICROS),
+            false,
+            numbers(&|e| Some(timestamp_micros(e.start))),
+        ),
+        column(
+            "end",
+            Some(TIMESTAMP_MICROS),
+            false,
+            numbers(&|e| Some(timestamp_micros(e.end))),
+        ),
+        column(
+            "duration_ns",
+            None,
+            false,
+            numbers(&|e| Some(e.end.saturating_sub(e.start) as i64)),
+        ),
+    ]
+}
+
+fn file_metadata(columns: &[Column], chunks: &[(i64, i64)], num_rows: i64) -> Vec<u8> {
+    let mut thrift = Thrift::default();
+    thrift.begin(None);
+    thrift.i32(1, 1);
+
+    // The root of the schema, then a leaf per column
+    thrift.list_header(2, T_STRUCT, columns.len() + 1);
+    thrift.begin(None);
+    thrift.string(4, "schema");
+    thrift.i32(5, columns.len() as i32);
+    thrift.end();
+    for column in columns {
+        thrift.begin(None);
+        thrift.i32(1, column.physical_type());
+        thrift.i32(3, if column.optional { OPTIONAL } else { REQUIRED });
+        thrift.string(4, column.name);
+        if let Some(converted_type) = column.converted_type {
+            thrift.i32(6, converted_type);
+        }
+        thrift.end();
+    }
+    thrift.i64(3, num_rows);
+
+    thrift.list_header(4, T_STRUCT, 1);
+    thrift.begin(None);
+    thrift.list_header(1, T_STRUCT, columns.len());
+    for (column, (offset, size)) in columns.iter().zip(chunks) {
+        thrift.begin(None);
+        thrift.i64(2, *offset);
+        thrift.begin(Some(3));
+        thrift.i32(1, column.physical_type());
+        thrift.i32_list(2, &[PLAIN, RLE]);
+        thrift.string_list(3, &[column.name]);
+        thrift.i32(4, 0);
+        thrift.i64(5, num_rows);
+        thrift.i64(6, *size);
+        thrift.i64(7, *size);
+        thrift.i64(9, *offset);
+        thrift.end();
+        thrift.end();
+    }
+    thrift.i64(2, chunks.iter().map(|(_, size)| size).sum());
+    thrift.i64(3, num_rows);
+    thrift.end();
+
+    thrift.string(
+        6,
+        concat!("crypto-usage-analyzer ", env!("CARGO_PKG_VERSION")),
+    );
+    thrift.end();
+    thrift.buffer
+}
+
+// Save the events and their spans as a flat Parquet table, one row each, for
+// analyses in pandas, Polars and the like
+pub fn export_events(events: &[AuditEvent], path: &Path) -> Result<()> {
+    if events.is_empty() {
+        return Err(anyhow!("No events to export"));
+    }
+    let columns = event_columns(events);
+    let num_rows = match &columns[0].values {
+        Values::Strings(values) => values.len(),
+        Values::Numbers(values) => values.len(),
+    } as i64;
+
+    let mut file = b"PAR1".to_vec();
+    let mut chunks = Vec::new();
+    for column in &columns {
+        let data = column.page_data();
+        let mut header = Thrift::default();
+        header.begin(None);
+        header.i32(1, 0);
+        header.i32(2, data.len() as i32);
+        header.i32(3, data.len() as i32);
+        header.begin(Some(5));
+        header.i32(1, num_rows as i32);
+        header.i32(2, PLAIN);
+        header.i32(3, RLE);
+        header.i32(4, RLE);
+        header.end();
+        header.end();
+
+        let offset = file.len() as i64;
+        file.extend_from_slice(&header.buffer);
+        file.extend_from_slice(&data);
+        chunks.push((offset, (header.buffer.len() + data.len()) as i64));
+    }
+
+    let metadata = file_metadata(&columns, &chunks, num_rows);
+    file.extend_from_slice(&metadata);
+    file.extend_from_slice(&(metadata.len() as u32).to_le_bytes());
+    file.extend_from_slice(b"PAR1");
+    fs::write(path, file)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bytes(write: impl FnOnce(&mut Thrift)) -> Vec<u8> {
+        let mut thrift = Thrift::default();
+        write(&mut thrift);
+        thrift.buffer
+    }
+
+    #[test]
+    fn varints() {
+        assert_eq!(bytes(|t| t.varint(0)), [0x00]);
+        assert_eq!(bytes(|t| t.varint(127)), [0x7f]);
+        assert_eq!(bytes(|t| t.varint(128)), [0x80, 0x01]);
+        assert_eq!(bytes(|t| t.varint(300)), [0xac, 0x02]);
+        assert_eq!(
+            bytes(|t| t.varint(u64::MAX)),
+            [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01]
+        );
+    }
+
+    #[test]
+    fn zigzags() {
+        assert_eq!(bytes(|t| t.zigzag(0)), [0x00]);
+        assert_eq!(bytes(|t| t.zigzag(-1)), [0x01]);
+        assert_eq!(bytes(|t| t.zigzag(1)), [0x02]);
+        assert_eq!(bytes(|t| t.zigzag(-64)), [0x7f]);
+        assert_eq!(bytes(|t| t.zigzag(64)), [0x80, 0x01]);
+        assert_eq!(
+            bytes(|t| t.zigzag(i64::MIN)),
+            [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01]
+        );
+    }
+
+    #[test]
+    fn field_headers() {
+        // Deltas of 1 to 15 share the byte with the type
+        assert_eq!(
+            bytes(|t| {
+                t.i32(1, 3);
+                t.i64(16, -2);
+            }),
+            [0x15, 0x06, 0xf6, 0x03]
+        );
+        // Longer or backward steps give the ID in full
+        assert_eq!(bytes(|t| t.i32(17, 0)), [0x05, 0x22, 0x00]);
+        assert_eq!(
+            bytes(|t| {
+                t.i32(5, 0);
+                t.i32(2, 0);
+            }),
+            [0x55, 0x00, 0x05, 0x04, 0x00]
+        );
+    }
+
+    #[test]
+    fn strings_and_lists() {
+        assert_eq!(bytes(|t| t.string(4, "ab")), [0x48, 0x02, b'a', b'b']);
+        assert_eq!(
+            bytes(|t| t.i32_list(2, &[PLAIN, RLE])),
+            [0x29, 0x25, 0x00, 0x06]
+        );
+        assert_eq!(
+            bytes(|t| t.string_list(3, &["a"])),
+            [0x39, 0x18, 0x01, b'a']
+        );
+        let long = bytes(|t| t.i32_list(1, &[0; 15]));
+        assert_eq!(long[..3], [0x19, 0xf5, 0x0f]);
+        assert_eq!(long.len(), 3 + 15);
+    }
+
+    #[test]
+    fn nested_structs() {
+        // Field IDs restart in a nested struct and resume after it
+        let buffer = bytes(|t| {
+            t.begin(None);
+            t.i32(1, 0);
+            t.begin(Some(3));
+            t.i32(1, 0);
+            t.end();
+            t.i32(4, 0);
+            t.end();
+        });
+        assert_eq!(
+            buffer,
+            [0x15, 0x00, 0x2c, 0x15, 0x00, 0x00, 0x15, 0x00, 0x00]
+        );
+    }
+
+    #[test]
+    fn optional_page_data() {
+        let column = Column {
+            name: "bits",
+            converted_type: None,
+            optional: true,
+            values: Values::Numbers(vec![Some(1), Some(2), None]),
+        };
+        let mut expected = vec![4, 0, 0, 0, 0x04, 0x01, 0x02, 0x00];
+        expected.extend_from_slice(&1i64.to_le_bytes());
+        expected.extend_from_slice(&2i64.to_le_bytes());
+        assert_eq!(column.page_data(), expected);
+    }
+
+    #[test]
+    fn required_page_data() {
+        let column = Column {
+            name: "name",
+            converted_type: Some(UTF8),
+            optional: false,
+            values: Values::Strings(vec![Some("ab".to_string()), Some("c".to_string())]),
+        };
+        assert_eq!(
+            column.page_data(),
+            [2, 0, 0, 0, b'a', b'b', 1, 0, 0, 0, b'c']
+        );
+        assert_eq!(column.physical_type(), BYTE_ARRAY);
+    }
+
+    #[test]
+    fn algorithms_in_details() {
+        assert_eq!(
+            algorithm_in("Handshake [TLS 1.3, TLS_AES_128_GCM_SHA256, 256 bits]").as_deref(),
+            Some("TLS_AES_128_GCM_SHA256")
+        );
+        assert_eq!(algorithm_in("Handshake [TLS 1.3]"), None);
+        assert_eq!(algorithm_in("Handshake"), None);
+    }
+}
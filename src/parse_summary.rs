@@ -0,0 +1,211 @@
+use crate::data::{AuditEvent, TreeNode};
+use crate::memory;
+use crate::sunburst::SunburstChart;
+use adw::prelude::*;
+use adw::ActionRow;
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::rc::Rc;
+use std::time::Duration;
+
+// Timings of the loaded capture: reading and parsing the file, as the
+// loaders record it, and building the tree of the view, as the chart does
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LoadTimings {
+    pub file_size: Option<usize>,
+    pub parse: Option<Duration>,
+    // The last rebuild of the tree, filtering and classifying included
+    pub aggregation: Option<Duration>,
+}
+
+// Shape of the events of a capture
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Shape {
+    pub events: usize,
+    pub spans: usize,
+    // Events and spans at each nesting depth, top-level events first
+    pub depths: Vec<usize>,
+    // Distinct event names, fields and origins, counted but never named
+    pub names: usize,
+    pub fields: usize,
+    pub origins: usize,
+}
+
+impl Shape {
+    // Nesting of the deepest span, 1 for events without spans
+    pub fn max_depth(&self) -> usize {
+        self.depths.len()
+    }
+}
+
+#[derive(Default)]
+struct Distinct<'a> {
+    names: HashSet<String>,
+    fields: HashSet<&'a str>,
+    origins: HashSet<&'a str>,
+}
+
+fn visit<'a>(
+    events: &'a [AuditEvent],
+    depth: usize,
+    shape: &mut Shape,
+    distinct: &mut Distinct<'a>,
+) {
+    if events.is_empty() {
+        return;
+    }
+    if shape.depths.len() <= depth {
+        shape.depths.resize(depth + 1, 0);
+    }
+    shape.depths[depth] += events.len();
+    for event in events {
+        shape.spans += event.spans.len();
+        distinct.names.insert(event.name());
+        distinct
+            .fields
+            .extend(event.events.keys().map(String::as_str));
+        distinct.origins.insert(&event.origin);
+        visit(&event.spans, depth + 1, shape, distinct);
+    }
+}
+
+pub fn shape(events: &[AuditEvent]) -> Shape {
+    let mut shape = Shape {
+        events: events.len(),
+        ..Shape::default()
+    };
+    let mut distinct = Distinct::default();
+    visit(events, 0, &mut shape, &mut distinct);
+    shape.names = distinct.names.len();
+    shape.fields = distinct.fields.len();
+    shape.origins = distinct.origins.len();
+    shape
+}
+
+pub fn format_duration(duration: Duration) -> String {
+    let millis = duration.as_secs_f64() * 1000.0;
+    if millis >= 1000.0 {
+        format!("{:.2} s", millis / 1000.0)
+    } else if millis < 1.0 {
+        format!("{:.3} ms", millis)
+    } else {
+        format!("{:.1} ms", millis)
+    }
+}
+
+fn count_nodes(node: &TreeNode) -> usize {
+    1 + node.children.iter().map(count_nodes).sum::<usize>()
+}
+
+// Plain-text report of the load timings and the shape of the capture, to
+// attach to reports of slow loading. It holds numbers only, no names,
+// contexts or values of the capture.
+pub fn performance_report(chart: &SunburstChart) -> String {
+    let timings = chart.load_timings();
+    let shape = shape(&chart.source_events());
+    let optional = |value: Option<String>| value.unwrap_or_else(|| "unknown".to_string());
+
+    let mut report = String::new();
+    let _ = writeln!(
+        report,
+        "crypto-usage-analyzer {} performance report",
+        env!("CARGO_PKG_VERSION")
+    );
+    let _ = writeln!(
+        report,
+        "File size: {}",
+        optional(timings.file_size.map(|size| format!("{} bytes", size)))
+    );
+    let _ = writeln!(
+        report,
+        "Parse time: {}",
+        optional(timings.parse.map(format_duration))
+    );
+    let _ = writeln!(
+        report,
+        "Aggregation time: {}",
+        optional(timings.aggregation.map(format_duration))
+    );
+    let _ = writeln!(report, "Events: {}", shape.events);
+    let _ = writeln!(report, "Spans: {}", shape.spans);
+    let _ = writeln!(report, "Maximum depth: {}", shape.max_depth());
+    for (depth, count) in shape.depths.iter().enumerate() {
+        let _ = writeln!(report, "  Depth {}: {}", depth + 1, count);
+    }
+    let _ = writeln!(report, "Distinct names: {}", shape.names);
+    let _ = writeln!(report, "Distinct fields: {}", shape.fields);
+    let _ = writeln!(report, "Distinct origins: {}", shape.origins);
+    let _ = writeln!(
+        report,
+        "Tree nodes: {}",
+        optional(chart.root().map(|root| count_nodes(&root).to_string()))
+    );
+    // Only measured while the performance overlay is shown
+    if let Some((segments, layout, hit_test)) = chart.render_timings() {
+        let _ = writeln!(report, "Segments drawn: {}", segments);
+        let _ = writeln!(report, "Layout time: {}", format_duration(layout));
+        let _ = writeln!(report, "Hit-test time: {}", format_duration(hit_test));
+    }
+    report
+}
+
+// Collapsed sidebar panel with the size, shape and load timings of the
+// capture, to keep in mind how big it is and to attach to reports of slow
+// loading
+pub fn create_parse_summary(chart: Rc<SunburstChart>) -> gtk4::Expander {
+    let list = gtk4::ListBox::new();
+    list.set_selection_mode(gtk4::SelectionMode::None);
+    list.add_css_class("boxed-list");
+    list.set_margin_top(6);
+
+    let expander = gtk4::Expander::builder()
+        .label("File Details")
+        .child(&list)
+        .margin_start(12)
+        .margin_end(12)
+        .margin_top(12)
+        .margin_bottom(12)
+        .visible(false)
+        .build();
+
+    let expander_clone = expander.clone();
+    let chart_clone = chart.clone();
+    chart.connect_view_changed(move |_| {
+        while let Some(row) = list.first_child() {
+            list.remove(&row);
+        }
+        let shape = shape(&chart_clone.events());
+        expander_clone.set_visible(shape.events > 0);
+
+        let timings = chart_clone.load_timings();
+        let unknown = || "unknown".to_string();
+        let entries = [
+            (
+                "File Size",
+                timings.file_size.map_or_else(unknown, memory::format_size),
+            ),
+            ("Events", shape.events.to_string()),
+            ("Spans", shape.spans.to_string()),
+            ("Maximum Depth", shape.max_depth().to_string()),
+            (
+                "Parse Time",
+                timings.parse.map_or_else(unknown, format_duration),
+            ),
+            (
+                "Aggregation Time",
+                timings.aggregation.map_or_else(unknown, format_duration),
+            ),
+        ];
+        for (title, value) in entries {
+            let row = ActionRow::builder()
+                .title(title)
+                .subtitle(value)
+                .use_markup(false)
+                .css_classes(["property"])
+                .build();
+            list.append(&row);
+        }
+    });
+
+    expander
+}
@@ -0,0 +1,85 @@
+use cairo::{Context, FontSlant, FontWeight};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+const FONT_SIZE: f64 = 12.0;
+const LINE_HEIGHT: f64 = 16.0;
+const PADDING: f64 = 8.0;
+const MARGIN: f64 = 8.0;
+
+// Timings of the chart's recent frames and hit tests, shown over it to
+// diagnose slow rendering of big captures
+#[derive(Debug, Default)]
+pub struct PerfStats {
+    // Start of the frames drawn within the last second
+    frames: VecDeque<Instant>,
+    // Laying out and painting the segments of the last frame, done in one pass
+    pub layout: Duration,
+    pub segments: usize,
+    // Finding the segment under the pointer, on its last motion
+    pub hit_test: Duration,
+}
+
+impl PerfStats {
+    // Count a frame starting now
+    pub fn start_frame(&mut self) -> Instant {
+        let now = Instant::now();
+        while self
+            .frames
+            .front()
+            .is_some_and(|start| now.duration_since(*start) > Duration::from_secs(1))
+        {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(now);
+        now
+    }
+
+    // Frames drawn in the last second. The chart is only redrawn when
+    // something changes, so this is the rate while hovering or resizing.
+    fn fps(&self) -> usize {
+        self.frames.len()
+    }
+
+    fn lines(&self) -> [String; 4] {
+        let millis = |duration: Duration| duration.as_secs_f64() * 1000.0;
+        [
+            format!("{} fps", self.fps()),
+            format!("{} segments", self.segments),
+            format!("Layout {:.2} ms", millis(self.layout)),
+            format!("Hit test {:.3} ms", millis(self.hit_test)),
+        ]
+    }
+
+    // Draw the figures in a translucent box at the top left corner
+    pub fn draw(&self, cr: &Context) {
+        let lines = self.lines();
+        cr.save().unwrap();
+        cr.select_font_face("Monospace", FontSlant::Normal, FontWeight::Normal);
+        cr.set_font_size(FONT_SIZE);
+        let width = lines
+            .iter()
+            .filter_map(|line| cr.text_extents(line).ok())
+            .map(|extents| extents.x_advance())
+            .fold(0.0, f64::max);
+
+        cr.set_source_rgba(0.0, 0.0, 0.0, 0.7);
+        cr.rectangle(
+            MARGIN,
+            MARGIN,
+            width + 2.0 * PADDING,
+            lines.len() as f64 * LINE_HEIGHT + 2.0 * PADDING,
+        );
+        cr.fill().unwrap();
+
+        cr.set_source_rgb(1.0, 1.0, 1.0);
+        for (i, line) in lines.iter().enumerate() {
+            cr.move_to(
+                MARGIN + PADDING,
+                MARGIN + PADDING + i as f64 * LINE_HEIGHT + FONT_SIZE,
+            );
+            cr.show_text(line).unwrap();
+        }
+        cr.restore().unwrap();
+    }
+}
@@ -0,0 +1,661 @@
+use crate::compliance::{self, Profile};
+use crate::config::Config;
+use crate::filters::{ExcludeRule, RuleTarget};
+use crate::live::Retention;
+use crate::palette::Palette;
+use crate::profile_editor;
+use crate::sunburst::{SunburstChart, TooltipOptions, TooltipSort};
+use crate::timezone::{self, DisplayZone};
+use adw::prelude::*;
+use adw::{
+    ActionRow, ComboRow, EntryRow, PreferencesDialog, PreferencesGroup, PreferencesPage, SpinRow,
+    SwitchRow,
+};
+use gtk4::{glib, StringList};
+use regex::Regex;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+pub fn show_preferences(
+    parent: &impl IsA<gtk4::Widget>,
+    chart: Rc<SunburstChart>,
+    config: Rc<RefCell<Config>>,
+) {
+    let dialog = PreferencesDialog::new();
+
+    let page = PreferencesPage::builder()
+        .title("Appearance")
+        .icon_name("applications-graphics-symbolic")
+        .build();
+
+    let colors_group = PreferencesGroup::builder()
+        .title("Colors")
+        .description("Applied to the chart and to severity indicators")
+        .build();
+
+    let palette_names: Vec<&str> = Palette::ALL.iter().map(|p| p.label()).collect();
+    let current_palette = config.borrow().palette;
+    let palette_row = ComboRow::builder()
+        .title("Color Palette")
+        .model(&StringList::new(&palette_names))
+        .selected(
+            Palette::ALL
+                .iter()
+                .position(|p| *p == current_palette)
+                .unwrap_or(0) as u32,
+        )
+        .build();
+
+    let chart_clone = chart.clone();
+    let config_clone = config.clone();
+    palette_row.connect_selected_notify(move |row| {
+        let Some(&palette) = Palette::ALL.get(row.selected() as usize) else {
+            return;
+        };
+        chart_clone.set_palette(palette);
+
+        let mut config = config_clone.borrow_mut();
+        config.palette = palette;
+        if let Err(e) = config.save() {
+            eprintln!("Failed to save preferences: {}", e);
+        }
+    });
+
+    colors_group.add(&palette_row);
+    page.add(&colors_group);
+    page.add(&create_tooltip_group(chart.clone(), config.clone()));
+    page.add(&create_time_group(chart.clone(), config.clone()));
+    page.add(&create_compliance_group(chart.clone(), config.clone()));
+    page.add(&create_debugging_group(chart.clone(), config.clone()));
+    dialog.add(&page);
+    dialog.add(&create_filters_page(chart, config.clone()));
+    dialog.add(&create_live_page(config.clone()));
+    dialog.add(&create_reports_page(config));
+
+    dialog.present(Some(parent));
+}
+
+// Children listed in the tooltips of chart segments
+fn create_tooltip_group(chart: Rc<SunburstChart>, config: Rc<RefCell<Config>>) -> PreferencesGroup {
+    let group = PreferencesGroup::builder()
+        .title("Tooltips")
+        .description("Children listed when hovering a chart segment")
+        .build();
+
+    let options = config.borrow().tooltip;
+    let children_row = SpinRow::builder()
+        .title("Listed Children")
+        .adjustment(&gtk4::Adjustment::new(
+            options.children as f64,
+            0.0,
+            50.0,
+            1.0,
+            5.0,
+            0.0,
+        ))
+        .build();
+    let sort_names: Vec<&str> = TooltipSort::ALL.iter().map(|s| s.label()).collect();
+    let sort_row = ComboRow::builder()
+        .title("Sort By")
+        .model(&StringList::new(&sort_names))
+        .selected(
+            TooltipSort::ALL
+                .iter()
+                .position(|s| *s == options.sort)
+                .unwrap_or(0) as u32,
+        )
+        .build();
+    let weak_first_row = SwitchRow::builder()
+        .title("Weak Algorithms First")
+        .subtitle("List children using MD5, SHA-1 or weak ciphers before the others")
+        .active(options.weak_first)
+        .build();
+
+    let update = {
+        let children_row = children_row.clone();
+        let sort_row = sort_row.clone();
+        let weak_first_row = weak_first_row.clone();
+        move || {
+            let options = TooltipOptions {
+                children: children_row.value() as usize,
+                sort: TooltipSort::ALL
+                    .get(sort_row.selected() as usize)
+                    .copied()
+                    .unwrap_or_default(),
+                weak_first: weak_first_row.is_active(),
+            };
+            chart.set_tooltip_options(options);
+
+            let mut config = config.borrow_mut();
+            config.tooltip = options;
+            if let Err(e) = config.save() {
+                eprintln!("Failed to save preferences: {}", e);
+            }
+        }
+    };
+    let update = Rc::new(update);
+    let update_clone = update.clone();
+    children_row.connect_value_notify(move |_| update_clone());
+    let update_clone = update.clone();
+    sort_row.connect_selected_notify(move |_| update_clone());
+    weak_first_row.connect_active_notify(move |_| update());
+
+    group.add(&children_row);
+    group.add(&sort_row);
+    group.add(&weak_first_row);
+    group
+}
+
+// Diagnostics for reporting slow rendering of big captures
+// Zone of the displayed times, for comparing with logs of servers elsewhere,
+// or offsets from the start of the capture, for correlating within it
+fn create_time_group(chart: Rc<SunburstChart>, config: Rc<RefCell<Config>>) -> PreferencesGroup {
+    let group = PreferencesGroup::builder()
+        .title("Time")
+        .description("Applied to the times in the views, tooltips and exports")
+        .build();
+
+    let zone = config.borrow().time_zone.clone();
+    let zone_row = ComboRow::builder()
+        .title("Time Zone")
+        .model(&StringList::new(&["UTC", "Local Time", "Other"]))
+        .selected(match zone {
+            DisplayZone::Utc => 0,
+            DisplayZone::Local => 1,
+            DisplayZone::Named(_) => 2,
+        })
+        .build();
+    let name_row = EntryRow::builder()
+        .title("Zone Name, e.g. Europe/Berlin")
+        .show_apply_button(true)
+        .visible(matches!(zone, DisplayZone::Named(_)))
+        .build();
+    if let DisplayZone::Named(name) = &zone {
+        name_row.set_text(name);
+    }
+
+    let relative_row = SwitchRow::builder()
+        .title("Relative Times")
+        .subtitle("Show times in the tooltips, time scrubber and charts as offsets from the start of the capture, e.g. +00:03:12.448")
+        .active(config.borrow().relative_times)
+        .build();
+    let chart_clone = chart.clone();
+    let config_clone = config.clone();
+    relative_row.connect_active_notify(move |row| {
+        timezone::set_relative(row.is_active());
+        chart_clone.refresh_times();
+
+        let mut config = config_clone.borrow_mut();
+        config.relative_times = row.is_active();
+        if let Err(e) = config.save() {
+            eprintln!("Failed to save preferences: {}", e);
+        }
+    });
+
+    // Show times in a zone that exists, and save it
+    let apply = move |zone: DisplayZone| -> bool {
+        if timezone::set_display_zone(&zone).is_err() {
+            return false;
+        }
+        chart.refresh_times();
+
+        let mut config = config.borrow_mut();
+        config.time_zone = zone;
+        if let Err(e) = config.save() {
+            eprintln!("Failed to save preferences: {}", e);
+        }
+        true
+    };
+    let apply = Rc::new(apply);
+
+    let name_row_clone = name_row.clone();
+    let apply_clone = apply.clone();
+    zone_row.connect_selected_notify(move |row| {
+        name_row_clone.set_visible(row.selected() == 2);
+        // Another zone applies once its name is entered
+        match row.selected() {
+            0 => apply_clone(DisplayZone::Utc),
+            1 => apply_clone(DisplayZone::Local),
+            _ => name_row_clone.grab_focus(),
+        };
+    });
+
+    name_row.connect_apply(move |row| {
+        let valid = apply(DisplayZone::Named(row.text().trim().to_string()));
+        if valid {
+            row.remove_css_class("error");
+        } else {
+            row.add_css_class("error");
+        }
+    });
+
+    group.add(&zone_row);
+    group.add(&name_row);
+    group.add(&relative_row);
+    group
+}
+
+// Names of the profiles offered: the built-in ones, then the custom ones
+fn profile_names() -> Vec<String> {
+    let mut names: Vec<String> = compliance::builtin_names()
+        .into_iter()
+        .map(str::to_string)
+        .collect();
+    names.extend(Profile::custom_profiles().into_iter().map(|p| p.name));
+    names
+}
+
+// Profile the chart colors, the policy panel and reports check against, and
+// the editor of custom ones
+fn create_compliance_group(
+    chart: Rc<SunburstChart>,
+    config: Rc<RefCell<Config>>,
+) -> PreferencesGroup {
+    let group = PreferencesGroup::builder()
+        .title("Compliance")
+        .description("Applied to the severity colors, the policy findings and the reports")
+        .build();
+
+    let names = Rc::new(RefCell::new(profile_names()));
+    let model = StringList::new(&["System Policy"]);
+    for name in names.borrow().iter() {
+        model.append(name);
+    }
+    // Index of a profile in the list, 0 for the system policy
+    let position = {
+        let names = names.clone();
+        move |name: Option<&str>| {
+            name.and_then(|name| names.borrow().iter().position(|n| n == name))
+                .map_or(0, |position| position as u32 + 1)
+        }
+    };
+    let profile_row = ComboRow::builder()
+        .title("Compliance Profile")
+        .subtitle("Crypto policy level, standard or custom profile to check observed usage against")
+        .model(&model)
+        .selected(position(config.borrow().compliance_profile.as_deref()))
+        .build();
+
+    let new_button = gtk4::Button::with_label("New");
+    let edit_button = gtk4::Button::with_label("Edit");
+    for button in [&new_button, &edit_button] {
+        button.set_valign(gtk4::Align::Center);
+    }
+    let editor_row = ActionRow::builder()
+        .title("Custom Profiles")
+        .subtitle("Allowed algorithms, minimum sizes and severities of a baseline of your own")
+        .build();
+    editor_row.add_suffix(&new_button);
+    editor_row.add_suffix(&edit_button);
+
+    // Selected profile, if it's a custom one the editor can change
+    let selected_custom = {
+        let names = names.clone();
+        let profile_row = profile_row.clone();
+        move || {
+            let index = (profile_row.selected() as usize).checked_sub(1)?;
+            let name = names.borrow().get(index)?.clone();
+            Profile::custom_profiles()
+                .into_iter()
+                .find(|profile| profile.name == name)
+        }
+    };
+    let selected_custom = Rc::new(selected_custom);
+    edit_button.set_sensitive(selected_custom().is_some());
+
+    let select = {
+        let chart = chart.clone();
+        let config = config.clone();
+        let names = names.clone();
+        let edit_button = edit_button.clone();
+        let selected_custom = selected_custom.clone();
+        move |row: &ComboRow| {
+            let name = (row.selected() as usize)
+                .checked_sub(1)
+                .and_then(|index| names.borrow().get(index).cloned());
+            chart.set_profile(name.as_deref().and_then(Profile::named));
+            edit_button.set_sensitive(selected_custom().is_some());
+
+            let mut config = config.borrow_mut();
+            config.compliance_profile = name;
+            if let Err(e) = config.save() {
+                eprintln!("Failed to save preferences: {}", e);
+            }
+        }
+    };
+    let select = Rc::new(select);
+    let select_clone = select.clone();
+    profile_row.connect_selected_notify(move |row| select_clone(row));
+
+    // Save an edited profile, list it and check against it
+    let edit = {
+        let profile_row = profile_row.clone();
+        move |button: &gtk4::Button, profile: Profile| {
+            if let Err(e) = profile.save_custom() {
+                // Shown in the preferences dialog the editor was opened from
+                if let Some(dialog) = button
+                    .ancestor(PreferencesDialog::static_type())
+                    .and_downcast::<PreferencesDialog>()
+                {
+                    dialog.add_toast(adw::Toast::new(&format!("Failed to save profile: {}", e)));
+                }
+                return;
+            }
+            *names.borrow_mut() = profile_names();
+            let labels: Vec<String> = names.borrow().clone();
+            let labels: Vec<&str> = labels.iter().map(String::as_str).collect();
+            model.splice(1, model.n_items() - 1, &labels);
+            profile_row.set_selected(position(Some(&profile.name)));
+            // Selecting the same row again notifies nothing
+            select(&profile_row);
+        }
+    };
+    let edit = Rc::new(edit);
+
+    let edit_clone = edit.clone();
+    new_button.connect_clicked(move |button| {
+        let edit = edit_clone.clone();
+        let button = button.clone();
+        glib::spawn_future_local(async move {
+            if let Some(profile) =
+                profile_editor::edit_profile(&button, &Profile::permissive("")).await
+            {
+                edit(&button, profile);
+            }
+        });
+    });
+    edit_button.connect_clicked(move |button| {
+        let Some(current) = selected_custom() else {
+            return;
+        };
+        let edit = edit.clone();
+        let button = button.clone();
+        glib::spawn_future_local(async move {
+            if let Some(profile) = profile_editor::edit_profile(&button, &current).await {
+                edit(&button, profile);
+            }
+        });
+    });
+
+    group.add(&profile_row);
+    group.add(&editor_row);
+    group
+}
+
+fn create_debugging_group(
+    chart: Rc<SunburstChart>,
+    config: Rc<RefCell<Config>>,
+) -> PreferencesGroup {
+    let group = PreferencesGroup::builder().title("Debugging").build();
+
+    let overlay_row = SwitchRow::builder()
+        .title("Performance Overlay")
+        .subtitle("Frames per second, segment count, layout and hit test times over the chart")
+        .active(config.borrow().performance_overlay)
+        .build();
+    overlay_row.connect_active_notify(move |row| {
+        chart.set_performance_overlay(row.is_active());
+
+        let mut config = config.borrow_mut();
+        config.performance_overlay = row.is_active();
+        if let Err(e) = config.save() {
+            eprintln!("Failed to save preferences: {}", e);
+        }
+    });
+
+    group.add(&overlay_row);
+    group
+}
+
+// Limits of the ring buffer holding the events of a followed file
+fn create_live_page(config: Rc<RefCell<Config>>) -> PreferencesPage {
+    let page = PreferencesPage::builder()
+        .title("Live")
+        .icon_name("media-playback-start-symbolic")
+        .build();
+
+    let group = PreferencesGroup::builder()
+        .title("Retention")
+        .description("Oldest events are evicted beyond these limits; zero means no limit")
+        .build();
+
+    let retention = config.borrow().live_retention;
+    let max_events_row = SpinRow::builder()
+        .title("Maximum Events")
+        .adjustment(&gtk4::Adjustment::new(
+            retention.max_events as f64,
+            0.0,
+            10_000_000.0,
+            1000.0,
+            10_000.0,
+            0.0,
+        ))
+        .build();
+    let max_age_row = SpinRow::builder()
+        .title("Maximum Age")
+        .subtitle("Seconds before the latest event")
+        .adjustment(&gtk4::Adjustment::new(
+            retention.max_age_secs as f64,
+            0.0,
+            30.0 * 24.0 * 3600.0,
+            60.0,
+            3600.0,
+            0.0,
+        ))
+        .build();
+
+    let update = {
+        let config = config.clone();
+        let max_events_row = max_events_row.clone();
+        let max_age_row = max_age_row.clone();
+        move || {
+            let mut config = config.borrow_mut();
+            config.live_retention = Retention {
+                max_events: max_events_row.value() as usize,
+                max_age_secs: max_age_row.value() as u64,
+            };
+            if let Err(e) = config.save() {
+                eprintln!("Failed to save preferences: {}", e);
+            }
+        }
+    };
+    let update = Rc::new(update);
+    let update_clone = update.clone();
+    max_events_row.connect_value_notify(move |_| update_clone());
+    max_age_row.connect_value_notify(move |_| update());
+
+    group.add(&max_events_row);
+    group.add(&max_age_row);
+    page.add(&group);
+    page
+}
+
+// Template of generated reports, a custom HTML file or the built-in one
+fn create_reports_page(config: Rc<RefCell<Config>>) -> PreferencesPage {
+    let page = PreferencesPage::builder()
+        .title("Reports")
+        .icon_name("x-office-document-symbolic")
+        .build();
+
+    let group = PreferencesGroup::builder()
+        .title("Template")
+        .description("HTML in which {{title}}, {{generated}}, {{metadata}}, {{chart}}, {{stats_table}} and {{findings}} are replaced with the report's contents")
+        .build();
+
+    let template_row = ActionRow::builder()
+        .title("Report Template")
+        .use_markup(false)
+        .build();
+    let show_template = {
+        let template_row = template_row.clone();
+        move |template: Option<&std::path::Path>| {
+            let subtitle = template
+                .map(|path| path.display().to_string())
+                .unwrap_or_else(|| "Built-in".to_string());
+            template_row.set_subtitle(&subtitle);
+        }
+    };
+    show_template(config.borrow().report_template.as_deref());
+
+    let reset_button = gtk4::Button::from_icon_name("edit-undo-symbolic");
+    reset_button.set_valign(gtk4::Align::Center);
+    reset_button.set_tooltip_text(Some("Use Built-in Template"));
+    reset_button.add_css_class("flat");
+    reset_button.set_sensitive(config.borrow().report_template.is_some());
+    let choose_button = gtk4::Button::with_label("Choose…");
+    choose_button.set_valign(gtk4::Align::Center);
+    template_row.add_suffix(&reset_button);
+    template_row.add_suffix(&choose_button);
+
+    let set_template = Rc::new({
+        let reset_button = reset_button.clone();
+        move |template: Option<std::path::PathBuf>| {
+            show_template(template.as_deref());
+            reset_button.set_sensitive(template.is_some());
+            let mut config = config.borrow_mut();
+            config.report_template = template;
+            if let Err(e) = config.save() {
+                eprintln!("Failed to save preferences: {}", e);
+            }
+        }
+    });
+
+    let set_template_clone = set_template.clone();
+    reset_button.connect_clicked(move |_| set_template_clone(None));
+
+    choose_button.connect_clicked(move |button| {
+        let parent = button.root().and_downcast::<gtk4::Window>();
+        let dialog = gtk4::FileChooserDialog::new(
+            Some("Choose Report Template"),
+            parent.as_ref(),
+            gtk4::FileChooserAction::Open,
+            &[
+                ("Cancel", gtk4::ResponseType::Cancel),
+                ("Choose", gtk4::ResponseType::Accept),
+            ],
+        );
+        let filter = gtk4::FileFilter::new();
+        filter.set_name(Some("HTML Files"));
+        filter.add_pattern("*.html");
+        filter.add_pattern("*.htm");
+        dialog.add_filter(&filter);
+
+        let set_template = set_template.clone();
+        dialog.connect_response(move |dialog, response| {
+            if response == gtk4::ResponseType::Accept {
+                if let Some(path) = dialog.file().and_then(|file| file.path()) {
+                    set_template(Some(path));
+                }
+            }
+            dialog.close();
+        });
+        dialog.show();
+    });
+
+    group.add(&template_row);
+    page.add(&group);
+    page
+}
+
+fn create_filters_page(chart: Rc<SunburstChart>, config: Rc<RefCell<Config>>) -> PreferencesPage {
+    let page = PreferencesPage::builder()
+        .title("Filters")
+        .icon_name("funnel-symbolic")
+        .build();
+
+    let rules_group = PreferencesGroup::builder()
+        .title("Exclusion Rules")
+        .description("Events matching any of these regular expressions are left out of the tree of every capture, together with the operations nested in them")
+        .build();
+    for rule in config.borrow().exclude_rules.clone() {
+        add_rule_row(&rules_group, rule, chart.clone(), config.clone());
+    }
+    page.add(&rules_group);
+
+    let new_rule_group = PreferencesGroup::builder().title("New Rule").build();
+    let target_names: Vec<&str> = RuleTarget::ALL.iter().map(|t| t.label()).collect();
+    let target_row = ComboRow::builder()
+        .title("Match Against")
+        .model(&StringList::new(&target_names))
+        .build();
+    let pattern_row = EntryRow::builder()
+        .title("Regular Expression")
+        .show_apply_button(true)
+        .build();
+    new_rule_group.add(&target_row);
+    new_rule_group.add(&pattern_row);
+    page.add(&new_rule_group);
+
+    pattern_row.connect_changed(|row| {
+        row.remove_css_class("error");
+        row.set_tooltip_text(None);
+    });
+
+    pattern_row.connect_apply(move |row| {
+        let pattern = row.text().to_string();
+        if let Err(e) = Regex::new(&pattern) {
+            row.add_css_class("error");
+            row.set_tooltip_text(Some(&e.to_string()));
+            return;
+        }
+        let rule = ExcludeRule {
+            target: RuleTarget::ALL
+                .get(target_row.selected() as usize)
+                .copied()
+                .unwrap_or_default(),
+            pattern,
+        };
+
+        {
+            let mut config = config.borrow_mut();
+            if config.exclude_rules.contains(&rule) {
+                return;
+            }
+            config.exclude_rules.push(rule.clone());
+            save_filters(&config);
+            chart.set_exclude_rules(&config.exclude_rules);
+        }
+        add_rule_row(&rules_group, rule, chart.clone(), config.clone());
+        row.set_text("");
+    });
+
+    page
+}
+
+fn add_rule_row(
+    group: &PreferencesGroup,
+    rule: ExcludeRule,
+    chart: Rc<SunburstChart>,
+    config: Rc<RefCell<Config>>,
+) {
+    let row = ActionRow::builder()
+        .title(&rule.pattern)
+        .subtitle(rule.target.label())
+        .use_markup(false)
+        .build();
+    row.add_css_class("monospace");
+
+    let remove_button = gtk4::Button::from_icon_name("user-trash-symbolic");
+    remove_button.set_valign(gtk4::Align::Center);
+    remove_button.set_tooltip_text(Some("Remove Rule"));
+    remove_button.add_css_class("flat");
+    row.add_suffix(&remove_button);
+
+    let group_clone = group.clone();
+    let row_clone = row.clone();
+    remove_button.connect_clicked(move |_| {
+        let mut config = config.borrow_mut();
+        config.exclude_rules.retain(|r| *r != rule);
+        save_filters(&config);
+        chart.set_exclude_rules(&config.exclude_rules);
+        group_clone.remove(&row_clone);
+    });
+
+    group.add(&row);
+}
+
+fn save_filters(config: &Config) {
+    if let Err(e) = config.save() {
+        eprintln!("Failed to save preferences: {}", e);
+    }
+}
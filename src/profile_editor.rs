@@ -0,0 +1,373 @@
+use crate::compliance::{Profile, Rule, Severity, Target};
+use adw::prelude::*;
+use adw::{
+    AlertDialog, ComboRow, EntryRow, ExpanderRow, PreferencesGroup, ResponseAppearance, SpinRow,
+    SwitchRow,
+};
+use gtk4::StringList;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+// Protocol floors offered, with their TLS version codes; None allows any
+const PROTOCOL_FLOORS: [(&str, u64); 6] = [
+    ("None", 0),
+    ("SSL 3.0", 0x0300),
+    ("TLS 1.0", 0x0301),
+    ("TLS 1.1", 0x0302),
+    ("TLS 1.2", 0x0303),
+    ("TLS 1.3", 0x0304),
+];
+
+const SEVERITIES: [Severity; 3] = [Severity::Ok, Severity::Warning, Severity::Error];
+
+const SEVERITY_LABELS: [&str; 3] = ["Acceptable", "Warning", "Error"];
+
+fn spin_row(title: &str, value: u64, max: f64, step: f64) -> SpinRow {
+    SpinRow::builder()
+        .title(title)
+        .adjustment(&gtk4::Adjustment::new(
+            value as f64,
+            0.0,
+            max,
+            step,
+            step * 10.0,
+            0.0,
+        ))
+        .build()
+}
+
+// Names of a list, comma-separated, as edited in an entry
+fn list_row(title: &str, names: &[String]) -> EntryRow {
+    EntryRow::builder()
+        .title(title)
+        .text(names.join(", "))
+        .build()
+}
+
+fn parse_list(row: &EntryRow) -> Vec<String> {
+    row.text()
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+// Rows editing one rule, folded into an expander titled by the rule
+struct RuleRows {
+    expander: ExpanderRow,
+    target: ComboRow,
+    allow: SwitchRow,
+    pattern: EntryRow,
+    min_bits: SpinRow,
+    severity: ComboRow,
+    from_year: SpinRow,
+    note: EntryRow,
+}
+
+impl RuleRows {
+    fn new(rule: &Rule) -> Self {
+        let target_names: Vec<&str> = Target::ALL.iter().map(|t| t.label()).collect();
+        let rows = Self {
+            expander: ExpanderRow::new(),
+            target: ComboRow::builder()
+                .title("Applies To")
+                .model(&StringList::new(&target_names))
+                .selected(
+                    Target::ALL
+                        .iter()
+                        .position(|t| *t == rule.target)
+                        .unwrap_or(0) as u32,
+                )
+                .build(),
+            allow: SwitchRow::builder()
+                .title("Allow List")
+                .subtitle("Flag everything of the kind that no allow rule matches, instead of what this one matches")
+                .active(rule.allow)
+                .build(),
+            pattern: EntryRow::builder()
+                .title("Algorithm, e.g. SHA1 or TLS 1.0, or * for any")
+                .text(&rule.pattern)
+                .build(),
+            min_bits: spin_row(
+                "Minimum Size in Bits, 0 for Any",
+                rule.min_bits.unwrap_or(0),
+                65536.0,
+                1.0,
+            ),
+            severity: ComboRow::builder()
+                .title("Severity")
+                .model(&StringList::new(&SEVERITY_LABELS))
+                .selected(
+                    SEVERITIES
+                        .iter()
+                        .position(|s| *s == rule.severity)
+                        .unwrap_or(2) as u32,
+                )
+                .build(),
+            from_year: spin_row(
+                "From Year, 0 for Always",
+                rule.from_year.unwrap_or(0) as u64,
+                9999.0,
+                1.0,
+            ),
+            note: EntryRow::builder()
+                .title("Note, e.g. deprecated after 2030")
+                .text(&rule.note)
+                .build(),
+        };
+        rows.expander.add_row(&rows.target);
+        rows.expander.add_row(&rows.allow);
+        rows.expander.add_row(&rows.pattern);
+        rows.expander.add_row(&rows.min_bits);
+        rows.expander.add_row(&rows.severity);
+        rows.expander.add_row(&rows.from_year);
+        rows.expander.add_row(&rows.note);
+        rows.update_title();
+        rows
+    }
+
+    fn rule(&self) -> Rule {
+        let min_bits = self.min_bits.value() as u64;
+        let from_year = self.from_year.value() as i16;
+        let pattern = self.pattern.text().trim().to_string();
+        Rule {
+            target: Target::ALL
+                .get(self.target.selected() as usize)
+                .copied()
+                .unwrap_or(Target::Cipher),
+            allow: self.allow.is_active(),
+            pattern: if pattern.is_empty() {
+                "*".to_string()
+            } else {
+                pattern
+            },
+            min_bits: (min_bits > 0).then_some(min_bits),
+            severity: SEVERITIES
+                .get(self.severity.selected() as usize)
+                .copied()
+                .unwrap_or(Severity::Error),
+            from_year: (from_year > 0).then_some(from_year),
+            note: self.note.text().trim().to_string(),
+        }
+    }
+
+    // Title the expander by what the rule does, e.g. "Deny Hash SHA1", with
+    // its severity, size and year below
+    fn update_title(&self) {
+        let rule = self.rule();
+        let action = if rule.allow { "Allow" } else { "Deny" };
+        self.expander.set_title(&format!(
+            "{} {} {}",
+            action,
+            rule.target.label(),
+            rule.pattern
+        ));
+        let mut subtitle = vec![rule.severity.name().to_string()];
+        if let Some(bits) = rule.min_bits {
+            let size = if rule.allow { "from" } else { "below" };
+            subtitle.push(format!("{} {} bits", size, bits));
+        }
+        if let Some(year) = rule.from_year {
+            subtitle.push(format!("from {}", year));
+        }
+        self.expander.set_subtitle(&subtitle.join(", "));
+    }
+}
+
+// Add the rows of a rule to the group, keeping them in the list until removed
+fn add_rule(group: &PreferencesGroup, rules: &Rc<RefCell<Vec<Rc<RuleRows>>>>, rule: &Rule) {
+    let rows = Rc::new(RuleRows::new(rule));
+
+    let update = {
+        let rows = Rc::downgrade(&rows);
+        move || {
+            if let Some(rows) = rows.upgrade() {
+                rows.update_title();
+            }
+        }
+    };
+    let update = Rc::new(update);
+    let update_clone = update.clone();
+    rows.target.connect_selected_notify(move |_| update_clone());
+    let update_clone = update.clone();
+    rows.allow.connect_active_notify(move |_| update_clone());
+    let update_clone = update.clone();
+    rows.pattern.connect_changed(move |_| update_clone());
+    let update_clone = update.clone();
+    rows.min_bits.connect_value_notify(move |_| update_clone());
+    let update_clone = update.clone();
+    rows.severity
+        .connect_selected_notify(move |_| update_clone());
+    rows.from_year.connect_value_notify(move |_| update());
+
+    let remove_button = gtk4::Button::from_icon_name("user-trash-symbolic");
+    remove_button.set_valign(gtk4::Align::Center);
+    remove_button.add_css_class("flat");
+    remove_button.set_tooltip_text(Some("Remove Rule"));
+    let group_clone = group.clone();
+    let rules_clone = rules.clone();
+    let rows_clone = Rc::downgrade(&rows);
+    remove_button.connect_clicked(move |_| {
+        let Some(rows) = rows_clone.upgrade() else {
+            return;
+        };
+        group_clone.remove(&rows.expander);
+        rules_clone
+            .borrow_mut()
+            .retain(|other| !Rc::ptr_eq(other, &rows));
+    });
+    rows.expander.add_suffix(&remove_button);
+
+    group.add(&rows.expander);
+    rules.borrow_mut().push(rows);
+}
+
+// Edit a custom profile: its protocol floor, minimum sizes, denied
+// algorithms and rules with their severities. Returns None when the dialog
+// was cancelled.
+pub async fn edit_profile(parent: &impl IsA<gtk4::Widget>, profile: &Profile) -> Option<Profile> {
+    let name_row = EntryRow::builder()
+        .title("Name")
+        .text(&profile.name)
+        .build();
+    let floor_names: Vec<&str> = PROTOCOL_FLOORS.iter().map(|(name, _)| *name).collect();
+    let floor_row = ComboRow::builder()
+        .title("Lowest TLS Version")
+        .model(&StringList::new(&floor_names))
+        .selected(
+            PROTOCOL_FLOORS
+                .iter()
+                .rposition(|(_, version)| *version <= profile.min_tls_version)
+                .unwrap_or(0) as u32,
+        )
+        .build();
+    let rsa_row = spin_row(
+        "Minimum RSA and DH Bits",
+        profile.min_rsa_bits,
+        16384.0,
+        1.0,
+    );
+    let kdf_row = spin_row(
+        "Minimum PBKDF2 Iterations",
+        profile.min_kdf_iterations,
+        10_000_000.0,
+        1000.0,
+    );
+    let drbg_row = SwitchRow::builder()
+        .title("Require DRBGs")
+        .subtitle("Only allow SP 800-90A DRBGs as random generators")
+        .active(profile.require_drbg)
+        .build();
+
+    let restrictions = PreferencesGroup::builder()
+        .title("Restrictions")
+        .description("Violations of these are errors")
+        .build();
+    restrictions.add(&name_row);
+    restrictions.add(&floor_row);
+    restrictions.add(&rsa_row);
+    restrictions.add(&kdf_row);
+    restrictions.add(&drbg_row);
+
+    let groups_row = list_row("Denied Groups, e.g. ffdhe2048", &profile.denied_groups);
+    let signatures_row = list_row(
+        "Denied Signatures, e.g. rsa_pkcs1_sha1",
+        &profile.denied_signatures,
+    );
+    let ciphers_row = list_row(
+        "Denied Cipher Weaknesses: NULL, RC4, DES, 3DES, CBC-SHA1",
+        &profile.denied_ciphers,
+    );
+    let hashes_row = list_row("Denied Hashes, e.g. MD5, SHA1", &profile.denied_hashes);
+    let denied = PreferencesGroup::builder()
+        .title("Denied Algorithms")
+        .build();
+    denied.add(&groups_row);
+    denied.add(&signatures_row);
+    denied.add(&ciphers_row);
+    denied.add(&hashes_row);
+
+    let rules_group = PreferencesGroup::builder()
+        .title("Rules")
+        .description("Allowed and denied algorithms with the severity of their findings")
+        .build();
+    let rules = Rc::new(RefCell::new(Vec::new()));
+    for rule in &profile.rules {
+        add_rule(&rules_group, &rules, rule);
+    }
+    let add_button = gtk4::Button::from_icon_name("list-add-symbolic");
+    add_button.add_css_class("flat");
+    add_button.set_tooltip_text(Some("Add Rule"));
+    let rules_group_clone = rules_group.clone();
+    let rules_clone = rules.clone();
+    add_button.connect_clicked(move |_| {
+        let rule = Rule {
+            target: Target::Cipher,
+            allow: false,
+            pattern: String::new(),
+            min_bits: None,
+            severity: Severity::Error,
+            from_year: None,
+            note: String::new(),
+        };
+        add_rule(&rules_group_clone, &rules_clone, &rule);
+    });
+    rules_group.set_header_suffix(Some(&add_button));
+
+    let content = gtk4::Box::new(gtk4::Orientation::Vertical, 18);
+    content.append(&restrictions);
+    content.append(&denied);
+    content.append(&rules_group);
+    let scrolled = gtk4::ScrolledWindow::builder()
+        .hscrollbar_policy(gtk4::PolicyType::Never)
+        .min_content_height(420)
+        .child(&content)
+        .build();
+
+    let dialog = AlertDialog::builder()
+        .heading("Custom Compliance Profile")
+        .extra_child(&scrolled)
+        .close_response("cancel")
+        .default_response("save")
+        .build();
+    dialog.add_response("cancel", "Cancel");
+    dialog.add_response("save", "Save");
+    dialog.set_response_appearance("save", ResponseAppearance::Suggested);
+
+    let name = name_row.clone();
+    let name_clone = name.clone();
+    let dialog_clone = dialog.clone();
+    let update_save = move || {
+        let valid = Profile::builtin(name_clone.text().trim()).is_none()
+            && !name_clone.text().trim().is_empty();
+        dialog_clone.set_response_enabled("save", valid);
+        if valid {
+            name_clone.remove_css_class("error");
+        } else {
+            name_clone.add_css_class("error");
+        }
+    };
+    update_save();
+    name.connect_changed(move |_| update_save());
+
+    if dialog.clone().choose_future(parent).await != "save" {
+        return None;
+    }
+    let rules = rules.borrow().iter().map(|rows| rows.rule()).collect();
+    Some(Profile {
+        name: name_row.text().trim().to_string(),
+        approximated: false,
+        min_tls_version: PROTOCOL_FLOORS
+            .get(floor_row.selected() as usize)
+            .map_or(0, |(_, version)| *version),
+        min_rsa_bits: rsa_row.value() as u64,
+        denied_groups: parse_list(&groups_row),
+        denied_signatures: parse_list(&signatures_row),
+        denied_ciphers: parse_list(&ciphers_row),
+        denied_hashes: parse_list(&hashes_row),
+        min_kdf_iterations: kdf_row.value() as u64,
+        require_drbg: drbg_row.is_active(),
+        rules,
+    })
+}
@@ -0,0 +1,266 @@
+use crate::data::AuditEvent;
+use crate::decoders;
+use crate::gcrypt;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+// Crypto libraries whose events differ from the keys and encodings the
+// decoders expect, which a profile converts them to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Library {
+    GnuTls,
+    OpenSsl,
+    // Its events have a namespace of their own, see gcrypt::decode
+    Gcrypt,
+}
+
+const VERSION: &str = "tls::protocol_version";
+const SUITE: &str = "tls::ciphersuite";
+const GROUP: &str = "tls::group";
+const SIGNATURE: &str = "tls::signature_algorithm";
+
+// Lookup table of a codepoint registry, as in decoders
+type NameOf = fn(u64) -> Option<&'static str>;
+
+// Fields the decoders take as numeric IANA codes, with the table naming them
+const CODE_FIELDS: [(&str, NameOf); 4] = [
+    (VERSION, decoders::protocol_version_name),
+    (SUITE, decoders::ciphersuite_name),
+    (GROUP, decoders::group_name),
+    (SIGNATURE, decoders::signature_scheme_name),
+];
+
+// Keys the libraries record under other names, with the name the decoders read
+const GNUTLS_KEYS: &[(&str, &str)] = &[
+    ("tls::version", VERSION),
+    ("tls::kx_group", GROUP),
+    ("tls::sign_algorithm", SIGNATURE),
+    ("pk::key_bits", "pk::bits"),
+    ("x509::pk_algorithm", "x509::public_key_algorithm"),
+    ("x509::pk_bits", "x509::public_key_bits"),
+];
+const OPENSSL_KEYS: &[(&str, &str)] = &[
+    ("tls::version", VERSION),
+    ("tls::cipher", SUITE),
+    ("tls::cipher_suite", SUITE),
+    ("tls::negotiated_group", GROUP),
+    ("tls::sigalg", SIGNATURE),
+    ("pk::key_bits", "pk::bits"),
+];
+
+// Names the libraries give codes, where they differ from the registered ones
+// beyond case and punctuation
+const GNUTLS_NAMES: &[(&str, &str, u64)] = &[
+    (SIGNATURE, "RSA-SHA1", 0x0201),
+    (SIGNATURE, "RSA-SHA256", 0x0401),
+    (SIGNATURE, "RSA-SHA384", 0x0501),
+    (SIGNATURE, "RSA-SHA512", 0x0601),
+    (SIGNATURE, "ECDSA-SHA1", 0x0203),
+    (SIGNATURE, "EdDSA-Ed25519", 0x0807),
+    (SIGNATURE, "EdDSA-Ed448", 0x0808),
+];
+const OPENSSL_NAMES: &[(&str, &str, u64)] = &[
+    (VERSION, "SSLv3", 0x0300),
+    (VERSION, "TLSv1", 0x0301),
+    (VERSION, "TLSv1.1", 0x0302),
+    (VERSION, "TLSv1.2", 0x0303),
+    (VERSION, "TLSv1.3", 0x0304),
+    (GROUP, "prime256v1", 0x0017),
+    (GROUP, "P-256", 0x0017),
+    (GROUP, "P-384", 0x0018),
+    (GROUP, "P-521", 0x0019),
+    (SUITE, "RC4-MD5", 0x0004),
+    (SUITE, "RC4-SHA", 0x0005),
+    (SUITE, "DES-CBC3-SHA", 0x000a),
+    (SUITE, "AES128-SHA", 0x002f),
+    (SUITE, "AES256-SHA", 0x0035),
+    (SUITE, "AES128-SHA256", 0x003c),
+    (SUITE, "AES256-SHA256", 0x003d),
+    (SUITE, "AES128-GCM-SHA256", 0x009c),
+    (SUITE, "AES256-GCM-SHA384", 0x009d),
+    (SUITE, "DHE-RSA-AES128-SHA", 0x0033),
+    (SUITE, "DHE-RSA-AES256-SHA", 0x0039),
+    (SUITE, "DHE-RSA-AES128-GCM-SHA256", 0x009e),
+    (SUITE, "DHE-RSA-AES256-GCM-SHA384", 0x009f),
+    (SUITE, "DHE-RSA-CHACHA20-POLY1305", 0xccaa),
+    (SUITE, "ECDHE-ECDSA-AES128-SHA", 0xc009),
+    (SUITE, "ECDHE-ECDSA-AES256-SHA", 0xc00a),
+    (SUITE, "ECDHE-RSA-AES128-SHA", 0xc013),
+    (SUITE, "ECDHE-RSA-AES256-SHA", 0xc014),
+    (SUITE, "ECDHE-ECDSA-AES128-SHA256", 0xc023),
+    (SUITE, "ECDHE-ECDSA-AES256-SHA384", 0xc024),
+    (SUITE, "ECDHE-RSA-AES128-SHA256", 0xc027),
+    (SUITE, "ECDHE-RSA-AES256-SHA384", 0xc028),
+    (SUITE, "ECDHE-ECDSA-AES128-GCM-SHA256", 0xc02b),
+    (SUITE, "ECDHE-ECDSA-AES256-GCM-SHA384", 0xc02c),
+    (SUITE, "ECDHE-RSA-AES128-GCM-SHA256", 0xc02f),
+    (SUITE, "ECDHE-RSA-AES256-GCM-SHA384", 0xc030),
+    (SUITE, "ECDHE-RSA-CHACHA20-POLY1305", 0xcca8),
+    (SUITE, "ECDHE-ECDSA-CHACHA20-POLY1305", 0xcca9),
+];
+
+// Public key algorithm names of the libraries, with those of the decoders
+const GNUTLS_ALGORITHMS: &[(&str, &str)] = &[
+    ("EC/ECDSA", "ECDSA"),
+    ("EdDSA (Ed25519)", "Ed25519"),
+    ("EdDSA (Ed448)", "Ed448"),
+];
+const OPENSSL_ALGORITHMS: &[(&str, &str)] = &[
+    ("rsaEncryption", "RSA"),
+    ("RSASSA-PSS", "RSA-PSS"),
+    ("id-ecPublicKey", "ECDSA"),
+    ("ED25519", "Ed25519"),
+    ("ED448", "Ed448"),
+];
+
+// Name of a code reduced to what the libraries agree on: letters and digits
+// in lowercase, with GnuTLS' prefix of suites as the registered one and
+// without the "WITH" of TLS 1.2 suites
+fn reduce(name: &str) -> String {
+    let name = name.to_lowercase();
+    let name = match name.strip_prefix("gnutls_") {
+        Some(rest) => format!("tls_{}", rest),
+        None => name,
+    };
+    name.replace("_with_", "_")
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .collect()
+}
+
+// Codes of the registered names of each code field, by reduced name
+fn registered_codes() -> &'static [HashMap<String, u64>; 4] {
+    static CODES: OnceLock<[HashMap<String, u64>; 4]> = OnceLock::new();
+    CODES.get_or_init(|| {
+        CODE_FIELDS.map(|(_, name_of)| {
+            (0..=0xffff)
+                .filter_map(|code| Some((reduce(name_of(code)?), code)))
+                .collect()
+        })
+    })
+}
+
+impl Library {
+    // Library an event origin is, such as "/usr/lib64/libgnutls.so.30"
+    pub fn of(origin: &str) -> Option<Self> {
+        let origin = origin.to_lowercase();
+        if origin.contains("gnutls") {
+            Some(Self::GnuTls)
+        } else if origin.contains("gcrypt") {
+            Some(Self::Gcrypt)
+        } else if ["openssl", "libssl", "libcrypto"]
+            .iter()
+            .any(|name| origin.contains(name))
+        {
+            Some(Self::OpenSsl)
+        } else {
+            None
+        }
+    }
+
+    fn keys(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Self::GnuTls => GNUTLS_KEYS,
+            Self::OpenSsl => OPENSSL_KEYS,
+            Self::Gcrypt => &[],
+        }
+    }
+
+    fn names(&self) -> &'static [(&'static str, &'static str, u64)] {
+        match self {
+            Self::GnuTls => GNUTLS_NAMES,
+            Self::OpenSsl => OPENSSL_NAMES,
+            Self::Gcrypt => &[],
+        }
+    }
+
+    fn algorithms(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Self::GnuTls => GNUTLS_ALGORITHMS,
+            Self::OpenSsl => OPENSSL_ALGORITHMS,
+            Self::Gcrypt => &[],
+        }
+    }
+
+    // Code of a code field from the way the library wrote it: a name, or a
+    // number in a string, e.g. "0x1301"
+    fn code(&self, field: usize, value: &str) -> Option<u64> {
+        let value = value.trim();
+        if let Some(hex) = value
+            .strip_prefix("0x")
+            .or_else(|| value.strip_prefix("0X"))
+        {
+            return u64::from_str_radix(hex, 16).ok();
+        }
+        if let Ok(code) = value.parse() {
+            return Some(code);
+        }
+        let reduced = reduce(value);
+        registered_codes()[field]
+            .get(&reduced)
+            .copied()
+            .or_else(|| {
+                self.names()
+                    .iter()
+                    .find(|(key, name, _)| *key == CODE_FIELDS[field].0 && reduce(name) == reduced)
+                    .map(|(_, _, code)| *code)
+            })
+    }
+
+    // Convert fields of an event of the library to the keys and encodings the
+    // decoders read, leaving those it can't convert as they are
+    fn normalize(&self, fields: &mut HashMap<String, Value>) {
+        if *self == Self::Gcrypt {
+            gcrypt::decode(fields);
+        }
+        for (key, canonical) in self.keys() {
+            if !fields.contains_key(*canonical) {
+                if let Some(value) = fields.remove(*key) {
+                    fields.insert(canonical.to_string(), value);
+                }
+            }
+        }
+        for (field, (key, _)) in CODE_FIELDS.iter().enumerate() {
+            let Some(Value::String(value)) = fields.get(*key) else {
+                continue;
+            };
+            if let Some(code) = self.code(field, value) {
+                fields.insert(key.to_string(), Value::from(code));
+            }
+        }
+        for key in ["pk::algorithm", "x509::public_key_algorithm"] {
+            let Some(Value::String(value)) = fields.get(key) else {
+                continue;
+            };
+            if let Some((_, canonical)) = self.algorithms().iter().find(|(name, _)| name == value) {
+                fields.insert(key.to_string(), Value::from(*canonical));
+            }
+        }
+        for key in ["pk::bits", "x509::public_key_bits"] {
+            if let Some(Value::String(bits)) = fields.get(key) {
+                if let Ok(bits) = bits.trim().parse::<u64>() {
+                    fields.insert(key.to_string(), Value::from(bits));
+                }
+            }
+        }
+    }
+}
+
+// Decode events of GnuTLS, OpenSSL and libgcrypt, and their spans, by the
+// profile of their origin, so all are shown alike rather than as raw values.
+// Events of libgcrypt's namespace are told by their name as well.
+pub fn normalize(events: &mut [AuditEvent]) {
+    for event in events {
+        let library = Library::of(&event.origin).or_else(|| {
+            event
+                .name()
+                .starts_with("gcry::")
+                .then_some(Library::Gcrypt)
+        });
+        if let Some(library) = library {
+            library.normalize(&mut event.events);
+        }
+        normalize(&mut event.spans);
+    }
+}
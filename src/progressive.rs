@@ -0,0 +1,267 @@
+use crate::data::{AuditEvent, Capture, CaptureMetadata};
+use crate::live;
+use crate::sunburst::SunburstChart;
+use anyhow::{anyhow, Result};
+use gtk4::{gio, glib};
+use std::cell::Cell;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::Path;
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+
+// Bytes parsed before the chart is first shown. Each following chunk is twice
+// as large, so the tree is rebuilt a logarithmic number of times.
+const FIRST_CHUNK: usize = 1 << 20;
+
+thread_local! {
+    // Bumped by every load, so a load that was superseded stops updating the chart
+    static GENERATION: Cell<u64> = const { Cell::new(0) };
+}
+
+// Stop a progressive load in progress, e.g. because another capture is being shown
+pub fn cancel() -> u64 {
+    GENERATION.with(|generation| {
+        generation.set(generation.get() + 1);
+        generation.get()
+    })
+}
+
+fn is_current(generation: u64) -> bool {
+    GENERATION.with(|current| current.get() == generation)
+}
+
+async fn run<T: Send + 'static>(f: impl FnOnce() -> Result<T> + Send + 'static) -> Result<T> {
+    gio::spawn_blocking(f)
+        .await
+        .map_err(|_| anyhow!("Loading was interrupted"))?
+}
+
+// Show the first events of a capture read from `path`
+fn show(chart: &SunburstChart, path: &Path, hash: Option<String>, events: Vec<AuditEvent>) {
+    chart.set_metadata(None);
+    chart.set_source_host(None);
+    chart.set_source_hash(hash);
+    chart.set_data(events);
+    chart.set_source_path(fs::canonicalize(path).ok());
+}
+
+fn checksum(bytes: &[u8]) -> String {
+    glib::compute_checksum_for_data(glib::ChecksumType::Sha256, bytes)
+        .map(|hash| hash.to_string())
+        .unwrap_or_default()
+}
+
+// Where the array of events starts and the metadata header before it, read
+// from the start of the file. A header is parsed as a capture without events.
+fn header(bytes: &[u8]) -> Result<(usize, Option<CaptureMetadata>)> {
+    let offset = live::events_offset(bytes)?;
+    if bytes[..offset].iter().all(u8::is_ascii_whitespace) {
+        return Ok((offset, None));
+    }
+    let mut header = bytes[..offset].to_vec();
+    header.extend_from_slice(b"[]}");
+    let capture = Capture::parse(std::str::from_utf8(&header)?)?;
+    Ok((offset, capture.metadata))
+}
+
+// Load a capture file, showing the chart with its first events while the rest
+// are read and parsed in the background and refining it as more arrive. The
+// file is hashed in the background too. `progress` is called with the share
+// of the file parsed so far, and with None once done. Captures with a
+// metadata header are streamed from their "events" array alike.
+pub async fn load(
+    path: &Path,
+    chart: &SunburstChart,
+    progress: impl Fn(Option<f64>),
+) -> Result<()> {
+    let generation = cancel();
+    progress(Some(0.0));
+    let result = load_chunks(path, chart, generation, &progress).await;
+    if is_current(generation) {
+        progress(None);
+    }
+    result
+}
+
+// The rest of the file being read in the background, joined
+async fn join(read: gio::JoinHandle<std::io::Result<Vec<u8>>>) -> Result<Arc<Vec<u8>>> {
+    let bytes = read
+        .await
+        .map_err(|_| anyhow!("Loading was interrupted"))??;
+    Ok(Arc::new(bytes))
+}
+
+async fn load_chunks(
+    path: &Path,
+    chart: &SunburstChart,
+    generation: u64,
+    progress: &impl Fn(Option<f64>),
+) -> Result<()> {
+    // Only the start of the file is read before the first events are shown
+    let file = path.to_path_buf();
+    let (head, file_size) = run(move || {
+        let file = File::open(file)?;
+        let file_size = file.metadata()?.len() as usize;
+        let mut head = Vec::new();
+        file.take(FIRST_CHUNK as u64 * 2).read_to_end(&mut head)?;
+        Ok((head, file_size))
+    })
+    .await?;
+    let mut bytes = Arc::new(head);
+    let mut rest = None;
+    let mut hash = None;
+    if bytes.len() < file_size {
+        let file = path.to_path_buf();
+        rest = Some(gio::spawn_blocking(move || fs::read(file)));
+    } else {
+        hash = Some(checksum(&bytes));
+    }
+
+    let header = match header(&bytes) {
+        Ok(header) => header,
+        // A header larger than the start of the file
+        Err(_) if rest.is_some() => {
+            bytes = join(rest.take().unwrap()).await?;
+            header(&bytes)?
+        }
+        Err(e) => return Err(e),
+    };
+    let (events_start, mut metadata) = header;
+    // Hashed once the whole file has been read, while parsing goes on
+    let hashed = Arc::new(OnceLock::new());
+    let mut hashing = None;
+
+    let mut events: Vec<AuditEvent> = Vec::new();
+    let mut offset = events_start;
+    let mut chunk = FIRST_CHUNK;
+    // Time spent parsing the chunks, not counting the rebuilds in between
+    let mut parse = Duration::ZERO;
+    loop {
+        if rest.is_none() && hash.is_none() && hashing.is_none() {
+            let content = bytes.clone();
+            let hashed = hashed.clone();
+            hashing = Some(gio::spawn_blocking(move || {
+                let _ = hashed.set(checksum(&content));
+            }));
+        }
+
+        let content = bytes.clone();
+        let start = Instant::now();
+        let at_start = offset == events_start;
+        let (parsed, consumed) =
+            run(move || live::parse_elements::<AuditEvent>(&content[offset..], at_start, chunk))
+                .await?;
+        if !is_current(generation) {
+            return Ok(());
+        }
+        parse += start.elapsed();
+        chart.set_parse_timing(Some(file_size), Some(parse));
+        offset += consumed;
+
+        if parsed.is_empty() {
+            // The start of the file ran out, go on with all of it
+            if let Some(read) = rest.take() {
+                bytes = join(read).await?;
+                continue;
+            }
+            break;
+        }
+        chunk *= 2;
+
+        let mut capture = Capture {
+            metadata: metadata.clone(),
+            events: parsed,
+        };
+        capture.resolve_boots();
+        let first = events.is_empty();
+        events.extend(capture.events);
+        if hash.is_none() {
+            hash = hashed.get().cloned();
+        }
+        if first {
+            show(chart, path, hash.clone(), events.clone());
+            chart.set_metadata(metadata.clone());
+        } else {
+            chart.set_source_hash(hash.clone());
+            chart.update_data(events.clone());
+        }
+        progress(Some(offset as f64 / file_size.max(1) as f64));
+
+        // Read the rest of the file once the first events are shown
+        if let Some(read) = rest.take() {
+            bytes = join(read).await?;
+        }
+    }
+
+    // The array must be closed past the last event
+    let close = bytes[offset..]
+        .iter()
+        .position(|b| !b.is_ascii_whitespace() && *b != b',')
+        .map(|position| offset + position);
+    let Some(close) = close.filter(|close| bytes[*close] == b']') else {
+        return Err(anyhow!("The capture file ends before its last event"));
+    };
+    // A metadata header may follow the events too
+    let is_object = bytes[..events_start]
+        .iter()
+        .any(|b| !b.is_ascii_whitespace());
+    if is_object && metadata.is_none() {
+        let mut trailer = b"{\"events\":[]".to_vec();
+        trailer.extend_from_slice(&bytes[close + 1..]);
+        metadata = Capture::parse(std::str::from_utf8(&trailer)?)?.metadata;
+        if metadata.is_some() {
+            let mut capture = Capture {
+                metadata: metadata.clone(),
+                events: std::mem::take(&mut events),
+            };
+            capture.resolve_boots();
+            events = capture.events;
+        }
+    }
+
+    let updated = hash.is_some();
+    if let Some(hashing) = hashing {
+        let _ = hashing.await;
+        hash = hashed.get().cloned();
+    }
+    if !is_current(generation) {
+        return Ok(());
+    }
+    if events.is_empty() {
+        show(chart, path, hash, events);
+        chart.set_metadata(metadata);
+    } else if !updated || metadata.is_some() && chart.metadata().is_none() {
+        // Shown before the hash or the trailing metadata were known
+        chart.set_source_hash(hash);
+        chart.set_metadata(metadata);
+        chart.update_data(events);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn header_of_bare_array() {
+        let (offset, metadata) = header(b"\n [{\"context\": \"a\"").unwrap();
+        assert_eq!(offset, 2);
+        assert!(metadata.is_none());
+    }
+
+    #[test]
+    fn header_before_events() {
+        let bytes = b"{\"metadata\": {\"hostname\": \"web-01\"}, \"events\": [{\"context\"";
+        let (offset, metadata) = header(bytes).unwrap();
+        assert_eq!(bytes[offset], b'[');
+        assert_eq!(metadata.unwrap().hostname.as_deref(), Some("web-01"));
+    }
+
+    #[test]
+    fn header_of_unsupported_version() {
+        assert!(header(b"{\"format_version\": 99, \"events\": []}").is_err());
+        assert!(header(b"{\"metadata\": {\"hostname\": ").is_err());
+    }
+}
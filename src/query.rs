@@ -0,0 +1,601 @@
+use crate::data::{AuditEvent, NodeKind, TreeNode};
+use crate::decoders;
+use anyhow::{bail, Result};
+use regex::Regex;
+use serde_json::Value;
+use std::collections::HashMap;
+
+// Filter expressions over events, e.g.
+// `origin == "gnutls" && name =~ "tls::sign" && bits < 2048`
+//
+// A comparison names a property of an event: its name, origin, context,
+// process, category, details (the description the tree shows) or duration, or
+// one of its fields, by its full name or the part after "::". Codepoint fields
+// compare to names too, as in `ciphersuite == "TLS_AES_128_GCM_SHA256"`.
+// Comparisons combine with &&, || and !, and group with parentheses.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operator {
+    Equal,
+    NotEqual,
+    Matches,
+    NotMatches,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Literal {
+    String(String),
+    Number(f64),
+    Bool(bool),
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Compare {
+        property: String,
+        operator: Operator,
+        literal: Literal,
+        // Compiled pattern of =~ and !~
+        regex: Option<Regex>,
+    },
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Literal(Literal),
+    Operator(Operator),
+    And,
+    Or,
+    Not,
+    Open,
+    Close,
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+        let (token, length) = match (c, next) {
+            ('&', Some('&')) => (Token::And, 2),
+            ('|', Some('|')) => (Token::Or, 2),
+            ('=', Some('=')) => (Token::Operator(Operator::Equal), 2),
+            ('=', Some('~')) => (Token::Operator(Operator::Matches), 2),
+            ('!', Some('=')) => (Token::Operator(Operator::NotEqual), 2),
+            ('!', Some('~')) => (Token::Operator(Operator::NotMatches), 2),
+            ('<', Some('=')) => (Token::Operator(Operator::LessOrEqual), 2),
+            ('>', Some('=')) => (Token::Operator(Operator::GreaterOrEqual), 2),
+            ('<', _) => (Token::Operator(Operator::Less), 1),
+            ('>', _) => (Token::Operator(Operator::Greater), 1),
+            ('!', _) => (Token::Not, 1),
+            ('(', _) => (Token::Open, 1),
+            (')', _) => (Token::Close, 1),
+            ('"', _) => {
+                let mut value = String::new();
+                let mut end = i + 1;
+                loop {
+                    match chars.get(end) {
+                        None => bail!("Unterminated string at position {}", i + 1),
+                        Some('"') => break,
+                        Some('\\') => {
+                            end += 1;
+                            match chars.get(end) {
+                                Some(escaped) => value.push(*escaped),
+                                None => bail!("Unterminated string at position {}", i + 1),
+                            }
+                        }
+                        Some(c) => value.push(*c),
+                    }
+                    end += 1;
+                }
+                (Token::Literal(Literal::String(value)), end + 1 - i)
+            }
+            _ if c.is_ascii_digit() || (c == '-' && next.is_some_and(|n| n.is_ascii_digit())) => {
+                let end = (i + 1..chars.len())
+                    .find(|&j| !(chars[j].is_ascii_alphanumeric() || chars[j] == '.'))
+                    .unwrap_or(chars.len());
+                let word: String = chars[i..end].iter().collect();
+                let number = match word.strip_prefix("0x") {
+                    Some(hex) => u64::from_str_radix(hex, 16).ok().map(|n| n as f64),
+                    None => word.parse().ok(),
+                };
+                match number {
+                    Some(number) => (Token::Literal(Literal::Number(number)), end - i),
+                    None => bail!("Invalid number \"{}\" at position {}", word, i + 1),
+                }
+            }
+            _ if c.is_alphabetic() || c == '_' => {
+                let end = (i + 1..chars.len())
+                    .find(|&j| !(chars[j].is_alphanumeric() || chars[j] == '_' || chars[j] == ':'))
+                    .unwrap_or(chars.len());
+                let word: String = chars[i..end].iter().collect();
+                let token = match word.as_str() {
+                    "true" => Token::Literal(Literal::Bool(true)),
+                    "false" => Token::Literal(Literal::Bool(false)),
+                    _ => Token::Ident(word),
+                };
+                (token, end - i)
+            }
+            _ => bail!("Unexpected \"{}\" at position {}", c, i + 1),
+        };
+        tokens.push(token);
+        i += length;
+    }
+    Ok(tokens)
+}
+
+// Recursive descent over the tokens; && binds tighter than ||
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn or(&mut self) -> Result<Expr> {
+        let mut expr = self.and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.position += 1;
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr> {
+        let mut expr = self.unary()?;
+        while self.peek() == Some(&Token::And) {
+            self.position += 1;
+            expr = Expr::And(Box::new(expr), Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Expr> {
+        match self.next() {
+            Some(Token::Not) => Ok(Expr::Not(Box::new(self.unary()?))),
+            Some(Token::Open) => {
+                let expr = self.or()?;
+                match self.next() {
+                    Some(Token::Close) => Ok(expr),
+                    _ => bail!("Expected \")\""),
+                }
+            }
+            Some(Token::Ident(property)) => {
+                let Some(Token::Operator(operator)) = self.next() else {
+                    bail!("Expected a comparison after \"{}\"", property);
+                };
+                let Some(Token::Literal(literal)) = self.next() else {
+                    bail!("Expected a value to compare \"{}\" with", property);
+                };
+                let regex = match (operator, &literal) {
+                    (Operator::Matches | Operator::NotMatches, Literal::String(pattern)) => {
+                        Some(Regex::new(pattern)?)
+                    }
+                    (Operator::Matches | Operator::NotMatches, _) => {
+                        bail!("Expected a pattern to match \"{}\" with", property)
+                    }
+                    _ => None,
+                };
+                Ok(Expr::Compare {
+                    property,
+                    operator,
+                    literal,
+                    regex,
+                })
+            }
+            Some(_) => bail!("Expected a comparison"),
+            None => bail!("Unexpected end of expression"),
+        }
+    }
+}
+
+// Decoded name of a codepoint field's value, for comparing with names
+fn decoded_name(key: &str, code: u64) -> Option<&'static str> {
+    match key {
+        "tls::protocol_version" => decoders::protocol_version_name(code),
+        "tls::ciphersuite" => decoders::ciphersuite_name(code),
+        "tls::group" => decoders::group_name(code),
+        "tls::signature_algorithm" | "x509::signature_algorithm" => {
+            decoders::signature_scheme_name(code)
+        }
+        _ => None,
+    }
+}
+
+// Event being matched, with the process of its context
+struct Subject<'a> {
+    event: &'a AuditEvent,
+    process: Option<&'a str>,
+}
+
+impl Subject<'_> {
+    // Value of a property, with the decoded name of a codepoint; None if the
+    // event doesn't have it
+    fn value(&self, property: &str) -> Option<(Value, Option<&'static str>)> {
+        let event = self.event;
+        let value = match property {
+            "name" => Value::from(event.name()),
+            "origin" => Value::from(event.origin.clone()),
+            "context" => Value::from(event.context.clone()),
+            "process" => Value::from(self.process?),
+            "category" => Value::from(decoders::category(&event.name())?),
+            "details" => Value::from(event.format_details()),
+            "duration" => Value::from(event.end.saturating_sub(event.start)),
+            _ => {
+                let suffix = format!("::{}", property);
+                let (key, value) = event.events.get_key_value(property).or_else(|| {
+                    event
+                        .events
+                        .iter()
+                        .filter(|(key, _)| key.ends_with(&suffix))
+                        .min_by_key(|(key, _)| key.as_str())
+                })?;
+                let name = value.as_u64().and_then(|code| decoded_name(key, code));
+                return Some((value.clone(), name));
+            }
+        };
+        Some((value, None))
+    }
+}
+
+fn number_of(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(number) => number.as_f64(),
+        Value::String(text) => text.parse().ok(),
+        _ => None,
+    }
+}
+
+fn text_of(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+impl Expr {
+    fn matches(&self, subject: &Subject) -> bool {
+        match self {
+            Expr::And(a, b) => a.matches(subject) && b.matches(subject),
+            Expr::Or(a, b) => a.matches(subject) || b.matches(subject),
+            Expr::Not(expr) => !expr.matches(subject),
+            Expr::Compare {
+                property,
+                operator,
+                literal,
+                regex,
+            } => {
+                let Some((value, name)) = subject.value(property) else {
+                    // A missing property differs from everything
+                    return matches!(operator, Operator::NotEqual | Operator::NotMatches);
+                };
+                let equal = match literal {
+                    Literal::String(text) => {
+                        name.is_some_and(|name| name.eq_ignore_ascii_case(text))
+                            || text_of(&value) == *text
+                    }
+                    Literal::Number(number) => number_of(&value) == Some(*number),
+                    Literal::Bool(bool) => value.as_bool() == Some(*bool),
+                };
+                let is_match = |regex: &Regex| {
+                    regex.is_match(&text_of(&value))
+                        || name.is_some_and(|name| regex.is_match(name))
+                };
+                let compare = |f: fn(f64, f64) -> bool| match (number_of(&value), literal) {
+                    (Some(value), Literal::Number(number)) => f(value, *number),
+                    _ => false,
+                };
+                match operator {
+                    Operator::Equal => equal,
+                    Operator::NotEqual => !equal,
+                    Operator::Matches => regex.as_ref().is_some_and(is_match),
+                    Operator::NotMatches => !regex.as_ref().is_some_and(is_match),
+                    Operator::Less => compare(|a, b| a < b),
+                    Operator::LessOrEqual => compare(|a, b| a <= b),
+                    Operator::Greater => compare(|a, b| a > b),
+                    Operator::GreaterOrEqual => compare(|a, b| a >= b),
+                }
+            }
+        }
+    }
+}
+
+// A parsed filter expression, along with its text and the name it's saved
+// under, if any
+#[derive(Debug, Clone)]
+pub struct Query {
+    text: String,
+    name: Option<String>,
+    expr: Expr,
+}
+
+impl Query {
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut parser = Parser {
+            tokens: tokenize(text)?,
+            position: 0,
+        };
+        let expr = parser.or()?;
+        if parser.position < parser.tokens.len() {
+            bail!("Expected && or || after a comparison");
+        }
+        Ok(Self {
+            text: text.trim().to_string(),
+            name: None,
+            expr,
+        })
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub fn with_name(self, name: Option<String>) -> Self {
+        Self { name, ..self }
+    }
+
+    fn matches(&self, event: &AuditEvent, process: Option<&str>) -> bool {
+        self.expr.matches(&Subject { event, process })
+            || event.spans.iter().any(|span| self.matches(span, process))
+    }
+
+    // Top-level events that match, or with a span that does, together with
+    // all of their spans
+    pub fn filter(&self, events: &[AuditEvent]) -> Vec<AuditEvent> {
+        // The process of a context, found as the tree finds it
+        let no_fields = HashMap::new();
+        let mut fields: HashMap<&str, &HashMap<String, Value>> = HashMap::new();
+        for event in events {
+            let context_fields = fields.entry(&event.context).or_insert(&no_fields);
+            if context_fields.is_empty() && decoders::process_name(&event.events, "").is_some() {
+                *context_fields = &event.events;
+            }
+        }
+        let processes: HashMap<&str, Option<String>> = fields
+            .into_iter()
+            .map(|(context, fields)| (context, decoders::process_name(fields, context)))
+            .collect();
+        events
+            .iter()
+            .filter(|event| {
+                let process = processes.get(event.context.as_str()).cloned().flatten();
+                self.matches(event, process.as_deref())
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+// String literal of an expression, with quotes and backslashes escaped
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+// Expression selecting the subtree at the path of nodes below the root: the
+// process and context it's in, and the event or category it's rooted at. Only
+// the innermost one counts, as an event can't be two of them.
+pub fn subtree_query(path: &[&TreeNode]) -> String {
+    let mut terms = Vec::new();
+    for node in path {
+        match node.kind {
+            NodeKind::Process => {
+                if let Some(process) = &node.process {
+                    terms.push(format!("process == {}", quote(process)));
+                }
+            }
+            NodeKind::Context => terms.push(format!("context == {}", quote(&node.name))),
+            _ => {}
+        }
+    }
+    let innermost = path
+        .iter()
+        .rev()
+        .find(|node| matches!(node.kind, NodeKind::Event | NodeKind::Category));
+    match innermost {
+        Some(node) if node.kind == NodeKind::Category => {
+            terms.push(format!("category == {}", quote(&node.name)))
+        }
+        Some(node) => terms.push(format!("details == {}", quote(&node.name))),
+        None => {}
+    }
+    terms.join(" && ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn event(fields: Value) -> AuditEvent {
+        AuditEvent {
+            context: "0a1b".to_string(),
+            origin: "gnutls".to_string(),
+            start: 100,
+            end: 150,
+            events: serde_json::from_value(fields).unwrap(),
+            spans: Vec::new(),
+            boot_id: None,
+        }
+    }
+
+    fn matches(text: &str, event: &AuditEvent) -> bool {
+        Query::parse(text).unwrap().matches(event, None)
+    }
+
+    #[test]
+    fn tokenize_comparisons() {
+        let tokens = tokenize(r#"origin == "gnutls" && tls::bits <= 0x800"#).unwrap();
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Ident("origin".to_string()),
+                Token::Operator(Operator::Equal),
+                Token::Literal(Literal::String("gnutls".to_string())),
+                Token::And,
+                Token::Ident("tls::bits".to_string()),
+                Token::Operator(Operator::LessOrEqual),
+                Token::Literal(Literal::Number(2048.0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn tokenize_literals() {
+        let tokens = tokenize(r#"!(a != -1.5 || b =~ "x\"y\\" || c > true)"#).unwrap();
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Not,
+                Token::Open,
+                Token::Ident("a".to_string()),
+                Token::Operator(Operator::NotEqual),
+                Token::Literal(Literal::Number(-1.5)),
+                Token::Or,
+                Token::Ident("b".to_string()),
+                Token::Operator(Operator::Matches),
+                Token::Literal(Literal::String("x\"y\\".to_string())),
+                Token::Or,
+                Token::Ident("c".to_string()),
+                Token::Operator(Operator::Greater),
+                Token::Literal(Literal::Bool(true)),
+                Token::Close,
+            ]
+        );
+    }
+
+    #[test]
+    fn tokenize_errors() {
+        assert!(tokenize(r#"name == "open"#).is_err());
+        assert!(tokenize("bits < 12ab").is_err());
+        assert!(tokenize("name = 1").is_err());
+    }
+
+    #[test]
+    fn parse_errors() {
+        for text in [
+            "",
+            "bits <",
+            "bits 2048",
+            "(bits < 2048",
+            "bits < 2048 origin == \"gnutls\"",
+            "name =~ 1",
+            "name =~ \"(\"",
+            "&& bits < 2048",
+        ] {
+            assert!(Query::parse(text).is_err(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn parse_keeps_text() {
+        let query = Query::parse("  bits < 2048 ").unwrap();
+        assert_eq!(query.text(), "bits < 2048");
+        assert_eq!(query.name(), None);
+        let query = query.with_name(Some("Weak keys".to_string()));
+        assert_eq!(query.name(), Some("Weak keys"));
+    }
+
+    #[test]
+    fn match_builtin_properties() {
+        let event = event(json!({"name": "tls::sign"}));
+        assert!(matches(r#"name == "tls::sign""#, &event));
+        assert!(matches(r#"origin =~ "^gnu""#, &event));
+        assert!(matches(r#"context == "0a1b""#, &event));
+        assert!(matches("duration == 50", &event));
+        assert!(!matches(r#"origin == "openssl""#, &event));
+    }
+
+    #[test]
+    fn match_fields() {
+        let event = event(json!({"name": "pk::generate", "pk::bits": 1024, "pk::fips": false}));
+        assert!(matches("pk::bits < 2048", &event));
+        assert!(matches("bits < 2048", &event));
+        assert!(matches("bits >= 1024 && bits <= 1024", &event));
+        assert!(matches(r#"bits == "1024""#, &event));
+        assert!(matches("fips == false", &event));
+        assert!(!matches("bits > 1024", &event));
+        assert!(!matches(r#"bits < "2048""#, &event));
+    }
+
+    #[test]
+    fn match_codepoint_names() {
+        let event = event(json!({"name": "tls::handshake_client", "tls::ciphersuite": 0x1301}));
+        assert!(matches(
+            r#"ciphersuite == "TLS_AES_128_GCM_SHA256""#,
+            &event
+        ));
+        assert!(matches(
+            r#"ciphersuite == "tls_aes_128_gcm_sha256""#,
+            &event
+        ));
+        assert!(matches(r#"ciphersuite =~ "GCM""#, &event));
+        assert!(matches("ciphersuite == 0x1301", &event));
+    }
+
+    #[test]
+    fn match_missing_properties() {
+        let event = event(json!({"name": "tls::sign"}));
+        assert!(!matches("bits == 2048", &event));
+        assert!(matches("bits != 2048", &event));
+        assert!(!matches(r#"bits =~ "2048""#, &event));
+        assert!(matches(r#"bits !~ "2048""#, &event));
+        assert!(!matches("bits < 2048", &event));
+        assert!(!matches("bits >= 2048", &event));
+        assert!(!matches(r#"process == "curl""#, &event));
+    }
+
+    #[test]
+    fn precedence() {
+        let event = event(json!({"a": 1, "b": 0}));
+        // && binds tighter than ||
+        assert!(matches("a == 1 || a == 2 && b == 3", &event));
+        assert!(!matches("(a == 1 || a == 2) && b == 3", &event));
+        assert!(matches("b == 3 && a == 2 || a == 1", &event));
+        // ! applies to the comparison that follows it
+        assert!(!matches("!a == 1 && b == 0", &event));
+        assert!(matches("!(a == 1 && b == 1)", &event));
+        assert!(matches("!!a == 1", &event));
+    }
+
+    #[test]
+    fn filter_keeps_events_with_matching_spans() {
+        let mut parent = event(json!({"name": "tls::handshake_client"}));
+        parent
+            .spans
+            .push(event(json!({"name": "pk::sign", "pk::bits": 1024})));
+        let other = event(json!({"name": "pk::sign", "pk::bits": 4096}));
+        let filtered = Query::parse("bits < 2048")
+            .unwrap()
+            .filter(&[parent, other]);
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].name(), "tls::handshake_client");
+        assert_eq!(filtered[0].spans.len(), 1);
+    }
+}
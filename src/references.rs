@@ -0,0 +1,173 @@
+use crate::data::{NodeKind, TreeNode};
+use crate::decoders;
+use adw::prelude::*;
+use adw::{ActionRow, PreferencesGroup};
+use gtk4::{LinkButton, ScrolledWindow};
+use std::collections::BTreeSet;
+
+const IANA_TLS: &str = "https://www.iana.org/assignments/tls-parameters/tls-parameters.xhtml";
+
+type Decoder = fn(u64) -> Option<&'static str>;
+
+// Codepoint fields with their decoder, heading and registry section; protocol
+// versions have no registry of their own
+const FIELDS: [(&str, Decoder, &str, Option<&str>); 4] = [
+    (
+        "tls::protocol_version",
+        decoders::protocol_version_name,
+        "Protocol Versions",
+        None,
+    ),
+    (
+        "tls::ciphersuite",
+        decoders::ciphersuite_name,
+        "Ciphersuites",
+        Some("#tls-parameters-4"),
+    ),
+    (
+        "tls::group",
+        decoders::group_name,
+        "Groups",
+        Some("#tls-parameters-8"),
+    ),
+    (
+        "tls::signature_algorithm",
+        decoders::signature_scheme_name,
+        "Signature Schemes",
+        Some("#tls-signaturescheme"),
+    ),
+];
+
+fn rfc(number: u32) -> (String, String) {
+    (
+        format!("RFC {}", number),
+        format!("https://www.rfc-editor.org/rfc/rfc{}", number),
+    )
+}
+
+fn draft(name: &str) -> (String, String) {
+    (
+        "Draft".to_string(),
+        format!("https://datatracker.ietf.org/doc/{}/", name),
+    )
+}
+
+// Document defining a named codepoint, with its short title and URI
+fn specification(field: &str, name: &str) -> Option<(String, String)> {
+    let spec = match field {
+        "tls::protocol_version" => match name {
+            "TLS 1.3" => rfc(8446),
+            "TLS 1.2" => rfc(5246),
+            "TLS 1.1" => rfc(4346),
+            "TLS 1.0" => rfc(2246),
+            "SSL 3.0" => rfc(6101),
+            _ => return None,
+        },
+        "tls::ciphersuite" => {
+            if !name.contains("_WITH_") {
+                rfc(8446)
+            } else if name.contains("CHACHA20") {
+                rfc(7905)
+            } else if name.ends_with("_CCM") {
+                rfc(7251)
+            } else if name.contains("_ECDHE_") && name.contains("GCM") {
+                rfc(5289)
+            } else if name.contains("_ECDHE_") {
+                rfc(8422)
+            } else if name.contains("GCM") {
+                rfc(5288)
+            } else {
+                rfc(5246)
+            }
+        }
+        "tls::group" => {
+            if name.starts_with("MLKEM") {
+                draft("draft-ietf-tls-mlkem")
+            } else if name.contains("MLKEM") || name.contains("Kyber") {
+                draft("draft-ietf-tls-ecdhe-mlkem")
+            } else if name.starts_with("ffdhe") {
+                rfc(7919)
+            } else if name.starts_with('x') {
+                rfc(7748)
+            } else {
+                rfc(8422)
+            }
+        }
+        "tls::signature_algorithm" => {
+            if name.starts_with("mldsa") {
+                draft("draft-ietf-tls-mldsa")
+            } else {
+                rfc(8446)
+            }
+        }
+        _ => return None,
+    };
+    Some(spec)
+}
+
+fn collect_codes(node: &TreeNode, codes: &mut BTreeSet<(usize, u64)>) {
+    if node.kind == NodeKind::Event {
+        for (i, (field, ..)) in FIELDS.iter().enumerate() {
+            if let Some(code) = node.fields.get(*field).and_then(|v| v.as_u64()) {
+                codes.insert((i, code));
+            }
+        }
+    }
+    for child in &node.children {
+        collect_codes(child, codes);
+    }
+}
+
+fn link(label: &str, uri: &str) -> LinkButton {
+    let button = LinkButton::with_label(uri, label);
+    button.set_valign(gtk4::Align::Center);
+    button
+}
+
+// Page listing the codepoints in a subtree by name, each linking to the
+// document defining it and to its IANA registry entry, opened in the default
+// browser. None if the subtree has none.
+pub fn create_references_page(node: &TreeNode) -> Option<ScrolledWindow> {
+    let mut codes = BTreeSet::new();
+    collect_codes(node, &mut codes);
+    if codes.is_empty() {
+        return None;
+    }
+
+    let content = gtk4::Box::new(gtk4::Orientation::Vertical, 18);
+    content.set_margin_start(12);
+    content.set_margin_end(12);
+    content.set_margin_top(12);
+    content.set_margin_bottom(12);
+
+    let mut current: Option<(usize, PreferencesGroup)> = None;
+    for (i, code) in codes {
+        let (field, decode, heading, anchor) = FIELDS[i];
+        if current.as_ref().map(|(index, _)| *index) != Some(i) {
+            let group = PreferencesGroup::builder().title(heading).build();
+            content.append(&group);
+            current = Some((i, group));
+        }
+        let Some((_, group)) = &current else {
+            continue;
+        };
+
+        let name = decode(code);
+        let row = ActionRow::builder()
+            .title(name.unwrap_or("Unknown"))
+            .subtitle(format!("0x{:04x}", code))
+            .use_markup(false)
+            .build();
+        if let Some((title, uri)) = name.and_then(|name| specification(field, name)) {
+            row.add_suffix(&link(&title, &uri));
+        }
+        if let Some(anchor) = anchor {
+            row.add_suffix(&link("IANA", &format!("{}{}", IANA_TLS, anchor)));
+        }
+        group.add(&row);
+    }
+
+    let scroll = ScrolledWindow::new();
+    scroll.set_child(Some(&content));
+    Some(scroll)
+}
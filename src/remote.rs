@@ -0,0 +1,116 @@
+use adw::prelude::*;
+use adw::{AlertDialog, EntryRow, PreferencesGroup, ResponseAppearance};
+use anyhow::{anyhow, Result};
+use gtk4::{gio, glib};
+use std::ffi::OsStr;
+
+// Default command run on the remote host to export the audit log as JSON
+pub const DEFAULT_COMMAND: &str = "crau-query --log-file /var/log/crypto-auditing/audit.cborseq";
+
+// Ask the user for the remote host and export command.
+// Returns None when the dialog was cancelled or the host was left empty.
+pub async fn ask_remote_source(parent: &impl IsA<gtk4::Widget>) -> Option<(String, String)> {
+    let host_row = EntryRow::builder().title("Host (user@hostname)").build();
+    let command_row = EntryRow::builder()
+        .title("Export Command")
+        .text(DEFAULT_COMMAND)
+        .build();
+
+    let group = PreferencesGroup::new();
+    group.add(&host_row);
+    group.add(&command_row);
+
+    let dialog = AlertDialog::builder()
+        .heading("Collect from Remote Host")
+        .body("The export command is run over SSH and its output is opened once it completes, with the amount received shown meanwhile. Key-based authentication is required.")
+        .extra_child(&group)
+        .close_response("cancel")
+        .default_response("collect")
+        .build();
+    dialog.add_response("cancel", "Cancel");
+    dialog.add_response("collect", "Collect");
+    dialog.set_response_appearance("collect", ResponseAppearance::Suggested);
+    dialog.set_response_enabled("collect", false);
+
+    // Only allow collecting once a host has been entered
+    let dialog_clone = dialog.clone();
+    host_row.connect_changed(move |row| {
+        dialog_clone.set_response_enabled("collect", !row.text().trim().is_empty());
+    });
+
+    if dialog.clone().choose_future(parent).await != "collect" {
+        return None;
+    }
+
+    let host = host_row.text().trim().to_string();
+    let command = command_row.text().trim().to_string();
+    if host.is_empty() {
+        return None;
+    }
+
+    let command = if command.is_empty() {
+        DEFAULT_COMMAND.to_string()
+    } else {
+        command
+    };
+
+    Some((host, command))
+}
+
+// Bytes read from the output of the export command at a time
+const CHUNK_SIZE: usize = 64 * 1024;
+
+// Everything left in a stream, read a chunk at a time. Reports the number of
+// bytes read so far after each chunk.
+async fn read_stream(stream: gio::InputStream, on_progress: impl Fn(usize)) -> Result<Vec<u8>> {
+    let mut data = Vec::new();
+    loop {
+        let chunk = stream
+            .read_bytes_future(CHUNK_SIZE, glib::Priority::DEFAULT)
+            .await?;
+        if chunk.is_empty() {
+            return Ok(data);
+        }
+        data.extend_from_slice(&chunk);
+        on_progress(data.len());
+    }
+}
+
+// Run the export command on the remote host and return its standard output,
+// which is read as it arrives. Reports the number of bytes received so far.
+pub async fn collect(host: &str, command: &str, on_progress: impl Fn(usize)) -> Result<String> {
+    // BatchMode prevents ssh from blocking on a password prompt we cannot answer,
+    // and "--" keeps a host starting with a dash from being parsed as an option
+    let argv: [&OsStr; 6] = [
+        OsStr::new("ssh"),
+        OsStr::new("-o"),
+        OsStr::new("BatchMode=yes"),
+        OsStr::new("--"),
+        OsStr::new(host),
+        OsStr::new(command),
+    ];
+
+    let process = gio::Subprocess::newv(
+        &argv,
+        gio::SubprocessFlags::STDOUT_PIPE | gio::SubprocessFlags::STDERR_PIPE,
+    )?;
+    let (Some(stdout), Some(stderr)) = (process.stdout_pipe(), process.stderr_pipe()) else {
+        return Err(anyhow!("Failed to read the output of ssh"));
+    };
+
+    // Drain stderr alongside, so a chatty command cannot block on a full pipe
+    let stderr = glib::spawn_future_local(read_stream(stderr, |_| {}));
+    let stdout = read_stream(stdout, on_progress).await;
+    let stderr = stderr.await.ok().and_then(Result::ok).unwrap_or_default();
+    process.wait_future().await?;
+
+    if !process.is_successful() {
+        let message = Some(String::from_utf8_lossy(&stderr).trim().to_string())
+            .filter(|s| !s.is_empty())
+            .unwrap_or_else(|| format!("exit status {}", process.exit_status()));
+        return Err(anyhow!("Collection from {} failed: {}", host, message));
+    }
+
+    String::from_utf8(stdout?)
+        .map_err(|_| anyhow!("The output of the export command on {} is not UTF-8", host))
+}
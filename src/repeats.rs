@@ -0,0 +1,168 @@
+use crate::data::{self, AuditEvent};
+use crate::resumption;
+use crate::sunburst::SunburstChart;
+use adw::prelude::*;
+use adw::ActionRow;
+use jiff::SignedDuration;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+// Fields of handshakes and their spans naming the peer, most specific first
+const PEER_FIELDS: [&str; 4] = [
+    "tls::server_name",
+    "tls::sni",
+    "tls::peer",
+    "tls::peer_address",
+];
+// Full handshakes with a peer closer than this to the previous one should
+// have resumed its session
+const REPEAT_INTERVAL: u64 = 10_000_000_000;
+// Repeats of a peer below this are left out, as sessions may expire
+const MIN_REPEATS: usize = 2;
+
+// Full handshakes of a context repeated with the same peer
+pub struct Repeat {
+    pub context: String,
+    pub peer: String,
+    pub handshakes: usize,
+    // Handshakes following the previous within the interval
+    pub repeats: usize,
+    // Shortest time between two of them
+    pub shortest: u64,
+}
+
+fn peer_of(event: &AuditEvent) -> Option<String> {
+    PEER_FIELDS
+        .iter()
+        .find_map(|field| match event.events.get(*field) {
+            Some(Value::String(peer)) if !peer.is_empty() => Some(peer.clone()),
+            _ => None,
+        })
+        .or_else(|| event.spans.iter().find_map(peer_of))
+}
+
+fn collect(events: &[AuditEvent], handshakes: &mut HashMap<(String, String), Vec<u64>>) {
+    for event in events {
+        if event.name().starts_with("tls::handshake_") {
+            if resumption::is_resumed(event) {
+                continue;
+            }
+            if let Some(peer) = peer_of(event) {
+                handshakes
+                    .entry((event.context.clone(), peer))
+                    .or_default()
+                    .push(event.start);
+            }
+        } else {
+            collect(&event.spans, handshakes);
+        }
+    }
+}
+
+// Contexts doing full handshakes with the same peer in quick succession,
+// where session reuse would have saved the key exchanges, most repeats first
+pub fn find_repeats(events: &[AuditEvent]) -> Vec<Repeat> {
+    let mut handshakes = HashMap::new();
+    collect(events, &mut handshakes);
+
+    let mut repeats: Vec<Repeat> = handshakes
+        .into_iter()
+        .filter_map(|((context, peer), mut starts)| {
+            starts.sort_unstable();
+            let gaps: Vec<u64> = starts
+                .windows(2)
+                .map(|pair| pair[1] - pair[0])
+                .filter(|gap| *gap < REPEAT_INTERVAL)
+                .collect();
+            (gaps.len() >= MIN_REPEATS).then(|| Repeat {
+                context,
+                peer,
+                handshakes: starts.len(),
+                repeats: gaps.len(),
+                shortest: gaps.iter().copied().min().unwrap_or(0),
+            })
+        })
+        .collect();
+    repeats.sort_by(|a, b| {
+        b.repeats
+            .cmp(&a.repeats)
+            .then_with(|| a.peer.cmp(&b.peer))
+            .then_with(|| a.context.cmp(&b.context))
+    });
+    repeats
+}
+
+// Performance finding listing contexts that handshake with the same peer again
+// and again without resuming the session, when handshakes tell of their peer
+pub fn create_repeats_section(chart: Rc<SunburstChart>) -> gtk4::Box {
+    let section = gtk4::Box::new(gtk4::Orientation::Vertical, 6);
+    section.set_margin_start(12);
+    section.set_margin_end(12);
+    section.set_margin_top(12);
+    section.set_margin_bottom(12);
+    section.set_visible(false);
+
+    let title = gtk4::Label::new(Some("Repeated Handshakes"));
+    title.set_halign(gtk4::Align::Start);
+    title.add_css_class("title-4");
+    section.append(&title);
+
+    let description = gtk4::Label::new(Some(&format!(
+        "Full handshakes with the same peer less than {} s apart, which session reuse would avoid",
+        REPEAT_INTERVAL / 1_000_000_000
+    )));
+    description.set_halign(gtk4::Align::Start);
+    description.set_wrap(true);
+    description.set_xalign(0.0);
+    description.add_css_class("dim-label");
+    section.append(&description);
+
+    let list = gtk4::ListBox::new();
+    list.set_selection_mode(gtk4::SelectionMode::None);
+    list.add_css_class("boxed-list");
+    section.append(&list);
+
+    let section_clone = section.clone();
+    let chart_clone = chart.clone();
+    chart.connect_view_changed(move |_| {
+        let repeats = find_repeats(&chart_clone.visible_events());
+
+        while let Some(row) = list.first_child() {
+            list.remove(&row);
+        }
+        section_clone.set_visible(!repeats.is_empty());
+        if repeats.is_empty() {
+            return;
+        }
+
+        let length = data::short_context_length(
+            chart_clone
+                .events()
+                .iter()
+                .map(|event| event.context.as_str()),
+        );
+        for repeat in &repeats {
+            let context = data::short_context(&repeat.context, length)
+                .unwrap_or_else(|| repeat.context.clone());
+            let row = ActionRow::builder()
+                .title(&repeat.peer)
+                .subtitle(format!(
+                    "{} full handshakes from {}, {} soon after the previous one, as close as {:#}",
+                    repeat.handshakes,
+                    context,
+                    repeat.repeats,
+                    SignedDuration::from_nanos(repeat.shortest as i64)
+                ))
+                .use_markup(false)
+                .tooltip_text(format!("Context: {}", repeat.context))
+                .build();
+            let icon = gtk4::Image::from_icon_name("dialog-warning-symbolic");
+            icon.add_css_class("warning");
+            row.add_prefix(&icon);
+            list.append(&row);
+        }
+    });
+
+    section
+}
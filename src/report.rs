@@ -0,0 +1,149 @@
+use crate::annotations::Annotation;
+use crate::compliance::Profile;
+use crate::export;
+use crate::html::{escape_html, render_template};
+use crate::models::StatsObject;
+use crate::sunburst::SunburstChart;
+use adw::prelude::*;
+use anyhow::{anyhow, Result};
+use cairo::{Context, SvgSurface};
+use gtk4::gio;
+use std::fs;
+use std::path::Path;
+
+// Size of the chart embedded in reports, in points
+const CHART_SIZE: f64 = 800.0;
+
+// Built-in report, used unless a custom template is configured
+const DEFAULT_TEMPLATE: &str = include_str!("report.html");
+
+// The visible chart as SVG markup, ready to be inlined in a page
+fn chart_svg(chart: &SunburstChart) -> Result<String> {
+    let surface = SvgSurface::for_stream(CHART_SIZE, CHART_SIZE, Vec::<u8>::new())?;
+    let cr = Context::new(&surface)?;
+    chart.render(&cr, CHART_SIZE, CHART_SIZE);
+    drop(cr);
+    let output = surface
+        .finish_output_stream()
+        .map_err(|e| anyhow!("Failed to render chart: {}", e))?;
+    let svg = output
+        .downcast::<Vec<u8>>()
+        .map_err(|_| anyhow!("Failed to render chart"))?;
+    let svg = String::from_utf8_lossy(&svg);
+    // Leave out the XML declaration, which has no place inside HTML
+    Ok(svg[svg.find("<svg").unwrap_or(0)..].to_string())
+}
+
+fn stats_table(store: &gio::ListStore) -> String {
+    let mut table =
+        String::from("<table>\n<tr><th>Algorithm</th><th>Count</th><th>Percentage</th></tr>\n");
+    for obj in store.iter::<StatsObject>().filter_map(Result::ok) {
+        table.push_str(&format!(
+            "<tr><td>{}</td><td class=\"number\">{}</td><td class=\"number\">{}</td></tr>\n",
+            escape_html(&obj.algorithm()),
+            escape_html(&obj.count()),
+            escape_html(&obj.percentage())
+        ));
+    }
+    table.push_str("</table>");
+    table
+}
+
+// Violations of the system crypto policy by the visible events
+fn findings(chart: &SunburstChart) -> String {
+    let Some(profile) = Profile::active() else {
+        return "<p class=\"none\">No system crypto policy found</p>".to_string();
+    };
+    let violations = profile.check(&chart.visible_events());
+    if violations.is_empty() {
+        return format!(
+            "<p class=\"none\">No violations of the {} policy</p>",
+            escape_html(&profile.name)
+        );
+    }
+
+    let mut table = format!(
+        "<p>Checked against the {} policy</p>\n<table>\n<tr><th>Severity</th><th>Finding</th><th>Origin</th><th>Events</th></tr>\n",
+        escape_html(&profile.name)
+    );
+    for violation in violations {
+        let severity = violation.severity.name();
+        table.push_str(&format!(
+            "<tr><td class=\"severity-{}\">{}</td><td>{}</td><td>{}</td><td class=\"number\">{}</td></tr>\n",
+            severity,
+            severity,
+            escape_html(&violation.description),
+            escape_html(&violation.origin),
+            violation.count
+        ));
+    }
+    table.push_str("</table>");
+    table
+}
+
+// Notes and triage states attached to nodes of the capture
+fn annotations(annotations: &[Annotation]) -> String {
+    if annotations.is_empty() {
+        return "<p class=\"none\">No annotations</p>".to_string();
+    }
+
+    let mut table = String::from("<table>\n<tr><th>Node</th><th>State</th><th>Note</th></tr>\n");
+    for annotation in annotations {
+        table.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+            escape_html(&annotation.path.join(" › ")),
+            escape_html(annotation.state.label()),
+            escape_html(&annotation.note)
+        ));
+    }
+    table.push_str("</table>");
+    table
+}
+
+// Generate an HTML report of the view from a template, or the built-in one.
+// Templates are HTML with {{title}}, {{generated}}, {{metadata}}, {{chart}},
+// {{stats_table}}, {{findings}} and {{annotations}} placeholders, each of
+// which may be left out.
+pub fn generate_report(
+    chart: &SunburstChart,
+    store: &gio::ListStore,
+    notes: &[Annotation],
+    template: Option<&Path>,
+    path: &Path,
+) -> Result<()> {
+    if chart.root().is_none() {
+        return Err(anyhow!("No capture is loaded"));
+    }
+    let template = match template {
+        Some(template) => fs::read_to_string(template)
+            .map_err(|e| anyhow!("Failed to read {}: {}", template.display(), e))?,
+        None => DEFAULT_TEMPLATE.to_string(),
+    };
+
+    let metadata: String = export::watermark_lines(chart)
+        .iter()
+        .map(|line| format!("<p class=\"meta\">{}</p>", escape_html(line)))
+        .collect();
+    let generated = jiff::Zoned::now().strftime("%F %T %Z").to_string();
+
+    // Rendering the chart is the costly part, so skip it if the template doesn't use it
+    let chart_markup = if template.contains("{{chart}}") {
+        chart_svg(chart)?
+    } else {
+        String::new()
+    };
+    let page = render_template(
+        &template,
+        &[
+            ("title", "Crypto Usage Report"),
+            ("generated", &escape_html(&generated)),
+            ("metadata", &metadata),
+            ("chart", &chart_markup),
+            ("stats_table", &stats_table(store)),
+            ("findings", &findings(chart)),
+            ("annotations", &annotations(notes)),
+        ],
+    );
+    fs::write(path, page)?;
+    Ok(())
+}
@@ -0,0 +1,143 @@
+use crate::data::AuditEvent;
+use crate::sunburst::SunburstChart;
+use adw::prelude::*;
+use adw::ActionRow;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+// Contexts beyond these have the fewest handshakes, left out of the list
+const MAX_CONTEXTS: usize = 10;
+// Parts of event and field names telling of a pre-shared key or session ticket
+const PSK_HINTS: [&str; 3] = ["psk", "ticket", "resum"];
+
+// Handshakes of a context or the whole view, and how many of them were resumed
+#[derive(Default, Clone, Copy)]
+struct Counts {
+    handshakes: usize,
+    resumed: usize,
+}
+
+impl Counts {
+    fn summary(&self) -> String {
+        let share = self.resumed as f64 / self.handshakes as f64 * 100.0;
+        format!(
+            "{} of {} resumed ({}%)",
+            self.resumed,
+            self.handshakes,
+            share.round() as u32
+        )
+    }
+}
+
+// Whether an event names or flags a pre-shared key or session ticket
+fn mentions_psk(event: &AuditEvent) -> bool {
+    let name = event.name().to_lowercase();
+    PSK_HINTS.iter().any(|hint| name.contains(hint))
+        || event.events.iter().any(|(key, value)| {
+            let key = key.to_lowercase();
+            PSK_HINTS.iter().any(|hint| key.contains(hint))
+                && value.as_bool() != Some(false)
+                && value.as_u64() != Some(0)
+        })
+}
+
+fn has_full_handshake_span(event: &AuditEvent) -> bool {
+    event.spans.iter().any(|span| {
+        matches!(
+            span.name().as_str(),
+            "tls::key_exchange" | "tls::sign" | "tls::verify"
+        ) || has_full_handshake_span(span)
+    })
+}
+
+// A handshake is resumed if it or one of its spans mentions a PSK or session
+// ticket, or if it neither exchanged keys nor signed anything
+pub fn is_resumed(handshake: &AuditEvent) -> bool {
+    fn any_psk(event: &AuditEvent) -> bool {
+        mentions_psk(event) || event.spans.iter().any(any_psk)
+    }
+    any_psk(handshake) || !has_full_handshake_span(handshake)
+}
+
+fn count_handshakes(events: &[AuditEvent], contexts: &mut HashMap<String, Counts>) {
+    for event in events {
+        if event.name().starts_with("tls::handshake_") {
+            let counts = contexts.entry(event.context.clone()).or_default();
+            counts.handshakes += 1;
+            if is_resumed(event) {
+                counts.resumed += 1;
+            }
+        } else {
+            count_handshakes(&event.spans, contexts);
+        }
+    }
+}
+
+// Share of TLS handshakes in the view that resumed a session rather than doing
+// a full handshake, overall and for the contexts with most handshakes
+pub fn create_resumption_section(chart: Rc<SunburstChart>) -> gtk4::Box {
+    let section = gtk4::Box::new(gtk4::Orientation::Vertical, 6);
+    section.set_margin_start(12);
+    section.set_margin_end(12);
+    section.set_margin_top(12);
+    section.set_margin_bottom(12);
+    section.set_visible(false);
+
+    let title = gtk4::Label::new(Some("Session Resumption"));
+    title.set_halign(gtk4::Align::Start);
+    title.add_css_class("title-4");
+    section.append(&title);
+
+    let overall = gtk4::Label::new(None);
+    overall.set_halign(gtk4::Align::Start);
+    overall.set_tooltip_text(Some(
+        "Handshakes with a PSK or session ticket, or without key exchange and signature operations, count as resumed",
+    ));
+    section.append(&overall);
+
+    let list = gtk4::ListBox::new();
+    list.set_selection_mode(gtk4::SelectionMode::None);
+    list.add_css_class("boxed-list");
+    section.append(&list);
+
+    let section_clone = section.clone();
+    let chart_clone = chart.clone();
+    chart.connect_view_changed(move |_| {
+        let mut contexts = HashMap::new();
+        count_handshakes(&chart_clone.visible_events(), &mut contexts);
+
+        while let Some(row) = list.first_child() {
+            list.remove(&row);
+        }
+        let total = contexts
+            .values()
+            .fold(Counts::default(), |total, counts| Counts {
+                handshakes: total.handshakes + counts.handshakes,
+                resumed: total.resumed + counts.resumed,
+            });
+        section_clone.set_visible(total.handshakes > 0);
+        if total.handshakes == 0 {
+            return;
+        }
+        overall.set_text(&total.summary());
+
+        let mut contexts: Vec<(String, Counts)> = contexts.into_iter().collect();
+        contexts.sort_by(|a, b| {
+            b.1.handshakes
+                .cmp(&a.1.handshakes)
+                .then_with(|| a.0.cmp(&b.0))
+        });
+        for (context, counts) in contexts.iter().take(MAX_CONTEXTS) {
+            let row = ActionRow::builder()
+                .title(context)
+                .subtitle(counts.summary())
+                .use_markup(false)
+                .css_classes(["property"])
+                .build();
+            list.append(&row);
+        }
+        list.set_visible(contexts.len() > 1);
+    });
+
+    section
+}
@@ -0,0 +1,77 @@
+use crate::config::Config;
+use adw::prelude::*;
+use adw::{AlertDialog, EntryRow, PreferencesGroup, ResponseAppearance};
+use gtk4::gio;
+use serde::{Deserialize, Serialize};
+
+// A filter expression kept under a name, with what it's for
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedQuery {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub query: String,
+}
+
+// List the saved queries in the menu of the filter bar
+pub fn update_menu(menu: &gio::Menu, config: &Config) {
+    menu.remove_all();
+    for (index, saved) in config.saved_queries.iter().enumerate() {
+        let item = gio::MenuItem::new(Some(&saved.name), None);
+        item.set_action_and_target_value(
+            Some("app.apply-saved-query"),
+            Some(&(index as u32).to_variant()),
+        );
+        menu.append_item(&item);
+    }
+}
+
+// Ask for the name and description to save an expression under, proposing
+// those it's saved under already. Returns None when the dialog was cancelled.
+pub async fn save_query(
+    parent: &impl IsA<gtk4::Widget>,
+    query: &str,
+    current: Option<&SavedQuery>,
+) -> Option<SavedQuery> {
+    let name_row = EntryRow::builder()
+        .title("Name")
+        .text(current.map(|saved| saved.name.as_str()).unwrap_or(""))
+        .build();
+    let description_row = EntryRow::builder()
+        .title("Description")
+        .text(
+            current
+                .map(|saved| saved.description.as_str())
+                .unwrap_or(""),
+        )
+        .build();
+
+    let group = PreferencesGroup::new();
+    group.add(&name_row);
+    group.add(&description_row);
+
+    let dialog = AlertDialog::builder()
+        .heading("Save Query")
+        .body(query)
+        .extra_child(&group)
+        .close_response("cancel")
+        .default_response("save")
+        .build();
+    dialog.add_response("cancel", "Cancel");
+    dialog.add_response("save", "Save");
+    dialog.set_response_appearance("save", ResponseAppearance::Suggested);
+    dialog.set_response_enabled("save", !name_row.text().trim().is_empty());
+    let dialog_clone = dialog.clone();
+    name_row.connect_changed(move |row| {
+        dialog_clone.set_response_enabled("save", !row.text().trim().is_empty());
+    });
+
+    if dialog.clone().choose_future(parent).await != "save" {
+        return None;
+    }
+    Some(SavedQuery {
+        name: name_row.text().trim().to_string(),
+        description: description_row.text().trim().to_string(),
+        query: query.to_string(),
+    })
+}
@@ -0,0 +1,257 @@
+use crate::timezone::format_time;
+use adw::prelude::*;
+use gtk4::{Button, DrawingArea, Label, Orientation};
+use jiff::SignedDuration;
+use std::cell::RefCell;
+use std::f64::consts::PI;
+use std::rc::Rc;
+
+// Horizontal space left of and right of the track, so handles aren't clipped
+const MARGIN: f64 = 12.0;
+const HANDLE_RADIUS: f64 = 7.0;
+
+// Called with the selected window in nanoseconds since boot, or None for the full period
+type ChangedHandler = Rc<dyn Fn(Option<(u64, u64)>)>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Handle {
+    Start,
+    End,
+}
+
+#[derive(Debug)]
+struct State {
+    // Full sampling period
+    range: Option<(u64, u64)>,
+    // Selected window as fractions of the period
+    selection: (f64, f64),
+    dragging: Option<Handle>,
+    drag_start_x: f64,
+}
+
+// Range slider spanning the sampling period, with one handle for each end of the window
+#[derive(Clone)]
+pub struct TimeScrubber {
+    container: gtk4::Box,
+    drawing_area: DrawingArea,
+    label: Label,
+    reset_button: Button,
+    state: Rc<RefCell<State>>,
+    changed_handlers: Rc<RefCell<Vec<ChangedHandler>>>,
+}
+
+impl TimeScrubber {
+    pub fn new() -> Self {
+        let container = gtk4::Box::new(Orientation::Vertical, 0);
+        container.set_margin_start(12);
+        container.set_margin_end(12);
+        container.set_margin_bottom(12);
+        container.set_visible(false);
+
+        let drawing_area = DrawingArea::new();
+        drawing_area.set_content_height(28);
+        drawing_area.set_hexpand(true);
+        container.append(&drawing_area);
+
+        let footer = gtk4::Box::new(Orientation::Horizontal, 6);
+        let label = Label::new(None);
+        label.set_halign(gtk4::Align::Start);
+        label.set_hexpand(true);
+        label.add_css_class("dim-label");
+        label.add_css_class("numeric");
+        footer.append(&label);
+
+        let reset_button = Button::with_label("Full Period");
+        reset_button.add_css_class("flat");
+        reset_button.set_visible(false);
+        footer.append(&reset_button);
+        container.append(&footer);
+
+        let scrubber = Self {
+            container,
+            drawing_area: drawing_area.clone(),
+            label,
+            reset_button: reset_button.clone(),
+            state: Rc::new(RefCell::new(State {
+                range: None,
+                selection: (0.0, 1.0),
+                dragging: None,
+                drag_start_x: 0.0,
+            })),
+            changed_handlers: Rc::new(RefCell::new(Vec::new())),
+        };
+
+        let state = scrubber.state.clone();
+        drawing_area.set_draw_func(move |_, cr, width, height| {
+            let state = state.borrow();
+            let track_width = width as f64 - 2.0 * MARGIN;
+            let cy = height as f64 / 2.0;
+            let (start, end) = state.selection;
+
+            // Track over the whole period
+            cr.set_source_rgba(0.5, 0.5, 0.5, 0.3);
+            cr.rectangle(MARGIN, cy - 2.0, track_width, 4.0);
+            cr.fill().unwrap();
+
+            // Selected window
+            cr.set_source_rgb(0.21, 0.52, 0.89);
+            cr.rectangle(
+                MARGIN + start * track_width,
+                cy - 3.0,
+                (end - start) * track_width,
+                6.0,
+            );
+            cr.fill().unwrap();
+
+            for fraction in [start, end] {
+                cr.arc(
+                    MARGIN + fraction * track_width,
+                    cy,
+                    HANDLE_RADIUS,
+                    0.0,
+                    2.0 * PI,
+                );
+                cr.set_source_rgb(1.0, 1.0, 1.0);
+                cr.fill_preserve().unwrap();
+                cr.set_source_rgb(0.21, 0.52, 0.89);
+                cr.set_line_width(2.0);
+                cr.stroke().unwrap();
+            }
+        });
+
+        // Dragging moves the handle closest to where the drag started
+        let drag = gtk4::GestureDrag::new();
+        let scrubber_clone = scrubber.clone();
+        drag.connect_drag_begin(move |_, x, _| {
+            let fraction = scrubber_clone.fraction_at(x);
+            let mut state = scrubber_clone.state.borrow_mut();
+            if state.range.is_none() {
+                return;
+            }
+            let (start, end) = state.selection;
+            state.dragging = Some(if (fraction - start).abs() <= (fraction - end).abs() {
+                Handle::Start
+            } else {
+                Handle::End
+            });
+            state.drag_start_x = x;
+        });
+
+        let scrubber_clone = scrubber.clone();
+        drag.connect_drag_update(move |_, offset_x, _| {
+            {
+                let mut state = scrubber_clone.state.borrow_mut();
+                let x = state.drag_start_x + offset_x;
+                let fraction = scrubber_clone.fraction_at(x);
+                match state.dragging {
+                    Some(Handle::Start) => state.selection.0 = fraction.min(state.selection.1),
+                    Some(Handle::End) => state.selection.1 = fraction.max(state.selection.0),
+                    None => return,
+                }
+            }
+            scrubber_clone.update_label();
+            scrubber_clone.drawing_area.queue_draw();
+        });
+
+        // The tree is only rebuilt once the handle is released
+        let scrubber_clone = scrubber.clone();
+        drag.connect_drag_end(move |_, _, _| {
+            if scrubber_clone.state.borrow_mut().dragging.take().is_some() {
+                scrubber_clone.notify_changed();
+            }
+        });
+        drawing_area.add_controller(drag);
+
+        let scrubber_clone = scrubber.clone();
+        reset_button.connect_clicked(move |_| {
+            scrubber_clone.state.borrow_mut().selection = (0.0, 1.0);
+            scrubber_clone.update_label();
+            scrubber_clone.drawing_area.queue_draw();
+            scrubber_clone.notify_changed();
+        });
+
+        scrubber
+    }
+
+    pub fn widget(&self) -> &gtk4::Box {
+        &self.container
+    }
+
+    // Span the scrubber over a new sampling period, selecting all of it
+    pub fn set_range(&self, range: Option<(u64, u64)>) {
+        {
+            let mut state = self.state.borrow_mut();
+            state.range = range;
+            state.selection = (0.0, 1.0);
+            state.dragging = None;
+        }
+        self.container.set_visible(range.is_some());
+        self.update_label();
+        self.drawing_area.queue_draw();
+    }
+
+    // Move the handles to the window without notifying the handlers
+    pub fn set_window(&self, window: Option<(u64, u64)>) {
+        {
+            let mut state = self.state.borrow_mut();
+            let Some((lo, hi)) = state.range else {
+                return;
+            };
+            let span = hi.saturating_sub(lo).max(1) as f64;
+            let fraction = |t: u64| (t.saturating_sub(lo) as f64 / span).clamp(0.0, 1.0);
+            state.selection = match window {
+                Some((start, end)) => (fraction(start), fraction(end)),
+                None => (0.0, 1.0),
+            };
+        }
+        self.update_label();
+        self.drawing_area.queue_draw();
+    }
+
+    pub fn connect_changed<F: Fn(Option<(u64, u64)>) + 'static>(&self, f: F) {
+        self.changed_handlers.borrow_mut().push(Rc::new(f));
+    }
+
+    // Selected window in nanoseconds since boot, or None if all of the period is selected
+    pub fn window(&self) -> Option<(u64, u64)> {
+        let state = self.state.borrow();
+        let (lo, hi) = state.range?;
+        let (start, end) = state.selection;
+        if start <= 0.0 && end >= 1.0 {
+            return None;
+        }
+        let at = |fraction: f64| lo + (hi.saturating_sub(lo) as f64 * fraction).round() as u64;
+        Some((at(start), at(end)))
+    }
+
+    fn fraction_at(&self, x: f64) -> f64 {
+        let track_width = (self.drawing_area.width() as f64 - 2.0 * MARGIN).max(1.0);
+        ((x - MARGIN) / track_width).clamp(0.0, 1.0)
+    }
+
+    fn update_label(&self) {
+        let Some(full) = self.state.borrow().range else {
+            self.label.set_text("");
+            return;
+        };
+        let window = self.window();
+        let (lo, hi) = window.unwrap_or(full);
+        let duration = SignedDuration::from_nanos(hi.saturating_sub(lo) as i64);
+        self.label.set_text(&format!(
+            "{} – {} ({:#})",
+            format_time(lo, "%T"),
+            format_time(hi, "%T"),
+            duration
+        ));
+        self.reset_button.set_visible(window.is_some());
+    }
+
+    fn notify_changed(&self) {
+        let window = self.window();
+        // Clone the list so handlers may register further handlers
+        let handlers: Vec<ChangedHandler> = self.changed_handlers.borrow().clone();
+        for handler in handlers {
+            handler(window);
+        }
+    }
+}
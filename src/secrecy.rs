@@ -0,0 +1,87 @@
+use crate::data::TreeNode;
+use crate::decoders;
+use crate::sunburst::SunburstChart;
+use adw::prelude::*;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+// Handshakes in the view with and without forward secrecy; those with an
+// unknown ciphersuite are left out
+fn count_forward_secrecy(root: &TreeNode) -> (usize, usize) {
+    let mut stats = HashMap::new();
+    root.extract_stats_by(
+        &|node| {
+            let fs = decoders::handshake_forward_secrecy(node.event_name()?, &node.fields)?;
+            Some(fs.to_string())
+        },
+        &mut stats,
+    );
+    let count = |key: &str| stats.get(key).copied().unwrap_or(0);
+    (count("true"), count("false"))
+}
+
+// Card with the share of handshakes in the view whose ciphersuite provides
+// forward secrecy, and a switch fading the chart but for those that don't
+pub fn create_forward_secrecy_card(chart: Rc<SunburstChart>) -> gtk4::Box {
+    let section = gtk4::Box::new(gtk4::Orientation::Vertical, 6);
+    section.set_margin_start(12);
+    section.set_margin_end(12);
+    section.set_margin_top(12);
+    section.set_margin_bottom(12);
+    section.set_visible(false);
+
+    let title = gtk4::Label::new(Some("Forward Secrecy"));
+    title.set_halign(gtk4::Align::Start);
+    title.add_css_class("title-4");
+    section.append(&title);
+
+    let card = gtk4::Box::new(gtk4::Orientation::Vertical, 6);
+    card.add_css_class("card");
+    card.set_tooltip_text(Some(
+        "Ciphersuites with an ephemeral (EC)DHE key exchange, and all TLS 1.3 ones, provide forward secrecy; those with RSA key transport don't",
+    ));
+    section.append(&card);
+
+    let share_label = gtk4::Label::new(None);
+    share_label.add_css_class("title-1");
+    share_label.set_margin_top(12);
+    card.append(&share_label);
+
+    let caption = gtk4::Label::new(None);
+    caption.add_css_class("dim-label");
+    card.append(&caption);
+
+    let highlight_row = gtk4::Box::new(gtk4::Orientation::Horizontal, 12);
+    highlight_row.set_margin_start(12);
+    highlight_row.set_margin_end(12);
+    highlight_row.set_margin_bottom(12);
+    let highlight_label = gtk4::Label::new(Some("Highlight non-FS usage"));
+    highlight_label.set_halign(gtk4::Align::Start);
+    highlight_label.set_hexpand(true);
+    highlight_row.append(&highlight_label);
+    let highlight_switch = gtk4::Switch::new();
+    highlight_switch.set_valign(gtk4::Align::Center);
+    highlight_row.append(&highlight_switch);
+    card.append(&highlight_row);
+
+    let chart_clone = chart.clone();
+    highlight_switch.connect_active_notify(move |switch| {
+        chart_clone.set_highlight_non_fs(switch.is_active());
+    });
+
+    let section_clone = section.clone();
+    chart.connect_view_changed(move |root| {
+        let (fs, non_fs) = count_forward_secrecy(root);
+        let total = fs + non_fs;
+        section_clone.set_visible(total > 0);
+        if total == 0 {
+            return;
+        }
+        let share = (fs as f64 / total as f64 * 100.0).round() as u32;
+        share_label.set_text(&format!("{}%", share));
+        caption.set_text(&format!("of {} handshakes; {} without it", total, non_fs));
+        highlight_row.set_sensitive(non_fs > 0);
+    });
+
+    section
+}
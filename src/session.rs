@@ -0,0 +1,135 @@
+use crate::annotations::Annotation;
+use crate::config::{Bookmark, Config};
+use crate::filters::{ExcludeRule, TlsVersion};
+use crate::history::ViewState;
+use crate::sunburst::SunburstChart;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Extension of analysis session files
+pub const EXTENSION: &str = "cua-session";
+
+// A view of a capture that can be handed to someone else: which file was
+// analyzed, how the tree was built and filtered, where it was zoomed and what
+// was noted about it
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Session {
+    pub source: PathBuf,
+    // SHA-256 of the capture when the session was saved
+    pub source_hash: Option<String>,
+    pub aggregate: bool,
+    pub group_by_process: bool,
+    pub collapse_contexts: bool,
+    pub show_codes: bool,
+    pub value_mode: String,
+    pub sort_order: String,
+    pub weighting: String,
+    pub percent_base: String,
+    pub layout: String,
+    // Time window in nanoseconds since boot, None for the whole sampling period
+    pub time_window: Option<(u64, u64)>,
+    pub search: String,
+    // Text of the filter expression
+    pub filter_query: Option<String>,
+    pub tls_version: Option<TlsVersion>,
+    pub hidden_origins: BTreeSet<String>,
+    pub failures_only: bool,
+    // Boot ID of the boot session shown, of a capture spanning several boots
+    pub boot_session: Option<String>,
+    // None in sessions saved before they kept the exclusion rules, which
+    // then leave the rules as they are
+    pub exclude_rules: Option<Vec<ExcludeRule>>,
+    pub zoom_path: Vec<String>,
+    pub bookmarks: Vec<Bookmark>,
+    pub annotations: Vec<Annotation>,
+}
+
+impl Session {
+    // Capture the current view of the chart, which must have been loaded from a file
+    pub fn capture(chart: &SunburstChart, config: &Config) -> Result<Self> {
+        let source = chart
+            .source_path()
+            .ok_or_else(|| anyhow!("Only captures opened from a file can be saved in a session"))?;
+        let source_hash = chart.source_hash();
+        let options = chart.tree_options();
+        let state = chart.view_state();
+        let bookmarks = source_hash
+            .as_ref()
+            .and_then(|hash| config.bookmarks.get(hash))
+            .cloned()
+            .unwrap_or_default();
+        let annotations = source_hash
+            .as_ref()
+            .and_then(|hash| config.annotations.get(hash))
+            .cloned()
+            .unwrap_or_default();
+
+        Ok(Self {
+            source,
+            source_hash,
+            aggregate: options.aggregate,
+            group_by_process: options.group_by_process,
+            collapse_contexts: options.collapse_contexts,
+            show_codes: options.show_codes,
+            value_mode: options.value_mode.name().to_string(),
+            sort_order: options.sort_order.name().to_string(),
+            weighting: chart.weighting().name().to_string(),
+            percent_base: chart.percent_base().name().to_string(),
+            layout: chart.layout().name().to_string(),
+            time_window: state.time_window,
+            search: state.search,
+            filter_query: state.filter_query,
+            tls_version: state.tls_version,
+            hidden_origins: state.hidden_origins.into_iter().collect(),
+            failures_only: state.failures_only,
+            boot_session: chart.boot_session(),
+            exclude_rules: Some(state.exclude_rules),
+            zoom_path: state.zoom_path,
+            bookmarks,
+            annotations,
+        })
+    }
+
+    // Filters, exclusions, time window and zoom of the view, applied together
+    pub fn view_state(&self, chart: &SunburstChart) -> ViewState {
+        ViewState {
+            search: self.search.clone(),
+            filter_query: self.filter_query.clone(),
+            tls_version: self.tls_version,
+            hidden_origins: self.hidden_origins.iter().cloned().collect(),
+            failures_only: self.failures_only,
+            time_window: self.time_window,
+            exclude_rules: self
+                .exclude_rules
+                .clone()
+                .unwrap_or_else(|| chart.view_state().exclude_rules),
+            zoom_path: self.zoom_path.clone(),
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    // Locate the capture. Sessions are often passed around together with the
+    // capture, so a file of the same name next to the session is used when the
+    // original path doesn't exist.
+    pub fn resolve_source(&self, session_path: &Path) -> PathBuf {
+        if self.source.exists() {
+            return self.source.clone();
+        }
+        match (session_path.parent(), self.source.file_name()) {
+            (Some(dir), Some(name)) => dir.join(name),
+            _ => self.source.clone(),
+        }
+    }
+}
@@ -0,0 +1,40 @@
+use gtk4::gio;
+use gtk4::prelude::*;
+
+// The app's GSettings, None if its schema isn't installed, e.g. when run from
+// the build tree with cargo
+pub fn settings() -> Option<gio::Settings> {
+    gio::SettingsSchemaSource::default()?.lookup(crate::APP_ID, true)?;
+    Some(gio::Settings::new(crate::APP_ID))
+}
+
+// Keep the window geometry, sidebar width and content page in GSettings, so
+// the next session starts where this one ended
+pub fn bind_window_state(
+    window: &adw::ApplicationWindow,
+    split_view: &adw::NavigationSplitView,
+    content: &adw::ViewStack,
+) {
+    let Some(settings) = settings() else {
+        return;
+    };
+    settings
+        .bind("window-width", window, "default-width")
+        .build();
+    settings
+        .bind("window-height", window, "default-height")
+        .build();
+    settings
+        .bind("window-maximized", window, "maximized")
+        .build();
+    settings
+        .bind(
+            "sidebar-width-fraction",
+            split_view,
+            "sidebar-width-fraction",
+        )
+        .build();
+    settings
+        .bind("content-page", content, "visible-child-name")
+        .build();
+}
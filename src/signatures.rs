@@ -0,0 +1,205 @@
+use crate::data::TreeNode;
+use crate::decoders;
+use crate::models::MatrixRowObject;
+use crate::palette::SERIES_COLORS;
+use crate::sunburst::SunburstChart;
+use adw::prelude::*;
+use gtk4::{gio, ColumnView, DrawingArea, NoSelection};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::f64::consts::PI;
+use std::rc::Rc;
+
+const SIZE: f64 = 120.0;
+const OUTER_RADIUS: f64 = 56.0;
+const INNER_RADIUS: f64 = 34.0;
+const LEGEND_ROW_HEIGHT: f64 = 22.0;
+const FONT_SIZE: f64 = 12.0;
+// Algorithms beyond these are combined into "Other" in the donuts
+const MAX_SLICES: usize = SERIES_COLORS.len() - 1;
+
+// Directions of a signature operation, in the order of the table columns
+const DIRECTIONS: [(&str, &str); 2] = [("tls::sign", "Sign"), ("tls::verify", "Verify")];
+
+// An algorithm with its sign and verify counts
+type AlgorithmCounts = (String, [usize; 2]);
+
+// Signature algorithm of a sign or verify event node, with the code of unknown schemes
+fn signature_algorithm(node: &TreeNode, event: &str) -> Option<String> {
+    if node.event_name()? != event {
+        return None;
+    }
+    let code = node.fields.get("tls::signature_algorithm")?.as_u64()?;
+    Some(
+        decoders::signature_scheme_name(code)
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("0x{:04x}", code)),
+    )
+}
+
+// Sign and verify counts of each algorithm in the view, most used first
+fn count_signatures(root: &TreeNode) -> Vec<AlgorithmCounts> {
+    let mut counts: HashMap<String, [usize; 2]> = HashMap::new();
+    for (i, (event, _)) in DIRECTIONS.iter().enumerate() {
+        let mut stats = HashMap::new();
+        root.extract_stats_by(&|node| signature_algorithm(node, event), &mut stats);
+        for (algorithm, count) in stats {
+            counts.entry(algorithm).or_default()[i] += count;
+        }
+    }
+    let mut counts: Vec<AlgorithmCounts> = counts.into_iter().collect();
+    counts.sort_by(|a, b| {
+        let total = |counts: &[usize; 2]| counts[0] + counts[1];
+        total(&b.1).cmp(&total(&a.1)).then_with(|| a.0.cmp(&b.0))
+    });
+    counts
+}
+
+// Slices of a donut: the most used algorithms, with the rest combined last.
+// Colors follow the overall order, so an algorithm looks the same in both donuts.
+fn slices(counts: &[AlgorithmCounts], direction: usize) -> Vec<usize> {
+    let mut slices: Vec<usize> = counts
+        .iter()
+        .take(MAX_SLICES)
+        .map(|(_, counts)| counts[direction])
+        .collect();
+    if counts.len() > MAX_SLICES {
+        slices.push(
+            counts[MAX_SLICES..]
+                .iter()
+                .map(|(_, counts)| counts[direction])
+                .sum(),
+        );
+    }
+    slices
+}
+
+fn draw_donut(cr: &cairo::Context, cx: f64, slices: &[usize], caption: &str) {
+    let cy = SIZE / 2.0;
+    let total: usize = slices.iter().sum();
+    if total == 0 {
+        cr.set_source_rgb(0.9, 0.9, 0.9);
+        cr.arc(cx, cy, OUTER_RADIUS, 0.0, 2.0 * PI);
+        cr.arc_negative(cx, cy, INNER_RADIUS, 2.0 * PI, 0.0);
+        cr.close_path();
+        cr.fill().unwrap();
+    }
+
+    let mut start = -PI / 2.0;
+    for (i, count) in slices.iter().enumerate() {
+        let end = start + *count as f64 / total.max(1) as f64 * 2.0 * PI;
+        let (r, g, b) = SERIES_COLORS[i];
+        cr.set_source_rgb(r, g, b);
+        cr.arc(cx, cy, OUTER_RADIUS, start, end);
+        cr.arc_negative(cx, cy, INNER_RADIUS, end, start);
+        cr.close_path();
+        cr.fill().unwrap();
+        start = end;
+    }
+
+    // Direction and number of operations in the hole
+    cr.set_source_rgb(0.3, 0.3, 0.3);
+    cr.set_font_size(FONT_SIZE);
+    for (line, text) in [caption.to_string(), total.to_string()].iter().enumerate() {
+        if let Ok(extents) = cr.text_extents(text) {
+            cr.move_to(
+                cx - extents.width() / 2.0 - extents.x_bearing(),
+                cy + (line as f64 - 0.5) * FONT_SIZE * 1.2 + FONT_SIZE / 2.0,
+            );
+            cr.show_text(text).unwrap();
+        }
+    }
+}
+
+// Breakdown of the signature algorithms of TLS sign and verify operations in
+// the view, so the signatures a host makes, usually as a server, can be
+// assessed apart from those it checks, usually as a client. Shows a donut per
+// direction and a table of both.
+pub fn create_signature_breakdown(chart: Rc<SunburstChart>) -> gtk4::Box {
+    let section = gtk4::Box::new(gtk4::Orientation::Vertical, 6);
+    section.set_margin_start(12);
+    section.set_margin_end(12);
+    section.set_margin_top(12);
+    section.set_margin_bottom(12);
+    section.set_visible(false);
+
+    let title = gtk4::Label::new(Some("Signature Algorithms"));
+    title.set_halign(gtk4::Align::Start);
+    title.add_css_class("title-4");
+    section.append(&title);
+
+    let counts: Rc<RefCell<Vec<AlgorithmCounts>>> = Rc::new(RefCell::new(Vec::new()));
+
+    let drawing_area = DrawingArea::new();
+    drawing_area.set_content_width((2.0 * SIZE + 12.0) as i32);
+    section.append(&drawing_area);
+
+    let counts_clone = counts.clone();
+    drawing_area.set_draw_func(move |_, cr, _, _| {
+        let counts = counts_clone.borrow();
+        if counts.is_empty() {
+            return;
+        }
+        for (i, (_, label)) in DIRECTIONS.iter().enumerate() {
+            let cx = SIZE / 2.0 + i as f64 * (SIZE + 12.0);
+            draw_donut(cr, cx, &slices(&counts, i), label);
+        }
+
+        // Legend under the donuts
+        cr.set_font_size(FONT_SIZE);
+        let mut labels: Vec<&str> = counts
+            .iter()
+            .take(MAX_SLICES)
+            .map(|(algorithm, _)| algorithm.as_str())
+            .collect();
+        if counts.len() > MAX_SLICES {
+            labels.push("Other");
+        }
+        for (i, label) in labels.iter().enumerate() {
+            let y = SIZE + 6.0 + i as f64 * LEGEND_ROW_HEIGHT;
+            let (r, g, b) = SERIES_COLORS[i];
+            cr.set_source_rgb(r, g, b);
+            cr.rectangle(0.0, y + 5.0, 12.0, 12.0);
+            cr.fill().unwrap();
+            cr.set_source_rgb(0.3, 0.3, 0.3);
+            cr.move_to(18.0, y + LEGEND_ROW_HEIGHT / 2.0 + FONT_SIZE / 3.0);
+            cr.show_text(label).unwrap();
+        }
+    });
+
+    let store = gio::ListStore::new::<MatrixRowObject>();
+    let view = ColumnView::new(Some(NoSelection::new(Some(store.clone()))));
+    view.add_css_class("data-table");
+    view.append_column(&crate::create_term_column(
+        "Algorithm",
+        |obj: &MatrixRowObject| obj.label(),
+    ));
+    for (i, (_, label)) in DIRECTIONS.iter().enumerate() {
+        view.append_column(&crate::create_text_column(
+            label,
+            gtk4::Align::End,
+            false,
+            move |obj: &MatrixRowObject| obj.count(i).to_string(),
+        ));
+    }
+    section.append(&view);
+
+    let section_clone = section.clone();
+    chart.connect_view_changed(move |root| {
+        let new_counts = count_signatures(root);
+        let rows: Vec<MatrixRowObject> = new_counts
+            .iter()
+            .map(|(algorithm, counts)| MatrixRowObject::new(algorithm, counts.to_vec()))
+            .collect();
+        store.splice(0, store.n_items(), &rows);
+
+        let legend_rows = new_counts.len().min(MAX_SLICES + 1);
+        drawing_area
+            .set_content_height((SIZE + 6.0 + legend_rows as f64 * LEGEND_ROW_HEIGHT) as i32);
+        section_clone.set_visible(!new_counts.is_empty());
+        *counts.borrow_mut() = new_counts;
+        drawing_area.queue_draw();
+    });
+
+    section
+}
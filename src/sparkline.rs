@@ -0,0 +1,86 @@
+use crate::boots;
+use crate::sunburst::SunburstChart;
+use gtk4::prelude::*;
+use gtk4::DrawingArea;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+const HEIGHT: i32 = 28;
+// Width of a bin, in pixels
+const BIN_WIDTH: f64 = 3.0;
+
+// Number of event starts in each of `bins` equal parts of the period
+pub fn density(starts: &[u64], (start, end): (u64, u64), bins: usize) -> Vec<usize> {
+    let bins = bins.max(1);
+    let mut counts = vec![0; bins];
+    let span = end.saturating_sub(start).max(1) as f64;
+    for &time in starts {
+        let bin = (time.saturating_sub(start) as f64 / span * bins as f64) as usize;
+        counts[bin.min(bins - 1)] += 1;
+    }
+    counts
+}
+
+// Start times of the events, and the period they fall in
+#[derive(Default)]
+struct Samples {
+    starts: Vec<u64>,
+    range: Option<(u64, u64)>,
+}
+
+// Tiny area chart of when the events of the sampling period start, telling
+// bursty workloads from steady ones
+pub fn create_density_sparkline(chart: Rc<SunburstChart>) -> DrawingArea {
+    let drawing_area = DrawingArea::new();
+    drawing_area.set_content_height(HEIGHT);
+    drawing_area.set_margin_top(6);
+    drawing_area.set_tooltip_text(Some("Events over the sampling period"));
+    drawing_area.set_visible(false);
+
+    let samples = Rc::new(RefCell::new(Samples::default()));
+
+    let samples_clone = samples.clone();
+    drawing_area.set_draw_func(move |_, cr, width, height| {
+        let samples = samples_clone.borrow();
+        let Some(range) = samples.range else {
+            return;
+        };
+        let (width, height) = (width as f64, height as f64);
+        let counts = density(&samples.starts, range, (width / BIN_WIDTH) as usize);
+        let peak = counts.iter().copied().max().unwrap_or(0).max(1) as f64;
+        let step = width / counts.len() as f64;
+
+        cr.move_to(0.0, height);
+        for (i, count) in counts.iter().enumerate() {
+            let y = height - *count as f64 / peak * (height - 1.0);
+            cr.line_to(i as f64 * step, y);
+            cr.line_to((i + 1) as f64 * step, y);
+        }
+        cr.line_to(width, height);
+        cr.close_path();
+        cr.set_source_rgba(0.21, 0.52, 0.89, 0.35);
+        cr.fill_preserve().unwrap();
+        cr.set_source_rgb(0.21, 0.52, 0.89);
+        cr.set_line_width(1.0);
+        cr.stroke().unwrap();
+    });
+
+    let drawing_area_clone = drawing_area.clone();
+    let chart_clone = chart.clone();
+    chart.connect_view_changed(move |_| {
+        // The boot session shown, not narrowed to the time window
+        let mut events = chart_clone.events();
+        if let Some(boot_id) = chart_clone.boot_session() {
+            events = boots::filter_boot(&events, &boot_id);
+        }
+        let starts: Vec<u64> = events.iter().map(|event| event.start).collect();
+        drawing_area_clone.set_visible(!starts.is_empty());
+        *samples.borrow_mut() = Samples {
+            starts,
+            range: chart_clone.session_range(),
+        };
+        drawing_area_clone.queue_draw();
+    });
+
+    drawing_area
+}
@@ -0,0 +1,400 @@
+use crate::csv_import;
+use crate::data::{AuditEvent, NodeKind, TreeNode};
+use crate::decoders;
+use crate::detail;
+use crate::parquet;
+use crate::sunburst::SunburstChart;
+use adw::prelude::*;
+use adw::{Toast, ToastOverlay};
+use anyhow::{anyhow, Context, Result};
+use gtk4::{
+    gio, glib, ColumnView, NoSelection, ScrolledWindow, StringList, StringObject, TextView,
+};
+use std::cell::RefCell;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::Path;
+use std::rc::Rc;
+
+// Databases are built and queried by the sqlite3 command, fed a script
+// creating the tables and inserting the events
+
+const SCHEMA: &str = "\
+CREATE TABLE events (
+  id INTEGER PRIMARY KEY,
+  parent INTEGER REFERENCES events(id),
+  context TEXT NOT NULL,
+  origin TEXT NOT NULL,
+  name TEXT NOT NULL,
+  algorithm TEXT,
+  bits INTEGER,
+  process TEXT,
+  start_ns INTEGER NOT NULL,
+  end_ns INTEGER NOT NULL,
+  duration_ns INTEGER NOT NULL,
+  details TEXT NOT NULL
+);
+CREATE TABLE fields (
+  event INTEGER NOT NULL REFERENCES events(id),
+  key TEXT NOT NULL,
+  value
+);
+";
+
+const DEFAULT_QUERY: &str = "SELECT name, algorithm, COUNT(*) AS events
+FROM events
+GROUP BY name, algorithm
+ORDER BY events DESC";
+
+// Rows of a query result, with the column names of its header
+#[derive(Default)]
+pub struct ResultTable {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+fn quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "''"))
+}
+
+fn optional<T: ToString>(value: Option<T>) -> String {
+    value.map_or_else(|| "NULL".to_string(), |value| value.to_string())
+}
+
+fn sql_value(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::Null => "NULL".to_string(),
+        serde_json::Value::Bool(value) => (*value as u8).to_string(),
+        serde_json::Value::Number(number) => number.to_string(),
+        serde_json::Value::String(text) => quote(text),
+        other => quote(&other.to_string()),
+    }
+}
+
+// Rows are numbered in the order they're inserted, from 1
+fn insert_events(
+    events: &[AuditEvent],
+    parent: Option<usize>,
+    last_id: &mut usize,
+    script: &mut String,
+) {
+    for event in events {
+        *last_id += 1;
+        let id = *last_id;
+        script.push_str(&format!(
+            "INSERT INTO events VALUES ({}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {});\n",
+            id,
+            optional(parent),
+            quote(&event.context),
+            quote(&event.origin),
+            quote(&event.name()),
+            optional(parquet::algorithm_of(event).map(|algorithm| quote(&algorithm))),
+            optional(parquet::bits_of(event)),
+            optional(
+                decoders::process_name(&event.events, &event.context).map(|name| quote(&name))
+            ),
+            event.start,
+            event.end,
+            event.end.saturating_sub(event.start),
+            quote(&event.format_details()),
+        ));
+        let mut keys: Vec<&String> = event.events.keys().filter(|key| *key != "name").collect();
+        keys.sort();
+        for key in keys {
+            script.push_str(&format!(
+                "INSERT INTO fields VALUES ({}, {}, {});\n",
+                id,
+                quote(key),
+                sql_value(&event.events[key]),
+            ));
+        }
+        insert_events(&event.spans, Some(id), last_id, script);
+    }
+}
+
+// Script creating the tables: a row per event and span in events, linked to
+// the event it's a span of, and a row per raw field of each in fields
+pub fn database_script(events: &[AuditEvent]) -> String {
+    let mut script = String::from("BEGIN;\n");
+    script.push_str(SCHEMA);
+    insert_events(events, None, &mut 0, &mut script);
+    script.push_str("COMMIT;\n");
+    script
+}
+
+// Run sqlite3 on a database with a script on its standard input, returning its output
+async fn sqlite3(database: &OsStr, options: &[&str], script: &str) -> Result<String> {
+    let mut argv: Vec<&OsStr> = vec![OsStr::new("sqlite3"), OsStr::new("-batch")];
+    argv.extend(options.iter().map(OsStr::new));
+    argv.push(database);
+
+    let process = gio::Subprocess::newv(
+        &argv,
+        gio::SubprocessFlags::STDIN_PIPE
+            | gio::SubprocessFlags::STDOUT_PIPE
+            | gio::SubprocessFlags::STDERR_PIPE,
+    )
+    .context("Running sqlite3 failed; is it installed?")?;
+
+    let (stdout, stderr) = process
+        .communicate_utf8_future(Some(script.to_string()))
+        .await?;
+
+    if !process.is_successful() {
+        let message = stderr
+            .as_ref()
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .unwrap_or_else(|| format!("exit status {}", process.exit_status()));
+        return Err(anyhow!("{}", message));
+    }
+
+    Ok(stdout.map(|s| s.to_string()).unwrap_or_default())
+}
+
+// Load the events into an in-memory database and run a query on it. A query
+// returning no rows gives an empty table, as sqlite3 prints no header then.
+pub async fn run_query(events: &[AuditEvent], query: &str) -> Result<ResultTable> {
+    let mut script = database_script(events);
+    script.push_str(query);
+    script.push('\n');
+    let output = sqlite3(
+        OsStr::new(":memory:"),
+        &["-bail", "-csv", "-header"],
+        &script,
+    )
+    .await?;
+
+    let mut rows = csv_import::parse(&output).into_iter();
+    let columns = rows.next().unwrap_or_default();
+    Ok(ResultTable {
+        columns,
+        rows: rows.collect(),
+    })
+}
+
+// Save the events as an SQLite database file, replacing any existing one
+pub async fn save_database(events: &[AuditEvent], path: &Path) -> Result<()> {
+    if events.is_empty() {
+        return Err(anyhow!("No events to save"));
+    }
+    if path.exists() {
+        fs::remove_file(path)?;
+    }
+    sqlite3(path.as_os_str(), &["-bail"], &database_script(events)).await?;
+    Ok(())
+}
+
+fn sort_by_value(node: &mut TreeNode) {
+    node.children
+        .sort_by(|a, b| b.value.cmp(&a.value).then_with(|| a.name.cmp(&b.name)));
+    for child in &mut node.children {
+        sort_by_value(child);
+    }
+}
+
+// Tree of a query result to chart: its text columns are the rings, from the
+// first, and a final numeric column, e.g. a COUNT(*), sizes the segments.
+// Without one, each row counts once.
+pub fn tree_from_table(table: &ResultTable) -> Result<TreeNode> {
+    if table.rows.is_empty() {
+        return Err(anyhow!("The query returned no rows"));
+    }
+    let last = table.columns.len().saturating_sub(1);
+    let numeric = last > 0
+        && table.rows.iter().all(|row| {
+            row.get(last)
+                .is_some_and(|value| value.parse::<f64>().is_ok())
+        });
+    let levels = if numeric { last } else { table.columns.len() };
+
+    let mut root = TreeNode::new(NodeKind::Root, "Query", 0);
+    for row in &table.rows {
+        let value = if numeric {
+            row[last].parse::<f64>().unwrap_or(0.0).max(0.0).round() as usize
+        } else {
+            1
+        };
+        let mut node = &mut root;
+        node.value += value;
+        node.count += value;
+        for level in 0..levels {
+            let name = row
+                .get(level)
+                .filter(|name| !name.is_empty())
+                .map_or("(null)", String::as_str);
+            let index = match node.children.iter().position(|child| child.name == name) {
+                Some(index) => index,
+                None => {
+                    node.children
+                        .push(TreeNode::new(NodeKind::Category, name, 0));
+                    node.children.len() - 1
+                }
+            };
+            node = &mut node.children[index];
+            node.value += value;
+            node.count += value;
+        }
+    }
+    sort_by_value(&mut root);
+    Ok(root)
+}
+
+fn update_view(view: &ColumnView, table: &Rc<RefCell<ResultTable>>) {
+    while let Some(column) = view
+        .columns()
+        .item(0)
+        .and_downcast::<gtk4::ColumnViewColumn>()
+    {
+        view.remove_column(&column);
+    }
+    for (index, title) in table.borrow().columns.iter().enumerate() {
+        let table = table.clone();
+        view.append_column(&crate::create_text_column(
+            title,
+            gtk4::Align::Start,
+            true,
+            move |obj: &StringObject| {
+                // Items are the indices of the rows
+                let row: usize = obj.string().parse().unwrap_or(0);
+                table
+                    .borrow()
+                    .rows
+                    .get(row)
+                    .and_then(|row| row.get(index))
+                    .cloned()
+                    .unwrap_or_default()
+            },
+        ));
+    }
+}
+
+// Panel running SQL queries on the events in the view, loaded in an in-memory
+// SQLite database with an events and a fields table. Results are shown as a
+// table, and can be charted as a sunburst grouped by their columns.
+pub fn create_sql_panel(chart: Rc<SunburstChart>, toast_overlay: ToastOverlay) -> gtk4::Box {
+    let panel = gtk4::Box::new(gtk4::Orientation::Vertical, 6);
+    panel.set_margin_start(12);
+    panel.set_margin_end(12);
+    panel.set_margin_top(12);
+    panel.set_margin_bottom(12);
+
+    let query_view = TextView::new();
+    query_view.set_monospace(true);
+    query_view.set_wrap_mode(gtk4::WrapMode::WordChar);
+    query_view.set_top_margin(6);
+    query_view.set_bottom_margin(6);
+    query_view.set_left_margin(6);
+    query_view.set_right_margin(6);
+    query_view.buffer().set_text(DEFAULT_QUERY);
+    query_view.set_tooltip_text(Some(
+        "Tables: events (id, parent, context, origin, name, algorithm, bits, process, start_ns, end_ns, duration_ns, details) and fields (event, key, value)",
+    ));
+    let query_scroll = ScrolledWindow::new();
+    query_scroll.set_min_content_height(96);
+    query_scroll.add_css_class("card");
+    query_scroll.set_child(Some(&query_view));
+    panel.append(&query_scroll);
+
+    let button_row = gtk4::Box::new(gtk4::Orientation::Horizontal, 6);
+    let status = gtk4::Label::new(None);
+    status.set_halign(gtk4::Align::Start);
+    status.set_hexpand(true);
+    status.add_css_class("dim-label");
+    button_row.append(&status);
+    let chart_button = gtk4::Button::with_label("Show as Chart");
+    chart_button.set_tooltip_text(Some(
+        "Chart the result, with a ring per text column, sized by a final numeric column",
+    ));
+    chart_button.set_sensitive(false);
+    button_row.append(&chart_button);
+    let run_button = gtk4::Button::with_label("Run");
+    run_button.add_css_class("suggested-action");
+    run_button.set_tooltip_text(Some("Run the query on the events in the view (Ctrl+Enter)"));
+    button_row.append(&run_button);
+    panel.append(&button_row);
+
+    let table = Rc::new(RefCell::new(ResultTable::default()));
+    let rows = StringList::new(&[]);
+    let view = ColumnView::new(Some(NoSelection::new(Some(rows.clone()))));
+    view.add_css_class("data-table");
+    let result_scroll = ScrolledWindow::new();
+    result_scroll.set_vexpand(true);
+    result_scroll.set_child(Some(&view));
+    panel.append(&result_scroll);
+
+    let query_view_clone = query_view.clone();
+    let chart_button_clone = chart_button.clone();
+    let toast_overlay_clone = toast_overlay.clone();
+    let table_clone = table.clone();
+    let chart_clone = chart.clone();
+    run_button.connect_clicked(move |button| {
+        let buffer = query_view_clone.buffer();
+        let query = buffer
+            .text(&buffer.start_iter(), &buffer.end_iter(), false)
+            .to_string();
+        if query.trim().is_empty() {
+            return;
+        }
+        let events = chart_clone.visible_events();
+        let button = button.clone();
+        let status = status.clone();
+        let view = view.clone();
+        let rows = rows.clone();
+        let table = table_clone.clone();
+        let chart_button = chart_button_clone.clone();
+        let toast_overlay = toast_overlay_clone.clone();
+        button.set_sensitive(false);
+        status.set_text("Running…");
+        glib::spawn_future_local(async move {
+            match run_query(&events, &query).await {
+                Ok(result) => {
+                    status.set_text(&format!(
+                        "{} rows from {} events in the view",
+                        result.rows.len(),
+                        events.len()
+                    ));
+                    let indices: Vec<String> =
+                        (0..result.rows.len()).map(|i| i.to_string()).collect();
+                    let indices: Vec<&str> = indices.iter().map(String::as_str).collect();
+                    chart_button.set_sensitive(!result.rows.is_empty());
+                    *table.borrow_mut() = result;
+                    update_view(&view, &table);
+                    rows.splice(0, rows.n_items(), &indices);
+                }
+                Err(e) => {
+                    status.set_text("");
+                    toast_overlay.add_toast(Toast::new(&format!("Query failed: {}", e)));
+                }
+            }
+            button.set_sensitive(true);
+        });
+    });
+
+    // Ctrl+Enter runs the query from the editor
+    let key_controller = gtk4::EventControllerKey::new();
+    let run_button_clone = run_button.clone();
+    key_controller.connect_key_pressed(move |_, key, _, modifiers| {
+        if modifiers.contains(gtk4::gdk::ModifierType::CONTROL_MASK)
+            && matches!(key, gtk4::gdk::Key::Return | gtk4::gdk::Key::KP_Enter)
+        {
+            run_button_clone.emit_clicked();
+            return glib::Propagation::Stop;
+        }
+        glib::Propagation::Proceed
+    });
+    query_view.add_controller(key_controller);
+
+    let panel_clone = panel.clone();
+    chart_button.connect_clicked(move |_| {
+        let Some(window) = panel_clone.root().and_downcast::<gtk4::Window>() else {
+            return;
+        };
+        match tree_from_table(&table.borrow()) {
+            Ok(tree) => detail::show_detail_window(&window, &tree, &chart),
+            Err(e) => toast_overlay.add_toast(Toast::new(&e.to_string())),
+        }
+    });
+
+    panel
+}
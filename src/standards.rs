@@ -0,0 +1,471 @@
+use crate::compliance::{Profile, Rule, Severity, Target};
+
+// Built-in profiles of published standards, as rules on top of a permissive
+// profile. Names are as given to --profile, in uppercase.
+pub const NAMES: [&str; 3] = ["SP800-131A", "CNSA2", "TR-02102"];
+
+pub fn profile(name: &str) -> Option<Profile> {
+    let rules = match name {
+        "SP800-131A" => sp800_131a(),
+        "CNSA2" => cnsa2(),
+        "TR-02102" => tr_02102(),
+        _ => return None,
+    };
+    Some(Profile {
+        rules,
+        ..Profile::permissive(name)
+    })
+}
+
+fn deny(target: Target, pattern: &str, severity: Severity, note: &str) -> Rule {
+    Rule {
+        target,
+        allow: false,
+        pattern: pattern.to_string(),
+        min_bits: None,
+        severity,
+        from_year: None,
+        note: note.to_string(),
+    }
+}
+
+fn allow(target: Target, pattern: &str, severity: Severity, note: &str) -> Rule {
+    Rule {
+        allow: true,
+        ..deny(target, pattern, severity, note)
+    }
+}
+
+// Usage deprecated until the end of a year and disallowed after it, as a
+// pair of rules of which the disallowing one takes over. The note of the
+// rule, if any, comes first.
+fn transition(rule: Rule, last_year: i16) -> [Rule; 2] {
+    let note = |status: String| match rule.note.as_str() {
+        "" => status,
+        note => format!("{}, {}", note, status),
+    };
+    let disallowed = Rule {
+        severity: Severity::Error,
+        from_year: Some(last_year + 1),
+        note: note(format!("disallowed after {}", last_year)),
+        ..rule.clone()
+    };
+    let deprecated = Rule {
+        severity: Severity::Warning,
+        note: note(format!("deprecated, disallowed after {}", last_year)),
+        ..rule
+    };
+    [deprecated, disallowed]
+}
+
+// NIST SP 800-131A Rev. 2 transitions, with the 2030 end of 112-bit security
+// strength of the Rev. 3 draft and IR 8547. Acceptable usage matches no
+// rule, deprecated usage is a warning and disallowed usage an error.
+fn sp800_131a() -> Vec<Rule> {
+    use Severity::Error;
+    use Target::*;
+    const GENERATION: &str = "disallowed for signature generation";
+
+    let mut rules = vec![
+        // SP 800-52 Rev. 2 requires TLS 1.2 or later
+        deny(Protocol, "SSL", Error, "disallowed"),
+        deny(Protocol, "TLS 1.0", Error, "disallowed"),
+        deny(Protocol, "TLS 1.1", Error, "disallowed"),
+        deny(Cipher, "DES", Error, "disallowed"),
+        deny(Cipher, "RC4", Error, "not approved"),
+        deny(Cipher, "NULL", Error, "not approved"),
+        deny(Cipher, "none", Error, "not approved"),
+        deny(Hash, "MD5", Error, "not approved"),
+        deny(Hash, "MD4", Error, "not approved"),
+        deny(Hash, "MD2", Error, "not approved"),
+        deny(Signature, "SHA1", Error, GENERATION),
+        deny(Signature, "ssh-rsa", Error, GENERATION),
+        deny(Signature, "DSA", Error, GENERATION),
+        deny(Signature, "ssh-dss", Error, GENERATION),
+        deny(Mac, "MD5", Error, "not approved"),
+        deny(KeyExchange, "group1", Error, "disallowed"),
+        // SP 800-90A DRBGs are the approved random bit generators
+        allow(Random, "DRBG", Error, "not an SP 800-90A DRBG"),
+    ];
+    // Less than 112 bits of security strength
+    for algorithm in ["RSA", "DSA", "DH", "ELG"] {
+        rules.push(Rule {
+            min_bits: Some(2048),
+            ..deny(PublicKey, algorithm, Error, "disallowed")
+        });
+    }
+    for algorithm in ["EC", "ECDSA", "ECDH"] {
+        rules.push(Rule {
+            min_bits: Some(224),
+            ..deny(PublicKey, algorithm, Error, "disallowed")
+        });
+    }
+    rules.push(Rule {
+        min_bits: Some(2048),
+        ..deny(KeyExchange, "MODP", Error, "disallowed")
+    });
+
+    // Three-key 3DES was deprecated through 2023
+    rules.extend(transition(deny(Cipher, "3DES", Error, ""), 2023));
+    // 112-bit security strength, SHA-1 for other uses than signatures and
+    // SHA-224 are deprecated through 2030
+    for algorithm in ["RSA", "DSA", "DH", "ELG"] {
+        rules.extend(transition(
+            Rule {
+                min_bits: Some(3072),
+                ..deny(PublicKey, algorithm, Error, "")
+            },
+            2030,
+        ));
+    }
+    for algorithm in ["EC", "ECDSA", "ECDH"] {
+        rules.extend(transition(
+            Rule {
+                min_bits: Some(256),
+                ..deny(PublicKey, algorithm, Error, "")
+            },
+            2030,
+        ));
+    }
+    rules.extend(transition(
+        Rule {
+            min_bits: Some(3072),
+            ..deny(KeyExchange, "MODP", Error, "")
+        },
+        2030,
+    ));
+    for group in ["ffdhe2048", "group14"] {
+        rules.extend(transition(deny(KeyExchange, group, Error, ""), 2030));
+    }
+    for hash in ["SHA1", "SHA224", "SHA3-224"] {
+        rules.extend(transition(deny(Hash, hash, Error, ""), 2030));
+    }
+    rules
+}
+
+// NSA's CNSA 2.0 suite: ML-KEM-1024, ML-DSA-87, AES-256 and SHA-384 or
+// SHA-512, with TLS 1.3. Everything else is an error, except the CNSA 1.0
+// algorithms, which are deprecated until systems must use CNSA 2.0
+// exclusively at the end of 2033.
+fn cnsa2() -> Vec<Rule> {
+    use Severity::Error;
+    use Target::*;
+    const NOTE: &str = "not a CNSA 2.0 algorithm";
+    const CNSA1: &str = "CNSA 1.0";
+
+    let mut rules = Vec::new();
+    let mut mandated = |target, patterns: &[&str]| {
+        for pattern in patterns {
+            rules.push(allow(target, pattern, Error, NOTE));
+        }
+    };
+    mandated(Protocol, &["TLS 1.3"]);
+    mandated(
+        KeyExchange,
+        &["MLKEM1024", "ML-KEM-1024", "SecP384r1MLKEM1024"],
+    );
+    mandated(Signature, &["mldsa87", "ML-DSA-87"]);
+    mandated(Cipher, &["AES 256", "AES256"]);
+    mandated(Hash, &["SHA384", "SHA512", "SHA-384", "SHA-512"]);
+    mandated(Mac, &["SHA384", "SHA512", "SHA2-384", "SHA2-512"]);
+    mandated(
+        PublicKey,
+        &["ML-KEM-1024", "ML-DSA-87", "MLKEM1024", "MLDSA87"],
+    );
+    rules.push(Rule {
+        min_bits: Some(256),
+        ..allow(Cipher, "AES", Error, NOTE)
+    });
+
+    // CNSA 1.0 algorithms are allowed, but deprecated
+    let mut legacy = |target, pattern: &str, min_bits| {
+        rules.push(Rule {
+            min_bits,
+            ..allow(target, pattern, Error, NOTE)
+        });
+        rules.extend(transition(deny(target, pattern, Error, CNSA1), 2033));
+    };
+    legacy(Protocol, "TLS 1.2", None);
+    for group in [
+        "secp384r1",
+        "ECP-384",
+        "ecdh-sha2-nistp384",
+        "ffdhe3072",
+        "ffdhe4096",
+        "ffdhe6144",
+        "ffdhe8192",
+    ] {
+        legacy(KeyExchange, group, None);
+    }
+    legacy(KeyExchange, "MODP", Some(3072));
+    for signature in [
+        "ecdsa_secp384r1_sha384",
+        "rsa_pkcs1_sha384",
+        "rsa_pss_rsae_sha384",
+        "rsa_pss_pss_sha384",
+        "ecdsa-sha2-nistp384",
+        "rsa-sha2-512",
+    ] {
+        legacy(Signature, signature, None);
+    }
+    legacy(PublicKey, "RSA", Some(3072));
+    legacy(PublicKey, "ECDSA", Some(384));
+    legacy(PublicKey, "ECDH", Some(384));
+    legacy(PublicKey, "DH", Some(3072));
+    rules
+}
+
+// BSI TR-02102-1 and -2 (2025): the recommended algorithms and key lengths,
+// with their validity horizons as transitions. Usage the guideline doesn't
+// recommend, without ruling it out, is a warning.
+fn tr_02102() -> Vec<Rule> {
+    use Severity::{Error, Warning};
+    use Target::*;
+    const BROKEN: &str = "not allowed";
+    const UNLISTED: &str = "not recommended by TR-02102";
+
+    let mut rules = vec![
+        deny(Protocol, "SSL", Error, BROKEN),
+        deny(Protocol, "TLS 1.0", Error, BROKEN),
+        deny(Protocol, "TLS 1.1", Error, BROKEN),
+        allow(Cipher, "AES", Warning, UNLISTED),
+        deny(Cipher, "DES", Error, BROKEN),
+        deny(Cipher, "3DES", Error, BROKEN),
+        deny(Cipher, "RC4", Error, BROKEN),
+        deny(Cipher, "NULL", Error, BROKEN),
+        deny(Cipher, "none", Error, BROKEN),
+        deny(Cipher, "ECB", Error, BROKEN),
+        // TLS 1.2 suites with HMAC-SHA1
+        deny(Cipher, "CBC SHA", Warning, UNLISTED),
+        deny(Hash, "MD2", Error, BROKEN),
+        deny(Hash, "MD4", Error, BROKEN),
+        deny(Hash, "MD5", Error, BROKEN),
+        deny(Hash, "SHA1", Error, BROKEN),
+        deny(Hash, "SHA224", Warning, UNLISTED),
+        deny(Hash, "SHA3-224", Warning, UNLISTED),
+        deny(Mac, "MD5", Error, BROKEN),
+        deny(Mac, "SHA1", Warning, UNLISTED),
+        deny(Signature, "SHA1", Error, BROKEN),
+        deny(Signature, "ssh-rsa", Error, BROKEN),
+        deny(Signature, "ed25519", Warning, UNLISTED),
+        deny(Signature, "ed448", Warning, UNLISTED),
+        deny(KeyExchange, "group1", Error, BROKEN),
+        deny(KeyExchange, "x25519", Warning, UNLISTED),
+        deny(KeyExchange, "x448", Warning, UNLISTED),
+    ];
+    // SSH spells the key length into the name
+    for cipher in ["aes128", "aes192", "aes256"] {
+        rules.push(allow(Cipher, cipher, Warning, UNLISTED));
+    }
+    // ML-KEM is only recommended combined with a classical key exchange
+    for group in ["MLKEM512", "MLKEM768", "MLKEM1024"] {
+        rules.push(deny(
+            KeyExchange,
+            group,
+            Warning,
+            "only recommended in hybrids",
+        ));
+    }
+    for algorithm in ["RSA", "DSA", "DH", "ELG"] {
+        rules.push(Rule {
+            min_bits: Some(2000),
+            ..deny(PublicKey, algorithm, Error, BROKEN)
+        });
+    }
+    for algorithm in ["EC", "ECDSA", "ECDH"] {
+        rules.push(Rule {
+            min_bits: Some(250),
+            ..deny(PublicKey, algorithm, Error, BROKEN)
+        });
+    }
+    rules.push(Rule {
+        min_bits: Some(2000),
+        ..deny(KeyExchange, "MODP", Error, BROKEN)
+    });
+
+    // 2000-bit keys and groups were recommended through 2023, suites without
+    // forward secrecy through 2025 and DSA is through 2029
+    for algorithm in ["RSA", "DSA", "DH", "ELG"] {
+        rules.extend(transition(
+            Rule {
+                min_bits: Some(3000),
+                ..deny(PublicKey, algorithm, Error, "")
+            },
+            2023,
+        ));
+    }
+    rules.extend(transition(
+        Rule {
+            min_bits: Some(3000),
+            ..deny(KeyExchange, "MODP", Error, "")
+        },
+        2023,
+    ));
+    for group in ["ffdhe2048", "group14"] {
+        rules.extend(transition(deny(KeyExchange, group, Error, ""), 2023));
+    }
+    rules.extend(transition(
+        deny(Cipher, "TLS RSA WITH", Error, "no forward secrecy"),
+        2025,
+    ));
+    for signature in ["DSA", "ssh-dss"] {
+        rules.extend(transition(deny(Signature, signature, Error, ""), 2029));
+    }
+    rules
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::AuditEvent;
+    use serde_json::{json, Value};
+
+    // Findings of a built-in profile on one event with the fields
+    fn findings(name: &str, fields: Value) -> Vec<(Severity, String)> {
+        let event: AuditEvent = serde_json::from_value(json!({
+            "context": "c",
+            "origin": "o",
+            "start": 0,
+            "end": 0,
+            "events": fields,
+        }))
+        .unwrap();
+        profile(name)
+            .unwrap()
+            .check(&[event])
+            .into_iter()
+            .map(|violation| (violation.severity, violation.description))
+            .collect()
+    }
+
+    fn finding(severity: Severity, description: &str) -> Vec<(Severity, String)> {
+        vec![(severity, description.to_string())]
+    }
+
+    #[test]
+    fn sp800_131a_findings() {
+        assert_eq!(
+            findings(
+                "SP800-131A",
+                json!({"name": "tls::handshake_client", "tls::protocol_version": 0x0301})
+            ),
+            finding(Severity::Error, "TLS 1.0 protocol (disallowed)")
+        );
+        assert_eq!(
+            findings(
+                "SP800-131A",
+                json!({"name": "tls::handshake_client", "tls::ciphersuite": 0x000a})
+            ),
+            finding(
+                Severity::Error,
+                "TLS_RSA_WITH_3DES_EDE_CBC_SHA cipher (disallowed after 2023)"
+            )
+        );
+        assert_eq!(
+            findings(
+                "SP800-131A",
+                json!({"name": "rng::generate", "rng::generator": "getrandom"})
+            ),
+            finding(
+                Severity::Error,
+                "getrandom random generator (not an SP 800-90A DRBG)"
+            )
+        );
+    }
+
+    #[test]
+    fn sp800_131a_acceptable() {
+        assert!(findings(
+            "SP800-131A",
+            json!({
+                "name": "tls::handshake_client",
+                "tls::protocol_version": 0x0304,
+                "tls::ciphersuite": 0x1302,
+            })
+        )
+        .is_empty());
+        assert!(findings(
+            "SP800-131A",
+            json!({"name": "rng::generate", "rng::generator": "CTR-DRBG"})
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn cnsa2_findings() {
+        assert!(findings(
+            "CNSA2",
+            json!({
+                "name": "tls::handshake_client",
+                "tls::protocol_version": 0x0304,
+                "tls::ciphersuite": 0x1302,
+                "tls::group": 0x0202,
+            })
+        )
+        .is_empty());
+        assert_eq!(
+            findings(
+                "CNSA2",
+                json!({"name": "tls::handshake_client", "tls::group": 0x001d})
+            ),
+            finding(
+                Severity::Error,
+                "x25519 key exchange (not a CNSA 2.0 algorithm)"
+            )
+        );
+        let cipher = |bits: u64| {
+            json!({
+                "name": "cipher::encrypt",
+                "cipher::algorithm": "AES",
+                "cipher::key_length": bits,
+                "cipher::mode": "GCM",
+            })
+        };
+        assert!(findings("CNSA2", cipher(256)).is_empty());
+        assert_eq!(
+            findings("CNSA2", cipher(128)),
+            finding(
+                Severity::Error,
+                "AES-128-GCM cipher (not a CNSA 2.0 algorithm)"
+            )
+        );
+    }
+
+    #[test]
+    fn tr_02102_findings() {
+        assert!(findings(
+            "TR-02102",
+            json!({
+                "name": "tls::handshake_client",
+                "tls::protocol_version": 0x0304,
+                "tls::ciphersuite": 0x1302,
+                "tls::group": 0x0018,
+            })
+        )
+        .is_empty());
+        assert_eq!(
+            findings(
+                "TR-02102",
+                json!({"name": "tls::handshake_client", "tls::protocol_version": 0x0301})
+            ),
+            finding(Severity::Error, "TLS 1.0 protocol (not allowed)")
+        );
+        assert_eq!(
+            findings(
+                "TR-02102",
+                json!({"name": "tls::handshake_client", "tls::ciphersuite": 0x1303})
+            ),
+            finding(
+                Severity::Warning,
+                "TLS_CHACHA20_POLY1305_SHA256 cipher (not recommended by TR-02102)"
+            )
+        );
+        assert_eq!(
+            findings(
+                "TR-02102",
+                json!({"name": "pk::sign", "pk::algorithm": "RSA", "pk::bits": 2048})
+            ),
+            finding(Severity::Error, "RSA-2048 key (disallowed after 2023)")
+        );
+    }
+}
@@ -0,0 +1,154 @@
+use crate::compliance::Severity;
+use crate::data::{NodeKind, TreeNode};
+use crate::parquet;
+use crate::sunburst::SunburstChart;
+use adw::prelude::*;
+use gtk4::{FlowBox, Label};
+use std::collections::HashSet;
+use std::rc::Rc;
+
+// Figures of the view the cards show
+#[derive(Default)]
+struct Summary {
+    events: usize,
+    contexts: usize,
+    algorithms: HashSet<String>,
+    violations: Option<usize>,
+}
+
+// Walk the view, weighting nodes by the contexts collapsed into their ancestors
+fn collect(node: &TreeNode, parent: Option<&TreeNode>, weight: usize, summary: &mut Summary) {
+    let weight = weight * node.multiplier;
+    match node.kind {
+        NodeKind::Context => summary.contexts += weight,
+        NodeKind::Event => {
+            // Spans are part of their top-level events
+            if parent.is_none_or(|parent| parent.kind != NodeKind::Event) {
+                summary.events += node.count * weight;
+            }
+            if let Some(algorithm) = parquet::algorithm_in(&node.name) {
+                summary.algorithms.insert(algorithm);
+            }
+            // Only the event breaking the policy counts, not those it's a span of
+            if let Some(severity) = node.severity {
+                let violations = summary.violations.get_or_insert(0);
+                let child_error = node
+                    .children
+                    .iter()
+                    .any(|child| child.severity == Some(Severity::Error));
+                if severity == Severity::Error && !child_error {
+                    *violations += node.count * weight;
+                }
+            }
+        }
+        _ => {}
+    }
+    for child in &node.children {
+        collect(child, Some(node), weight, summary);
+    }
+}
+
+// A capture duration to the second, or the millisecond when shorter
+fn format_duration(ns: u64) -> String {
+    if ns >= 1_000_000_000 {
+        format!(
+            "{:#}",
+            jiff::SignedDuration::from_secs((ns / 1_000_000_000) as i64)
+        )
+    } else {
+        format!("{} ms", ns / 1_000_000)
+    }
+}
+
+fn create_card(caption: &str, tooltip: &str) -> (gtk4::Box, Label) {
+    let card = gtk4::Box::new(gtk4::Orientation::Vertical, 2);
+    card.add_css_class("card");
+    card.set_tooltip_text(Some(tooltip));
+
+    let value = Label::new(None);
+    value.add_css_class("title-2");
+    value.set_margin_top(8);
+    value.set_margin_start(8);
+    value.set_margin_end(8);
+    card.append(&value);
+
+    let caption = Label::new(Some(caption));
+    caption.add_css_class("caption");
+    caption.add_css_class("dim-label");
+    caption.set_margin_bottom(8);
+    card.append(&caption);
+    (card, value)
+}
+
+// Row of cards with the totals of the view: events, contexts, algorithms,
+// duration and rate of the period, and system policy violations. They
+// follow zooming, the time window and the filters.
+pub fn create_summary_cards(chart: Rc<SunburstChart>) -> FlowBox {
+    let flow_box = FlowBox::new();
+    flow_box.set_selection_mode(gtk4::SelectionMode::None);
+    flow_box.set_homogeneous(true);
+    flow_box.set_min_children_per_line(3);
+    flow_box.set_max_children_per_line(6);
+    flow_box.set_column_spacing(6);
+    flow_box.set_row_spacing(6);
+    flow_box.set_margin_start(12);
+    flow_box.set_margin_end(12);
+    flow_box.set_margin_top(6);
+    flow_box.set_margin_bottom(6);
+    flow_box.set_visible(false);
+
+    let cards = [
+        ("Events", "Top-level events in the view"),
+        (
+            "Contexts",
+            "Contexts the events in the view were recorded in",
+        ),
+        (
+            "Algorithms",
+            "Distinct algorithms, ciphersuites and groups used",
+        ),
+        (
+            "Duration",
+            "Time from the first to the last event in the view",
+        ),
+        ("Events/s", "Average rate of events over the duration"),
+        (
+            "Violations",
+            "Events the system crypto policy should have prevented",
+        ),
+    ]
+    .map(|(caption, tooltip)| {
+        let (card, value) = create_card(caption, tooltip);
+        flow_box.insert(&card, -1);
+        value
+    });
+
+    let flow_box_clone = flow_box.clone();
+    chart.connect_view_changed(move |root| {
+        let mut summary = Summary::default();
+        collect(root, None, 1, &mut summary);
+        flow_box_clone.set_visible(summary.events > 0);
+
+        let duration = root
+            .time_range
+            .map_or(0, |(start, end)| end.saturating_sub(start));
+        let rate = if duration > 0 {
+            format!("{:.1}", summary.events as f64 / (duration as f64 / 1e9))
+        } else {
+            "—".to_string()
+        };
+        let [events, contexts, algorithms, duration_value, rate_value, violations] = &cards;
+        events.set_text(&summary.events.to_string());
+        contexts.set_text(&summary.contexts.to_string());
+        algorithms.set_text(&summary.algorithms.len().to_string());
+        duration_value.set_text(&format_duration(duration));
+        rate_value.set_text(&rate);
+        violations.set_text(
+            &summary
+                .violations
+                .map_or_else(|| "—".to_string(), |count| count.to_string()),
+        );
+    });
+
+    flow_box
+}
@@ -1,24 +1,50 @@
-use crate::data::{AuditEvent, TreeNode};
-use crate::models::{StatsObject, TreeNodeObject};
-use adw::Banner;
+use crate::anonymize::Anonymizer;
+use crate::boots;
+use crate::compliance::{Profile, Severity};
+use crate::data::{self, AuditEvent, CaptureMetadata, NodeKind, TreeNode, TreeOptions};
+use crate::decoders;
+use crate::filters::{self, ExcludeRule, Exclusions, TlsVersion};
+use crate::history::ViewState;
+use crate::memory;
+use crate::models::{StatsObject, TreeNodeObject, UnknownCodeObject};
+use crate::palette::Palette;
+use crate::parse_summary::LoadTimings;
+use crate::perf::PerfStats;
+use crate::profiles;
+use crate::query::Query;
+use crate::scrubber::TimeScrubber;
+use crate::timezone::{self, display_time, format_time};
+use adw::prelude::*;
+use adw::ActionRow;
 use cairo::Context;
-use gtk4::prelude::*;
-use gtk4::{gio, ColumnView, Label};
-use std::cell::RefCell;
-use std::collections::HashMap;
+use gtk4::{
+    gio, glib, ColumnView, ColumnViewColumn, Label, ListBox, SingleSelection, TreeListModel,
+    TreeListRow,
+};
+use jiff::SignedDuration;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
 use std::f64::consts::PI;
+use std::path::PathBuf;
 use std::rc::Rc;
-use std::time::{Duration, UNIX_EPOCH};
-use sysinfo::System;
+use std::time::{Duration, Instant};
 
 #[derive(Debug, Clone)]
 struct Segment {
     node: TreeNode,
+    // Names from the drawn root down to the node
+    path: Vec<String>,
     start_angle: f64,
     end_angle: f64,
     inner_radius: f64,
     outer_radius: f64,
     depth: usize,
+    // Value of the parent node, or of the node itself for the drawn root
+    parent_value: usize,
+    // Levels below the node that don't fit into the rings
+    hidden_levels: usize,
 }
 
 impl Segment {
@@ -35,29 +61,73 @@ impl Segment {
         if angle < 0.0 {
             angle += 2.0 * PI;
         }
+        // Segments placed by time start at the top and may extend past a full turn
+        if angle < self.start_angle {
+            angle += 2.0 * PI;
+        }
 
         angle >= self.start_angle && angle <= self.end_angle
     }
 
-    fn format_tooltip(&self) -> String {
+    // Percentages are relative to the base: the parent of each node, or the
+    // value of the zoomed node or of the whole capture
+    fn format_tooltip(
+        &self,
+        options: &TooltipOptions,
+        base: PercentBase,
+        view_value: usize,
+        capture_value: usize,
+    ) -> String {
         let total = self.node.value;
         let children_count = self.node.children.len();
+        let (own_base, children_base) = match base {
+            PercentBase::Parent => (self.parent_value, total),
+            PercentBase::View => (view_value, view_value),
+            PercentBase::Capture => (capture_value, capture_value),
+        };
 
-        let mut tooltip = format!("{}\n", self.node.name);
+        let mut tooltip = format!("{}\n", self.node.display_name());
+        if self.node.kind == NodeKind::Context {
+            tooltip.push_str(&format!("Context: {}\n", self.node.name));
+        }
         tooltip.push_str(&format!("Count: {}\n", total));
+        tooltip.push_str(&format!(
+            "Share: {}% {}\n",
+            percent(total, own_base),
+            base.label()
+        ));
+        tooltip.push_str(&format!(
+            "Duration: {:#}\n",
+            SignedDuration::from_nanos(self.node.duration as i64)
+        ));
+        if let Some((start, end)) = self.node.time_range {
+            tooltip.push_str(&format!(
+                "Time: {} – {}\n",
+                format_time(start, "%T"),
+                format_time(end, "%T")
+            ));
+        }
+
+        if self.hidden_levels > 0 {
+            let levels = if self.hidden_levels == 1 {
+                "1 more level".to_string()
+            } else {
+                format!("{} more levels", self.hidden_levels)
+            };
+            tooltip.push_str(&format!("{} below; zoom in to show them\n", levels));
+        }
 
         if children_count > 0 {
             tooltip.push_str(&format!("Children: {}\n", children_count));
 
-            // Show top 5 children by value
-            let mut sorted_children = self.node.children.clone();
-            sorted_children.sort_by(|a, b| b.value.cmp(&a.value));
+            let mut sorted_children: Vec<&TreeNode> = self.node.children.iter().collect();
+            options.sort_children(&mut sorted_children);
 
-            if !sorted_children.is_empty() {
-                tooltip.push_str("\nTop operations:\n");
-                for child in sorted_children.iter().take(5) {
-                    let percentage = (child.value as f64 / total as f64 * 100.0).round() as u32;
-                    tooltip.push_str(&format!("  • {} ({}%)\n", child.name, percentage));
+            if options.children > 0 {
+                tooltip.push_str(&format!("\n{}:\n", options.sort.heading()));
+                for child in sorted_children.iter().take(options.children) {
+                    let percentage = percent(child.value, children_base);
+                    tooltip.push_str(&format!("  • {} ({}%)\n", child.display_name(), percentage));
                 }
             }
         }
@@ -66,21 +136,321 @@ impl Segment {
     }
 }
 
+// Order of the children listed in segment tooltips
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TooltipSort {
+    #[default]
+    Count,
+    Duration,
+    Name,
+}
+
+impl TooltipSort {
+    pub const ALL: [TooltipSort; 3] =
+        [TooltipSort::Count, TooltipSort::Duration, TooltipSort::Name];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Count => "Count",
+            Self::Duration => "Duration",
+            Self::Name => "Name",
+        }
+    }
+
+    fn heading(&self) -> &'static str {
+        match self {
+            Self::Count => "Top operations",
+            Self::Duration => "Longest operations",
+            Self::Name => "Operations",
+        }
+    }
+}
+
+// Which children segment tooltips list, and in what order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TooltipOptions {
+    pub children: usize,
+    pub sort: TooltipSort,
+    // List children with weak algorithms (MD5, SHA-1, weak ciphers) before the others
+    pub weak_first: bool,
+}
+
+impl Default for TooltipOptions {
+    fn default() -> Self {
+        Self {
+            children: 5,
+            sort: TooltipSort::default(),
+            weak_first: false,
+        }
+    }
+}
+
+impl TooltipOptions {
+    fn sort_children(&self, children: &mut [&TreeNode]) {
+        match self.sort {
+            TooltipSort::Count => children.sort_by_key(|c| std::cmp::Reverse(c.value)),
+            TooltipSort::Duration => children.sort_by_key(|c| std::cmp::Reverse(c.duration)),
+            TooltipSort::Name => children.sort_by_key(|c| c.display_name()),
+        }
+        // Stable, so the order above is kept among weak and among other children
+        if self.weak_first {
+            children.sort_by_key(|c| !decoders::is_weak_algorithm(&c.name));
+        }
+    }
+}
+
+const LABEL_FONT_SIZE: f64 = 11.0;
+
+// A duration with three significant digits at most, e.g. "12.3 ms"
+fn short_duration(ns: u64) -> String {
+    let ns = ns as f64;
+    let (value, unit) = if ns >= 1e9 {
+        (ns / 1e9, "s")
+    } else if ns >= 1e6 {
+        (ns / 1e6, "ms")
+    } else if ns >= 1e3 {
+        (ns / 1e3, "µs")
+    } else {
+        return format!("{} ns", ns);
+    };
+    if value >= 100.0 {
+        format!("{:.0} {}", value, unit)
+    } else {
+        format!("{:.1} {}", value, unit)
+    }
+}
+
+// Number of rings drawn, from the drawn root outwards
+const RINGS: usize = 6;
+
+// What determines the angle of each arc
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Weighting {
+    #[default]
+    Count,
+    Duration,
+}
+
+impl Weighting {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "count" => Some(Self::Count),
+            "duration" => Some(Self::Duration),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Count => "count",
+            Self::Duration => "duration",
+        }
+    }
+
+    fn weight(&self, node: &TreeNode) -> f64 {
+        match self {
+            Self::Count => node.value as f64,
+            Self::Duration => node.duration as f64,
+        }
+    }
+}
+
+fn percent(value: usize, total: usize) -> u32 {
+    if total > 0 {
+        (value as f64 / total as f64 * 100.0).round() as u32
+    } else {
+        0
+    }
+}
+
+// What percentages in tooltips and the stats tables are relative to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PercentBase {
+    // The immediate parent; the stats tables, which have no parents, use the view
+    Parent,
+    // The zoomed node, or the root when not zoomed
+    #[default]
+    View,
+    Capture,
+}
+
+impl PercentBase {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "parent" => Some(Self::Parent),
+            "view" => Some(Self::View),
+            "capture" => Some(Self::Capture),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Parent => "parent",
+            Self::View => "view",
+            Self::Capture => "capture",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Parent => "of Parent",
+            Self::View => "of View",
+            Self::Capture => "of Capture",
+        }
+    }
+}
+
+// Collects the stats shown in one of the stats tables from a tree
+pub type StatsExtract = fn(&TreeNode, &mut HashMap<String, usize>);
+
+// How the first ring is laid out around the circle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Layout {
+    // Arcs one after another, sized by their weight
+    #[default]
+    Proportional,
+    // Like a clock: arcs span the time their events happened, starting at the
+    // top with the start of the visible tree
+    WallTime,
+}
+
+impl Layout {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "proportional" => Some(Self::Proportional),
+            "wall-time" => Some(Self::WallTime),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Proportional => "proportional",
+            Self::WallTime => "wall-time",
+        }
+    }
+}
+
+// Whether the subtree has a handshake negotiating a ciphersuite without forward secrecy
+fn has_non_fs_usage(node: &TreeNode) -> bool {
+    node.event_name()
+        .and_then(|name| decoders::handshake_forward_secrecy(name, &node.fields))
+        == Some(false)
+        || node.children.iter().any(has_non_fs_usage)
+}
+
+// Narrowest arc of a short-lived node in the wall time layout, so it stays visible
+const MIN_TIME_ANGLE: f64 = PI / 180.0;
+
+// Per-frame state shared while laying out and drawing the segments
+struct DrawState<'a> {
+    cr: &'a Context,
+    cx: f64,
+    cy: f64,
+    radius: f64,
+    hover_segment: Option<usize>,
+    selected_path: &'a [String],
+    weighting: Weighting,
+    layout: Layout,
+    palette: Palette,
+    highlight_non_fs: bool,
+    // Color segments by their worst finding instead of their algorithm
+    severity_colors: bool,
+    // Typed text, in lowercase, whose matching segments are brightened and
+    // the others dimmed
+    highlight_query: &'a str,
+    segments: Vec<Segment>,
+}
+
+// Whether the name or label of the node contains the lowercase query
+fn matches_highlight(node: &TreeNode, query: &str) -> bool {
+    node.name.to_lowercase().contains(query) || node.display_name().to_lowercase().contains(query)
+}
+
+// Called with the root of the visible tree whenever it changes (new data, zoom, reset)
+type ViewChangedHandler = Rc<dyn Fn(&TreeNode)>;
+// Called with the node of a double-clicked segment
+type SegmentActivatedHandler = Rc<dyn Fn(&TreeNode)>;
+
+#[derive(Clone)]
 pub struct SunburstChart {
     drawing_area: gtk4::DrawingArea,
     data: Rc<RefCell<Option<TreeNode>>>,
-    segments: Rc<RefCell<Vec<Segment>>>,
-    hover_segment: Rc<RefCell<Option<usize>>>,
     zoom_node: Rc<RefCell<Option<TreeNode>>>,
-    banner: Rc<RefCell<Option<Banner>>>,
+    // Names from the root's children down to the zoomed node, empty if not zoomed
+    zoom_path: Rc<RefCell<Vec<String>>>,
     tree_store: Rc<RefCell<Option<gio::ListStore>>>,
     selected_path: Rc<RefCell<Vec<String>>>,
     column_view: Rc<RefCell<Option<ColumnView>>>,
     stats_store: Rc<RefCell<Option<gio::ListStore>>>,
+    // Tree of the capture the stats are compared against
+    baseline: Rc<RefCell<Option<TreeNode>>>,
+    percent_base: Rc<RefCell<PercentBase>>,
+    tooltip_options: Rc<RefCell<TooltipOptions>>,
+    // Percentage and Δ columns of the stats tables
+    stats_columns: Rc<RefCell<Vec<(ColumnViewColumn, ColumnViewColumn)>>>,
     events: Rc<RefCell<Vec<AuditEvent>>>,
+    // Masks identifiers of the events, which are then kept unmasked here
+    anonymizer: Rc<RefCell<Option<Anonymizer>>>,
+    unmasked_events: Rc<RefCell<Option<Vec<AuditEvent>>>>,
+    // Handshakes of deprecated versions in the capture, counted once per load
+    deprecated_handshakes: Rc<Cell<usize>>,
+    // Events left by the time window, exclusions, search and other filters
+    visible_events: Rc<RefCell<Vec<AuditEvent>>>,
+    // SHA-256 of the loaded capture, identifying it across sessions
+    source_hash: Rc<RefCell<Option<String>>>,
+    // File the capture was loaded from, if any
+    source_path: Rc<RefCell<Option<PathBuf>>>,
+    // Remote host the capture was collected from, None for local captures
+    source_host: Rc<RefCell<Option<String>>>,
+    // Header of the capture file, if it had one
+    metadata: Rc<RefCell<Option<CaptureMetadata>>>,
+    load_timings: Rc<RefCell<LoadTimings>>,
+    capture_info_box: Rc<RefCell<Option<gtk4::Box>>>,
+    capture_info_list: Rc<RefCell<Option<ListBox>>>,
+    tree_options: Rc<RefCell<TreeOptions>>,
+    // Events left out of the tree
+    exclusions: Rc<RefCell<Exclusions>>,
+    exclude_rules: Rc<RefCell<Vec<ExcludeRule>>>,
+    // Only events whose description contains this text are shown, if not empty
+    search: Rc<RefCell<String>>,
+    // Only events matching this filter expression are shown, if set
+    filter_query: Rc<RefCell<Option<Query>>>,
+    // Only events with a handshake of this version are shown, if set
+    tls_version: Rc<RefCell<Option<TlsVersion>>>,
+    // Boot ID of the boot session shown, of a capture spanning several boots
+    boot_session: Rc<RefCell<Option<String>>>,
+    // Events of these origins are left out, together with their spans
+    hidden_origins: Rc<RefCell<HashSet<String>>>,
+    // Only events with a failed operation are shown, if set
+    failures_only: Rc<RefCell<bool>>,
+    // Labels shown for context IDs
+    context_labels: Rc<RefCell<HashMap<String, String>>>,
+    // Only events overlapping this window, in nanoseconds since boot, are shown
+    time_window: Rc<RefCell<Option<(u64, u64)>>>,
+    time_scrubber: Rc<RefCell<Option<TimeScrubber>>>,
+    weighting: Rc<RefCell<Weighting>>,
+    layout: Rc<RefCell<Layout>>,
+    palette: Rc<RefCell<Palette>>,
+    // Fade segments without connections lacking forward secrecy
+    highlight_non_fs: Rc<RefCell<bool>>,
+    severity_colors: Rc<RefCell<bool>>,
+    // Text typed while the chart has focus, highlighting matching segments
+    highlight_query: Rc<RefCell<String>>,
+    // Frame and hit test timings, collected and drawn over the chart if set
+    perf: Rc<RefCell<Option<PerfStats>>>,
     period_start_label: Rc<RefCell<Option<Label>>>,
     period_end_label: Rc<RefCell<Option<Label>>>,
     period_duration_label: Rc<RefCell<Option<Label>>>,
+    unknown_store: Rc<RefCell<Option<gio::ListStore>>>,
+    view_changed_handlers: Rc<RefCell<Vec<ViewChangedHandler>>>,
+    segment_activated_handlers: Rc<RefCell<Vec<SegmentActivatedHandler>>>,
+    policy_status: Rc<RefCell<Option<gtk4::Box>>>,
+    policy_label: Rc<RefCell<Option<Label>>>,
+    policy_list: Rc<RefCell<Option<ListBox>>>,
 }
 
 impl SunburstChart {
@@ -96,7 +466,6 @@ impl SunburstChart {
         let segments = Rc::new(RefCell::new(Vec::new()));
         let hover_segment = Rc::new(RefCell::new(None));
         let zoom_node = Rc::new(RefCell::new(None));
-        let banner = Rc::new(RefCell::new(None));
         let tree_store = Rc::new(RefCell::new(None));
         let selected_path = Rc::new(RefCell::new(Vec::new()));
         let stats_store = Rc::new(RefCell::new(None));
@@ -104,24 +473,75 @@ impl SunburstChart {
         let period_start_label = Rc::new(RefCell::new(None));
         let period_end_label = Rc::new(RefCell::new(None));
         let period_duration_label = Rc::new(RefCell::new(None));
+        let policy_label = Rc::new(RefCell::new(None));
+        let policy_list = Rc::new(RefCell::new(None));
 
         let column_view = Rc::new(RefCell::new(None));
+        let view_changed_handlers: Rc<RefCell<Vec<ViewChangedHandler>>> =
+            Rc::new(RefCell::new(Vec::new()));
+        let weighting = Rc::new(RefCell::new(Weighting::default()));
+        let layout = Rc::new(RefCell::new(Layout::default()));
+        let palette = Rc::new(RefCell::new(Palette::default()));
+        let highlight_non_fs = Rc::new(RefCell::new(false));
+        let severity_colors = Rc::new(RefCell::new(false));
+        let highlight_query = Rc::new(RefCell::new(String::new()));
+        let perf: Rc<RefCell<Option<PerfStats>>> = Rc::new(RefCell::new(None));
+        let percent_base = Rc::new(RefCell::new(PercentBase::default()));
+        let tooltip_options = Rc::new(RefCell::new(TooltipOptions::default()));
 
         let chart = Self {
             drawing_area: drawing_area.clone(),
             data: data.clone(),
-            segments: segments.clone(),
-            hover_segment: hover_segment.clone(),
             zoom_node: zoom_node.clone(),
-            banner: banner.clone(),
+            zoom_path: Rc::new(RefCell::new(Vec::new())),
             tree_store: tree_store.clone(),
             selected_path: selected_path.clone(),
             column_view: column_view.clone(),
             stats_store: stats_store.clone(),
+            baseline: Rc::new(RefCell::new(None)),
+            percent_base: percent_base.clone(),
+            tooltip_options: tooltip_options.clone(),
+            stats_columns: Rc::new(RefCell::new(Vec::new())),
             events: events.clone(),
+            anonymizer: Rc::new(RefCell::new(None)),
+            unmasked_events: Rc::new(RefCell::new(None)),
+            deprecated_handshakes: Rc::new(Cell::new(0)),
+            visible_events: Rc::new(RefCell::new(Vec::new())),
+            source_hash: Rc::new(RefCell::new(None)),
+            source_path: Rc::new(RefCell::new(None)),
+            source_host: Rc::new(RefCell::new(None)),
+            metadata: Rc::new(RefCell::new(None)),
+            load_timings: Rc::new(RefCell::new(LoadTimings::default())),
+            capture_info_box: Rc::new(RefCell::new(None)),
+            capture_info_list: Rc::new(RefCell::new(None)),
+            tree_options: Rc::new(RefCell::new(TreeOptions::default())),
+            exclusions: Rc::new(RefCell::new(Exclusions::new(&[]))),
+            exclude_rules: Rc::new(RefCell::new(Vec::new())),
+            search: Rc::new(RefCell::new(String::new())),
+            filter_query: Rc::new(RefCell::new(None)),
+            tls_version: Rc::new(RefCell::new(None)),
+            boot_session: Rc::new(RefCell::new(None)),
+            hidden_origins: Rc::new(RefCell::new(HashSet::new())),
+            failures_only: Rc::new(RefCell::new(false)),
+            context_labels: Rc::new(RefCell::new(HashMap::new())),
+            time_window: Rc::new(RefCell::new(None)),
+            time_scrubber: Rc::new(RefCell::new(None)),
+            weighting: weighting.clone(),
+            layout: layout.clone(),
+            palette: palette.clone(),
+            highlight_non_fs: highlight_non_fs.clone(),
+            severity_colors: severity_colors.clone(),
+            highlight_query: highlight_query.clone(),
+            perf: perf.clone(),
             period_start_label: period_start_label.clone(),
             period_end_label: period_end_label.clone(),
             period_duration_label: period_duration_label.clone(),
+            unknown_store: Rc::new(RefCell::new(None)),
+            view_changed_handlers: view_changed_handlers.clone(),
+            segment_activated_handlers: Rc::new(RefCell::new(Vec::new())),
+            policy_status: Rc::new(RefCell::new(None)),
+            policy_label: policy_label.clone(),
+            policy_list: policy_list.clone(),
         };
 
         // Set up drawing
@@ -130,6 +550,13 @@ impl SunburstChart {
         let hover_clone = hover_segment.clone();
         let zoom_clone = zoom_node.clone();
         let selected_path_clone = selected_path.clone();
+        let weighting_clone = weighting.clone();
+        let layout_clone = layout.clone();
+        let palette_clone = palette.clone();
+        let highlight_non_fs_clone = highlight_non_fs.clone();
+        let severity_colors_clone = severity_colors.clone();
+        let highlight_query_clone = highlight_query.clone();
+        let perf_clone = perf.clone();
 
         drawing_area.set_draw_func(move |_, cr, width, height| {
             cr.set_source_rgb(1.0, 1.0, 1.0);
@@ -150,23 +577,39 @@ impl SunburstChart {
             let cy = height as f64 / 2.0;
             let max_radius = cx.min(cy) - 20.0;
 
-            let mut new_segments = Vec::new();
-            Self::draw_node(
+            let selected_path = selected_path_clone.borrow();
+            let highlight_query = highlight_query_clone.borrow();
+            let mut state = DrawState {
                 cr,
-                root,
-                0.0,
-                2.0 * PI,
-                0.0,
-                max_radius,
-                0,
-                &mut new_segments,
                 cx,
                 cy,
-                &hover_clone,
-                &selected_path_clone,
-                &Vec::new(),
-            );
+                radius: max_radius,
+                hover_segment: *hover_clone.borrow(),
+                selected_path: &selected_path,
+                weighting: *weighting_clone.borrow(),
+                layout: *layout_clone.borrow(),
+                palette: *palette_clone.borrow(),
+                highlight_non_fs: *highlight_non_fs_clone.borrow(),
+                severity_colors: *severity_colors_clone.borrow(),
+                highlight_query: &highlight_query,
+                segments: Vec::new(),
+            };
+            let mut perf = perf_clone.borrow_mut();
+            let frame_start = perf.as_mut().map(PerfStats::start_frame);
+            Self::draw_node(&mut state, root, 0.0, 2.0 * PI, 0, &[], root.value);
+            if state.severity_colors {
+                Self::draw_severity_legend(&state, root, height as f64);
+            }
+            if !highlight_query.is_empty() {
+                Self::draw_highlight_query(&state, width as f64, height as f64);
+            }
+            let new_segments = state.segments;
 
+            if let (Some(perf), Some(frame_start)) = (perf.as_mut(), frame_start) {
+                perf.layout = frame_start.elapsed();
+                perf.segments = new_segments.len();
+                perf.draw(cr);
+            }
             *segments_clone.borrow_mut() = new_segments;
         });
 
@@ -175,6 +618,11 @@ impl SunburstChart {
         let hover_clone = hover_segment.clone();
         let segments_clone = segments.clone();
         let drawing_area_clone = drawing_area.clone();
+        let data_clone = data.clone();
+        let zoom_clone = zoom_node.clone();
+        let percent_base_clone = percent_base.clone();
+        let tooltip_options_clone = tooltip_options.clone();
+        let perf_clone = perf.clone();
 
         motion_controller.connect_motion(move |_, x, y| {
             let width = drawing_area_clone.width() as f64;
@@ -185,16 +633,30 @@ impl SunburstChart {
             let segments_ref = segments_clone.borrow();
             let mut found = None;
 
+            let hit_test_start = std::time::Instant::now();
             for (i, seg) in segments_ref.iter().enumerate().rev() {
                 if seg.contains_point(x, y, cx, cy) {
                     found = Some(i);
                     break;
                 }
             }
+            if let Some(perf) = perf_clone.borrow_mut().as_mut() {
+                perf.hit_test = hit_test_start.elapsed();
+            }
 
             // Update tooltip
             if let Some(idx) = found {
-                let tooltip_text = segments_ref[idx].format_tooltip();
+                let capture_value = data_clone.borrow().as_ref().map_or(0, |data| data.value);
+                let view_value = zoom_clone
+                    .borrow()
+                    .as_ref()
+                    .map_or(capture_value, |zoom| zoom.value);
+                let tooltip_text = segments_ref[idx].format_tooltip(
+                    &tooltip_options_clone.borrow(),
+                    *percent_base_clone.borrow(),
+                    view_value,
+                    capture_value,
+                );
                 drawing_area_clone.set_tooltip_text(Some(&tooltip_text));
             } else {
                 drawing_area_clone.set_tooltip_text(None);
@@ -209,126 +671,304 @@ impl SunburstChart {
 
         drawing_area.add_controller(motion_controller);
 
-        // Set up click handler
+        // Set up click handler. Zooming waits for the double-click time, so that
+        // a double-click doesn't zoom before opening the segment.
         let click_controller = gtk4::GestureClick::new();
         let segments_clone = segments.clone();
-        let zoom_clone = zoom_node.clone();
-        let drawing_area_clone = drawing_area.clone();
-        let banner_clone = banner.clone();
-        let tree_store_clone = tree_store.clone();
-        let data_clone = data.clone();
-        let stats_store_clone = stats_store.clone();
+        let chart_clone = chart.clone();
+        let pending_zoom: Rc<RefCell<Option<glib::SourceId>>> = Rc::new(RefCell::new(None));
 
-        let selected_path_clone = selected_path.clone();
-
-        click_controller.connect_released(move |_, _, x, y| {
-            let width = drawing_area_clone.width() as f64;
-            let height = drawing_area_clone.height() as f64;
+        click_controller.connect_released(move |_, n_press, x, y| {
+            let width = chart_clone.drawing_area.width() as f64;
+            let height = chart_clone.drawing_area.height() as f64;
             let cx = width / 2.0;
             let cy = height / 2.0;
 
-            let segments_ref = segments_clone.borrow();
+            let target = segments_clone
+                .borrow()
+                .iter()
+                .rev()
+                .find(|seg| seg.contains_point(x, y, cx, cy))
+                .map(|seg| (seg.depth, seg.path[1..].to_vec(), seg.node.clone()));
+            let Some((depth, relative, node)) = target else {
+                return;
+            };
 
-            for seg in segments_ref.iter().rev() {
-                if seg.contains_point(x, y, cx, cy) {
-                    if seg.depth == 0 {
-                        // Reset zoom on root click
-                        *zoom_clone.borrow_mut() = None;
-                        // Hide banner
-                        if let Some(banner) = banner_clone.borrow().as_ref() {
-                            banner.set_revealed(false);
-                        }
-                        // Restore full tree
-                        if let Some(data) = data_clone.borrow().as_ref() {
-                            if let Some(store) = tree_store_clone.borrow().as_ref() {
-                                store.remove_all();
-                                SunburstChart::populate_tree_store(store, data);
-                            }
-                            // Restore full stats
-                            if let Some(store) = stats_store_clone.borrow().as_ref() {
-                                SunburstChart::populate_stats_store(store, data);
-                            }
-                        }
-                        // Clear selection highlighting
-                        *selected_path_clone.borrow_mut() = Vec::new();
-                    } else {
-                        // Zoom into this segment
-                        *zoom_clone.borrow_mut() = Some(seg.node.clone());
-                        // Show banner
-                        if let Some(banner) = banner_clone.borrow().as_ref() {
-                            banner.set_revealed(true);
-                        }
-                        // Update tree store to show only the zoomed subtree
-                        if let Some(store) = tree_store_clone.borrow().as_ref() {
-                            store.remove_all();
-                            SunburstChart::populate_tree_store(store, &seg.node);
-                        }
-                        // Update stats store for the zoomed subtree
-                        if let Some(store) = stats_store_clone.borrow().as_ref() {
-                            SunburstChart::populate_stats_store(store, &seg.node);
-                        }
-                        // Clear selection highlighting when zooming
-                        *selected_path_clone.borrow_mut() = Vec::new();
-                    }
-                    drawing_area_clone.queue_draw();
-                    break;
+            if n_press >= 2 {
+                if let Some(source) = pending_zoom.borrow_mut().take() {
+                    source.remove();
                 }
+                let handlers: Vec<SegmentActivatedHandler> =
+                    chart_clone.segment_activated_handlers.borrow().clone();
+                for handler in handlers {
+                    handler(&node);
+                }
+            } else if depth == 0 {
+                // Reset zoom on root click
+                chart_clone.zoom_to(&[]);
+            } else if *chart_clone.selected_path.borrow() == relative {
+                // Zoom into the selected segment, whose path is relative to the current zoom
+                let mut path = chart_clone.zoom_path();
+                path.extend(relative);
+                let chart = chart_clone.clone();
+                let pending = pending_zoom.clone();
+                let delay = gtk4::Settings::default()
+                    .map_or(400, |settings| settings.gtk_double_click_time())
+                    .max(0) as u64;
+                let source = glib::timeout_add_local_once(
+                    std::time::Duration::from_millis(delay),
+                    move || {
+                        pending.borrow_mut().take();
+                        chart.zoom_to(&path);
+                    },
+                );
+                if let Some(previous) = pending_zoom.borrow_mut().replace(source) {
+                    previous.remove();
+                }
+            } else {
+                chart_clone.select_tree_row(&relative);
+                chart_clone.set_selected_path(relative);
             }
         });
 
         drawing_area.add_controller(click_controller);
 
+        // Typing while the chart has focus highlights the segments whose names
+        // match, and Enter zooms into the first of them. Clicks focus the chart.
+        drawing_area.set_focusable(true);
+        let focus_click = gtk4::GestureClick::new();
+        let drawing_area_clone = drawing_area.clone();
+        focus_click.connect_pressed(move |_, _, _, _| {
+            drawing_area_clone.grab_focus();
+        });
+        drawing_area.add_controller(focus_click);
+
+        let key_controller = gtk4::EventControllerKey::new();
+        let chart_clone = chart.clone();
+        key_controller.connect_key_pressed(move |_, key, _, modifiers| {
+            use gtk4::gdk::{Key, ModifierType};
+
+            let mut query = chart_clone.highlight_query.borrow_mut();
+            match key {
+                Key::Escape if !query.is_empty() => query.clear(),
+                Key::BackSpace if !query.is_empty() => {
+                    query.pop();
+                }
+                Key::Return | Key::KP_Enter if !query.is_empty() => {
+                    let text = query.clone();
+                    drop(query);
+                    chart_clone.zoom_to_first_match(&text);
+                    return glib::Propagation::Stop;
+                }
+                _ => {
+                    let shortcut =
+                        modifiers.intersects(ModifierType::CONTROL_MASK | ModifierType::ALT_MASK);
+                    match key.to_unicode() {
+                        // Space only continues a query, so it can't start one
+                        Some(c)
+                            if !shortcut && !c.is_control() && (c != ' ' || !query.is_empty()) =>
+                        {
+                            query.extend(c.to_lowercase());
+                        }
+                        _ => return glib::Propagation::Proceed,
+                    }
+                }
+            }
+            chart_clone.drawing_area.queue_draw();
+            glib::Propagation::Stop
+        });
+        drawing_area.add_controller(key_controller);
+
+        // Forget the typed text once the chart loses focus
+        let focus_controller = gtk4::EventControllerFocus::new();
+        let chart_clone = chart.clone();
+        focus_controller.connect_leave(move |_| {
+            chart_clone.highlight_query.borrow_mut().clear();
+            chart_clone.drawing_area.queue_draw();
+        });
+        drawing_area.add_controller(focus_controller);
+
         chart
     }
 
+    // Zoom into the first node below the drawn root, in the order the
+    // segments are drawn, matching the lowercase query
+    fn zoom_to_first_match(&self, query: &str) {
+        fn find(node: &TreeNode, query: &str, path: &mut Vec<String>) -> bool {
+            for child in &node.children {
+                path.push(child.name.clone());
+                if matches_highlight(child, query) || find(child, query, path) {
+                    return true;
+                }
+                path.pop();
+            }
+            false
+        }
+
+        let root = self
+            .zoom_node
+            .borrow()
+            .clone()
+            .or(self.data.borrow().clone());
+        let mut path = self.zoom_path();
+        if root.is_some_and(|root| find(&root, query, &mut path)) {
+            self.zoom_to(&path);
+        }
+    }
+
+    // Share of the leaves below the root, weighted as the arcs, of every
+    // severity, in the order of Severity
+    fn severity_shares(node: &TreeNode, weighting: Weighting, shares: &mut [f64; 3]) {
+        if node.children.is_empty() {
+            let index = match node.severity.unwrap_or(Severity::Ok) {
+                Severity::Ok => 0,
+                Severity::Warning => 1,
+                Severity::Error => 2,
+            };
+            shares[index] += weighting.weight(node);
+        }
+        for child in &node.children {
+            Self::severity_shares(child, weighting, shares);
+        }
+    }
+
+    // Legend of the severity colors in the bottom left corner, with how much
+    // of the view each covers
+    fn draw_severity_legend(state: &DrawState, root: &TreeNode, height: f64) {
+        const SWATCH: f64 = 10.0;
+        const LINE_HEIGHT: f64 = 16.0;
+        const MARGIN: f64 = 8.0;
+
+        let mut shares = [0.0; 3];
+        Self::severity_shares(root, state.weighting, &mut shares);
+        let total: f64 = shares.iter().sum();
+        if total <= 0.0 {
+            return;
+        }
+
+        let cr = state.cr;
+        cr.save().unwrap();
+        cr.set_font_size(LABEL_FONT_SIZE);
+        let rows = [
+            (Severity::Ok, "No findings"),
+            (Severity::Warning, "Warnings"),
+            (Severity::Error, "Errors"),
+        ];
+        for (i, ((severity, label), share)) in rows.iter().zip(shares).enumerate() {
+            let y = height - MARGIN - (rows.len() - i) as f64 * LINE_HEIGHT;
+            let (r, g, b) = state.palette.severity_color(*severity);
+            cr.set_source_rgb(r, g, b);
+            cr.rectangle(MARGIN, y + (LINE_HEIGHT - SWATCH) / 2.0, SWATCH, SWATCH);
+            cr.fill().unwrap();
+            cr.set_source_rgb(0.2, 0.2, 0.2);
+            cr.move_to(
+                MARGIN + SWATCH + 6.0,
+                y + LINE_HEIGHT / 2.0 + LABEL_FONT_SIZE / 3.0,
+            );
+            cr.show_text(&format!("{} {:.0}%", label, share / total * 100.0))
+                .unwrap();
+        }
+        cr.restore().unwrap();
+    }
+
+    // Typed text and its number of matching segments, at the bottom right
+    fn draw_highlight_query(state: &DrawState, width: f64, height: f64) {
+        const MARGIN: f64 = 8.0;
+
+        let matches = state
+            .segments
+            .iter()
+            .filter(|seg| seg.depth > 0 && matches_highlight(&seg.node, state.highlight_query))
+            .count();
+        let text = match matches {
+            1 => format!("“{}”: 1 match", state.highlight_query),
+            n => format!("“{}”: {} matches", state.highlight_query, n),
+        };
+        let cr = state.cr;
+        cr.save().unwrap();
+        cr.set_font_size(LABEL_FONT_SIZE);
+        if let Ok(extents) = cr.text_extents(&text) {
+            cr.set_source_rgb(0.2, 0.2, 0.2);
+            cr.move_to(width - MARGIN - extents.x_advance(), height - MARGIN);
+            cr.show_text(&text).unwrap();
+        }
+        cr.restore().unwrap();
+    }
+
     fn draw_node(
-        cr: &Context,
+        state: &mut DrawState,
         node: &TreeNode,
         start_angle: f64,
         end_angle: f64,
-        inner_radius: f64,
-        outer_radius: f64,
         depth: usize,
-        segments: &mut Vec<Segment>,
-        cx: f64,
-        cy: f64,
-        hover_segment: &Rc<RefCell<Option<usize>>>,
-        selected_path: &Rc<RefCell<Vec<String>>>,
-        current_path: &Vec<String>,
+        current_path: &[String],
+        parent_value: usize,
     ) {
-        if node.value == 0 {
+        let weight = state.weighting.weight(node);
+        if weight <= 0.0 {
             return;
         }
 
-        let ring_thickness = (outer_radius - inner_radius) / 6.0;
-        let current_inner = inner_radius + (depth as f64 * ring_thickness);
+        let cr = state.cr;
+        let (cx, cy) = (state.cx, state.cy);
+        let ring_thickness = state.radius / RINGS as f64;
+        let current_inner = depth as f64 * ring_thickness;
         let current_outer = current_inner + ring_thickness;
 
-        if current_outer > outer_radius || current_outer <= current_inner {
+        if current_outer > state.radius || current_outer <= current_inner {
             return;
         }
 
-        // Generate color based on node name
-        let (r, g, b) = Self::get_color(&node.name, depth);
+        // Color by algorithm family so the same algorithm always looks the same,
+        // or by the worst finding below the node as a posture map
+        let (r, g, b) = if state.severity_colors {
+            state
+                .palette
+                .node_severity_color(node.severity.unwrap_or(Severity::Ok), depth)
+        } else {
+            state.palette.node_color(&node.name, node.kind, depth)
+        };
+        // Fade everything but the paths to connections without forward secrecy
+        let (r, g, b) = if state.highlight_non_fs && !has_non_fs_usage(node) {
+            (0.7 + 0.3 * r, 0.7 + 0.3 * g, 0.7 + 0.3 * b)
+        } else {
+            (r, g, b)
+        };
+        // Brighten the segments matching the typed text and fade the others
+        let (r, g, b) = match state.highlight_query {
+            "" => (r, g, b),
+            query if matches_highlight(node, query) => (
+                (r * 1.25).min(1.0),
+                (g * 1.25).min(1.0),
+                (b * 1.25).min(1.0),
+            ),
+            _ => (0.8 + 0.2 * r, 0.8 + 0.2 * g, 0.8 + 0.2 * b),
+        };
 
-        let segment_idx = segments.len();
-        let is_hovered = *hover_segment.borrow() == Some(segment_idx);
+        let segment_idx = state.segments.len();
+        let is_hovered = state.hover_segment == Some(segment_idx);
 
         // Check if this segment is selected via tree view
-        let mut path_with_current = current_path.clone();
+        let mut path_with_current = current_path.to_vec();
         path_with_current.push(node.name.clone());
-        let is_selected = {
-            let selected = selected_path.borrow();
-            !selected.is_empty() && *selected == path_with_current
-        };
+        // The selected path, like tree rows, doesn't include the drawn root
+        let is_selected =
+            !state.selected_path.is_empty() && state.selected_path == &path_with_current[1..];
+
+        // Depth counts from the drawn root, so zooming into a node always
+        // brings the levels cut off here into the rings
+        let hidden_levels = if depth + 1 == RINGS { node.height() } else { 0 };
 
-        segments.push(Segment {
+        state.segments.push(Segment {
             node: node.clone(),
+            path: path_with_current.clone(),
             start_angle,
             end_angle,
             inner_radius: current_inner,
             outer_radius: current_outer,
             depth,
+            parent_value,
+            hidden_levels,
         });
 
         // Draw the arc
@@ -354,7 +994,8 @@ impl SunburstChart {
             cr.set_source_rgb(0.0, 0.4, 0.8);
             cr.set_line_width(3.0);
         } else {
-            cr.set_source_rgb(1.0, 1.0, 1.0);
+            let (r, g, b) = state.palette.border_color();
+            cr.set_source_rgb(r, g, b);
             cr.set_line_width(1.0);
         }
         cr.arc(cx, cy, current_outer, start_angle, end_angle);
@@ -362,31 +1003,92 @@ impl SunburstChart {
         cr.close_path();
         cr.stroke().unwrap();
 
+        // Contexts of the first ring go by their short form, durations
+        // elsewhere when they weight the segments
+        if depth == 1 && node.kind == NodeKind::Context {
+            Self::draw_segment_label(
+                state,
+                &node.display_name(),
+                (start_angle, end_angle),
+                (current_inner, current_outer),
+                (r, g, b),
+            );
+        } else if state.weighting == Weighting::Duration {
+            Self::draw_segment_label(
+                state,
+                &short_duration(node.duration),
+                (start_angle, end_angle),
+                (current_inner, current_outer),
+                (r, g, b),
+            );
+        }
+
+        // Mark the outer edge of segments with levels beyond the outermost ring
+        if hidden_levels > 0 {
+            cr.new_path();
+            cr.set_source_rgb(0.5, 0.5, 0.5);
+            cr.set_line_width(3.0);
+            cr.arc(cx, cy, current_outer + 5.0, start_angle, end_angle);
+            cr.stroke().unwrap();
+        }
+
         cr.restore().unwrap();
 
+        // Place the first ring by time, spanning the root's time range once around
+        if state.layout == Layout::WallTime && depth == 0 {
+            if let Some((first, last)) = node.time_range {
+                let period = last.saturating_sub(first).max(1) as f64;
+                let angle_at =
+                    |t: u64| 1.5 * PI + 2.0 * PI * t.saturating_sub(first) as f64 / period;
+                for child in &node.children {
+                    let Some((start, end)) = child.time_range else {
+                        continue;
+                    };
+                    let child_start_angle = angle_at(start);
+                    let child_end_angle = angle_at(end)
+                        .max(child_start_angle + MIN_TIME_ANGLE)
+                        .min(3.5 * PI);
+                    Self::draw_node(
+                        state,
+                        child,
+                        child_start_angle,
+                        child_end_angle,
+                        depth + 1,
+                        &path_with_current,
+                        node.value,
+                    );
+                }
+                return;
+            }
+        }
+
         // Draw children
-        if !node.children.is_empty() && depth < 5 {
+        if !node.children.is_empty() && depth + 1 < RINGS {
             let angle_span = end_angle - start_angle;
             let mut current_angle = start_angle;
 
+            // Children may not add up to the parent (e.g. a span's own time
+            // outside its sub-spans), which leaves a gap; if they exceed it,
+            // scale them down to fit
+            let children_weight: f64 = node
+                .children
+                .iter()
+                .map(|child| state.weighting.weight(child))
+                .sum();
+            let total_weight = weight.max(children_weight);
+
             for child in &node.children {
-                let child_angle_span = angle_span * (child.value as f64 / node.value as f64);
+                let child_angle_span = angle_span * (state.weighting.weight(child) / total_weight);
                 let child_end_angle = current_angle + child_angle_span;
 
                 Self::draw_node(
-                    cr,
+                    state,
                     child,
                     current_angle,
                     child_end_angle,
-                    inner_radius,
-                    outer_radius,
                     depth + 1,
-                    segments,
-                    cx,
-                    cy,
-                    hover_segment,
-                    selected_path,
                     &path_with_current,
+                    node.value,
                 );
 
                 current_angle = child_end_angle;
@@ -394,44 +1096,540 @@ impl SunburstChart {
         }
     }
 
-    fn get_color(name: &str, depth: usize) -> (f64, f64, f64) {
-        // Simple hash-based color generation
-        let mut hash: u32 = depth as u32 * 100;
-        for byte in name.bytes() {
-            hash = hash.wrapping_mul(31).wrapping_add(byte as u32);
+    // Print the text in the middle of the segment's arc, if it fits
+    fn draw_segment_label(
+        state: &DrawState,
+        text: &str,
+        (start_angle, end_angle): (f64, f64),
+        (inner, outer): (f64, f64),
+        (r, g, b): (f64, f64, f64),
+    ) {
+        let cr = state.cr;
+        cr.set_font_size(LABEL_FONT_SIZE);
+        let Ok(extents) = cr.text_extents(text) else {
+            return;
+        };
+
+        // The drawn root is a full disc, whose label goes in its center
+        let (mid_radius, available_width) = if inner == 0.0 {
+            (0.0, 2.0 * outer)
+        } else {
+            let mid_radius = (inner + outer) / 2.0;
+            (mid_radius, (end_angle - start_angle) * mid_radius)
+        };
+        if extents.width() + 8.0 > available_width || LABEL_FONT_SIZE + 4.0 > outer - inner {
+            return;
+        }
+
+        let mid_angle = (start_angle + end_angle) / 2.0;
+        let x = state.cx + mid_radius * mid_angle.cos();
+        let y = state.cy + mid_radius * mid_angle.sin();
+
+        // Dark text on light segments and white text on dark ones
+        let luminance = 0.299 * r + 0.587 * g + 0.114 * b;
+        if luminance > 0.5 {
+            cr.set_source_rgb(0.1, 0.1, 0.1);
+        } else {
+            cr.set_source_rgb(1.0, 1.0, 1.0);
         }
+        cr.move_to(
+            x - extents.width() / 2.0 - extents.x_bearing(),
+            y - extents.height() / 2.0 - extents.y_bearing(),
+        );
+        cr.show_text(text).unwrap();
+    }
+
+    pub fn source_hash(&self) -> Option<String> {
+        self.source_hash.borrow().clone()
+    }
+
+    pub fn set_source_hash(&self, hash: Option<String>) {
+        *self.source_hash.borrow_mut() = hash;
+    }
 
-        let hue = (hash % 360) as f64 / 360.0;
-        let saturation = 0.6 + ((hash / 360) % 20) as f64 / 100.0;
-        let value = 0.7 + ((hash / 7200) % 20) as f64 / 100.0;
+    pub fn source_path(&self) -> Option<PathBuf> {
+        self.source_path.borrow().clone()
+    }
+
+    pub fn set_source_path(&self, path: Option<PathBuf>) {
+        *self.source_path.borrow_mut() = path;
+    }
+
+    pub fn source_host(&self) -> Option<String> {
+        self.source_host.borrow().clone()
+    }
+
+    pub fn set_source_host(&self, host: Option<String>) {
+        *self.source_host.borrow_mut() = host;
+    }
+
+    pub fn metadata(&self) -> Option<CaptureMetadata> {
+        self.metadata.borrow().clone()
+    }
+
+    pub fn set_metadata(&self, metadata: Option<CaptureMetadata>) {
+        // Wall-clock times are of the capture's boots, if it records them
+        data::set_boot_time(metadata.as_ref().and_then(CaptureMetadata::epoch));
+        *self.metadata.borrow_mut() = metadata;
+        self.update_capture_info();
+        self.refresh_times();
+    }
+
+    // Size of the loaded file and how long parsing it took, if known, recorded
+    // by the loaders before they show the events
+    pub fn set_parse_timing(&self, file_size: Option<usize>, parse: Option<Duration>) {
+        let mut timings = self.load_timings.borrow_mut();
+        timings.file_size = file_size;
+        timings.parse = parse;
+    }
+
+    pub fn load_timings(&self) -> LoadTimings {
+        *self.load_timings.borrow()
+    }
+
+    // Segments, layout and hit-test time of the last frame, while the
+    // performance overlay measures them
+    pub fn render_timings(&self) -> Option<(usize, Duration, Duration)> {
+        self.perf
+            .borrow()
+            .as_ref()
+            .map(|perf| (perf.segments, perf.layout, perf.hit_test))
+    }
+
+    pub fn set_capture_info_widgets(&self, section: gtk4::Box, list: ListBox) {
+        *self.capture_info_box.borrow_mut() = Some(section);
+        *self.capture_info_list.borrow_mut() = Some(list);
+    }
 
-        // Convert HSV to RGB
-        let h = hue * 6.0;
-        let i = h.floor();
-        let f = h - i;
-        let p = value * (1.0 - saturation);
-        let q = value * (1.0 - saturation * f);
-        let t = value * (1.0 - saturation * (1.0 - f));
+    // List the metadata header and the memory the capture takes, hiding the
+    // section until one is loaded
+    fn update_capture_info(&self) {
+        let section = self.capture_info_box.borrow();
+        let list = self.capture_info_list.borrow();
+        let (Some(section), Some(list)) = (section.as_ref(), list.as_ref()) else {
+            return;
+        };
 
-        match i as i32 % 6 {
-            0 => (value, t, p),
-            1 => (q, value, p),
-            2 => (p, value, t),
-            3 => (p, q, value),
-            4 => (t, p, value),
-            _ => (value, p, q),
+        while let Some(child) = list.first_child() {
+            list.remove(&child);
+        }
+        let metadata = self.metadata.borrow();
+        let entries = match (metadata.as_ref(), self.anonymizer.borrow().as_ref()) {
+            (Some(metadata), Some(anonymizer)) => anonymizer.metadata(metadata).entries(),
+            (Some(metadata), None) => metadata.entries(),
+            (None, _) => Vec::new(),
+        };
+        for (label, value) in &entries {
+            let row = ActionRow::builder()
+                .title(label)
+                .subtitle(value)
+                .use_markup(false)
+                .css_classes(["property"])
+                .build();
+            list.append(&row);
         }
+
+        // The visible events are a filtered copy of the loaded ones
+        let Some(tree_size) = self.data.borrow().as_ref().map(memory::tree_size) else {
+            section.set_visible(!entries.is_empty());
+            return;
+        };
+        let events_size = memory::events_size(&self.events.borrow())
+            + memory::events_size(&self.visible_events.borrow());
+        let row = ActionRow::builder()
+            .title("Memory")
+            .subtitle(format!(
+                "≈ {} ({} events, {} tree)",
+                memory::format_size(events_size + tree_size),
+                memory::format_size(events_size),
+                memory::format_size(tree_size)
+            ))
+            .css_classes(["property"])
+            .tooltip_text(
+                "Approximate memory taken by the parsed events and the tree built from them",
+            )
+            .build();
+        list.append(&row);
+        section.set_visible(true);
     }
 
-    pub fn set_data(&self, data: TreeNode, events: Vec<AuditEvent>) {
-        *self.data.borrow_mut() = Some(data.clone());
-        *self.events.borrow_mut() = events;
+    // Draw the visible tree without hover or selection highlighting, e.g. for export
+    pub fn render(&self, cr: &Context, width: f64, height: f64) {
+        cr.set_source_rgb(1.0, 1.0, 1.0);
+        cr.paint().unwrap();
+
+        let data = self.data.borrow();
+        let zoom = self.zoom_node.borrow();
+        let Some(root) = zoom.as_ref().or(data.as_ref()) else {
+            return;
+        };
+
+        let mut state = DrawState {
+            cr,
+            cx: width / 2.0,
+            cy: height / 2.0,
+            radius: (width / 2.0).min(height / 2.0) - 20.0,
+            hover_segment: None,
+            selected_path: &[],
+            weighting: self.weighting(),
+            layout: self.layout(),
+            palette: *self.palette.borrow(),
+            highlight_non_fs: self.highlight_non_fs(),
+            severity_colors: self.severity_colors(),
+            highlight_query: "",
+            segments: Vec::new(),
+        };
+        Self::draw_node(&mut state, root, 0.0, 2.0 * PI, 0, &[], root.value);
+    }
+
+    // Show an already built tree, e.g. a subtree of another chart, instead of
+    // building one from events
+    pub fn set_tree(&self, tree: TreeNode) {
+        *self.data.borrow_mut() = Some(tree.clone());
         *self.zoom_node.borrow_mut() = None;
+        self.zoom_path.borrow_mut().clear();
+        self.selected_path.borrow_mut().clear();
+
+        if let Some(store) = self.tree_store.borrow().as_ref() {
+            store.remove_all();
+            Self::populate_tree_store(store, &tree);
+        }
+        if let Some(store) = self.stats_store.borrow().as_ref() {
+            self.fill_stats_for(store, &tree, TreeNode::extract_algorithm_stats);
+        }
+        Self::notify_view_changed(&self.view_changed_handlers, &tree);
+        self.drawing_area.queue_draw();
+    }
+
+    pub fn set_data(&self, events: Vec<AuditEvent>) {
+        self.store_events(events);
+        *self.time_window.borrow_mut() = None;
+        *self.boot_session.borrow_mut() = None;
+        self.update_capture_start();
+
+        self.rebuild();
+
+        // Span the time scrubber over the new sampling period
+        if let Some(scrubber) = self.time_scrubber.borrow().as_ref() {
+            scrubber.set_range(self.session_range());
+        }
+
+        // Update period labels
+        self.update_period_labels();
+
+        // Cross-check against the system crypto policy or selected profile
+        self.update_policy_list();
+
+        // List codes the decoders didn't recognize
+        self.update_unknown_store();
+    }
+
+    // Keep the events of the capture, decoded by the profile of their origin
+    // and masked if anonymizing
+    fn store_events(&self, mut events: Vec<AuditEvent>) {
+        profiles::normalize(&mut events);
+        self.deprecated_handshakes
+            .set(filters::count_deprecated_handshakes(&events));
+        match self.anonymizer.borrow().as_ref() {
+            Some(anonymizer) => {
+                *self.events.borrow_mut() = anonymizer.events(&events);
+                *self.unmasked_events.borrow_mut() = Some(events);
+            }
+            None => {
+                *self.events.borrow_mut() = events;
+                *self.unmasked_events.borrow_mut() = None;
+            }
+        }
+    }
+
+    // Events of the capture as shown, masked if anonymizing
+    pub fn events(&self) -> Vec<AuditEvent> {
+        self.events.borrow().clone()
+    }
+
+    // Handshakes of the capture negotiating a deprecated version, whatever the
+    // filters show
+    pub fn deprecated_handshakes(&self) -> usize {
+        self.deprecated_handshakes.get()
+    }
+
+    // Events of the capture as loaded, to replace them by changed ones
+    pub fn source_events(&self) -> Vec<AuditEvent> {
+        self.unmasked_events
+            .borrow()
+            .clone()
+            .unwrap_or_else(|| self.events())
+    }
+
+    pub fn anonymizer(&self) -> Option<Anonymizer> {
+        self.anonymizer.borrow().clone()
+    }
+
+    // Mask the contexts, origins, context labels and host of the capture with
+    // pseudonyms throughout the views and exports, or stop doing so
+    pub fn set_anonymizer(&self, anonymizer: Option<Anonymizer>) {
+        let events = self.source_events();
+        *self.anonymizer.borrow_mut() = anonymizer;
+        // Hidden origins are known by the names shown
+        self.hidden_origins.borrow_mut().clear();
+        if self.data.borrow().is_some() {
+            self.update_data(events);
+        } else {
+            self.store_events(events);
+        }
+        self.update_capture_info();
+    }
+
+    // Events the tree was built from, after filtering
+    pub fn visible_events(&self) -> Vec<AuditEvent> {
+        self.visible_events.borrow().clone()
+    }
+
+    // Replace the events of the loaded capture, as when a followed file changes,
+    // keeping the time window and zoom location where they still apply
+    pub fn update_data(&self, events: Vec<AuditEvent>) {
+        let zoom_path = self.zoom_path();
+        let window = self.time_window();
+        self.store_events(events);
+        self.update_capture_start();
+        // Show all boots again once the one shown is gone
+        let shown = self.boot_session();
+        if shown.is_some_and(|boot_id| {
+            !boots::sessions(&self.events.borrow())
+                .iter()
+                .any(|session| session.boot_id == boot_id)
+        }) {
+            *self.boot_session.borrow_mut() = None;
+        }
+
+        self.rebuild();
+        if !zoom_path.is_empty() {
+            self.zoom_to(&zoom_path);
+        }
+
+        if let Some(scrubber) = self.time_scrubber.borrow().as_ref() {
+            scrubber.set_range(self.session_range());
+            scrubber.set_window(window);
+        }
+
+        self.update_period_labels();
+        self.update_policy_list();
+        self.update_unknown_store();
+    }
+
+    pub fn tree_options(&self) -> TreeOptions {
+        self.tree_options.borrow().clone()
+    }
+
+    pub fn set_tree_options(&self, options: TreeOptions) {
+        *self.tree_options.borrow_mut() = options;
+        self.rebuild();
+    }
+
+    // Rebuild the tree from the loaded events and refresh all views
+    pub fn set_time_scrubber(&self, scrubber: TimeScrubber) {
+        let chart = self.clone();
+        scrubber.connect_changed(move |window| {
+            chart.set_time_window(window);
+        });
+        *self.time_scrubber.borrow_mut() = Some(scrubber);
+    }
+
+    pub fn set_exclude_rules(&self, rules: &[ExcludeRule]) {
+        *self.exclusions.borrow_mut() = Exclusions::new(rules);
+        *self.exclude_rules.borrow_mut() = rules.to_vec();
+        self.refresh();
+    }
+
+    pub fn view_state(&self) -> ViewState {
+        ViewState {
+            search: self.search(),
+            filter_query: self.filter_query().map(|query| query.text().to_string()),
+            tls_version: self.tls_version(),
+            hidden_origins: self.hidden_origins(),
+            failures_only: self.failures_only(),
+            time_window: self.time_window(),
+            exclude_rules: self.exclude_rules.borrow().clone(),
+            zoom_path: self.zoom_path(),
+        }
+    }
+
+    // Apply the filters, exclusions and zoom of a state at once
+    pub fn restore_view_state(&self, state: &ViewState) {
+        *self.search.borrow_mut() = state.search.clone();
+        *self.filter_query.borrow_mut() = state
+            .filter_query
+            .as_deref()
+            .and_then(|text| Query::parse(text).ok());
+        *self.tls_version.borrow_mut() = state.tls_version;
+        *self.hidden_origins.borrow_mut() = state.hidden_origins.clone();
+        *self.failures_only.borrow_mut() = state.failures_only;
+        *self.time_window.borrow_mut() = state.time_window;
+        if let Some(scrubber) = self.time_scrubber.borrow().as_ref() {
+            scrubber.set_window(state.time_window);
+        }
+        *self.exclusions.borrow_mut() = Exclusions::new(&state.exclude_rules);
+        *self.exclude_rules.borrow_mut() = state.exclude_rules.clone();
+        self.refresh();
+        if !state.zoom_path.is_empty() {
+            self.zoom_to(&state.zoom_path);
+        }
+    }
+
+    pub fn search(&self) -> String {
+        self.search.borrow().clone()
+    }
+
+    pub fn set_search(&self, query: &str) {
+        *self.search.borrow_mut() = query.trim().to_string();
+        self.refresh();
+    }
+
+    pub fn filter_query(&self) -> Option<Query> {
+        self.filter_query.borrow().clone()
+    }
+
+    pub fn set_filter_query(&self, query: Option<Query>) {
+        *self.filter_query.borrow_mut() = query;
+        self.refresh();
+    }
+
+    pub fn tls_version(&self) -> Option<TlsVersion> {
+        *self.tls_version.borrow()
+    }
+
+    pub fn set_tls_version(&self, version: Option<TlsVersion>) {
+        *self.tls_version.borrow_mut() = version;
+        self.refresh();
+    }
+
+    pub fn boot_session(&self) -> Option<String> {
+        self.boot_session.borrow().clone()
+    }
+
+    // Show the events of one boot of a capture spanning several, with the
+    // sampling period and time scrubber of that boot, or all of them for None
+    pub fn set_boot_session(&self, boot_id: Option<String>) {
+        *self.boot_session.borrow_mut() = boot_id;
+        *self.time_window.borrow_mut() = None;
+        self.refresh();
+        if let Some(scrubber) = self.time_scrubber.borrow().as_ref() {
+            scrubber.set_range(self.session_range());
+        }
+        self.update_period_labels();
+    }
+
+    // Sampling period of the boot session shown, or of the whole capture
+    pub fn session_range(&self) -> Option<(u64, u64)> {
+        let events = self.events.borrow();
+        match self.boot_session.borrow().as_deref() {
+            Some(boot_id) => AuditEvent::get_time_range(&boots::filter_boot(&events, boot_id)),
+            None => AuditEvent::get_time_range(&events),
+        }
+    }
+
+    pub fn hidden_origins(&self) -> HashSet<String> {
+        self.hidden_origins.borrow().clone()
+    }
+
+    pub fn set_hidden_origins(&self, origins: HashSet<String>) {
+        *self.hidden_origins.borrow_mut() = origins;
+        self.refresh();
+    }
+
+    pub fn failures_only(&self) -> bool {
+        *self.failures_only.borrow()
+    }
+
+    pub fn set_failures_only(&self, failures_only: bool) {
+        *self.failures_only.borrow_mut() = failures_only;
+        self.refresh();
+    }
+
+    pub fn has_exclude_rules(&self) -> bool {
+        !self.exclusions.borrow().is_empty()
+    }
 
-        // Hide banner when loading new data
-        if let Some(banner) = self.banner.borrow().as_ref() {
-            banner.set_revealed(false);
+    // Whether a capture with events is loaded, even if the filters hide all of them
+    pub fn has_events(&self) -> bool {
+        !self.events.borrow().is_empty()
+    }
+
+    pub fn set_context_labels(&self, labels: HashMap<String, String>) {
+        *self.context_labels.borrow_mut() = labels;
+        self.refresh();
+    }
+
+    pub fn time_window(&self) -> Option<(u64, u64)> {
+        *self.time_window.borrow()
+    }
+
+    // Restrict the tree, chart and stats to events overlapping the window
+    pub fn set_time_window(&self, window: Option<(u64, u64)>) {
+        *self.time_window.borrow_mut() = window;
+        if let Some(scrubber) = self.time_scrubber.borrow().as_ref() {
+            scrubber.set_window(window);
+        }
+        self.refresh();
+    }
+
+    // Rebuild the tree if a capture is loaded
+    fn refresh(&self) {
+        if self.data.borrow().is_some() {
+            self.rebuild();
+        }
+    }
+
+    fn rebuild(&self) {
+        let build_start = Instant::now();
+        let all_events = self.events.borrow();
+        let mut events = Cow::Borrowed(all_events.as_slice());
+        if let Some(boot_id) = self.boot_session.borrow().as_deref() {
+            events = Cow::Owned(boots::filter_boot(&events, boot_id));
+        }
+        if let Some((start, end)) = *self.time_window.borrow() {
+            events = Cow::Owned(AuditEvent::filter_time_window(&events, start, end));
+        }
+        let exclusions = self.exclusions.borrow();
+        if !exclusions.is_empty() {
+            events = Cow::Owned(exclusions.apply(&events));
+        }
+        let query = self.search.borrow();
+        if !query.is_empty() {
+            events = Cow::Owned(filters::search(&events, &query));
+        }
+        if let Some(query) = self.filter_query.borrow().as_ref() {
+            events = Cow::Owned(query.filter(&events));
         }
+        if let Some(version) = *self.tls_version.borrow() {
+            events = Cow::Owned(filters::filter_tls_version(&events, version));
+        }
+        let hidden_origins = self.hidden_origins.borrow();
+        if !hidden_origins.is_empty() {
+            events = Cow::Owned(filters::filter_origins(&events, &hidden_origins));
+        }
+        if *self.failures_only.borrow() {
+            events = Cow::Owned(filters::filter_failures(&events));
+        }
+        let mut data = TreeNode::from_events(&events, &self.tree_options.borrow());
+        *self.visible_events.borrow_mut() = events.into_owned();
+        match self.anonymizer.borrow().as_ref() {
+            Some(anonymizer) => {
+                data.apply_context_labels(&anonymizer.context_labels(&self.context_labels.borrow()))
+            }
+            None => data.apply_context_labels(&self.context_labels.borrow()),
+        }
+        data.abbreviate_contexts(data::short_context_length(
+            all_events.iter().map(|event| event.context.as_str()),
+        ));
+        if let Some(profile) = Profile::active() {
+            profile.classify(&mut data);
+        }
+        self.load_timings.borrow_mut().aggregation = Some(build_start.elapsed());
+        *self.data.borrow_mut() = Some(data.clone());
+        *self.zoom_node.borrow_mut() = None;
+        self.zoom_path.borrow_mut().clear();
+        self.selected_path.borrow_mut().clear();
 
         // Populate tree store
         if let Some(store) = self.tree_store.borrow().as_ref() {
@@ -441,15 +1639,34 @@ impl SunburstChart {
 
         // Populate stats store
         if let Some(store) = self.stats_store.borrow().as_ref() {
-            Self::populate_stats_store(store, &data);
+            self.fill_stats_for(store, &data, TreeNode::extract_algorithm_stats);
         }
-
-        // Update period labels
-        self.update_period_labels();
+        Self::notify_view_changed(&self.view_changed_handlers, &data);
+        self.update_capture_info();
 
         self.drawing_area.queue_draw();
     }
 
+    // Register a handler called with the visible root whenever it changes
+    pub fn connect_view_changed<F: Fn(&TreeNode) + 'static>(&self, f: F) {
+        self.view_changed_handlers.borrow_mut().push(Rc::new(f));
+    }
+
+    // Register a handler called with the node of a double-clicked segment
+    pub fn connect_segment_activated<F: Fn(&TreeNode) + 'static>(&self, f: F) {
+        self.segment_activated_handlers
+            .borrow_mut()
+            .push(Rc::new(f));
+    }
+
+    fn notify_view_changed(handlers: &Rc<RefCell<Vec<ViewChangedHandler>>>, node: &TreeNode) {
+        // Clone the list so handlers may register further handlers
+        let handlers: Vec<ViewChangedHandler> = handlers.borrow().clone();
+        for handler in handlers {
+            handler(node);
+        }
+    }
+
     fn populate_tree_store(store: &gio::ListStore, node: &TreeNode) {
         for child in &node.children {
             let child_obj = Self::tree_node_to_object(child);
@@ -459,6 +1676,10 @@ impl SunburstChart {
 
     fn tree_node_to_object(node: &TreeNode) -> TreeNodeObject {
         let obj = TreeNodeObject::new(&node.name, &node.value.to_string(), node.value as u32);
+        obj.set_label(node.display_name());
+        if let Some(severity) = node.severity {
+            obj.set_severity(severity.name());
+        }
 
         if !node.children.is_empty() {
             let children_store = gio::ListStore::new::<TreeNodeObject>();
@@ -491,103 +1712,515 @@ impl SunburstChart {
     }
 
     fn update_period_labels(&self) {
-        let events = self.events.borrow();
-
-        if let Some((start_ns, end_ns)) = AuditEvent::get_time_range(&events) {
+        if let Some((start_ns, end_ns)) = self.session_range() {
             // Format as human-readable dates
-            let boot_time = UNIX_EPOCH + Duration::from_secs(System::boot_time());
+            let start_time = display_time(start_ns);
+            let end_time = display_time(end_ns);
+            let start_text = format!("Start: {}", start_time.strftime("%c %Z"));
+            let end_text = format!("End: {}", end_time.strftime("%c %Z"));
+
+            // Calculate duration, leaving out the time between boots when
+            // showing all boots of a capture spanning several
+            let sessions = boots::sessions(&self.events.borrow());
+            let duration_text = if self.boot_session.borrow().is_none() && sessions.len() > 1 {
+                let nanos: u64 = sessions.iter().map(|s| s.end.saturating_sub(s.start)).sum();
+                let duration = jiff::SignedDuration::from_nanos(nanos as i64);
+                format!("Duration: {duration:#} over {} boots", sessions.len())
+            } else {
+                let duration = end_time.duration_since(&start_time);
+                format!("Duration: {duration:#}")
+            };
+            if let Some(label) = self.period_start_label.borrow().as_ref() {
+                label.set_text(&start_text);
+            }
+            if let Some(label) = self.period_end_label.borrow().as_ref() {
+                label.set_text(&end_text);
+            }
+            if let Some(label) = self.period_duration_label.borrow().as_ref() {
+                label.set_text(&duration_text);
+            }
+        }
+    }
 
-            let start_time = boot_time + Duration::from_nanos(start_ns);
-            let end_time = boot_time + Duration::from_nanos(end_ns);
+    pub fn set_unknown_store(&self, unknown_store: gio::ListStore) {
+        *self.unknown_store.borrow_mut() = Some(unknown_store);
+    }
 
-            let start_time: jiff::Timestamp = start_time.try_into().unwrap();
-            let end_time: jiff::Timestamp = end_time.try_into().unwrap();
-            let start_text = format!("Start: {}", start_time.strftime("%c"));
-            let end_text = format!("End: {}", end_time.strftime("%c"));
+    fn update_unknown_store(&self) {
+        let Some(store) = self.unknown_store.borrow().clone() else {
+            return;
+        };
 
-            // Calculate duration
-            let duration = end_time.duration_since(start_time);
-                if let Some(label) = self.period_start_label.borrow().as_ref() {
-                    label.set_text(&start_text);
-                }
-                if let Some(label) = self.period_end_label.borrow().as_ref() {
-                    label.set_text(&end_text);
-                }
-                if let Some(label) = self.period_duration_label.borrow().as_ref() {
-                    label.set_text(&format!("Duration: {duration:#}"));
-                }
+        let objects: Vec<UnknownCodeObject> = decoders::collect_unknown(&self.events.borrow())
+            .into_iter()
+            .map(|unknown| {
+                UnknownCodeObject::new(&unknown.field, &unknown.code, &unknown.count.to_string())
+            })
+            .collect();
+        store.splice(0, store.n_items(), &objects);
+    }
+
+    pub fn set_policy_widgets(&self, status: gtk4::Box, label: Label, list: ListBox) {
+        *self.policy_status.borrow_mut() = Some(status);
+        *self.policy_label.borrow_mut() = Some(label);
+        *self.policy_list.borrow_mut() = Some(list);
+    }
+
+    fn update_policy_list(&self) {
+        // Nothing to cross-check until a capture is loaded
+        if self.data.borrow().is_none() {
+            return;
+        }
+
+        let status = self.policy_status.borrow();
+        let label = self.policy_label.borrow();
+        let list = self.policy_list.borrow();
+        let (Some(status), Some(label), Some(list)) =
+            (status.as_ref(), label.as_ref(), list.as_ref())
+        else {
+            return;
+        };
+
+        while let Some(child) = list.first_child() {
+            list.remove(&child);
+        }
+        if let Some(indicator) = label.prev_sibling() {
+            status.remove(&indicator);
+        }
+
+        let Some(profile) = Profile::active() else {
+            label.set_text("No system crypto policy found");
+            list.set_visible(false);
+            return;
+        };
+
+        let palette = *self.palette.borrow();
+        let violations = profile.check(&self.events.borrow());
+
+        // Summarize the result next to the policy name; a policy we only
+        // approximate can't be reported as clean with confidence
+        let severity = if let Some(worst) = violations.iter().map(|v| v.severity).max() {
+            worst
+        } else if profile.approximated {
+            Severity::Warning
+        } else {
+            Severity::Ok
+        };
+        status.prepend(&Self::severity_indicator(palette, severity));
+
+        if violations.is_empty() {
+            label.set_text(&format!(
+                "Policy: {} — all observed usage is permitted",
+                profile.name
+            ));
+            list.set_visible(false);
+            return;
+        }
+
+        // Standards sort usage by how acceptable it is, rather than only
+        // what they prohibit
+        if !profile.rules.is_empty() {
+            let counts = profile.count_usages(&self.events.borrow());
+            let count = |severity| counts.get(&severity).copied().unwrap_or(0);
+            label.set_text(&format!(
+                "Profile: {} — {} acceptable, {} deprecated and {} disallowed usages",
+                profile.name,
+                count(Severity::Ok),
+                count(Severity::Warning),
+                count(Severity::Error)
+            ));
+        } else {
+            let total: usize = violations.iter().map(|v| v.count).sum();
+            label.set_text(&format!(
+                "Policy: {} — {} events the policy should have prevented",
+                profile.name, total
+            ));
+        }
+
+        // Each origin listed here likely bypasses the system policy
+        for violation in violations {
+            let row = ActionRow::builder()
+                .title(&violation.description)
+                .subtitle(format!("Origin {}", violation.origin))
+                .subtitle_lines(1)
+                .build();
+            row.add_prefix(&Self::severity_indicator(palette, violation.severity));
+            let count_label = Label::new(Some(&violation.count.to_string()));
+            count_label.add_css_class("dim-label");
+            row.add_suffix(&count_label);
+            list.append(&row);
         }
+        list.set_visible(true);
+    }
+
+    // Small colored dot showing a severity in the current palette
+    fn severity_indicator(palette: Palette, severity: Severity) -> gtk4::DrawingArea {
+        let indicator = gtk4::DrawingArea::new();
+        indicator.set_content_width(12);
+        indicator.set_content_height(12);
+        indicator.set_valign(gtk4::Align::Center);
+
+        let (r, g, b) = palette.severity_color(severity);
+        indicator.set_draw_func(move |_, cr, width, height| {
+            let radius = width.min(height) as f64 / 2.0;
+            cr.arc(
+                width as f64 / 2.0,
+                height as f64 / 2.0,
+                radius,
+                0.0,
+                2.0 * PI,
+            );
+            cr.set_source_rgb(r, g, b);
+            cr.fill().unwrap();
+        });
+
+        indicator
+    }
+
+    // Fill a stats table with the stats of a node, as the percentage base and
+    // baseline require. Returns whether the table has any rows.
+    pub fn fill_stats_for(
+        &self,
+        store: &gio::ListStore,
+        node: &TreeNode,
+        extract: StatsExtract,
+    ) -> bool {
+        let collect = |node: &TreeNode| {
+            let mut stats = HashMap::new();
+            extract(node, &mut stats);
+            stats
+        };
+        let stats = collect(node);
+        let baseline_stats = self.baseline.borrow().as_ref().map(collect);
+        let base_total = match *self.percent_base.borrow() {
+            PercentBase::Capture => self
+                .data
+                .borrow()
+                .as_ref()
+                .map(|data| collect(data).values().sum()),
+            PercentBase::Parent | PercentBase::View => None,
+        };
+        Self::fill_stats_store(store, stats, baseline_stats, base_total);
+        store.n_items() > 0
     }
 
-    fn populate_stats_store(store: &gio::ListStore, node: &TreeNode) {
+    // Fill a store of StatsObjects with counts, sorted descending with percentages
+    // of base_total, or of the sum of the counts. With baseline stats, entries only
+    // found in the baseline are listed with a count of zero, and each entry carries
+    // its change against the baseline.
+    fn fill_stats_store(
+        store: &gio::ListStore,
+        mut stats: HashMap<String, usize>,
+        baseline: Option<HashMap<String, usize>>,
+        base_total: Option<usize>,
+    ) {
         store.remove_all();
 
-        let mut stats: HashMap<String, usize> = HashMap::new();
-        node.extract_algorithm_stats(&mut stats);
+        if let Some(baseline) = &baseline {
+            for name in baseline.keys() {
+                stats.entry(name.clone()).or_insert(0);
+            }
+        }
 
         if stats.is_empty() {
             return;
         }
 
-        // Calculate total for percentages
-        let total: usize = stats.values().sum();
+        // Calculate totals for percentages
+        let share = |count: usize, total: usize| {
+            if total > 0 {
+                count as f64 / total as f64 * 100.0
+            } else {
+                0.0
+            }
+        };
+        let total = base_total.unwrap_or_else(|| stats.values().sum());
+        let baseline_total: usize = baseline.iter().flat_map(|b| b.values()).sum();
 
         // Sort by count (descending)
         let mut stats_vec: Vec<_> = stats.into_iter().collect();
-        stats_vec.sort_by(|a, b| b.1.cmp(&a.1));
+        stats_vec.sort_by_key(|s| std::cmp::Reverse(s.1));
 
         // Populate store
         for (algorithm, count) in stats_vec {
-            let percentage = if total > 0 {
-                (count as f64 / total as f64 * 100.0).round() as u32
-            } else {
-                0
-            };
+            let percentage = share(count, total);
+            let stats_obj = StatsObject::new(
+                &algorithm,
+                &count.to_string(),
+                &format!("{}%", percentage.round() as u32),
+            );
 
-            let stats_obj =
-                StatsObject::new(&algorithm, &count.to_string(), &format!("{}%", percentage));
+            if let Some(baseline) = &baseline {
+                let baseline_count = baseline.get(&algorithm).copied().unwrap_or(0);
+                let points = percentage - share(baseline_count, baseline_total);
+                stats_obj.set_delta(if baseline_count == 0 {
+                    "new".to_string()
+                } else {
+                    format!(
+                        "{:+} ({:+.1} pp)",
+                        count as i64 - baseline_count as i64,
+                        points
+                    )
+                });
+
+                // Only changes in the use of weak algorithms are colored
+                if decoders::is_weak_algorithm(&algorithm) && count != baseline_count {
+                    stats_obj.set_delta_class(if points > 0.0 || baseline_count == 0 {
+                        "error"
+                    } else {
+                        "success"
+                    });
+                }
+            }
             store.append(&stats_obj);
         }
     }
 
+    // Compare the stats against another capture's tree from now on, or stop comparing
+    pub fn set_baseline(&self, baseline: Option<TreeNode>) {
+        for (_, delta_column) in self.stats_columns.borrow().iter() {
+            delta_column.set_visible(baseline.is_some());
+        }
+        *self.baseline.borrow_mut() = baseline;
+        self.refresh_stats();
+    }
+
+    pub fn percent_base(&self) -> PercentBase {
+        *self.percent_base.borrow()
+    }
+
+    pub fn set_percent_base(&self, base: PercentBase) {
+        *self.percent_base.borrow_mut() = base;
+        for (percent_column, _) in self.stats_columns.borrow().iter() {
+            percent_column.set_title(Some(&format!("% {}", base.label())));
+        }
+        self.refresh_stats();
+        self.drawing_area.queue_draw();
+    }
+
+    // Register the Percentage and Δ columns of a stats table, which follow the
+    // percentage base and baseline
+    pub fn add_stats_columns(
+        &self,
+        percent_column: ColumnViewColumn,
+        delta_column: ColumnViewColumn,
+    ) {
+        percent_column.set_title(Some(&format!("% {}", self.percent_base().label())));
+        delta_column.set_visible(self.baseline.borrow().is_some());
+        self.stats_columns
+            .borrow_mut()
+            .push((percent_column, delta_column));
+    }
+
+    // Refill the stats tables of the visible subtree
+    fn refresh_stats(&self) {
+        let node = self
+            .zoom_node
+            .borrow()
+            .clone()
+            .or(self.data.borrow().clone());
+        if let Some(node) = node {
+            if let Some(store) = self.stats_store.borrow().as_ref() {
+                self.fill_stats_for(store, &node, TreeNode::extract_algorithm_stats);
+            }
+            Self::notify_view_changed(&self.view_changed_handlers, &node);
+        }
+    }
+
+    // Tree of the loaded capture, regardless of zoom
+    pub fn root(&self) -> Option<TreeNode> {
+        self.data.borrow().clone()
+    }
+
+    pub fn layout(&self) -> Layout {
+        *self.layout.borrow()
+    }
+
+    pub fn set_layout(&self, layout: Layout) {
+        *self.layout.borrow_mut() = layout;
+        self.drawing_area.queue_draw();
+    }
+
+    pub fn weighting(&self) -> Weighting {
+        *self.weighting.borrow()
+    }
+
+    pub fn set_weighting(&self, weighting: Weighting) {
+        *self.weighting.borrow_mut() = weighting;
+        self.drawing_area.queue_draw();
+    }
+
+    pub fn tooltip_options(&self) -> TooltipOptions {
+        *self.tooltip_options.borrow()
+    }
+
+    // Applies to the next tooltip shown
+    pub fn set_tooltip_options(&self, options: TooltipOptions) {
+        *self.tooltip_options.borrow_mut() = options;
+    }
+
+    pub fn palette(&self) -> Palette {
+        *self.palette.borrow()
+    }
+
+    pub fn highlight_non_fs(&self) -> bool {
+        *self.highlight_non_fs.borrow()
+    }
+
+    pub fn set_highlight_non_fs(&self, highlight: bool) {
+        *self.highlight_non_fs.borrow_mut() = highlight;
+        self.drawing_area.queue_draw();
+    }
+
+    pub fn severity_colors(&self) -> bool {
+        *self.severity_colors.borrow()
+    }
+
+    pub fn set_severity_colors(&self, enabled: bool) {
+        *self.severity_colors.borrow_mut() = enabled;
+        self.drawing_area.queue_draw();
+    }
+
+    pub fn performance_overlay(&self) -> bool {
+        self.perf.borrow().is_some()
+    }
+
+    pub fn set_performance_overlay(&self, enabled: bool) {
+        if enabled != self.performance_overlay() {
+            *self.perf.borrow_mut() = enabled.then(PerfStats::default);
+            self.drawing_area.queue_draw();
+        }
+    }
+
+    // Count relative times from the start of the loaded capture
+    fn update_capture_start(&self) {
+        if let Some((start, _)) = AuditEvent::get_time_range(&self.events.borrow()) {
+            timezone::set_capture_start(start);
+        }
+    }
+
+    // Show the times in the newly displayed zone or mode: the period labels and time
+    // scrubber, and the views drawing times, which redraw on a view change
+    pub fn refresh_times(&self) {
+        self.update_period_labels();
+        if let Some(scrubber) = self.time_scrubber.borrow().as_ref() {
+            scrubber.set_window(*self.time_window.borrow());
+        }
+        self.refresh_stats();
+        self.drawing_area.queue_draw();
+    }
+
+    // Check against the profile instead of the system policy, or the system
+    // policy again for None, coloring the chart and listing findings anew
+    pub fn set_profile(&self, profile: Option<Profile>) {
+        Profile::select(profile);
+        self.refresh();
+        self.update_policy_list();
+    }
+
+    pub fn set_palette(&self, palette: Palette) {
+        *self.palette.borrow_mut() = palette;
+        // Severity indicators in the sidebar use the palette too
+        self.update_policy_list();
+        self.drawing_area.queue_draw();
+    }
+
     pub fn set_selected_path(&self, path: Vec<String>) {
         *self.selected_path.borrow_mut() = path;
         self.drawing_area.queue_draw();
     }
 
-    pub fn set_zoom_banner(&self, banner: Banner) {
-        // Set up banner button to reset zoom
-        let zoom_clone = self.zoom_node.clone();
-        let drawing_area_clone = self.drawing_area.clone();
-        let banner_clone = banner.clone();
-        let data_clone = self.data.clone();
-        let tree_store_clone = self.tree_store.clone();
-        let stats_store_clone = self.stats_store.clone();
-        let selected_path_clone = self.selected_path.clone();
-
-        banner.connect_button_clicked(move |_| {
-            *zoom_clone.borrow_mut() = None;
-            banner_clone.set_revealed(false);
-
-            // Restore full tree
-            if let Some(data) = data_clone.borrow().as_ref() {
-                if let Some(store) = tree_store_clone.borrow().as_ref() {
-                    store.remove_all();
-                    SunburstChart::populate_tree_store(store, data);
-                }
-                // Restore full stats
-                if let Some(store) = stats_store_clone.borrow().as_ref() {
-                    SunburstChart::populate_stats_store(store, data);
+    // Select, expand and scroll to the tree row of the node at the path below the visible root
+    fn select_tree_row(&self, path: &[String]) {
+        let Some(column_view) = self.column_view.borrow().clone() else {
+            return;
+        };
+        let Some(selection) = column_view.model().and_downcast::<SingleSelection>() else {
+            return;
+        };
+        let Some(tree_model) = selection.model().and_downcast::<TreeListModel>() else {
+            return;
+        };
+
+        let mut row: Option<TreeListRow> = None;
+        for name in path {
+            let n_children = match &row {
+                Some(row) => row.children().map_or(0, |children| children.n_items()),
+                None => tree_model.model().n_items(),
+            };
+            let child = (0..n_children).find_map(|i| {
+                let child = match &row {
+                    Some(row) => row.child_row(i),
+                    None => tree_model.child_row(i),
+                }?;
+                child
+                    .item()
+                    .and_downcast::<TreeNodeObject>()
+                    .is_some_and(|node| node.name() == *name)
+                    .then_some(child)
+            });
+            let Some(child) = child else {
+                return;
+            };
+            child.set_expanded(true);
+            row = Some(child);
+        }
+
+        let Some(row) = row else {
+            return;
+        };
+        let position = row.position();
+        selection.set_selected(position);
+
+        // The list inside the column view knows how to scroll to an item
+        let mut child = column_view.first_child();
+        while let Some(widget) = child {
+            if widget.is::<gtk4::ListView>() {
+                let _ = widget.activate_action("list.scroll-to-item", Some(&position.to_variant()));
+                break;
+            }
+            child = widget.next_sibling();
+        }
+    }
+
+    pub fn zoom_path(&self) -> Vec<String> {
+        self.zoom_path.borrow().clone()
+    }
+
+    // Zoom into the node at the given path of names below the root, or reset the
+    // zoom for an empty path. Returns false if the current tree has no such node.
+    pub fn zoom_to(&self, path: &[String]) -> bool {
+        let node = {
+            let data = self.data.borrow();
+            let Some(mut node) = data.as_ref() else {
+                return false;
+            };
+            for name in path {
+                match node.children.iter().find(|child| &child.name == name) {
+                    Some(child) => node = child,
+                    None => return false,
                 }
             }
+            node.clone()
+        };
 
-            // Clear selection highlighting
-            *selected_path_clone.borrow_mut() = Vec::new();
+        *self.zoom_node.borrow_mut() = (!path.is_empty()).then(|| node.clone());
+        *self.zoom_path.borrow_mut() = path.to_vec();
 
-            drawing_area_clone.queue_draw();
-        });
+        // Show only the zoomed subtree in the tree and stats
+        if let Some(store) = self.tree_store.borrow().as_ref() {
+            store.remove_all();
+            Self::populate_tree_store(store, &node);
+        }
+        if let Some(store) = self.stats_store.borrow().as_ref() {
+            self.fill_stats_for(store, &node, TreeNode::extract_algorithm_stats);
+        }
+        Self::notify_view_changed(&self.view_changed_handlers, &node);
 
-        *self.banner.borrow_mut() = Some(banner);
+        // Clear selection highlighting when zooming
+        self.selected_path.borrow_mut().clear();
+        self.drawing_area.queue_draw();
+        true
     }
 
     pub fn widget(&self) -> &gtk4::DrawingArea {
@@ -0,0 +1,261 @@
+use crate::data::AuditEvent;
+use crate::sunburst::SunburstChart;
+use adw::Banner;
+use std::cell::Cell;
+use std::rc::Rc;
+
+const SECOND: u64 = 1_000_000_000;
+// Captures are unlikely to start within a minute of boot, on a host up for
+// over two years, or to last over a year
+const MIN_UPTIME: u64 = 60 * SECOND;
+const MAX_UPTIME: u64 = 2 * MAX_PERIOD;
+const MAX_PERIOD: u64 = 365 * 24 * 3600 * SECOND;
+// Crypto operations take from microseconds to, for long-lived contexts,
+// hours; they typically take about a millisecond
+const MIN_DURATION: u64 = 1_000;
+const MAX_DURATION: u64 = 3600 * SECOND;
+const TYPICAL_DURATION: f64 = 1e6;
+const TYPICAL_UPTIME: f64 = 24.0 * 3600.0 * SECOND as f64;
+
+// Factors to nanoseconds of the units timestamps are tried in
+const FACTORS: [u64; 3] = [1, 1_000, 1_000_000];
+
+// Something off about the timestamps of a capture, read as nanoseconds since boot
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Suspicion {
+    // They're likely in a coarser unit, by the factor to nanoseconds, as the
+    // capture's uptime, period and durations only make sense in that unit
+    Coarse(u64),
+    // The sampling period lasts years, as with timestamps of another clock
+    Implausible(u64),
+}
+
+fn unit_name(factor: u64) -> &'static str {
+    if factor == 1_000 {
+        "microseconds"
+    } else {
+        "milliseconds"
+    }
+}
+
+fn collect(events: &[AuditEvent], times: &mut Vec<(u64, u64)>) {
+    for event in events {
+        times.push((event.start, event.end));
+        collect(&event.spans, times);
+    }
+}
+
+// Timing of a capture, in the unit of its timestamps
+struct Timing {
+    // How long after boot the capture ends
+    uptime: u64,
+    period: u64,
+    // Median of the durations that aren't zero, if any are
+    duration: Option<u64>,
+}
+
+impl Timing {
+    fn plausible(&self, factor: u64) -> bool {
+        let uptime = self.uptime.saturating_mul(factor);
+        (MIN_UPTIME..=MAX_UPTIME).contains(&uptime)
+            && self.period.saturating_mul(factor) <= MAX_PERIOD
+            && self.duration.is_none_or(|duration| {
+                (MIN_DURATION..=MAX_DURATION).contains(&duration.saturating_mul(factor))
+            })
+    }
+
+    // How far, in orders of magnitude, the durations and then the uptime are
+    // from typical ones when read in a unit
+    fn distance(&self, factor: u64) -> (f64, f64) {
+        let distance = |value: u64, typical: f64| {
+            ((value as f64 * factor as f64).max(1.0) / typical)
+                .log10()
+                .abs()
+        };
+        (
+            self.duration
+                .map_or(0.0, |duration| distance(duration, TYPICAL_DURATION)),
+            distance(self.uptime, TYPICAL_UPTIME),
+        )
+    }
+}
+
+// Guess whether the timestamps aren't nanoseconds since boot. When read as
+// nanoseconds the capture would end right after boot, last years, or its
+// events would take no time or hours, the unit putting all of them in a
+// plausible range is the likely one.
+pub fn check(events: &[AuditEvent]) -> Option<Suspicion> {
+    let mut times = Vec::new();
+    collect(events, &mut times);
+    let first = times.iter().map(|(start, _)| *start).min()?;
+    let last = times.iter().map(|(start, end)| *start.max(end)).max()?;
+    if last == 0 {
+        return None;
+    }
+
+    let mut durations: Vec<u64> = times
+        .iter()
+        .map(|(start, end)| end.saturating_sub(*start))
+        .filter(|duration| *duration > 0)
+        .collect();
+    durations.sort_unstable();
+    let timing = Timing {
+        uptime: last,
+        period: last - first,
+        duration: durations.get(durations.len() / 2).copied(),
+    };
+    if timing.plausible(1) {
+        return None;
+    }
+
+    let coarse = FACTORS[1..]
+        .iter()
+        .copied()
+        .filter(|factor| timing.plausible(*factor))
+        .min_by(|a, b| {
+            timing
+                .distance(*a)
+                .partial_cmp(&timing.distance(*b))
+                .unwrap()
+        });
+    match coarse {
+        Some(factor) => Some(Suspicion::Coarse(factor)),
+        None => (timing.period > MAX_PERIOD).then_some(Suspicion::Implausible(timing.period)),
+    }
+}
+
+// Convert timestamps of a coarser unit to nanoseconds
+pub fn rescale(events: &mut [AuditEvent], factor: u64) {
+    for event in events {
+        event.start = event.start.saturating_mul(factor);
+        event.end = event.end.saturating_mul(factor);
+        rescale(&mut event.spans, factor);
+    }
+}
+
+// Banner warning about timestamps that don't look like nanoseconds since
+// boot, instead of displaying an absurd sampling period. When they look like
+// a coarser unit, its button reinterprets them as such.
+pub fn create_timestamp_banner(chart: Rc<SunburstChart>) -> Banner {
+    let banner = Banner::new("");
+    banner.set_revealed(false);
+
+    let factor = Rc::new(Cell::new(0));
+    let chart_clone = chart.clone();
+    let factor_clone = factor.clone();
+    banner.connect_button_clicked(move |banner| {
+        let mut events = chart_clone.source_events();
+        rescale(&mut events, factor_clone.get());
+        banner.set_revealed(false);
+        chart_clone.set_data(events);
+    });
+
+    let banner_clone = banner.clone();
+    let chart_clone = chart.clone();
+    chart.connect_view_changed(move |_| {
+        let events = chart_clone.events();
+        let zero_durations = events.iter().filter(|event| event.end == event.start).count();
+        match check(&events) {
+            Some(Suspicion::Coarse(unit)) => {
+                factor.set(unit);
+                let durations = if zero_durations * 2 > events.len() {
+                    ", and most events take no time"
+                } else {
+                    ""
+                };
+                banner_clone.set_title(&format!(
+                    "Timestamps look like {} rather than nanoseconds, judging by when the capture ends and how long its events take{}",
+                    unit_name(unit),
+                    durations
+                ));
+                banner_clone.set_button_label(Some(&format!("Read as {}", unit_name(unit))));
+                banner_clone.set_revealed(true);
+            }
+            Some(Suspicion::Implausible(period)) => {
+                let years = period as f64 / MAX_PERIOD as f64;
+                banner_clone.set_title(&format!(
+                    "The sampling period lasts {:.0} years; timestamps may not count nanoseconds since boot",
+                    years
+                ));
+                banner_clone.set_button_label(None);
+                banner_clone.set_revealed(true);
+            }
+            None => banner_clone.set_revealed(false),
+        }
+    });
+
+    banner
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    const HOUR: u64 = 3600;
+
+    // Events every second of a ten-minute capture ending `uptime` seconds
+    // after boot, each taking `duration`, all in a unit of `factor` ns
+    fn capture(factor: u64, uptime: u64, duration: u64) -> Vec<AuditEvent> {
+        let second = SECOND / factor;
+        let end = uptime * second;
+        (0..600)
+            .map(|i| {
+                let start = end - (600 - i) * second;
+                AuditEvent {
+                    context: format!("{:x}", i),
+                    origin: "gnutls".to_string(),
+                    start,
+                    end: start + duration,
+                    events: HashMap::new(),
+                    spans: Vec::new(),
+                    boot_id: None,
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn nanoseconds() {
+        assert_eq!(check(&capture(1, 2 * HOUR, 100_000)), None);
+        assert_eq!(check(&capture(1, 600, 50_000)), None);
+        assert_eq!(check(&capture(1, 30 * 24 * HOUR, 2_000_000)), None);
+        assert_eq!(check(&[]), None);
+    }
+
+    #[test]
+    fn milliseconds() {
+        let coarse = Some(Suspicion::Coarse(1_000_000));
+        assert_eq!(check(&capture(1_000_000, HOUR / 2, 3)), coarse);
+        assert_eq!(check(&capture(1_000_000, 20 * HOUR, 3)), coarse);
+        assert_eq!(check(&capture(1_000_000, 20 * HOUR, 0)), coarse);
+        assert_eq!(check(&capture(1_000_000, 90 * 24 * HOUR, 3)), coarse);
+    }
+
+    #[test]
+    fn microseconds() {
+        let coarse = Some(Suspicion::Coarse(1_000));
+        assert_eq!(check(&capture(1_000, HOUR / 2, 200)), coarse);
+        assert_eq!(check(&capture(1_000, 20 * HOUR, 200)), coarse);
+        assert_eq!(check(&capture(1_000, 90 * 24 * HOUR, 200)), coarse);
+    }
+
+    #[test]
+    fn implausible_period() {
+        let mut events = capture(1, 2 * HOUR, 100_000);
+        events[0].start = 0;
+        events[0].end = 100_000;
+        events[599].start = 3 * MAX_PERIOD;
+        events[599].end = 3 * MAX_PERIOD + 100_000;
+        assert!(matches!(check(&events), Some(Suspicion::Implausible(_))));
+    }
+
+    #[test]
+    fn rescale_spans() {
+        let mut events = capture(1_000, 20 * HOUR, 200);
+        events[0].spans = capture(1_000, 20 * HOUR, 200);
+        rescale(&mut events, 1_000);
+        assert_eq!(check(&events), None);
+        assert_eq!(events[0].spans[0].end - events[0].spans[0].start, 200_000);
+    }
+}
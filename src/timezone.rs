@@ -0,0 +1,72 @@
+use crate::data::boot_timestamp;
+use anyhow::{anyhow, Result};
+use jiff::tz::TimeZone;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::RwLock;
+
+// Zone displayed times are in, as chosen in the preferences
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DisplayZone {
+    #[default]
+    Utc,
+    // The system's zone
+    Local,
+    // A zone of the tz database, e.g. "America/New_York"
+    Named(String),
+}
+
+impl DisplayZone {
+    pub fn time_zone(&self) -> Result<TimeZone> {
+        match self {
+            Self::Utc => Ok(TimeZone::UTC),
+            Self::Local => Ok(TimeZone::system()),
+            Self::Named(name) => {
+                TimeZone::get(name).map_err(|_| anyhow!("Unknown time zone {}", name))
+            }
+        }
+    }
+}
+
+static ZONE: RwLock<TimeZone> = RwLock::new(TimeZone::UTC);
+// Whether times are shown as offsets from the start of the capture
+static RELATIVE: AtomicBool = AtomicBool::new(false);
+static CAPTURE_START: AtomicU64 = AtomicU64::new(0);
+
+// Show times in the zone from now on
+pub fn set_display_zone(zone: &DisplayZone) -> Result<()> {
+    let time_zone = zone.time_zone()?;
+    *ZONE.write().unwrap() = time_zone;
+    Ok(())
+}
+
+// Time of an event timestamp in the displayed zone
+pub fn display_time(ns: u64) -> jiff::Zoned {
+    boot_timestamp(ns).to_zoned(ZONE.read().unwrap().clone())
+}
+
+pub fn set_relative(relative: bool) {
+    RELATIVE.store(relative, Ordering::Relaxed);
+}
+
+// Start of the loaded capture, which relative times count from
+pub fn set_capture_start(start: u64) {
+    CAPTURE_START.store(start, Ordering::Relaxed);
+}
+
+// Time of an event timestamp in the format, or as its offset from the start
+// of the capture in relative mode, e.g. "+00:03:12.448"
+pub fn format_time(ns: u64, format: &str) -> String {
+    if !RELATIVE.load(Ordering::Relaxed) {
+        return display_time(ns).strftime(format).to_string();
+    }
+    let ms = ns.saturating_sub(CAPTURE_START.load(Ordering::Relaxed)) / 1_000_000;
+    format!(
+        "+{:02}:{:02}:{:02}.{:03}",
+        ms / 3_600_000,
+        ms / 60_000 % 60,
+        ms / 1000 % 60,
+        ms % 1000
+    )
+}
@@ -0,0 +1,342 @@
+use crate::compliance::Profile;
+use crate::data::{NodeKind, TreeNode};
+use crate::export;
+use crate::html::escape_html;
+use crate::matrix;
+use crate::models::StatsObject;
+use crate::palette::Family;
+use crate::sunburst::SunburstChart;
+use adw::prelude::*;
+use anyhow::{anyhow, Result};
+use gtk4::gio;
+use std::fs;
+use std::path::Path;
+
+// Workbooks are written by hand: a zip archive of SpreadsheetML parts, stored
+// without compression, which every spreadsheet application reads
+
+enum Cell {
+    Text(String),
+    Number(f64),
+    // A fraction, shown as a percentage
+    Percent(f64),
+}
+
+impl From<&str> for Cell {
+    fn from(text: &str) -> Self {
+        Cell::Text(text.to_string())
+    }
+}
+
+impl From<String> for Cell {
+    fn from(text: String) -> Self {
+        Cell::Text(text)
+    }
+}
+
+impl From<usize> for Cell {
+    fn from(number: usize) -> Self {
+        Cell::Number(number as f64)
+    }
+}
+
+// A named sheet whose first row is a bold header
+struct Sheet {
+    name: &'static str,
+    rows: Vec<Vec<Cell>>,
+}
+
+// Column letters of a zero-based index, e.g. "AB" for 27
+fn column_name(mut index: usize) -> String {
+    let mut name = String::new();
+    loop {
+        name.insert(0, (b'A' + (index % 26) as u8) as char);
+        if index < 26 {
+            return name;
+        }
+        index = index / 26 - 1;
+    }
+}
+
+fn sheet_xml(sheet: &Sheet) -> String {
+    let mut xml = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n<worksheet xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\"><sheetData>",
+    );
+    for (r, row) in sheet.rows.iter().enumerate() {
+        xml.push_str(&format!("<row r=\"{}\">", r + 1));
+        // Style 1 is the bold font of the header
+        let style = if r == 0 { " s=\"1\"" } else { "" };
+        for (c, cell) in row.iter().enumerate() {
+            let reference = format!("{}{}", column_name(c), r + 1);
+            match cell {
+                Cell::Text(text) => xml.push_str(&format!(
+                    "<c r=\"{}\"{} t=\"inlineStr\"><is><t xml:space=\"preserve\">{}</t></is></c>",
+                    reference,
+                    style,
+                    escape_html(text)
+                )),
+                Cell::Number(number) => xml.push_str(&format!(
+                    "<c r=\"{}\"{}><v>{}</v></c>",
+                    reference, style, number
+                )),
+                // Style 2 is the percentage format
+                Cell::Percent(fraction) => xml.push_str(&format!(
+                    "<c r=\"{}\" s=\"2\"><v>{}</v></c>",
+                    reference, fraction
+                )),
+            }
+        }
+        xml.push_str("</row>");
+    }
+    xml.push_str("</sheetData></worksheet>");
+    xml
+}
+
+// Parts of the workbook with their names in the archive
+fn workbook_parts(sheets: &[Sheet]) -> Vec<(String, String)> {
+    const HEADER: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n";
+    let mut content_types = String::from(HEADER);
+    content_types.push_str(
+        "<Types xmlns=\"http://schemas.openxmlformats.org/package/2006/content-types\">\
+         <Default Extension=\"rels\" ContentType=\"application/vnd.openxmlformats-package.relationships+xml\"/>\
+         <Default Extension=\"xml\" ContentType=\"application/xml\"/>\
+         <Override PartName=\"/xl/workbook.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml\"/>\
+         <Override PartName=\"/xl/styles.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.styles+xml\"/>",
+    );
+    let mut workbook = String::from(HEADER);
+    workbook.push_str(
+        "<workbook xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\" \
+         xmlns:r=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships\"><sheets>",
+    );
+    let mut relationships = String::from(HEADER);
+    relationships.push_str(
+        "<Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">",
+    );
+    let mut parts = Vec::new();
+    for (i, sheet) in sheets.iter().enumerate() {
+        let n = i + 1;
+        content_types.push_str(&format!(
+            "<Override PartName=\"/xl/worksheets/sheet{}.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml\"/>",
+            n
+        ));
+        workbook.push_str(&format!(
+            "<sheet name=\"{}\" sheetId=\"{}\" r:id=\"rId{}\"/>",
+            sheet.name, n, n
+        ));
+        relationships.push_str(&format!(
+            "<Relationship Id=\"rId{}\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet\" Target=\"worksheets/sheet{}.xml\"/>",
+            n, n
+        ));
+        parts.push((format!("xl/worksheets/sheet{}.xml", n), sheet_xml(sheet)));
+    }
+    content_types.push_str("</Types>");
+    workbook.push_str("</sheets></workbook>");
+    relationships.push_str(&format!(
+        "<Relationship Id=\"rId{}\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles\" Target=\"styles.xml\"/></Relationships>",
+        sheets.len() + 1
+    ));
+
+    let package_relationships = format!(
+        "{}<Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">\
+         <Relationship Id=\"rId1\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument\" Target=\"xl/workbook.xml\"/></Relationships>",
+        HEADER
+    );
+    let styles = format!(
+        "{}<styleSheet xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\">\
+         <fonts count=\"2\"><font><sz val=\"11\"/><name val=\"Calibri\"/></font><font><b/><sz val=\"11\"/><name val=\"Calibri\"/></font></fonts>\
+         <fills count=\"2\"><fill><patternFill patternType=\"none\"/></fill><fill><patternFill patternType=\"gray125\"/></fill></fills>\
+         <borders count=\"1\"><border><left/><right/><top/><bottom/><diagonal/></border></borders>\
+         <cellStyleXfs count=\"1\"><xf numFmtId=\"0\" fontId=\"0\" fillId=\"0\" borderId=\"0\"/></cellStyleXfs>\
+         <cellXfs count=\"3\"><xf numFmtId=\"0\" fontId=\"0\" fillId=\"0\" borderId=\"0\" xfId=\"0\"/><xf numFmtId=\"0\" fontId=\"1\" fillId=\"0\" borderId=\"0\" xfId=\"0\" applyFont=\"1\"/>\
+         <xf numFmtId=\"10\" fontId=\"0\" fillId=\"0\" borderId=\"0\" xfId=\"0\" applyNumberFormat=\"1\"/></cellXfs>\
+         </styleSheet>",
+        HEADER
+    );
+
+    let mut all = vec![
+        ("[Content_Types].xml".to_string(), content_types),
+        ("_rels/.rels".to_string(), package_relationships),
+        ("xl/workbook.xml".to_string(), workbook),
+        ("xl/_rels/workbook.xml.rels".to_string(), relationships),
+        ("xl/styles.xml".to_string(), styles),
+    ];
+    all.extend(parts);
+    all
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+// Zip archive of the files, stored without compression
+fn zip(files: &[(String, String)]) -> Vec<u8> {
+    let mut archive = Vec::new();
+    let mut directory = Vec::new();
+    for (name, content) in files {
+        let offset = archive.len() as u32;
+        let crc = crc32(content.as_bytes());
+        let size = content.len() as u32;
+        // Version, flags (UTF-8 names), method (stored), time and date (1980-01-01)
+        let common = |buffer: &mut Vec<u8>| {
+            buffer.extend_from_slice(&20u16.to_le_bytes());
+            buffer.extend_from_slice(&0x0800u16.to_le_bytes());
+            buffer.extend_from_slice(&0u16.to_le_bytes());
+            buffer.extend_from_slice(&0u16.to_le_bytes());
+            buffer.extend_from_slice(&0x0021u16.to_le_bytes());
+            buffer.extend_from_slice(&crc.to_le_bytes());
+            buffer.extend_from_slice(&size.to_le_bytes());
+            buffer.extend_from_slice(&size.to_le_bytes());
+            buffer.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            buffer.extend_from_slice(&0u16.to_le_bytes());
+        };
+
+        archive.extend_from_slice(&0x0403_4b50u32.to_le_bytes());
+        common(&mut archive);
+        archive.extend_from_slice(name.as_bytes());
+        archive.extend_from_slice(content.as_bytes());
+
+        directory.extend_from_slice(&0x0201_4b50u32.to_le_bytes());
+        directory.extend_from_slice(&20u16.to_le_bytes());
+        common(&mut directory);
+        // Comment length, disk, internal and external attributes, offset
+        directory.extend_from_slice(&0u16.to_le_bytes());
+        directory.extend_from_slice(&0u16.to_le_bytes());
+        directory.extend_from_slice(&0u16.to_le_bytes());
+        directory.extend_from_slice(&0u32.to_le_bytes());
+        directory.extend_from_slice(&offset.to_le_bytes());
+        directory.extend_from_slice(name.as_bytes());
+    }
+
+    let directory_offset = archive.len() as u32;
+    archive.extend_from_slice(&directory);
+    archive.extend_from_slice(&0x0605_4b50u32.to_le_bytes());
+    archive.extend_from_slice(&0u16.to_le_bytes());
+    archive.extend_from_slice(&0u16.to_le_bytes());
+    archive.extend_from_slice(&(files.len() as u16).to_le_bytes());
+    archive.extend_from_slice(&(files.len() as u16).to_le_bytes());
+    archive.extend_from_slice(&(directory.len() as u32).to_le_bytes());
+    archive.extend_from_slice(&directory_offset.to_le_bytes());
+    archive.extend_from_slice(&0u16.to_le_bytes());
+    archive
+}
+
+// Contexts, or processes when grouping by them, with their events and time
+// by algorithm family
+fn context_rows(root: &TreeNode) -> Vec<Vec<Cell>> {
+    let mut header: Vec<Cell> = vec!["Context".into(), "Process".into(), "Events".into()];
+    header.push("Duration (ms)".into());
+    header.extend(Family::ALL.iter().map(|family| family.label().into()));
+    let mut rows = vec![header];
+    for node in &root.children {
+        if !matches!(node.kind, NodeKind::Context | NodeKind::Process) {
+            continue;
+        }
+        let mut row: Vec<Cell> = vec![
+            node.display_name().into(),
+            node.process.clone().unwrap_or_default().into(),
+            node.value.into(),
+            Cell::Number(node.duration as f64 / 1e6),
+        ];
+        row.extend(matrix::family_counts(node).into_iter().map(Cell::from));
+        rows.push(row);
+    }
+    rows
+}
+
+// Save the view as a workbook with a summary sheet and sheets of the algorithm
+// statistics, the contexts and the violations of the system crypto policy
+pub fn export_workbook(chart: &SunburstChart, store: &gio::ListStore, path: &Path) -> Result<()> {
+    let Some(root) = chart.root() else {
+        return Err(anyhow!("No capture is loaded"));
+    };
+    let events = chart.visible_events();
+    let profile = Profile::active().or_else(|| Profile::builtin("DEFAULT"));
+    let violations = profile
+        .as_ref()
+        .map(|profile| profile.check(&events))
+        .unwrap_or_default();
+
+    let mut algorithms = vec![vec![
+        "Algorithm".into(),
+        "Count".into(),
+        "Percentage".into(),
+    ]];
+    for obj in store.iter::<StatsObject>().filter_map(Result::ok) {
+        let count = obj.count().parse().map(Cell::Number);
+        let percentage = obj.percentage().trim_end_matches('%').parse::<f64>();
+        algorithms.push(vec![
+            obj.algorithm().into(),
+            count.unwrap_or_else(|_| obj.count().into()),
+            percentage
+                .map(|percentage| Cell::Percent(percentage / 100.0))
+                .unwrap_or_else(|_| obj.percentage().into()),
+        ]);
+    }
+
+    let contexts = context_rows(&root);
+
+    let mut violation_rows = vec![vec![
+        "Severity".into(),
+        "Finding".into(),
+        "Origin".into(),
+        "Events".into(),
+    ]];
+    for violation in &violations {
+        violation_rows.push(vec![
+            violation.severity.name().into(),
+            violation.description.clone().into(),
+            violation.origin.clone().into(),
+            violation.count.into(),
+        ]);
+    }
+
+    let mut summary: Vec<Vec<Cell>> = vec![vec!["Crypto Usage Report".into()]];
+    summary.extend(
+        export::watermark_lines(chart)
+            .into_iter()
+            .map(|line| vec![line.into()]),
+    );
+    summary.push(Vec::new());
+    summary.push(vec!["Events".into(), events.len().into()]);
+    summary.push(vec!["Contexts".into(), (contexts.len() - 1).into()]);
+    summary.push(vec!["Algorithms".into(), (algorithms.len() - 1).into()]);
+    summary.push(vec![
+        match &profile {
+            Some(profile) => format!("Violations of {}", profile.name).into(),
+            None => "Violations".into(),
+        },
+        violations.len().into(),
+    ]);
+
+    let sheets = [
+        Sheet {
+            name: "Summary",
+            rows: summary,
+        },
+        Sheet {
+            name: "Algorithms",
+            rows: algorithms,
+        },
+        Sheet {
+            name: "Contexts",
+            rows: contexts,
+        },
+        Sheet {
+            name: "Violations",
+            rows: violation_rows,
+        },
+    ];
+    fs::write(path, zip(&workbook_parts(&sheets)))?;
+    Ok(())
+}